@@ -1,5 +1,7 @@
 use alloc::boxed::Box;
 
+use crate::structures::mbr::Partition;
+
 /// # Block Device Driver Interface
 ///
 /// Exposes the common functionality for all Block Device Drivers
@@ -21,6 +23,16 @@ pub trait BlockDeviceDriver<
     /// Returns an error if initialization failed.
     fn initialize(&self) -> Result<(), BlockDeviceError>;
 
+    /// Get the largest number of blocks which the device can efficiently transfer in a single request. Larger reads
+    /// are split into requests of at most this many blocks.
+    fn optimal_io_blocks(&self) -> u32 {
+        1
+    }
+
+    /// Handle an interrupt raised by the device, completing any requests it has finished. Does nothing by default,
+    /// for drivers which do not wait on interrupts.
+    fn handle_interrupt(&self) {}
+
     /// Read a block from the block device
     async fn read_blocks<'b, 'a: 'b>(
         &'b self,
@@ -35,3 +47,433 @@ pub trait BlockDeviceDriver<
         buffer: &'a [[u8; BLOCK_SIZE]],
     ) -> Result<(), BlockDeviceError>;
 }
+
+/// A request made of a [`SectorSizeAdapter`] whose first native sector can not be addressed by the underlying device,
+/// reported through the underlying device's error type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorOutOfRange {
+    /// First 512 byte sector of the request
+    pub index: u32,
+    /// Number of 512 byte sectors requested
+    pub count: usize,
+}
+
+/// Presents a block device with `FROM` byte native sectors as a device with 512 byte sectors, so it can be used by
+/// consumers such as [`crate::fs::ext2::Ext2FileSystem`] which expect them.
+///
+/// Requests are widened to whole native sectors. For `FROM` larger than 512, a write which only covers part of a
+/// native sector reads the sector first and writes it back with the new data, so such writes are not atomic with
+/// respect to other writers of the same native sector.
+pub struct SectorSizeAdapter<const FROM: usize, E: 'static> {
+    device: &'static (dyn BlockDeviceDriver<FROM, E, u32> + Send + Sync),
+}
+
+impl<const FROM: usize, E: 'static> SectorSizeAdapter<FROM, E> {
+    /// Wrap a device with `FROM` byte sectors.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if neither `FROM` nor 512 is a multiple of the other.
+    #[must_use]
+    pub const fn new(device: &'static (dyn BlockDeviceDriver<FROM, E, u32> + Send + Sync)) -> Self {
+        assert!(
+            FROM != 0 && (FROM.is_multiple_of(512) || 512usize.is_multiple_of(FROM)),
+            "Sector size must be a multiple or a divisor of 512"
+        );
+
+        Self { device }
+    }
+
+    /// Get the range of native sectors covering `count` 512 byte sectors starting at `index`, along with the byte
+    /// offset of the first 512 byte sector within the first native sector.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SectorOutOfRange` if the first native sector does not fit in a `u32` sector index.
+    fn native_range(index: u32, count: usize) -> Result<(u32, usize, usize), SectorOutOfRange> {
+        let start = index as usize * 512;
+        let end = start + count * 512;
+
+        let first = start / FROM;
+        let native_count = end.div_ceil(FROM) - first;
+
+        Ok((
+            u32::try_from(first).map_err(|_| SectorOutOfRange { index, count })?,
+            native_count,
+            start - first * FROM,
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl<const FROM: usize, E: core::fmt::Debug + Send + Sync + From<SectorOutOfRange> + 'static>
+    BlockDeviceDriver<512, E, u32> for SectorSizeAdapter<FROM, E>
+{
+    fn is_initialized(&self) -> bool {
+        self.device.is_initialized()
+    }
+
+    fn initialize(&self) -> Result<(), E> {
+        self.device.initialize()
+    }
+
+    fn optimal_io_blocks(&self) -> u32 {
+        let bytes = self.device.optimal_io_blocks() as usize * FROM;
+        u32::try_from(bytes / 512).unwrap_or(u32::MAX).max(1)
+    }
+
+    fn handle_interrupt(&self) {
+        self.device.handle_interrupt();
+    }
+
+    async fn read_blocks<'b, 'a: 'b>(
+        &'b self,
+        index: u32,
+        buffer: &'a mut [[u8; 512]],
+    ) -> Result<(), E> {
+        let (first, native_count, offset) = Self::native_range(index, buffer.len())?;
+
+        let mut native = alloc::vec![[0u8; FROM]; native_count];
+        self.device.read_blocks(first, &mut native).await?;
+
+        let length = buffer.len() * 512;
+        buffer
+            .as_flattened_mut()
+            .copy_from_slice(&native.as_flattened()[offset..offset + length]);
+
+        Ok(())
+    }
+
+    async fn write_blocks<'b, 'a: 'b>(
+        &'b self,
+        index: u32,
+        buffer: &'a [[u8; 512]],
+    ) -> Result<(), E> {
+        let (first, native_count, offset) = Self::native_range(index, buffer.len())?;
+
+        let mut native = alloc::vec![[0u8; FROM]; native_count];
+
+        // Native sectors only partly covered by the write must keep the rest of their contents
+        if offset != 0 || buffer.len() * 512 != native_count * FROM {
+            self.device.read_blocks(first, &mut native).await?;
+        }
+
+        native.as_flattened_mut()[offset..offset + buffer.len() * 512]
+            .copy_from_slice(buffer.as_flattened());
+
+        self.device.write_blocks(first, &native).await
+    }
+}
+
+/// A request made of a [`PartitionAdapter`] which runs past the end of its partition, reported through the
+/// underlying device's error type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfPartition {
+    /// First sector of the request, relative to the start of the partition
+    pub index: u32,
+    /// Number of sectors requested
+    pub count: usize,
+}
+
+/// Presents a single partition of a block device with 512 byte sectors as a device of its own, so a file system can
+/// be mounted on it. Sector indices are relative to the start of the partition.
+pub struct PartitionAdapter<E: 'static> {
+    device: &'static (dyn BlockDeviceDriver<512, E, u32> + Send + Sync),
+    partition: Partition,
+}
+
+impl<E: 'static> PartitionAdapter<E> {
+    /// Wrap the given partition of a device.
+    #[must_use]
+    pub const fn new(
+        device: &'static (dyn BlockDeviceDriver<512, E, u32> + Send + Sync),
+        partition: Partition,
+    ) -> Self {
+        Self { device, partition }
+    }
+
+    /// Get the partition this adapter exposes
+    #[must_use]
+    pub const fn partition(&self) -> Partition {
+        self.partition
+    }
+
+    /// Translate a request for `count` sectors starting at `index` into a sector index on the underlying device.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfPartition` if the request runs past the end of the partition.
+    fn device_index(&self, index: u32, count: usize) -> Result<u32, OutOfPartition> {
+        let out_of_partition = OutOfPartition { index, count };
+
+        let end = (index as usize)
+            .checked_add(count)
+            .ok_or(out_of_partition)?;
+        if end > self.partition.sector_count as usize {
+            return Err(out_of_partition);
+        }
+
+        self.partition
+            .start_lba
+            .checked_add(index)
+            .ok_or(out_of_partition)
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: core::fmt::Debug + Send + Sync + From<OutOfPartition> + 'static>
+    BlockDeviceDriver<512, E, u32> for PartitionAdapter<E>
+{
+    fn is_initialized(&self) -> bool {
+        self.device.is_initialized()
+    }
+
+    fn initialize(&self) -> Result<(), E> {
+        self.device.initialize()
+    }
+
+    fn optimal_io_blocks(&self) -> u32 {
+        self.device.optimal_io_blocks()
+    }
+
+    fn handle_interrupt(&self) {
+        self.device.handle_interrupt();
+    }
+
+    async fn read_blocks<'b, 'a: 'b>(
+        &'b self,
+        index: u32,
+        buffer: &'a mut [[u8; 512]],
+    ) -> Result<(), E> {
+        let index = self.device_index(index, buffer.len())?;
+        self.device.read_blocks(index, buffer).await
+    }
+
+    async fn write_blocks<'b, 'a: 'b>(
+        &'b self,
+        index: u32,
+        buffer: &'a [[u8; 512]],
+    ) -> Result<(), E> {
+        let index = self.device_index(index, buffer.len())?;
+        self.device.write_blocks(index, buffer).await
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use super::{
+        BlockDeviceDriver, OutOfPartition, PartitionAdapter, SectorOutOfRange, SectorSizeAdapter,
+    };
+    use crate::{structures::mbr::parse_partition_table, tasks::block_on};
+
+    /// Errors returned by a [`MemoryDevice`], which only fails requests an adapter refuses
+    #[derive(Debug, PartialEq, Eq)]
+    enum AdapterError {
+        OutOfPartition(OutOfPartition),
+        OutOfRange(SectorOutOfRange),
+    }
+
+    impl From<OutOfPartition> for AdapterError {
+        fn from(value: OutOfPartition) -> Self {
+            Self::OutOfPartition(value)
+        }
+    }
+
+    impl From<SectorOutOfRange> for AdapterError {
+        fn from(value: SectorOutOfRange) -> Self {
+            Self::OutOfRange(value)
+        }
+    }
+
+    /// In memory device with `SECTOR` byte sectors, recording the requests made of it.
+    struct MemoryDevice<const SECTOR: usize> {
+        image: spin::Mutex<Vec<u8>>,
+        /// Whether each request was a write, its starting sector, and its length in sectors
+        requests: spin::Mutex<Vec<(bool, u32, usize)>>,
+    }
+
+    impl<const SECTOR: usize> MemoryDevice<SECTOR> {
+        fn new(length: usize) -> &'static Self {
+            Box::leak(Box::new(Self {
+                image: spin::Mutex::new(
+                    (0..length)
+                        .map(|i| u8::try_from(i % 251).unwrap())
+                        .collect(),
+                ),
+                requests: spin::Mutex::new(Vec::new()),
+            }))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl<const SECTOR: usize> BlockDeviceDriver<SECTOR, AdapterError, u32> for MemoryDevice<SECTOR> {
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn initialize(&self) -> Result<(), AdapterError> {
+            Ok(())
+        }
+
+        async fn read_blocks<'b, 'a: 'b>(
+            &'b self,
+            index: u32,
+            buffer: &'a mut [[u8; SECTOR]],
+        ) -> Result<(), AdapterError> {
+            self.requests.lock().push((false, index, buffer.len()));
+
+            let start = index as usize * SECTOR;
+            let end = start + buffer.len() * SECTOR;
+            buffer
+                .as_flattened_mut()
+                .copy_from_slice(&self.image.lock()[start..end]);
+            Ok(())
+        }
+
+        async fn write_blocks<'b, 'a: 'b>(
+            &'b self,
+            index: u32,
+            buffer: &'a [[u8; SECTOR]],
+        ) -> Result<(), AdapterError> {
+            self.requests.lock().push((true, index, buffer.len()));
+
+            let start = index as usize * SECTOR;
+            self.image.lock()[start..start + buffer.len() * SECTOR]
+                .copy_from_slice(buffer.as_flattened());
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn large_sector_read_test() {
+        let device = MemoryDevice::<4096>::new(4 * 4096);
+        let adapter = SectorSizeAdapter::new(device);
+
+        // Sectors 6 through 9 straddle the boundary between the first two native sectors
+        let mut buffer = [[0u8; 512]; 4];
+        block_on(adapter.read_blocks(6, &mut buffer)).unwrap();
+
+        assert_eq!(
+            buffer.as_flattened(),
+            &device.image.lock()[6 * 512..10 * 512]
+        );
+        assert_eq!(*device.requests.lock(), [(false, 0, 2)]);
+    }
+
+    #[test]
+    pub fn large_sector_write_test() {
+        let device = MemoryDevice::<4096>::new(4 * 4096);
+        let adapter = SectorSizeAdapter::new(device);
+        let original = device.image.lock().clone();
+
+        block_on(adapter.write_blocks(9, &[[0xaa; 512]])).unwrap();
+
+        let image = device.image.lock();
+        assert!(image[9 * 512..10 * 512].iter().all(|byte| *byte == 0xaa));
+        assert_eq!(image[..9 * 512], original[..9 * 512]);
+        assert_eq!(image[10 * 512..], original[10 * 512..]);
+
+        // The partly covered native sector is read before it is written back
+        assert_eq!(*device.requests.lock(), [(false, 1, 1), (true, 1, 1)]);
+    }
+
+    #[test]
+    pub fn small_sector_test() {
+        let device = MemoryDevice::<256>::new(16 * 256);
+        let adapter = SectorSizeAdapter::new(device);
+
+        let mut buffer = [[0u8; 512]; 2];
+        block_on(adapter.read_blocks(3, &mut buffer)).unwrap();
+        assert_eq!(
+            buffer.as_flattened(),
+            &device.image.lock()[3 * 512..5 * 512]
+        );
+
+        // Whole native sectors are written without being read first
+        block_on(adapter.write_blocks(1, &[[0x55; 512]])).unwrap();
+        assert!(device.image.lock()[512..1024]
+            .iter()
+            .all(|byte| *byte == 0x55));
+        assert_eq!(*device.requests.lock(), [(false, 6, 4), (true, 2, 2)]);
+    }
+
+    #[test]
+    pub fn small_sector_out_of_range_test() {
+        let device = MemoryDevice::<256>::new(16 * 256);
+        let adapter = SectorSizeAdapter::new(device);
+
+        // The first native sector is past what a `u32` can index, so the request never reaches the device
+        let mut buffer = [[0u8; 512]; 1];
+        assert_eq!(
+            block_on(adapter.read_blocks(u32::MAX, &mut buffer)),
+            Err(AdapterError::OutOfRange(SectorOutOfRange {
+                index: u32::MAX,
+                count: 1
+            }))
+        );
+        assert_eq!(
+            block_on(adapter.write_blocks(1 << 31, &[[0; 512]])),
+            Err(AdapterError::OutOfRange(SectorOutOfRange {
+                index: 1 << 31,
+                count: 1
+            }))
+        );
+        assert!(device.requests.lock().is_empty());
+    }
+
+    #[test]
+    pub fn partition_test() {
+        let device = MemoryDevice::<512>::new(16 * 512);
+        {
+            // A single partition covering sectors 4 through 11
+            let mut image = device.image.lock();
+            image[..512].fill(0);
+            image[0x1BE + 4] = 0x83;
+            image[0x1BE + 8..0x1BE + 12].copy_from_slice(&4u32.to_le_bytes());
+            image[0x1BE + 12..0x1BE + 16].copy_from_slice(&8u32.to_le_bytes());
+            image[510..512].copy_from_slice(&[0x55, 0xAA]);
+        }
+
+        let mut sector = [[0u8; 512]];
+        block_on(device.read_blocks(0, &mut sector)).unwrap();
+        let partition = parse_partition_table(&sector[0]).unwrap()[0].unwrap();
+        let adapter = PartitionAdapter::new(device, partition);
+
+        let mut buffer = [[0u8; 512]; 2];
+        block_on(adapter.read_blocks(1, &mut buffer)).unwrap();
+        assert_eq!(
+            buffer.as_flattened(),
+            &device.image.lock()[5 * 512..7 * 512]
+        );
+
+        block_on(adapter.write_blocks(7, &[[0xaa; 512]])).unwrap();
+        assert!(device.image.lock()[11 * 512..12 * 512]
+            .iter()
+            .all(|byte| *byte == 0xaa));
+
+        // Requests running past the end of the partition are refused without reaching the device
+        let mut buffer = [[0u8; 512]; 2];
+        assert_eq!(
+            block_on(adapter.read_blocks(7, &mut buffer)),
+            Err(AdapterError::OutOfPartition(OutOfPartition {
+                index: 7,
+                count: 2
+            }))
+        );
+        assert_eq!(
+            block_on(adapter.write_blocks(u32::MAX, &[[0; 512]])),
+            Err(AdapterError::OutOfPartition(OutOfPartition {
+                index: u32::MAX,
+                count: 1
+            }))
+        );
+
+        assert_eq!(
+            *device.requests.lock(),
+            [(false, 0, 1), (false, 5, 2), (true, 11, 1)]
+        );
+    }
+}
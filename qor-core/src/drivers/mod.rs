@@ -1,4 +1,7 @@
 pub mod block;
 pub mod plic;
+pub mod random;
+#[cfg(feature = "alloc")]
+pub mod status;
 pub mod timer;
 pub mod uart;
@@ -0,0 +1,60 @@
+/// # Random Source Interface
+///
+/// Exposes the common functionality for all sources of random bytes, such as hardware entropy devices
+pub trait RandomSource {
+    type RandomSourceError;
+
+    /// Fill every byte of `buffer` with random data, waiting on the source until enough is available
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source failed to supply random data.
+    fn fill_bytes(&self, buffer: &mut [u8]) -> Result<(), Self::RandomSourceError>;
+
+    /// Get a random `u64`, such as for a stack canary or an address space offset
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source failed to supply random data.
+    fn next_u64(&self) -> Result<u64, Self::RandomSourceError> {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes)?;
+
+        Ok(u64::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    use super::RandomSource;
+
+    /// Source which hands out consecutive bytes, so the order bytes are used in can be checked
+    struct CountingSource {
+        next: AtomicU8,
+    }
+
+    impl RandomSource for CountingSource {
+        type RandomSourceError = ();
+
+        fn fill_bytes(&self, buffer: &mut [u8]) -> Result<(), ()> {
+            for byte in buffer {
+                *byte = self.next.fetch_add(1, Ordering::Relaxed);
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn next_u64_test() {
+        let source = CountingSource {
+            next: AtomicU8::new(1),
+        };
+
+        assert_eq!(source.next_u64(), Ok(0x0807_0605_0403_0201));
+        assert_eq!(source.next_u64(), Ok(0x100F_0E0D_0C0B_0A09));
+    }
+}
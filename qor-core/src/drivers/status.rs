@@ -0,0 +1,144 @@
+use alloc::vec::Vec;
+
+use crate::{structures::time::Hertz, sync::Mutex};
+
+/// Summary of a single device, as listed in a status report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceStatus {
+    /// Serial port, and whether its driver has been initialized
+    Uart { initialized: bool },
+    /// Hardware timer, and the rate at which it raises interrupts
+    Timer { frequency: Hertz },
+    /// Platform interrupt controller, and whether its driver has been initialized
+    InterruptController { initialized: bool },
+    /// Virt IO device found while probing its MMIO range, along with the name and queue size of the driver attached to
+    /// it, if any
+    VirtIO {
+        address: usize,
+        device_id: u32,
+        driver: Option<&'static str>,
+        queue_size: Option<u32>,
+    },
+}
+
+impl core::fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let initialized = |initialized: bool| {
+            if initialized {
+                "initialized"
+            } else {
+                "not initialized"
+            }
+        };
+
+        match self {
+            Self::Uart { initialized: state } => write!(f, "uart: {}", initialized(*state)),
+            Self::Timer { frequency } => write!(f, "timer: {} Hz", frequency.0),
+            Self::InterruptController { initialized: state } => {
+                write!(f, "interrupt controller: {}", initialized(*state))
+            }
+            Self::VirtIO {
+                address,
+                device_id,
+                driver,
+                queue_size,
+            } => {
+                write!(f, "virtio@{address:#x}: device {device_id}")?;
+
+                match driver {
+                    Some(driver) => write!(f, ", {driver} driver")?,
+                    None => write!(f, ", no driver")?,
+                }
+
+                if let Some(queue_size) = queue_size {
+                    write!(f, ", queue size {queue_size}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Devices found while probing the platform, kept so they can be listed in a status report.
+#[allow(clippy::module_name_repetitions)]
+pub struct DeviceRegistry {
+    devices: Mutex<Vec<DeviceStatus>>,
+}
+
+impl DeviceRegistry {
+    /// Construct an empty registry
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            devices: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record a device, it is listed after every device registered before it
+    pub fn register(&self, device: DeviceStatus) {
+        self.devices.spin_lock().push(device);
+    }
+
+    /// Get every registered device, in the order they were registered
+    #[must_use]
+    pub fn devices(&self) -> Vec<DeviceStatus> {
+        self.devices.spin_lock().clone()
+    }
+}
+
+impl Default for DeviceRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::{DeviceRegistry, DeviceStatus};
+    use crate::structures::time::Hertz;
+
+    #[test]
+    pub fn registry_report_test() {
+        let registry = DeviceRegistry::new();
+        assert!(registry.devices().is_empty());
+
+        registry.register(DeviceStatus::Uart { initialized: true });
+        registry.register(DeviceStatus::Timer {
+            frequency: Hertz(100),
+        });
+        registry.register(DeviceStatus::InterruptController { initialized: false });
+        registry.register(DeviceStatus::VirtIO {
+            address: 0x1000_8000,
+            device_id: 2,
+            driver: Some("block"),
+            queue_size: Some(128),
+        });
+        registry.register(DeviceStatus::VirtIO {
+            address: 0x1000_7000,
+            device_id: 4,
+            driver: None,
+            queue_size: None,
+        });
+
+        let report = registry
+            .devices()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            report,
+            [
+                "uart: initialized",
+                "timer: 100 Hz",
+                "interrupt controller: not initialized",
+                "virtio@0x10008000: device 2, block driver, queue size 128",
+                "virtio@0x10007000: device 4, no driver",
+            ]
+        );
+    }
+}
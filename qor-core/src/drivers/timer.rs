@@ -32,8 +32,7 @@ pub trait HardwareTimerDriver {
     ///
     /// Returns an error if the time to next tick could not be set.
     fn set_time_rate(&self, id: HartID, frequency: Hertz) -> Result<(), Self::HardwareTimerError> {
-        let time = Microseconds(1_000_000 / frequency.0);
-        self.set_time(id, time)
+        self.set_time(id, frequency.period())
     }
 
     /// Get the current time since last reset.
@@ -0,0 +1,388 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+use spin::RwLock;
+
+use crate::{
+    drivers::random::RandomSource,
+    interfaces::{
+        bytes::GenericByteInterface,
+        fs::{
+            DirectoryEntry, DirectoryFileDescriptor, FileDescriptor, FileSystem, FileSystemError,
+            FileType, GenericDeviceFileDescriptor, INodeData, INodeReference, MountableFileSystem,
+            OpenFlags, SeekMode,
+        },
+    },
+};
+
+/// Inode number of the root directory, listing every device
+const ROOT_INODE: usize = 1;
+
+/// Mode of the root directory, read and search permission for everyone
+const DIRECTORY_MODE: u16 = 0x4000 | 0o555;
+/// Mode of the devices, character devices which everyone can read and write
+const DEVICE_MODE: u16 = 0x2000 | 0o666;
+
+/// Constructs a fresh descriptor forwarding to a device each time it is opened
+type Opener = Box<dyn Fn() -> Arc<dyn FileDescriptor> + Send + Sync>;
+
+/// File system presenting device drivers as files, similar to `/dev` on Linux.
+///
+/// The root directory lists each registered device, and opening one gives a descriptor which forwards reads and
+/// writes to its driver. Devices are numbered in the order they are added, after the root.
+#[allow(clippy::module_name_repetitions)]
+pub struct DevFs {
+    device_id: core::sync::atomic::AtomicUsize,
+    devices: RwLock<Vec<(String, Opener)>>,
+}
+
+impl DevFs {
+    /// Construct a new [`DevFs`] with no devices.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            device_id: core::sync::atomic::AtomicUsize::new(0),
+            devices: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// List a device under `name`, opened with `open`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlreadyExists` if a device is already listed under `name`.
+    pub fn add_device(&self, name: &str, open: Opener) -> Result<(), FileSystemError> {
+        let mut devices = self.devices.write();
+
+        if devices.iter().any(|(existing, _)| existing == name) {
+            return Err(FileSystemError::AlreadyExists);
+        }
+
+        devices.push((name.to_string(), open));
+        Ok(())
+    }
+
+    /// List a device which sends and receives bytes, such as a UART, under `name`. Reads return only the bytes the
+    /// device already has.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlreadyExists` if a device is already listed under `name`.
+    pub fn add_byte_device<E, D>(
+        &self,
+        name: &str,
+        device: &'static D,
+    ) -> Result<(), FileSystemError>
+    where
+        E: Send + Sync + 'static,
+        D: GenericByteInterface<E> + Send + Sync,
+    {
+        self.add_device(
+            name,
+            Box::new(move || Arc::new(GenericDeviceFileDescriptor::new(device))),
+        )
+    }
+
+    /// List a source of random bytes under `name`. Reads fill the whole buffer, and writes are refused.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlreadyExists` if a device is already listed under `name`.
+    pub fn add_random_source<R: RandomSource + Send + Sync>(
+        &self,
+        name: &str,
+        source: &'static R,
+    ) -> Result<(), FileSystemError> {
+        self.add_device(
+            name,
+            Box::new(move || Arc::new(RandomFileDescriptor { source })),
+        )
+    }
+
+    fn inode_ref(&self, inode: usize) -> INodeReference {
+        INodeReference {
+            inode,
+            device: self.device_id.load(core::sync::atomic::Ordering::Acquire),
+        }
+    }
+
+    /// Get the index of the device `inode` refers to, or `None` for the root directory.
+    fn device_index(&self, inode: INodeReference) -> Result<Option<usize>, FileSystemError> {
+        if inode.device != self.device_id.load(core::sync::atomic::Ordering::Acquire) {
+            return Err(FileSystemError::BadInodeWrongDevice(inode));
+        }
+
+        if inode.inode == ROOT_INODE {
+            return Ok(None);
+        }
+
+        inode
+            .inode
+            .checked_sub(ROOT_INODE + 1)
+            .filter(|index| *index < self.devices.read().len())
+            .map(Some)
+            .ok_or(FileSystemError::BadInode(inode))
+    }
+}
+
+impl Default for DevFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl FileSystem for DevFs {
+    async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
+        Ok(self.inode_ref(ROOT_INODE))
+    }
+
+    async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
+        let mode = match self.device_index(inode)? {
+            None => DIRECTORY_MODE,
+            Some(_) => DEVICE_MODE,
+        };
+
+        Ok(INodeData {
+            mode: mode.into(),
+            link_count: 1,
+            uid: 0.into(),
+            gid: 0.into(),
+            size: 0,
+            access_time: 0.into(),
+            modify_time: 0.into(),
+            change_time: 0.into(),
+            reference: inode,
+        })
+    }
+
+    async fn directory_entries(
+        &self,
+        inode: INodeReference,
+    ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError> {
+        if self.device_index(inode)?.is_some() {
+            return Err(FileSystemError::NotDirectory);
+        }
+
+        let mut entries = alloc::vec![
+            DirectoryEntry {
+                inode,
+                name: ".".into(),
+                file_type: FileType::Directory,
+            },
+            DirectoryEntry {
+                inode,
+                name: "..".into(),
+                file_type: FileType::Directory,
+            },
+        ];
+
+        entries.extend(
+            self.devices
+                .read()
+                .iter()
+                .enumerate()
+                .map(|(index, (name, _))| DirectoryEntry {
+                    inode: self.inode_ref(ROOT_INODE + 1 + index),
+                    name: name.clone().into(),
+                    file_type: FileType::CharacterDevice,
+                }),
+        );
+
+        Ok(entries)
+    }
+
+    /// Open a device, or a listing of the devices. Devices have no contents to truncate or append to, so the flags
+    /// are ignored.
+    async fn open_with(
+        &self,
+        inode: INodeReference,
+        _flags: OpenFlags,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+        if let Some(index) = self.device_index(inode)? {
+            return Ok((self.devices.read()[index].1)());
+        }
+
+        let entries = self
+            .directory_entries(inode)
+            .await?
+            .into_iter()
+            .map(DirectoryEntry::into_owned)
+            .collect();
+        Ok(Arc::new(DirectoryFileDescriptor::new(inode, entries)))
+    }
+
+    async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError> {
+        match self.device_index(inode)? {
+            None => Err(FileSystemError::IsDirectory),
+            Some(_) => Err(FileSystemError::Unsupported),
+        }
+    }
+}
+
+impl MountableFileSystem for DevFs {
+    fn set_mount_device_id(&self, device_id: usize) {
+        self.device_id
+            .store(device_id, core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Descriptor filling reads from a source of random bytes
+struct RandomFileDescriptor<R: RandomSource + Send + Sync + 'static> {
+    source: &'static R,
+}
+
+#[async_trait::async_trait]
+impl<R: RandomSource + Send + Sync + 'static> FileDescriptor for RandomFileDescriptor<R> {
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        self.source
+            .fill_bytes(buffer)
+            .map_err(|_| FileSystemError::GenericError)?;
+
+        Ok(buffer.len())
+    }
+
+    async fn write(&self, _buffer: &[u8]) -> Result<usize, FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    async fn seek(&self, _seek: SeekMode) -> Result<usize, FileSystemError> {
+        Ok(0)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{boxed::Box, collections::VecDeque, sync::Arc, vec::Vec};
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    use super::DevFs;
+    use crate::{
+        drivers::random::RandomSource,
+        fs::tmp::TmpFs,
+        interfaces::{
+            bytes::{GenericByteInterface, GenericByteReadInterface, GenericByteWriteInterface},
+            fs::{
+                FileSystem, FileSystemError, MountingFilesystem, OpenFlags, ParentFileSystem,
+                PathLookup, VirtualFileSystem,
+            },
+        },
+        sync::Mutex,
+        tasks::block_on,
+    };
+
+    /// Pseudo random source stepping a xorshift generator
+    struct XorShift {
+        state: AtomicU64,
+    }
+
+    impl RandomSource for XorShift {
+        type RandomSourceError = ();
+
+        fn fill_bytes(&self, buffer: &mut [u8]) -> Result<(), ()> {
+            for byte in buffer {
+                let mut state = self.state.load(Ordering::Relaxed);
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                self.state.store(state, Ordering::Relaxed);
+
+                *byte = state.to_le_bytes()[0];
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Byte device which echoes what is written to it
+    struct Loopback {
+        bytes: Mutex<VecDeque<u8>>,
+    }
+
+    impl GenericByteReadInterface<()> for Loopback {
+        fn read_byte(&self) -> Result<Option<u8>, ()> {
+            Ok(self.bytes.spin_lock().pop_front())
+        }
+    }
+
+    impl GenericByteWriteInterface<()> for Loopback {
+        fn send_byte(&self, byte: u8) -> Result<(), ()> {
+            self.bytes.spin_lock().push_back(byte);
+            Ok(())
+        }
+    }
+
+    impl GenericByteInterface<()> for Loopback {}
+
+    /// Virtual file system with a [`TmpFs`] root, and `devices` mounted at `/dev`
+    fn mounted(devices: DevFs) -> VirtualFileSystem {
+        let mut vfs = VirtualFileSystem::new();
+        let root = block_on(vfs.root_inode()).unwrap();
+        vfs.mount_filesystem(root, Arc::new(TmpFs::new())).unwrap();
+
+        let tmp_root = block_on(vfs.root_inode()).unwrap();
+        block_on(vfs.mkdir(tmp_root, "dev")).unwrap();
+        block_on(vfs.mount_at_path("/dev", Arc::new(devices))).unwrap();
+
+        vfs
+    }
+
+    #[test]
+    pub fn random_test() {
+        let devices = DevFs::new();
+        let source = Box::leak(Box::new(XorShift {
+            state: AtomicU64::new(0x2545_F491_4F6C_DD1D),
+        }));
+        devices.add_random_source("random0", source).unwrap();
+        let vfs = mounted(devices);
+
+        let descriptor = block_on(vfs.open_path("/dev/random0", OpenFlags::default())).unwrap();
+        let mut first = [0; 16];
+        let mut second = [0; 16];
+        assert_eq!(block_on(descriptor.read(&mut first)), Ok(16));
+        assert_eq!(block_on(descriptor.read(&mut second)), Ok(16));
+
+        assert_ne!(first, second);
+        assert!(first.iter().any(|byte| *byte != first[0]));
+        assert_eq!(
+            block_on(descriptor.write(&first)),
+            Err(FileSystemError::Unsupported)
+        );
+    }
+
+    #[test]
+    pub fn byte_device_test() {
+        let devices = DevFs::new();
+        let uart = Box::leak(Box::new(Loopback {
+            bytes: Mutex::new(VecDeque::new()),
+        }));
+        devices.add_byte_device("uart0", &*uart).unwrap();
+        assert_eq!(
+            devices.add_byte_device("uart0", &*uart),
+            Err(FileSystemError::AlreadyExists)
+        );
+        let vfs = mounted(devices);
+
+        let descriptor = block_on(vfs.open_path("/dev/uart0", OpenFlags::default())).unwrap();
+        assert_eq!(block_on(descriptor.write(b"ping")), Ok(4));
+
+        // Only the bytes waiting on the device are read
+        let mut buffer = [0; 8];
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(4));
+        assert_eq!(&buffer[..4], b"ping");
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(0));
+
+        let dev = block_on(vfs.lookup("/dev")).unwrap();
+        let names = block_on(vfs.directory_entries(dev))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name.into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [".", "..", "uart0"]);
+    }
+}
@@ -0,0 +1,79 @@
+use alloc::collections::VecDeque;
+
+/// Least recently used cache of KiB sized blocks read from the underlying block device, keyed by the KiB block index.
+pub struct BlockCache {
+    capacity: usize,
+    entries: VecDeque<(u32, [u8; 1024])>,
+}
+
+impl BlockCache {
+    /// Construct a new, empty [`BlockCache`] which will hold at most `capacity` blocks.
+    #[must_use]
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Get the maximum number of blocks held by the cache.
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of blocks currently held by the cache.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache holds no blocks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if the block is held by the cache, without marking it as recently used.
+    #[must_use]
+    pub fn contains(&self, block: u32) -> bool {
+        self.entries.iter().any(|(index, _)| *index == block)
+    }
+
+    /// Look up a block in the cache, marking it as the most recently used on a hit.
+    pub fn get(&mut self, block: u32) -> Option<&[u8; 1024]> {
+        let position = self.entries.iter().position(|(index, _)| *index == block)?;
+
+        // Move the entry to the back of the queue, which holds the most recently used block
+        let entry = self.entries.remove(position)?;
+        self.entries.push_back(entry);
+
+        self.entries.back().map(|(_, data)| data)
+    }
+
+    /// Insert a block into the cache, replacing any existing copy and evicting the least recently used block if the
+    /// cache is full.
+    pub fn insert(&mut self, block: u32, data: &[u8; 1024]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.invalidate(block);
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back((block, *data));
+    }
+
+    /// Remove a block from the cache if it is present.
+    pub fn invalidate(&mut self, block: u32) {
+        self.entries.retain(|(index, _)| *index != block);
+    }
+
+    /// Remove every block from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,118 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloc::{boxed::Box, sync::Arc};
+
+use crate::interfaces::fs::{
+    FileDescriptor, FileSystem, FileSystemError, INodeReference, SeekMode,
+};
+
+use super::{Ext2FileSystem, InodeWriteError};
+
+/// Descriptor for the data of an inode on an ext2 file system. Reads and writes start at the cursor, and advance it
+/// past the bytes transferred.
+#[allow(clippy::module_name_repetitions)]
+pub struct Ext2FileDescriptor<E: 'static + core::fmt::Debug + Send + Sync> {
+    fs: Arc<Ext2FileSystem<E>>,
+    inode: INodeReference,
+    cursor: AtomicUsize,
+}
+
+impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileDescriptor<E> {
+    /// Construct a descriptor for `inode` on `fs`, with the cursor at the start of the file.
+    #[must_use]
+    pub const fn new(fs: Arc<Ext2FileSystem<E>>, inode: INodeReference) -> Self {
+        Self {
+            fs,
+            inode,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    fn inode_index(&self) -> Result<u32, FileSystemError> {
+        u32::try_from(self.inode.inode).map_err(|_| FileSystemError::BadInode(self.inode))
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: 'static + core::fmt::Debug + Send + Sync> FileDescriptor for Ext2FileDescriptor<E> {
+    /// Read bytes from the file starting at the cursor into `buffer`. Returns the number of bytes read, which is less
+    /// than the length of `buffer` once the end of the file is reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inode or its data could not be read.
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        let inode = self
+            .fs
+            .get_inode(self.inode_index()?)
+            .await
+            .map_err(|e| e.for_inode(self.inode))?;
+
+        let position = self.cursor.load(Ordering::Acquire);
+        let length = self
+            .fs
+            .read_inode_range(&inode, position, buffer)
+            .await
+            .map_err(|e| e.for_inode(self.inode))?;
+        self.cursor.store(position + length, Ordering::Release);
+
+        Ok(length)
+    }
+
+    /// Write bytes to the file starting at the cursor, overwriting what is there and extending the file past its end.
+    /// Returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NoSpace` if the file system is full, `Unsupported` if the file would grow past the blocks ext2 can
+    /// allocate for it, or an error if the device could not be read or written.
+    async fn write(&self, buffer: &[u8]) -> Result<usize, FileSystemError> {
+        let position = self.cursor.load(Ordering::Acquire);
+        self.fs
+            .write_inode_range(self.inode_index()?, position, buffer)
+            .await
+            .map_err(|e| match e {
+                InodeWriteError::Device(_) => FileSystemError::BadInode(self.inode),
+                InodeWriteError::NoSpace => FileSystemError::NoSpace,
+                InodeWriteError::TooLarge => FileSystemError::Unsupported,
+                InodeWriteError::BadBlock(_) | InodeWriteError::Corrupted(_) => {
+                    FileSystemError::CorruptedFilesystem
+                }
+            })?;
+        self.cursor
+            .store(position + buffer.len(), Ordering::Release);
+
+        Ok(buffer.len())
+    }
+
+    /// Move the cursor, returning its new position. The cursor may be placed past the end of the file, a later write
+    /// fills the gap with zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GenericError` if the position would be negative, or an error if the size of the file could not be
+    /// read.
+    async fn seek(&self, seek: SeekMode) -> Result<usize, FileSystemError> {
+        let position = match seek {
+            SeekMode::Set(position) => Some(position),
+            SeekMode::End(offset) => self
+                .fs
+                .inode_data(self.inode)
+                .await?
+                .size
+                .checked_add_signed(offset),
+            SeekMode::Current(offset) => self
+                .cursor
+                .load(Ordering::Acquire)
+                .checked_add_signed(offset),
+        }
+        .ok_or(FileSystemError::GenericError)?;
+
+        self.cursor.store(position, Ordering::Release);
+        Ok(position)
+    }
+
+    fn inode(&self) -> Option<INodeReference> {
+        Some(self.inode)
+    }
+}
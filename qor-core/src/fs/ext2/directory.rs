@@ -0,0 +1,221 @@
+//! Helpers for manipulating the linked list of directory entry records stored within a single directory data block.
+//!
+//! Each record starts with an eight byte header (inode, record length, name length, type indicator) followed by the
+//! name. The record length of an entry may exceed the space required for its name, in which case the slack at the end
+//! of the record is unused and can be handed to a new record by splitting.
+
+use crate::utils::parser::ParseError;
+
+use super::Ext2Error;
+
+/// Size of the fixed portion of a directory entry record.
+const RECORD_HEADER_SIZE: usize = 8;
+
+/// Maximum length of a name stored in a directory entry record.
+pub const MAXIMUM_NAME_LENGTH: usize = 255;
+
+/// Errors which can occur while modifying the entries of a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryError<E> {
+    /// The underlying block device returned an error.
+    Device(E),
+    /// No entry with the requested name exists in the directory.
+    NotFound,
+    /// An entry with the requested name already exists in the directory.
+    AlreadyExists,
+    /// The requested name is empty, too long, or contains a path separator.
+    InvalidName,
+    /// There is no space left in the directory's data blocks for the entry.
+    NoSpace,
+    /// A structure read from the device is malformed.
+    Corrupted(ParseError),
+}
+
+impl<E> From<E> for DirectoryError<E> {
+    fn from(value: E) -> Self {
+        Self::Device(value)
+    }
+}
+
+impl<E> From<Ext2Error<E>> for DirectoryError<E> {
+    fn from(value: Ext2Error<E>) -> Self {
+        match value {
+            Ext2Error::Device(e) => Self::Device(e),
+            Ext2Error::Corrupted(e) => Self::Corrupted(e),
+        }
+    }
+}
+
+/// Header of a single directory entry record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordHeader {
+    pub inode: u32,
+    pub record_length: usize,
+    pub name_length: usize,
+    pub type_indicator: u8,
+}
+
+impl RecordHeader {
+    /// Read the header of the record at `offset` within `block`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the header extends past the end of the block.
+    #[must_use]
+    pub fn read(block: &[u8], offset: usize) -> Self {
+        let header = &block[offset..offset + RECORD_HEADER_SIZE];
+
+        Self {
+            inode: u32::from_le_bytes(header[0..4].try_into().unwrap()),
+            record_length: u16::from_le_bytes(header[4..6].try_into().unwrap()) as usize,
+            name_length: header[6] as usize,
+            type_indicator: header[7],
+        }
+    }
+
+    /// Write this header to the record at `offset` within `block`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the header extends past the end of the block, or if the record length does not fit
+    /// in a `u16`.
+    pub fn write(&self, block: &mut [u8], offset: usize) {
+        let header = &mut block[offset..offset + RECORD_HEADER_SIZE];
+
+        header[0..4].copy_from_slice(&self.inode.to_le_bytes());
+        header[4..6].copy_from_slice(&u16::try_from(self.record_length).unwrap().to_le_bytes());
+        header[6] = u8::try_from(self.name_length).unwrap();
+        header[7] = self.type_indicator;
+    }
+
+    /// Number of bytes actually used by this record, zero if the record is unused.
+    #[must_use]
+    pub const fn used_length(&self) -> usize {
+        if self.inode == 0 {
+            0
+        } else {
+            record_size(self.name_length)
+        }
+    }
+}
+
+/// Number of bytes required for a record holding a name of the given length, rounded up to a four byte boundary.
+#[must_use]
+pub const fn record_size(name_length: usize) -> usize {
+    (RECORD_HEADER_SIZE + name_length + 3) & !3
+}
+
+/// Returns true if the name can be stored in a directory entry record.
+#[must_use]
+pub fn is_valid_name(name: &[u8]) -> bool {
+    !name.is_empty() && name.len() <= MAXIMUM_NAME_LENGTH && !name.contains(&b'/')
+}
+
+/// Collect the offsets of every record in the block. Walking stops early at a record with a length too small to hold a
+/// header, as that indicates a corrupted block.
+#[must_use]
+pub fn record_offsets(block: &[u8]) -> alloc::vec::Vec<usize> {
+    let mut offsets = alloc::vec::Vec::new();
+    let mut offset = 0;
+
+    while offset + RECORD_HEADER_SIZE <= block.len() {
+        let header = RecordHeader::read(block, offset);
+        offsets.push(offset);
+
+        if header.record_length < RECORD_HEADER_SIZE {
+            break;
+        }
+
+        offset += header.record_length;
+    }
+
+    offsets
+}
+
+/// Find the offset of the in use record with the given name.
+#[must_use]
+pub fn find_record(block: &[u8], name: &[u8]) -> Option<usize> {
+    record_offsets(block).into_iter().find(|offset| {
+        let header = RecordHeader::read(block, *offset);
+        let name_start = offset + RECORD_HEADER_SIZE;
+
+        header.inode != 0
+            && header.name_length == name.len()
+            && block.get(name_start..name_start + header.name_length) == Some(name)
+    })
+}
+
+/// Replace the name of the record at `offset` if the new name fits within the existing record length. Returns false
+/// and leaves the block untouched if the record is too small.
+pub fn rename_record_in_place(block: &mut [u8], offset: usize, name: &[u8]) -> bool {
+    let mut header = RecordHeader::read(block, offset);
+
+    if record_size(name.len()) > header.record_length {
+        return false;
+    }
+
+    header.name_length = name.len();
+    header.write(block, offset);
+
+    let name_start = offset + RECORD_HEADER_SIZE;
+    block[name_start..name_start + name.len()].copy_from_slice(name);
+
+    true
+}
+
+/// Remove the record at `offset`, merging its space into the preceding record. The first record in a block has no
+/// preceding record, so it is instead marked as unused by clearing its inode.
+pub fn remove_record(block: &mut [u8], offset: usize) {
+    let mut header = RecordHeader::read(block, offset);
+
+    let previous = record_offsets(block)
+        .into_iter()
+        .take_while(|record| *record < offset)
+        .last();
+
+    if let Some(previous) = previous {
+        let mut previous_header = RecordHeader::read(block, previous);
+        previous_header.record_length += header.record_length;
+        previous_header.write(block, previous);
+    } else {
+        header.inode = 0;
+        header.name_length = 0;
+        header.write(block, offset);
+    }
+}
+
+/// Insert a new record into the block, either by reusing an unused record or by splitting the slack off the end of an
+/// existing record. Returns false if no record in the block has enough free space.
+pub fn insert_record(block: &mut [u8], inode: u32, type_indicator: u8, name: &[u8]) -> bool {
+    let required = record_size(name.len());
+
+    for offset in record_offsets(block) {
+        let mut header = RecordHeader::read(block, offset);
+        let used = header.used_length();
+
+        if used + required > header.record_length {
+            continue;
+        }
+
+        let new_offset = offset + used;
+        let new_header = RecordHeader {
+            inode,
+            record_length: header.record_length - used,
+            name_length: name.len(),
+            type_indicator,
+        };
+
+        if used > 0 {
+            header.record_length = used;
+            header.write(block, offset);
+        }
+
+        new_header.write(block, new_offset);
+        let name_start = new_offset + RECORD_HEADER_SIZE;
+        block[name_start..name_start + name.len()].copy_from_slice(name);
+
+        return true;
+    }
+
+    false
+}
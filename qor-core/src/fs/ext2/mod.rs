@@ -1,50 +1,163 @@
 use crate::{
     drivers::block::BlockDeviceDriver,
     interfaces::fs::{
-        FileDescriptor, FileSystem, FileSystemError, INodeData, INodeReference, MountableFileSystem,
+        FileDescriptor, FileSystem, FileSystemError, FileType, INodeData, INodeReference,
+        MountableFileSystem, OpenFlag, OpenFlags, SeekMode,
     },
     structures::{
         id::{GroupID, UserID},
         time::UnixTimestamp,
     },
     sync::Mutex,
-    utils::rawstr::OsStrRef,
+    utils::{parser::ParseError, rawstr::OsStrRef},
 };
 
-use self::raw::{DirectoryEntry, Inode, SuperBlock};
+use self::{
+    cache::BlockCache,
+    descriptor::Ext2FileDescriptor,
+    directory::DirectoryError,
+    raw::{DirectoryEntry, Inode, SuperBlock},
+};
 
+pub mod cache;
+pub mod descriptor;
+pub mod directory;
 pub mod raw;
 
+/// Errors which can occur while reading or writing the blocks and structures of the file system.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ext2Error<E> {
+    /// The underlying block device returned an error.
+    Device(E),
+    /// A structure read from the device, such as the super block or an inode, is malformed.
+    Corrupted(ParseError),
+}
+
+impl<E> From<E> for Ext2Error<E> {
+    fn from(value: E) -> Self {
+        Self::Device(value)
+    }
+}
+
+impl<E> Ext2Error<E> {
+    /// Convert to the error reported for an access to `inode`, a malformed structure is reported as a corrupted file
+    /// system and anything else as a bad inode
+    pub fn for_inode(self, inode: INodeReference) -> FileSystemError {
+        InodeReadError::<E>::from(self).for_inode(inode)
+    }
+}
+
+/// Errors which can occur while reading the data of an inode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InodeReadError<E> {
+    /// The underlying block device returned an error.
+    Device(E),
+    /// A structure read from the device is malformed.
+    Corrupted(ParseError),
+    /// The requested buffer is longer than the data stored in the inode.
+    BufferTooLarge { requested: usize, size: usize },
+    /// The inode's size reaches past the last block its triple indirect block can name, leaving this many bytes of
+    /// the buffer unread.
+    BeyondMaximumSize { unread: usize },
+}
+
+impl<E> From<E> for InodeReadError<E> {
+    fn from(value: E) -> Self {
+        Self::Device(value)
+    }
+}
+
+impl<E> InodeReadError<E> {
+    /// Convert to the error reported for an access to `inode`, a malformed structure is reported as a corrupted file
+    /// system and anything else as a bad inode
+    pub fn for_inode(self, inode: INodeReference) -> FileSystemError {
+        match self {
+            Self::Corrupted(_) => FileSystemError::CorruptedFilesystem,
+            Self::Device(_) | Self::BufferTooLarge { .. } | Self::BeyondMaximumSize { .. } => {
+                FileSystemError::BadInode(inode)
+            }
+        }
+    }
+}
+
+impl<E> From<Ext2Error<E>> for InodeReadError<E> {
+    fn from(value: Ext2Error<E>) -> Self {
+        match value {
+            Ext2Error::Device(e) => Self::Device(e),
+            Ext2Error::Corrupted(e) => Self::Corrupted(e),
+        }
+    }
+}
+
+/// Errors which can occur while writing the data of an inode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InodeWriteError<E> {
+    /// The underlying block device returned an error.
+    Device(E),
+    /// There are no free blocks left to grow the inode into.
+    NoSpace,
+    /// The inode would need blocks past those reached through its single indirect block.
+    TooLarge,
+    /// A block pointer of the inode names a block outside the file system.
+    BadBlock(u32),
+    /// A structure read from the device is malformed.
+    Corrupted(ParseError),
+}
+
+impl<E> From<E> for InodeWriteError<E> {
+    fn from(value: E) -> Self {
+        Self::Device(value)
+    }
+}
+
+impl<E> From<Ext2Error<E>> for InodeWriteError<E> {
+    fn from(value: Ext2Error<E>) -> Self {
+        match value {
+            Ext2Error::Device(e) => Self::Device(e),
+            Ext2Error::Corrupted(e) => Self::Corrupted(e),
+        }
+    }
+}
+
 const fn div_ceil(a: usize, b: usize) -> usize {
     (a + b - 1) / b
 }
 
 #[allow(clippy::module_name_repetitions)]
 pub struct Ext2FileSystem<E: 'static + core::fmt::Debug + Send + Sync> {
+    this: alloc::sync::Weak<Self>,
     device_id: core::sync::atomic::AtomicUsize,
     device: &'static (dyn BlockDeviceDriver<512, E, u32> + Send + Sync),
     cached_super_block: Mutex<Option<SuperBlock>>,
+    block_cache: Mutex<BlockCache>,
+    /// Held while an inode's data or the block bitmaps are modified, so writes are applied one at a time
+    write_lock: Mutex<()>,
 }
 
 impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
-    pub fn new(device: &'static (dyn BlockDeviceDriver<512, E, u32> + Send + Sync)) -> Self {
-        Self {
+    /// Construct a new [`Ext2FileSystem<E>`] on the given block device, caching up to `cache_capacity` KiB blocks.
+    /// The file system is shared so the descriptors opened on it can keep it alive.
+    pub fn new(
+        device: &'static (dyn BlockDeviceDriver<512, E, u32> + Send + Sync),
+        cache_capacity: usize,
+    ) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            this: this.clone(),
             device_id: 0.into(),
             device,
             cached_super_block: Mutex::new(None),
-        }
+            block_cache: Mutex::new(BlockCache::new(cache_capacity)),
+            write_lock: Mutex::new(()),
+        })
     }
 
     /// Returns the read super block of this [`Ext2FileSystem<E>`].
     ///
     /// # Errors
     ///
-    /// This function will return an error if the super block could not be read.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if the internal buffer is improperly sized.
-    pub async fn read_super_block(&self) -> Result<SuperBlock, E> {
+    /// This function will return an error if the super block could not be read, or is malformed.
+    pub async fn read_super_block(&self) -> Result<SuperBlock, Ext2Error<E>> {
         let lock = self.cached_super_block.async_lock().await;
         if let Some(cached_super_block) = lock.as_ref() {
             Ok(*cached_super_block)
@@ -54,7 +167,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
             let mut buffer = [0; 1024];
             self.read_kb_block(1, &mut buffer).await?;
 
-            let super_block = SuperBlock::from_bytes(&buffer);
+            let super_block = SuperBlock::from_bytes(&buffer).map_err(Ext2Error::Corrupted)?;
             self.cached_super_block
                 .async_lock()
                 .await
@@ -63,7 +176,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         }
     }
 
-    /// Read a given block from the block device.
+    /// Read a given block from the block device, consulting the block cache first.
     ///
     /// # Errors
     ///
@@ -73,6 +186,11 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         block: u32,
         buffer: &'a mut [u8; 1024],
     ) -> Result<&'a mut [u8; 1024], E> {
+        if let Some(cached) = self.block_cache.async_lock().await.get(block) {
+            *buffer = *cached;
+            return Ok(buffer);
+        }
+
         let mut inner_buffer = [[0u8; 512]; 2];
 
         self.device
@@ -80,9 +198,32 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
             .await?;
 
         *buffer = unsafe { core::mem::transmute(inner_buffer) };
+        self.block_cache.async_lock().await.insert(block, buffer);
+
         Ok(buffer)
     }
 
+    /// Write a given block to the block device, updating the block cache with the new contents.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the block could not be written.
+    pub async fn write_kb_block(&self, block: u32, buffer: &[u8; 1024]) -> Result<(), E> {
+        let inner_buffer = unsafe { core::mem::transmute::<[u8; 1024], [[u8; 512]; 2]>(*buffer) };
+
+        let result = self.device.write_blocks(2 * block, &inner_buffer).await;
+
+        let mut cache = self.block_cache.async_lock().await;
+        if result.is_ok() {
+            cache.insert(block, buffer);
+        } else {
+            // The state of the block on the device is unknown after a failed write
+            cache.invalidate(block);
+        }
+
+        result
+    }
+
     /// Read a given block from the block device.
     ///
     /// # Errors
@@ -96,20 +237,10 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         &self,
         block: u32,
         buffer: &'a mut [u8],
-    ) -> Result<&'a mut [u8], E> {
-        let sb = self.read_super_block().await?;
-        let block_size = sb.block_size(); // We know this will be a multiple of a KiB because the block size is stored
-                                          // as log base 2 of the block size in bytes minus 1024.
-        let block_size_kib = block_size / 1024;
+    ) -> Result<&'a mut [u8], Ext2Error<E>> {
+        let block_size = self.read_super_block().await?.block_size();
 
-        let block_index = block as usize * block_size_kib;
-
-        for kib_index in 0..block_size_kib {
-            let mut buf = [0; 1024];
-            self.read_kb_block((block_index + kib_index).try_into().unwrap(), &mut buf)
-                .await?;
-            buffer[1024 * kib_index..1024 * (kib_index + 1)].copy_from_slice(&buf);
-        }
+        self.read_blocks(block, &mut buffer[..block_size]).await?;
 
         Ok(buffer)
     }
@@ -123,27 +254,19 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
     /// # Panics
     ///
     /// This function will panic if the block index cannot fit within a `u32` or if the buffer is not the proper length.
-    pub async fn read_block_alloc<'a>(&self, block: u32) -> Result<alloc::vec::Vec<u8>, E> {
-        let sb = self.read_super_block().await?;
-        let block_size = sb.block_size(); // We know this will be a multiple of a KiB because the block size is stored
-                                          // as log base 2 of the block size in bytes minus 1024.
-        let block_size_kib = block_size / 1024;
-
-        let block_index = block as usize * block_size_kib;
-
-        let mut buffer = alloc::vec![0; block_size];
+    pub async fn read_block_alloc<'a>(
+        &self,
+        block: u32,
+    ) -> Result<alloc::vec::Vec<u8>, Ext2Error<E>> {
+        let mut buffer = alloc::vec![0; self.read_super_block().await?.block_size()];
 
-        for kib_index in 0..block_size_kib {
-            let mut buf = [0; 1024];
-            self.read_kb_block((block_index + kib_index).try_into().unwrap(), &mut buf)
-                .await?;
-            buffer[1024 * kib_index..1024 * (kib_index + 1)].copy_from_slice(&buf);
-        }
+        self.read_blocks(block, &mut buffer).await?;
 
         Ok(buffer)
     }
 
-    /// Read enough blocks to fill the given buffer up to a KiB boundary.
+    /// Read enough blocks to fill the given buffer up to a KiB boundary. Blocks held by the block cache are copied
+    /// from it, and each run of uncached blocks is read from the device with as few requests as possible.
     ///
     /// # Errors
     ///
@@ -156,27 +279,107 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         &self,
         block: u32,
         buffer: &'a mut [u8],
-    ) -> Result<&'a mut [u8], E> {
+    ) -> Result<&'a mut [u8], Ext2Error<E>> {
         let sb = self.read_super_block().await?;
         let block_size = sb.block_size(); // We know this will be a multiple of a KiB because the block size is stored
                                           // as log base 2 of the block size in bytes minus 1024.
         let block_size_kib = block_size / 1024;
 
         let block_index = block as usize * block_size_kib;
+        let kib_block = |kib_index: usize| -> u32 { (block_index + kib_index).try_into().unwrap() };
 
         let size_kib = buffer.len() / 1024;
+        let mut kib_index = 0;
+
+        while kib_index < size_kib {
+            if let Some(cached) = self
+                .block_cache
+                .async_lock()
+                .await
+                .get(kib_block(kib_index))
+            {
+                buffer[1024 * kib_index..1024 * (kib_index + 1)].copy_from_slice(cached);
+                kib_index += 1;
+                continue;
+            }
+
+            let run_start = kib_index;
+            {
+                let cache = self.block_cache.async_lock().await;
+                while kib_index < size_kib && !cache.contains(kib_block(kib_index)) {
+                    kib_index += 1;
+                }
+            }
 
-        for kib_index in 0..size_kib {
-            let mut buf = [0; 1024];
-            self.read_kb_block((block_index + kib_index).try_into().unwrap(), &mut buf)
+            let mut sectors = alloc::vec![[0u8; 512]; 2 * (kib_index - run_start)];
+            self.read_blocks_contiguous(2 * kib_block(run_start), &mut sectors)
                 .await?;
 
-            buffer[1024 * kib_index..1024 * (kib_index + 1)].copy_from_slice(&buf);
+            let mut cache = self.block_cache.async_lock().await;
+            for (offset, pair) in sectors.chunks_exact(2).enumerate() {
+                let data =
+                    &mut buffer[1024 * (run_start + offset)..1024 * (run_start + offset + 1)];
+                data[..512].copy_from_slice(&pair[0]);
+                data[512..].copy_from_slice(&pair[1]);
+
+                cache.insert(kib_block(run_start + offset), (&*data).try_into().unwrap());
+            }
         }
 
         Ok(buffer)
     }
 
+    /// Read a contiguous range of device sectors starting at `sector`, splitting the read into requests no larger
+    /// than the device's optimal I/O size. The block cache is not consulted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the sectors could not be read.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the sector index of a request cannot fit within a `u32`.
+    pub async fn read_blocks_contiguous(
+        &self,
+        sector: u32,
+        buffer: &mut [[u8; 512]],
+    ) -> Result<(), E> {
+        let request_size = self.device.optimal_io_blocks().max(1) as usize;
+
+        for (request_index, chunk) in buffer.chunks_mut(request_size).enumerate() {
+            let offset: u32 = (request_index * request_size).try_into().unwrap();
+            self.device.read_blocks(sector + offset, chunk).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a given block to the block device.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the block could not be written.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the block index cannot fit within a `u32` or if the buffer is not the proper length.
+    pub async fn write_block(&self, block: u32, buffer: &[u8]) -> Result<(), Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let block_size_kib = sb.block_size() / 1024;
+
+        let block_index = block as usize * block_size_kib;
+
+        for (kib_index, chunk) in buffer.chunks_exact(1024).enumerate() {
+            self.write_kb_block(
+                (block_index + kib_index).try_into().unwrap(),
+                chunk.try_into().unwrap(),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
     /// Read a block group descriptor with the given index.
     ///
     /// # Errors
@@ -189,11 +392,11 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
     pub async fn block_group_descriptor(
         &self,
         index: usize,
-    ) -> Result<raw::BlockGroupDescriptor, E> {
+    ) -> Result<raw::BlockGroupDescriptor, Ext2Error<E>> {
         let sb = self.read_super_block().await?;
         let desc_count = sb.block_group_count();
 
-        let desc_size = 64;
+        let desc_size = sb.block_group_descriptor_size();
         let buffer_length = div_ceil(desc_count * desc_size, 1024) * 1024;
         let mut buffer = alloc::vec![0; buffer_length];
 
@@ -203,10 +406,10 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         )
         .await?;
 
-        // Chunk the buffer into descriptor table sized chunks
+        // Chunk the buffer into descriptor table sized chunks, only the fields in the first 32 bytes are read
         let mut chunks = buffer.chunks_exact(desc_size);
         Ok(raw::BlockGroupDescriptor::from_bytes(
-            chunks.nth(index).unwrap().try_into().unwrap(),
+            chunks.nth(index).unwrap()[..32].try_into().unwrap(),
         ))
     }
 
@@ -214,12 +417,47 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the inode could not be read.
+    /// This function will return an error if the inode could not be read, or runs past the end of its block.
     ///
     /// # Panics
     ///
     /// This function will panic if the block index of the inode cannot fit within a `u32`.
-    pub async fn get_inode(&self, inode_index: u32) -> Result<Inode, E> {
+    pub async fn get_inode(&self, inode_index: u32) -> Result<Inode, Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let (block_index, offset) = self.inode_location(inode_index).await?;
+
+        let mut buffer = alloc::vec![0; sb.block_size()];
+        self.read_block(block_index, buffer.as_mut_slice()).await?;
+
+        Inode::from_bytes(&buffer[offset..]).map_err(Ext2Error::Corrupted)
+    }
+
+    /// Write an inode back to the block device, leaving the rest of its inode table block as it is.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the inode table could not be read or written.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the block index of the inode cannot fit within a `u32`.
+    pub async fn write_inode(&self, inode_index: u32, inode: &Inode) -> Result<(), Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let (block_index, offset) = self.inode_location(inode_index).await?;
+
+        let mut buffer = alloc::vec![0; sb.block_size()];
+        self.read_block(block_index, buffer.as_mut_slice()).await?;
+        inode.write_bytes(&mut buffer[offset..]);
+
+        self.write_block(block_index, &buffer).await
+    }
+
+    /// Find the block of the inode table holding an inode, and the offset of the inode within that block.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `inode_index` is zero, or the block index cannot fit within a `u32`.
+    async fn inode_location(&self, inode_index: u32) -> Result<(u32, usize), Ext2Error<E>> {
         // Inodes start at zero
         assert!(inode_index > 0);
         let inode_index = inode_index - 1;
@@ -238,12 +476,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         let block_index = block_index_start + inode_index_in_group / inodes_per_block;
         let index_in_block = inode_index_in_group % inodes_per_block;
 
-        let mut buffer = alloc::vec![0; sb.block_size()];
-        self.read_block(block_index.try_into().unwrap(), buffer.as_mut_slice())
-            .await?;
-
-        let mut chunks = buffer.chunks_exact(inode_size);
-        Ok(Inode::from_bytes(chunks.nth(index_in_block).unwrap()))
+        Ok((block_index.try_into().unwrap(), index_in_block * inode_size))
     }
 
     /// # Panics
@@ -253,7 +486,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         &self,
         block: u32,
         buffer: &mut [u8],
-    ) -> Result<alloc::vec::Vec<u32>, E> {
+    ) -> Result<alloc::vec::Vec<u32>, Ext2Error<E>> {
         self.read_block(block, buffer).await?;
 
         Ok(buffer
@@ -262,23 +495,111 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
             .collect())
     }
 
-    /// Read data from an inode.
+    /// Get the indices of the blocks holding the data of an inode, in order.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the data could not be read from the inode.
+    /// This function will return an error if any of the indirect blocks could not be read.
+    pub async fn inode_block_indices(
+        &self,
+        inode: &Inode,
+    ) -> Result<alloc::vec::Vec<u32>, Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let block_size = sb.block_size();
+        let block_count = div_ceil(inode.size(sb.use_64_bit_sizes()), block_size);
+
+        let mut indices = alloc::vec::Vec::with_capacity(block_count);
+        let mut buffer = alloc::vec![0; block_size];
+
+        indices.extend(inode.block_pointers[0..=11].iter().take(block_count));
+
+        // Single Indirect
+        if indices.len() < block_count {
+            let remaining = block_count - indices.len();
+            indices.extend(
+                self.read_block_to_u32_buffer(inode.block_pointers[12], &mut buffer)
+                    .await?
+                    .into_iter()
+                    .take(remaining),
+            );
+        }
+
+        // Double Indirect
+        if indices.len() < block_count {
+            for block_index_a in self
+                .read_block_to_u32_buffer(inode.block_pointers[13], &mut buffer)
+                .await?
+            {
+                let remaining = block_count - indices.len();
+                if remaining == 0 {
+                    break;
+                }
+
+                indices.extend(
+                    self.read_block_to_u32_buffer(block_index_a, &mut buffer)
+                        .await?
+                        .into_iter()
+                        .take(remaining),
+                );
+            }
+        }
+
+        // Triple Indirect
+        if indices.len() < block_count {
+            'outer: for block_index_a in self
+                .read_block_to_u32_buffer(inode.block_pointers[14], &mut buffer)
+                .await?
+            {
+                for block_index_b in self
+                    .read_block_to_u32_buffer(block_index_a, &mut buffer)
+                    .await?
+                {
+                    let remaining = block_count - indices.len();
+                    if remaining == 0 {
+                        break 'outer;
+                    }
+
+                    indices.extend(
+                        self.read_block_to_u32_buffer(block_index_b, &mut buffer)
+                            .await?
+                            .into_iter()
+                            .take(remaining),
+                    );
+                }
+            }
+        }
+
+        Ok(indices)
+    }
+
+    /// Read data from an inode. An empty buffer, which is all an empty file can be read into, is filled without reading
+    /// any of the inode's blocks.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the buffer is bigger than the file size or maximum file size for the file system.
-    pub async fn read_inode_data(&self, inode: &Inode, buffer: &mut [u8]) -> Result<(), E> {
+    /// This function will return an error if the data could not be read from the inode, if the buffer is bigger than
+    /// the file size, or if the buffer is bigger than the maximum file size for the file system.
+    #[allow(clippy::too_many_lines)]
+    pub async fn read_inode_data(
+        &self,
+        inode: &Inode,
+        buffer: &mut [u8],
+    ) -> Result<(), InodeReadError<E>> {
         let sb = self.read_super_block().await?;
         let block_size = sb.block_size();
 
-        assert!(
-            buffer.len() <= inode.size(sb.use_64_bit_sizes()),
-            "Buffer is bigger than file size"
-        );
+        let size = inode.size(sb.use_64_bit_sizes());
+        if buffer.len() > size {
+            return Err(InodeReadError::BufferTooLarge {
+                requested: buffer.len(),
+                size,
+            });
+        }
+
+        // The block pointers of an empty file are left as zero, so they must not be followed
+        if buffer.is_empty() {
+            return Ok(());
+        }
 
         let mut remaining_buffer = buffer;
         let mut this_buffer = alloc::vec![0; block_size];
@@ -303,10 +624,11 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
         }
 
         // Single Indirect
-        for block_index in self
+        let indirect_pointers = self
             .read_block_to_u32_buffer(inode.block_pointers[12], &mut this_buffer)
-            .await?
-        {
+            .await?;
+
+        for block_index in indirect_pointers {
             if remaining_buffer.len() < block_size {
                 self.read_block(block_index, this_buffer.as_mut_slice())
                     .await?;
@@ -351,7 +673,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
 
         // Triple Indirect
         for block_index_a in self
-            .read_block_to_u32_buffer(inode.block_pointers[13], &mut this_buffer)
+            .read_block_to_u32_buffer(inode.block_pointers[14], &mut this_buffer)
             .await?
         {
             for block_index_b in self
@@ -366,6 +688,8 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
                         self.read_block(block_index_c, this_buffer.as_mut_slice())
                             .await?;
                         remaining_buffer.copy_from_slice(&this_buffer[0..remaining_buffer.len()]);
+                        let l = remaining_buffer.len();
+                        remaining_buffer = &mut remaining_buffer[l..];
                     } else {
                         self.read_block(block_index_c, remaining_buffer).await?;
                         remaining_buffer = &mut remaining_buffer[block_size..];
@@ -378,12 +702,9 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
             }
         }
 
-        assert!(
-            remaining_buffer.is_empty(),
-            "Buffer was not filled completely"
-        );
-
-        Ok(())
+        Err(InodeReadError::BeyondMaximumSize {
+            unread: remaining_buffer.len(),
+        })
     }
 
     /// Read directory entries from an inode.
@@ -394,7 +715,7 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
     pub async fn read_directory_entries(
         &self,
         inode: &Inode,
-    ) -> Result<alloc::vec::Vec<DirectoryEntry>, E> {
+    ) -> Result<alloc::vec::Vec<DirectoryEntry>, InodeReadError<E>> {
         let sb = self.read_super_block().await?;
         // Determine if sizes are 64 bits
         let use_64_bit_sizes = sb.use_64_bit_sizes();
@@ -404,63 +725,523 @@ impl<E: 'static + core::fmt::Debug + Send + Sync> Ext2FileSystem<E> {
 
         Ok(DirectoryEntry::from_bytes(buffer.as_slice()))
     }
-}
 
-use alloc::{boxed::Box, string::ToString, sync::Arc};
+    /// Read the target of a symbolic link. Targets of up to 60 bytes are stored in place of the block pointers when
+    /// no blocks are allocated to the inode, longer ones are stored as the inode's data.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the target is stored in blocks which could not be read.
+    pub async fn read_symlink(
+        &self,
+        inode: &Inode,
+    ) -> Result<alloc::vec::Vec<u8>, InodeReadError<E>> {
+        // The upper size field holds the directory ACL for anything other than a regular file
+        let size = inode.size(false);
+
+        if inode.disk_sectors == 0 && size <= 60 {
+            return Ok(inode
+                .block_pointers
+                .iter()
+                .flat_map(|pointer| pointer.to_le_bytes())
+                .take(size)
+                .collect());
+        }
+
+        let mut buffer = alloc::vec![0; size];
+        self.read_inode_data(inode, &mut buffer).await?;
 
-#[async_trait::async_trait]
-impl<E: core::fmt::Debug + Send + Sync> FileSystem for Ext2FileSystem<E> {
-    async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
-        Ok(INodeReference {
-            inode: 2,
-            device: self.device_id.load(core::sync::atomic::Ordering::Acquire),
-        })
+        Ok(buffer)
     }
 
-    async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
-        let sb = self
-            .read_super_block()
-            .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
-        // Determine if sizes are 64 bits
-        let use_64_bit_sizes = sb.use_64_bit_sizes();
+    /// Read from the data of an inode starting `offset` bytes in, returning the number of bytes read. This is less
+    /// than the length of `buffer` if the end of the file is reached first. Unallocated blocks read as zeros.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the blocks of the inode could not be read.
+    pub async fn read_inode_range(
+        &self,
+        inode: &Inode,
+        offset: usize,
+        buffer: &mut [u8],
+    ) -> Result<usize, Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let block_size = sb.block_size();
 
-        let inner = self
-            .get_inode(inode.inode.try_into().unwrap())
-            .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
+        let size = inode.size(sb.use_64_bit_sizes());
+        let length = buffer.len().min(size.saturating_sub(offset));
+        if length == 0 {
+            return Ok(0);
+        }
 
-        Ok(INodeData {
-            mode: inner.mode.into(),
-            link_count: inner.hard_link_count as usize,
-            uid: UserID(inner.user_id),
-            gid: GroupID(inner.group_id),
-            size: inner.size(use_64_bit_sizes),
-            access_time: UnixTimestamp(u64::from(inner.last_access_time)),
-            modify_time: UnixTimestamp(u64::from(inner.last_modify_time)),
-            change_time: UnixTimestamp(u64::from(inner.last_modify_time)),
-            reference: inode,
-        })
+        let block_indices = self.inode_block_indices(inode).await?;
+        let mut block = alloc::vec![0; block_size];
+
+        let end = offset + length;
+        let mut position = offset;
+        while position < end {
+            let within = position % block_size;
+            let count = (block_size - within).min(end - position);
+            let destination = &mut buffer[position - offset..position - offset + count];
+
+            match block_indices[position / block_size] {
+                0 => destination.fill(0),
+                block_index => {
+                    self.read_block(block_index, &mut block).await?;
+                    destination.copy_from_slice(&block[within..within + count]);
+                }
+            }
+
+            position += count;
+        }
+
+        Ok(length)
     }
 
-    async fn directory_entries(
+    /// Write `data` into an inode starting `offset` bytes in, growing the file if it ends past the current end. Any
+    /// gap between the old end of the file and `offset` is filled with zeros.
+    ///
+    /// Blocks are only allocated directly or through the single indirect block, so a file can grow to twelve plus a
+    /// quarter of the block size blocks.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there are not enough free blocks, the file would grow past its single
+    /// indirect block, or the device could not be read or written.
+    pub async fn write_inode_range(
         &self,
-        inode: INodeReference,
-    ) -> Result<alloc::vec::Vec<crate::interfaces::fs::DirectoryEntry<'_>>, FileSystemError> {
-        // Get this device id
-        let device_id = self.device_id.load(core::sync::atomic::Ordering::Acquire);
+        inode_index: u32,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), InodeWriteError<E>> {
+        let _guard = self.write_lock.async_lock().await;
 
-        // First, read the inode structure from disk
-        let inode_data = self
-            .get_inode(inode.inode.try_into().unwrap())
-            .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
+        let sb = self.read_super_block().await?;
+        let block_size = sb.block_size();
 
-        // Next, load the directory entries
-        let directory_entries = self
-            .read_directory_entries(&inode_data)
+        let mut inode = self.get_inode(inode_index).await?;
+        let size = inode.size(sb.use_64_bit_sizes());
+
+        // New blocks can only be allocated up to the end of the single indirect block, anything past the current end of
+        // the file beyond that can not be written
+        let end = offset
+            .checked_add(data.len())
+            .filter(|end| {
+                div_ceil(*end, block_size) <= div_ceil(size, block_size).max(12 + block_size / 4)
+            })
+            .ok_or(InodeWriteError::TooLarge)?;
+
+        // Writing past the end of the file writes zeros up to the start of the data, a block at a time
+        let zeros = alloc::vec![0; block_size];
+        let mut result = Ok(());
+        let mut position = size;
+        while result.is_ok() && position < offset {
+            let count = (block_size - position % block_size).min(offset - position);
+            result = self
+                .write_blocks_of(&mut inode, position, &zeros[..count], block_size)
+                .await;
+            position += count;
+        }
+
+        if result.is_ok() {
+            result = self
+                .write_blocks_of(&mut inode, offset, data, block_size)
+                .await;
+        }
+
+        if result.is_ok() && end > size {
+            inode.set_size(end, sb.use_64_bit_sizes());
+        }
+
+        // Blocks taken before a failure are kept by the inode, so they are not lost from the bitmaps
+        self.write_inode(inode_index, &inode).await?;
+        result
+    }
+
+    /// Copy `data` into the blocks of an inode starting `offset` bytes in, allocating any which are missing. The caller
+    /// must hold the write lock.
+    async fn write_blocks_of(
+        &self,
+        inode: &mut Inode,
+        offset: usize,
+        data: &[u8],
+        block_size: usize,
+    ) -> Result<(), InodeWriteError<E>> {
+        let mut block = alloc::vec![0; block_size];
+
+        let end = offset + data.len();
+        let mut position = offset;
+        while position < end {
+            let within = position % block_size;
+            let count = (block_size - within).min(end - position);
+            let block_index = self
+                .ensure_block(inode, position / block_size, block_size)
+                .await?;
+
+            // Only a partially overwritten block needs its old contents
+            if count < block_size {
+                self.read_block(block_index, &mut block).await?;
+            }
+            block[within..within + count]
+                .copy_from_slice(&data[position - offset..position - offset + count]);
+            self.write_block(block_index, &block).await?;
+
+            position += count;
+        }
+
+        Ok(())
+    }
+
+    /// Free every block holding the data of a regular file, leaving it empty. Other kinds of inode, such as symbolic
+    /// links whose target is kept in the block pointers, must not be cleared this way.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the inode, its indirect blocks, or the block bitmaps could not be read or
+    /// written, or if the inode points at a block outside the file system.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of sectors freed cannot fit within a `u32`.
+    pub async fn clear_inode_data(&self, inode_index: u32) -> Result<(), InodeWriteError<E>> {
+        let _guard = self.write_lock.async_lock().await;
+
+        let sb = self.read_super_block().await?;
+        let mut inode = self.get_inode(inode_index).await?;
+
+        let mut freed = 0;
+        for (index, pointer) in inode.block_pointers.iter().enumerate() {
+            // The last three pointers lead through one, two and three levels of indirect blocks
+            freed += self
+                .free_block_tree(*pointer, index.saturating_sub(11))
+                .await?;
+        }
+
+        inode.block_pointers = [0; 15];
+        inode.disk_sectors = inode
+            .disk_sectors
+            .saturating_sub(u32::try_from(freed * sb.block_size() / 512).unwrap());
+        inode.set_size(0, sb.use_64_bit_sizes());
+
+        Ok(self.write_inode(inode_index, &inode).await?)
+    }
+
+    /// Get the block holding the `position`th block of an inode's data, allocating it if there is none. Blocks past
+    /// those reached through the single indirect block are only followed, never allocated. The caller must hold the
+    /// write lock.
+    async fn ensure_block(
+        &self,
+        inode: &mut Inode,
+        position: usize,
+        block_size: usize,
+    ) -> Result<u32, InodeWriteError<E>> {
+        if position < 12 {
+            if inode.block_pointers[position] == 0 {
+                inode.block_pointers[position] = self.allocate_block(inode).await?;
+            }
+
+            return Ok(inode.block_pointers[position]);
+        }
+
+        let slot = position - 12;
+        if slot >= block_size / 4 {
+            return self
+                .inode_block_indices(inode)
+                .await?
+                .get(position)
+                .copied()
+                .filter(|block_index| *block_index != 0)
+                .ok_or(InodeWriteError::TooLarge);
+        }
+
+        if inode.block_pointers[12] == 0 {
+            inode.block_pointers[12] = self.allocate_block(inode).await?;
+        }
+
+        let mut buffer = alloc::vec![0; block_size];
+        let pointers = self
+            .read_block_to_u32_buffer(inode.block_pointers[12], &mut buffer)
+            .await?;
+        if pointers[slot] != 0 {
+            return Ok(pointers[slot]);
+        }
+
+        let block_index = self.allocate_block(inode).await?;
+        buffer[slot * 4..slot * 4 + 4].copy_from_slice(&block_index.to_le_bytes());
+        self.write_block(inode.block_pointers[12], &buffer).await?;
+
+        Ok(block_index)
+    }
+
+    /// Take a free block from the block bitmaps, zero it, and count it against `inode`. The caller must hold the write
+    /// lock.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the block index cannot fit within a `u32`.
+    async fn allocate_block(&self, inode: &mut Inode) -> Result<u32, InodeWriteError<E>> {
+        let sb = self.read_super_block().await?;
+        let block_size = sb.block_size();
+        let blocks_per_group = sb.blocks_per_block_group as usize;
+        let mut bitmap = alloc::vec![0u8; block_size];
+
+        for group in 0..sb.block_group_count() {
+            let descriptor = self.block_group_descriptor(group).await?;
+            if descriptor.remaining_unallocated_blocks == 0 {
+                continue;
+            }
+
+            // The bitmap of the last group may cover blocks past the end of the device
+            let first = sb.super_block_block_number as usize + group * blocks_per_group;
+            let count = blocks_per_group.min((sb.block_count as usize).saturating_sub(first));
+
+            self.read_block(descriptor.block_usage_bitmap, &mut bitmap)
+                .await?;
+            let Some(bit) = (0..count).find(|bit| bitmap[bit / 8] & (1 << (bit % 8)) == 0) else {
+                continue;
+            };
+
+            bitmap[bit / 8] |= 1 << (bit % 8);
+            self.write_block(descriptor.block_usage_bitmap, &bitmap)
+                .await?;
+            self.adjust_free_blocks(group, -1).await?;
+
+            let block_index = u32::try_from(first + bit).unwrap();
+            bitmap.fill(0);
+            self.write_block(block_index, &bitmap).await?;
+            inode.disk_sectors += u32::try_from(block_size / 512).unwrap();
+
+            return Ok(block_index);
+        }
+
+        Err(InodeWriteError::NoSpace)
+    }
+
+    /// Free `block_index` and, if it is an indirect block `depth` levels above the data, every block it leads to.
+    /// Returns the number of blocks freed. The caller must hold the write lock.
+    #[async_recursion::async_recursion]
+    async fn free_block_tree(
+        &self,
+        block_index: u32,
+        depth: usize,
+    ) -> Result<usize, InodeWriteError<E>> {
+        if block_index == 0 {
+            return Ok(0);
+        }
+
+        let mut freed = 1;
+        if depth > 0 {
+            let sb = self.read_super_block().await?;
+            let mut buffer = alloc::vec![0; sb.block_size()];
+
+            for pointer in self
+                .read_block_to_u32_buffer(block_index, &mut buffer)
+                .await?
+            {
+                freed += self.free_block_tree(pointer, depth - 1).await?;
+            }
+        }
+
+        let sb = self.read_super_block().await?;
+        let index = block_index
+            .checked_sub(sb.super_block_block_number)
+            .filter(|_| block_index < sb.block_count)
+            .ok_or(InodeWriteError::BadBlock(block_index))? as usize;
+        let (group, bit) = (
+            index / sb.blocks_per_block_group as usize,
+            index % sb.blocks_per_block_group as usize,
+        );
+
+        let descriptor = self.block_group_descriptor(group).await?;
+        let mut bitmap = alloc::vec![0u8; sb.block_size()];
+        self.read_block(descriptor.block_usage_bitmap, &mut bitmap)
+            .await?;
+        bitmap[bit / 8] &= !(1 << (bit % 8));
+        self.write_block(descriptor.block_usage_bitmap, &bitmap)
+            .await?;
+        self.adjust_free_blocks(group, 1).await?;
+
+        Ok(freed)
+    }
+
+    /// Change the count of free blocks in a block group's descriptor, and in the super block, by `delta`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the block index of the descriptor cannot fit within a `u32`.
+    async fn adjust_free_blocks(&self, group: usize, delta: i16) -> Result<(), Ext2Error<E>> {
+        let sb = self.read_super_block().await?;
+        let mut buffer = [0; 1024];
+
+        // Descriptors are laid out as read by `block_group_descriptor`, the free block count is 12 bytes in
+        let offset = group * sb.block_group_descriptor_size() + 12;
+        let kb_block =
+            sb.block_group_descriptor_table_index() * (sb.block_size() / 1024) + offset / 1024;
+        let kb_block = u32::try_from(kb_block).unwrap();
+        let field = offset % 1024..offset % 1024 + 2;
+
+        self.read_kb_block(kb_block, &mut buffer).await?;
+        let free = u16::from_le_bytes(buffer[field.clone()].try_into().unwrap());
+        buffer[field].copy_from_slice(&free.saturating_add_signed(delta).to_le_bytes());
+        self.write_kb_block(kb_block, &buffer).await?;
+
+        // The super block always sits at the start of the second KiB of the device
+        self.read_kb_block(1, &mut buffer).await?;
+        let free = u32::from_le_bytes(buffer[12..16].try_into().unwrap())
+            .saturating_add_signed(i32::from(delta));
+        buffer[12..16].copy_from_slice(&free.to_le_bytes());
+        self.write_kb_block(1, &buffer).await?;
+
+        if let Some(cached) = self.cached_super_block.async_lock().await.as_mut() {
+            cached.unallocated_blocks = free;
+        }
+
+        Ok(())
+    }
+
+    /// Rename an entry within the directory `parent` from `old_name` to `new_name`. The entry is renamed in place if
+    /// the new name fits within its existing record, otherwise the record is removed (merging its space into the
+    /// preceding record) and a new record is split off the slack of a record with enough free space.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `old_name` does not exist, `new_name` already exists or is invalid, there
+    /// is no space in the directory for the new record, or the directory could not be read or written.
+    pub async fn rename(
+        &self,
+        parent: &Inode,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), DirectoryError<E>> {
+        let old_name = old_name.as_bytes();
+        let new_name = new_name.as_bytes();
+
+        if !directory::is_valid_name(new_name) {
+            return Err(DirectoryError::InvalidName);
+        }
+
+        let block_indices = self.inode_block_indices(parent).await?;
+        let mut blocks = alloc::vec::Vec::with_capacity(block_indices.len());
+
+        for block_index in &block_indices {
+            blocks.push(self.read_block_alloc(*block_index).await?);
+        }
+
+        let (old_block, old_offset) = blocks
+            .iter()
+            .enumerate()
+            .find_map(|(i, block)| directory::find_record(block, old_name).map(|o| (i, o)))
+            .ok_or(DirectoryError::NotFound)?;
+
+        if old_name == new_name {
+            return Ok(());
+        }
+
+        if blocks
+            .iter()
+            .any(|block| directory::find_record(block, new_name).is_some())
+        {
+            return Err(DirectoryError::AlreadyExists);
+        }
+
+        let mut dirty = alloc::vec![false; blocks.len()];
+
+        if directory::rename_record_in_place(&mut blocks[old_block], old_offset, new_name) {
+            dirty[old_block] = true;
+        } else {
+            let header = directory::RecordHeader::read(&blocks[old_block], old_offset);
+
+            directory::remove_record(&mut blocks[old_block], old_offset);
+            dirty[old_block] = true;
+
+            let new_block = blocks
+                .iter_mut()
+                .position(|block| {
+                    directory::insert_record(block, header.inode, header.type_indicator, new_name)
+                })
+                .ok_or(DirectoryError::NoSpace)?;
+            dirty[new_block] = true;
+        }
+
+        for ((block_index, block), dirty) in block_indices.iter().zip(&blocks).zip(dirty) {
+            if dirty {
+                self.write_block(*block_index, block).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+};
+
+#[async_trait::async_trait]
+impl<E: core::fmt::Debug + Send + Sync> FileSystem for Ext2FileSystem<E> {
+    async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
+        Ok(INodeReference {
+            inode: 2,
+            device: self.device_id.load(core::sync::atomic::Ordering::Acquire),
+        })
+    }
+
+    async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
+        let sb = self
+            .read_super_block()
+            .await
+            .map_err(|e| e.for_inode(inode))?;
+        // Determine if sizes are 64 bits
+        let use_64_bit_sizes = sb.use_64_bit_sizes();
+
+        let inner = self
+            .get_inode(inode.inode.try_into().unwrap())
             .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
+            .map_err(|e| e.for_inode(inode))?;
+
+        Ok(INodeData {
+            mode: inner.mode.into(),
+            link_count: inner.hard_link_count as usize,
+            uid: UserID(inner.user_id),
+            gid: GroupID(inner.group_id),
+            size: inner.size(use_64_bit_sizes),
+            // ext2 keeps the same three POSIX timestamps, `i_atime`, `i_mtime` and `i_ctime`, so each maps across
+            // directly. The deletion time has no counterpart and is only meaningful for freed inodes.
+            access_time: UnixTimestamp(u64::from(inner.last_access_time)),
+            modify_time: UnixTimestamp(u64::from(inner.last_modify_time)),
+            change_time: UnixTimestamp(u64::from(inner.change_time)),
+            reference: inode,
+        })
+    }
+
+    async fn directory_entries(
+        &self,
+        inode: INodeReference,
+    ) -> Result<alloc::vec::Vec<crate::interfaces::fs::DirectoryEntry<'_>>, FileSystemError> {
+        // Get this device id
+        let device_id = self.device_id.load(core::sync::atomic::Ordering::Acquire);
+
+        // Entries only record their type if the file system was created with that feature
+        let typed = self
+            .read_super_block()
+            .await
+            .map_err(|_| FileSystemError::CorruptedFilesystem)?
+            .directory_entries_have_type();
+
+        // First, read the inode structure from disk
+        let inode_data = self
+            .get_inode(inode.inode.try_into().unwrap())
+            .await
+            .map_err(|e| e.for_inode(inode))?;
+
+        // Next, load the directory entries
+        let directory_entries = self
+            .read_directory_entries(&inode_data)
+            .await
+            .map_err(|e| e.for_inode(inode))?;
 
         // Finally, we map to the regular struct
         Ok(directory_entries
@@ -471,15 +1252,58 @@ impl<E: core::fmt::Debug + Send + Sync> FileSystem for Ext2FileSystem<E> {
                     device: device_id,
                 },
                 name: OsStrRef::new(&entry.name).to_string().into(),
+                file_type: if typed {
+                    entry.file_type()
+                } else {
+                    FileType::Unknown
+                },
             })
             .collect())
     }
 
-    async fn open(
+    /// Open a descriptor on the data of `inode`. [`OpenFlag::Truncate`] frees the blocks of a regular file, and
+    /// [`OpenFlag::Append`] starts the cursor at the end of the file.
+    async fn open_with(
         &self,
-        _inode: INodeReference,
+        inode: INodeReference,
+        flags: OpenFlags,
     ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
-        todo!()
+        let inode_index =
+            u32::try_from(inode.inode).map_err(|_| FileSystemError::BadInode(inode))?;
+
+        if flags & OpenFlag::Truncate {
+            let inode_data = self
+                .get_inode(inode_index)
+                .await
+                .map_err(|e| e.for_inode(inode))?;
+
+            match FileType::from_mode(inode_data.mode.into()) {
+                FileType::Regular => {}
+                FileType::Directory => return Err(FileSystemError::IsDirectory),
+                _ => return Err(FileSystemError::Unsupported),
+            }
+
+            self.clear_inode_data(inode_index)
+                .await
+                .map_err(|e| match e {
+                    InodeWriteError::BadBlock(_) | InodeWriteError::Corrupted(_) => {
+                        FileSystemError::CorruptedFilesystem
+                    }
+                    _ => FileSystemError::BadInode(inode),
+                })?;
+        }
+
+        let fs = self
+            .this
+            .upgrade()
+            .expect("Ext2 file systems are only constructed behind an Arc");
+        let descriptor = Ext2FileDescriptor::new(fs, inode);
+
+        if flags & OpenFlag::Append {
+            descriptor.seek(SeekMode::End(0)).await?;
+        }
+
+        Ok(Arc::new(descriptor))
     }
 
     async fn read_to_data(
@@ -496,16 +1320,58 @@ impl<E: core::fmt::Debug + Send + Sync> FileSystem for Ext2FileSystem<E> {
         let inode_data = self
             .get_inode(inode.inode.try_into().unwrap())
             .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
+            .map_err(|e| e.for_inode(inode))?;
 
         // Next, load the data from the file
         let mut buffer = alloc::vec![0; inode_data.size(sb.use_64_bit_sizes())];
         self.read_inode_data(&inode_data, &mut buffer)
             .await
-            .map_err(|_| FileSystemError::BadInode(inode))?;
+            .map_err(|e| e.for_inode(inode))?;
 
         Ok(buffer)
     }
+
+    async fn readlink(&self, inode: INodeReference) -> Result<String, FileSystemError> {
+        let inode_data = self
+            .get_inode(inode.inode.try_into().unwrap())
+            .await
+            .map_err(|e| e.for_inode(inode))?;
+
+        if FileType::from_mode(inode_data.mode.into()) != FileType::SymbolicLink {
+            return Err(FileSystemError::NotSymbolicLink);
+        }
+
+        let target = self
+            .read_symlink(&inode_data)
+            .await
+            .map_err(|e| e.for_inode(inode))?;
+
+        String::from_utf8(target).map_err(|_| FileSystemError::CorruptedFilesystem)
+    }
+
+    async fn rename(
+        &self,
+        parent: INodeReference,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), FileSystemError> {
+        let parent_data = self
+            .get_inode(parent.inode.try_into().unwrap())
+            .await
+            .map_err(|e| e.for_inode(parent))?;
+
+        Self::rename(self, &parent_data, old_name, new_name)
+            .await
+            .map_err(|e| match e {
+                DirectoryError::Device(_) => FileSystemError::BadInode(parent),
+                DirectoryError::NotFound => FileSystemError::PathNotFound,
+                DirectoryError::AlreadyExists => FileSystemError::AlreadyExists,
+                DirectoryError::InvalidName | DirectoryError::NoSpace => {
+                    FileSystemError::GenericError
+                }
+                DirectoryError::Corrupted(_) => FileSystemError::CorruptedFilesystem,
+            })
+    }
 }
 
 impl<E: core::fmt::Debug + Send + Sync> MountableFileSystem for Ext2FileSystem<E> {
@@ -514,3 +1380,1022 @@ impl<E: core::fmt::Debug + Send + Sync> MountableFileSystem for Ext2FileSystem<E
             .store(device_id, core::sync::atomic::Ordering::Release);
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::boxed::Box;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::drivers::block::BlockDeviceDriver;
+
+    /// Block device which fills every sector with its own index, and counts the number of reads issued to it.
+    struct CountingDevice {
+        reads: AtomicUsize,
+        writes: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl BlockDeviceDriver<512, (), u32> for CountingDevice {
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn initialize(&self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        async fn read_blocks<'b, 'a: 'b>(
+            &'b self,
+            index: u32,
+            buffer: &'a mut [[u8; 512]],
+        ) -> Result<(), ()> {
+            self.reads.fetch_add(1, Ordering::AcqRel);
+            for (offset, sector) in buffer.iter_mut().enumerate() {
+                sector.fill(u8::try_from(index as usize + offset).unwrap());
+            }
+            Ok(())
+        }
+
+        async fn write_blocks<'b, 'a: 'b>(
+            &'b self,
+            _index: u32,
+            _buffer: &'a [[u8; 512]],
+        ) -> Result<(), ()> {
+            self.writes.fetch_add(1, Ordering::AcqRel);
+            Ok(())
+        }
+    }
+
+    fn counting_file_system(
+        capacity: usize,
+    ) -> (
+        &'static CountingDevice,
+        alloc::sync::Arc<super::Ext2FileSystem<()>>,
+    ) {
+        let device = Box::leak(Box::new(CountingDevice {
+            reads: AtomicUsize::new(0),
+            writes: AtomicUsize::new(0),
+        }));
+
+        (device, super::Ext2FileSystem::new(device, capacity))
+    }
+
+    /// Block device backed by an in memory disk image.
+    struct MemoryDevice {
+        image: spin::Mutex<alloc::vec::Vec<u8>>,
+        writes: AtomicUsize,
+        optimal_io_blocks: u32,
+        /// Starting sector and length of every read request issued to the device
+        reads: spin::Mutex<alloc::vec::Vec<(u32, usize)>>,
+    }
+
+    impl MemoryDevice {
+        fn new(image: alloc::vec::Vec<u8>, optimal_io_blocks: u32) -> &'static Self {
+            Box::leak(Box::new(Self {
+                image: spin::Mutex::new(image),
+                writes: AtomicUsize::new(0),
+                optimal_io_blocks,
+                reads: spin::Mutex::new(alloc::vec::Vec::new()),
+            }))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl BlockDeviceDriver<512, (), u32> for MemoryDevice {
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn initialize(&self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn optimal_io_blocks(&self) -> u32 {
+            self.optimal_io_blocks
+        }
+
+        async fn read_blocks<'b, 'a: 'b>(
+            &'b self,
+            index: u32,
+            buffer: &'a mut [[u8; 512]],
+        ) -> Result<(), ()> {
+            self.reads.lock().push((index, buffer.len()));
+
+            let image = self.image.lock();
+            for (offset, sector) in buffer.iter_mut().enumerate() {
+                let start = (index as usize + offset) * 512;
+                sector.copy_from_slice(&image[start..start + 512]);
+            }
+            Ok(())
+        }
+
+        async fn write_blocks<'b, 'a: 'b>(
+            &'b self,
+            index: u32,
+            buffer: &'a [[u8; 512]],
+        ) -> Result<(), ()> {
+            self.writes.fetch_add(1, Ordering::AcqRel);
+            let mut image = self.image.lock();
+            for (offset, sector) in buffer.iter().enumerate() {
+                let start = (index as usize + offset) * 512;
+                image[start..start + 512].copy_from_slice(sector);
+            }
+            Ok(())
+        }
+    }
+
+    const DIRECTORY_BLOCK: usize = 5;
+
+    fn write_record(image: &mut [u8], offset: usize, inode: u32, record_length: u16, name: &str) {
+        let start = DIRECTORY_BLOCK * 1024 + offset;
+        image[start..start + 4].copy_from_slice(&inode.to_le_bytes());
+        image[start + 4..start + 6].copy_from_slice(&record_length.to_le_bytes());
+        image[start + 6] = u8::try_from(name.len()).unwrap();
+        image[start + 7] = 2;
+        image[start + 8..start + 8 + name.len()].copy_from_slice(name.as_bytes());
+    }
+
+    /// Construct a file system with 1 KiB blocks holding a single directory in block 5 containing `.`, `..`, `a` and
+    /// `bb`, along with the inode for that directory.
+    fn directory_file_system() -> (
+        &'static MemoryDevice,
+        alloc::sync::Arc<super::Ext2FileSystem<()>>,
+        super::raw::Inode,
+    ) {
+        let mut image = alloc::vec![0; 8 * 1024];
+
+        // Super block, only the block size matters here and it is left as zero for 1 KiB blocks
+        image[1024 + 76..1024 + 80].copy_from_slice(&0u32.to_le_bytes());
+
+        write_record(&mut image, 0, 2, 12, ".");
+        write_record(&mut image, 12, 2, 12, "..");
+        write_record(&mut image, 24, 12, 12, "a");
+        write_record(&mut image, 36, 13, 1024 - 36, "bb");
+
+        let device = MemoryDevice::new(image, 1);
+
+        let mut block_pointers = [0; 15];
+        block_pointers[0] = u32::try_from(DIRECTORY_BLOCK).unwrap();
+
+        let inode = super::raw::Inode {
+            lower_32_size: 1024,
+            block_pointers,
+            ..Default::default()
+        };
+
+        (device, super::Ext2FileSystem::new(device, 0), inode)
+    }
+
+    fn directory_layout(device: &MemoryDevice) -> alloc::vec::Vec<(usize, usize)> {
+        let image = device.image.lock();
+        let block = &image[DIRECTORY_BLOCK * 1024..(DIRECTORY_BLOCK + 1) * 1024];
+
+        super::directory::record_offsets(block)
+            .into_iter()
+            .map(|offset| {
+                (
+                    offset,
+                    super::directory::RecordHeader::read(block, offset).record_length,
+                )
+            })
+            .collect()
+    }
+
+    fn directory_names(
+        fs: &super::Ext2FileSystem<()>,
+        inode: &super::raw::Inode,
+    ) -> alloc::vec::Vec<(u32, alloc::vec::Vec<u8>)> {
+        block_on(fs.read_directory_entries(inode))
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.inode, entry.name))
+            .collect()
+    }
+
+    /// Construct a file system with 1 KiB blocks holding a single file which spans all twelve direct blocks and
+    /// `indirect_blocks` blocks reached through the single indirect block, followed by `tail` bytes of a final partial
+    /// block. Every block of the file is filled with its one based position within the file, and the blocks are laid
+    /// out in reverse order on disk so a read of the wrong block index is detected.
+    fn indirect_file_system(
+        indirect_blocks: usize,
+        tail: usize,
+    ) -> (
+        alloc::sync::Arc<super::Ext2FileSystem<()>>,
+        super::raw::Inode,
+        usize,
+    ) {
+        const INDIRECT_BLOCK: usize = 3;
+        const FIRST_DATA_BLOCK: usize = 4;
+
+        let data_blocks = 12 + indirect_blocks + usize::from(tail > 0);
+        let mut image = alloc::vec![0; (FIRST_DATA_BLOCK + data_blocks) * 1024];
+
+        let disk_block = |position: usize| FIRST_DATA_BLOCK + data_blocks - 1 - position;
+
+        let mut block_pointers = [0; 15];
+        for (position, block) in (0..data_blocks).map(disk_block).enumerate() {
+            image[block * 1024..(block + 1) * 1024].fill(u8::try_from(position + 1).unwrap());
+
+            let pointer = u32::try_from(block).unwrap();
+            if position < 12 {
+                block_pointers[position] = pointer;
+            } else {
+                let start = INDIRECT_BLOCK * 1024 + (position - 12) * 4;
+                image[start..start + 4].copy_from_slice(&pointer.to_le_bytes());
+            }
+        }
+        block_pointers[12] = u32::try_from(INDIRECT_BLOCK).unwrap();
+
+        let size = (12 + indirect_blocks) * 1024 + tail;
+
+        let device = MemoryDevice::new(image, 1);
+
+        let inode = super::raw::Inode {
+            lower_32_size: u32::try_from(size).unwrap(),
+            block_pointers,
+            ..Default::default()
+        };
+
+        (super::Ext2FileSystem::new(device, 0), inode, size)
+    }
+
+    /// Number of blocks on the device built by [`writable_file_system`]
+    const WRITABLE_BLOCKS: usize = 32;
+
+    /// Inode of the regular file on the device built by [`writable_file_system`]
+    const WRITABLE_INODE: crate::interfaces::fs::INodeReference =
+        crate::interfaces::fs::INodeReference {
+            inode: 12,
+            device: 0,
+        };
+
+    /// Construct a file system with 1 KiB blocks and a single block group, with its block bitmap in block 3 and inode
+    /// table in blocks 5 and 6. Inode 12 is a regular file holding `content` in the blocks from 7 onwards, and every
+    /// other block after those is free.
+    fn writable_file_system(
+        content: &[u8],
+    ) -> (
+        &'static MemoryDevice,
+        alloc::sync::Arc<super::Ext2FileSystem<()>>,
+    ) {
+        const BITMAP_BLOCK: usize = 3;
+        const INODE_TABLE_BLOCK: usize = 5;
+        const FIRST_DATA_BLOCK: usize = 7;
+
+        let mut image = alloc::vec![0; WRITABLE_BLOCKS * 1024];
+        let data_blocks = content.len().div_ceil(1024);
+        let used_blocks = FIRST_DATA_BLOCK - 1 + data_blocks;
+
+        let put = |image: &mut [u8], offset: usize, value: u32| {
+            image[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        // Super block, counting the blocks from 1 as block 0 lies before the file system
+        put(&mut image, 1024, 16);
+        put(
+            &mut image,
+            1024 + 4,
+            u32::try_from(WRITABLE_BLOCKS).unwrap(),
+        );
+        put(
+            &mut image,
+            1024 + 12,
+            u32::try_from(WRITABLE_BLOCKS - 1 - used_blocks).unwrap(),
+        );
+        put(&mut image, 1024 + 20, 1);
+        put(&mut image, 1024 + 32, 8192);
+        put(&mut image, 1024 + 40, 16);
+
+        // Block group descriptor
+        put(&mut image, 2048, u32::try_from(BITMAP_BLOCK).unwrap());
+        put(&mut image, 2048 + 4, 4);
+        put(
+            &mut image,
+            2048 + 8,
+            u32::try_from(INODE_TABLE_BLOCK).unwrap(),
+        );
+        image[2048 + 12..2048 + 14].copy_from_slice(
+            &u16::try_from(WRITABLE_BLOCKS - 1 - used_blocks)
+                .unwrap()
+                .to_le_bytes(),
+        );
+
+        for bit in 0..used_blocks {
+            image[BITMAP_BLOCK * 1024 + bit / 8] |= 1 << (bit % 8);
+        }
+
+        let mut block_pointers = [0; 15];
+        for (index, pointer) in block_pointers.iter_mut().take(data_blocks).enumerate() {
+            *pointer = u32::try_from(FIRST_DATA_BLOCK + index).unwrap();
+        }
+
+        let inode = super::raw::Inode {
+            mode: 0x81A4,
+            lower_32_size: u32::try_from(content.len()).unwrap(),
+            disk_sectors: u32::try_from(2 * data_blocks).unwrap(),
+            block_pointers,
+            ..Default::default()
+        };
+        inode.write_bytes(&mut image[INODE_TABLE_BLOCK * 1024 + 11 * 128..]);
+
+        image[FIRST_DATA_BLOCK * 1024..FIRST_DATA_BLOCK * 1024 + content.len()]
+            .copy_from_slice(content);
+
+        let device = MemoryDevice::new(image, 1);
+        (device, super::Ext2FileSystem::new(device, 0))
+    }
+
+    /// Count the free blocks in the bitmap, checking the counts in the descriptor and super block agree
+    fn free_blocks(device: &MemoryDevice) -> usize {
+        let image = device.image.lock();
+
+        let free = (0..WRITABLE_BLOCKS - 1)
+            .filter(|bit| image[3 * 1024 + bit / 8] & (1 << (bit % 8)) == 0)
+            .count();
+        assert_eq!(
+            usize::from(u16::from_le_bytes(
+                image[2048 + 12..2048 + 14].try_into().unwrap()
+            )),
+            free
+        );
+        assert_eq!(
+            u32::from_le_bytes(image[1024 + 12..1024 + 16].try_into().unwrap()) as usize,
+            free
+        );
+
+        free
+    }
+
+    /// Bytes of a file which never repeat within a block, so a misplaced read is noticed
+    fn pattern(length: usize) -> alloc::vec::Vec<u8> {
+        (0..length)
+            .map(|i| u8::try_from(i % 251).unwrap())
+            .collect()
+    }
+
+    fn block_on<T>(future: impl core::future::Future<Output = T>) -> T {
+        let mut result = None;
+        crate::tasks::execute_task(crate::tasks::Task::new(async {
+            result = Some(future.await);
+        }));
+        result.unwrap()
+    }
+
+    #[test]
+    pub fn test_block_cache_hit() {
+        let (device, fs) = counting_file_system(4);
+        let mut buffer = [0; 1024];
+
+        block_on(fs.read_kb_block(5, &mut buffer)).unwrap();
+        assert_eq!(buffer[0], 10);
+        assert_eq!(buffer[512], 11);
+
+        block_on(fs.read_kb_block(5, &mut buffer)).unwrap();
+        assert_eq!(buffer[0], 10);
+        assert_eq!(device.reads.load(Ordering::Acquire), 1);
+    }
+
+    #[test]
+    pub fn test_block_cache_eviction() {
+        let (device, fs) = counting_file_system(2);
+        let mut buffer = [0; 1024];
+
+        block_on(fs.read_kb_block(1, &mut buffer)).unwrap();
+        block_on(fs.read_kb_block(2, &mut buffer)).unwrap();
+        // Touch block 1 so block 2 becomes the least recently used
+        block_on(fs.read_kb_block(1, &mut buffer)).unwrap();
+        block_on(fs.read_kb_block(3, &mut buffer)).unwrap();
+        assert_eq!(device.reads.load(Ordering::Acquire), 3);
+
+        block_on(fs.read_kb_block(1, &mut buffer)).unwrap();
+        assert_eq!(device.reads.load(Ordering::Acquire), 3);
+
+        block_on(fs.read_kb_block(2, &mut buffer)).unwrap();
+        assert_eq!(device.reads.load(Ordering::Acquire), 4);
+    }
+
+    #[test]
+    pub fn test_block_cache_write_updates() {
+        let (device, fs) = counting_file_system(4);
+        let mut buffer = [0; 1024];
+
+        block_on(fs.read_kb_block(7, &mut buffer)).unwrap();
+        block_on(fs.write_kb_block(7, &[0xAA; 1024])).unwrap();
+        block_on(fs.read_kb_block(7, &mut buffer)).unwrap();
+
+        assert_eq!(buffer, [0xAA; 1024]);
+        assert_eq!(device.reads.load(Ordering::Acquire), 1);
+        assert_eq!(device.writes.load(Ordering::Acquire), 1);
+    }
+
+    #[test]
+    pub fn test_rename_splits_record() {
+        let (device, fs, inode) = directory_file_system();
+
+        block_on(fs.rename(&inode, "a", "a-much-longer-name")).unwrap();
+
+        // The old record is merged into `..`, and the new record is split off the slack at the end of `bb`
+        assert_eq!(
+            directory_layout(device),
+            alloc::vec![(0, 12), (12, 24), (36, 12), (48, 1024 - 48)]
+        );
+        assert_eq!(
+            directory_names(&fs, &inode),
+            alloc::vec![
+                (2, b".".to_vec()),
+                (2, b"..".to_vec()),
+                (13, b"bb".to_vec()),
+                (12, b"a-much-longer-name".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_rename_in_place() {
+        let (device, fs, inode) = directory_file_system();
+
+        block_on(fs.rename(&inode, "bb", "c")).unwrap();
+
+        assert_eq!(
+            directory_layout(device),
+            alloc::vec![(0, 12), (12, 12), (24, 12), (36, 1024 - 36)]
+        );
+        assert_eq!(
+            directory_names(&fs, &inode),
+            alloc::vec![
+                (2, b".".to_vec()),
+                (2, b"..".to_vec()),
+                (12, b"a".to_vec()),
+                (13, b"c".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_rename_rejects_existing() {
+        let (device, fs, inode) = directory_file_system();
+
+        assert_eq!(
+            block_on(fs.rename(&inode, "a", "bb")),
+            Err(super::directory::DirectoryError::AlreadyExists)
+        );
+        assert_eq!(
+            block_on(fs.rename(&inode, "missing", "c")),
+            Err(super::directory::DirectoryError::NotFound)
+        );
+        assert_eq!(device.writes.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    pub fn test_read_single_indirect_blocks() {
+        let (fs, inode, size) = indirect_file_system(3, 100);
+        let mut buffer = alloc::vec![0; size];
+
+        block_on(fs.read_inode_data(&inode, &mut buffer)).unwrap();
+
+        for (position, block) in buffer.chunks(1024).enumerate() {
+            let expected = u8::try_from(position + 1).unwrap();
+            assert!(
+                block.iter().all(|b| *b == expected),
+                "Block {} of the file was read from the wrong disk block",
+                position
+            );
+        }
+        assert_eq!(buffer.chunks(1024).count(), 16);
+    }
+
+    #[test]
+    pub fn test_read_oversized_buffer() {
+        let (fs, inode, size) = indirect_file_system(3, 100);
+        let mut buffer = alloc::vec![0; size + 1];
+
+        assert_eq!(
+            block_on(fs.read_inode_data(&inode, &mut buffer)),
+            Err(super::InodeReadError::BufferTooLarge {
+                requested: size + 1,
+                size
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_read_chunks_to_optimal_io_size() {
+        // Every sector holds its own index, the super block is left zeroed for 1 KiB blocks
+        let mut image = alloc::vec![0; 16 * 1024];
+        for (index, sector) in image.chunks_exact_mut(512).enumerate().skip(4) {
+            sector.fill(u8::try_from(index).unwrap());
+        }
+
+        let device = MemoryDevice::new(image, 4);
+        let fs = super::Ext2FileSystem::new(device, 16);
+
+        block_on(fs.read_super_block()).unwrap();
+        // Warm the cache with block 5, splitting the read of blocks 2 to 8 into two runs
+        let mut kb_buffer = [0; 1024];
+        block_on(fs.read_kb_block(5, &mut kb_buffer)).unwrap();
+        device.reads.lock().clear();
+
+        let mut buffer = alloc::vec![0; 7 * 1024];
+        block_on(fs.read_blocks(2, &mut buffer)).unwrap();
+
+        // Blocks 2 to 4 are sectors 4 to 9, and blocks 6 to 8 are sectors 12 to 17, each split into requests of at
+        // most four sectors
+        assert_eq!(
+            *device.reads.lock(),
+            alloc::vec![(4, 4), (8, 2), (12, 4), (16, 2)]
+        );
+
+        for (index, sector) in buffer.chunks_exact(512).enumerate() {
+            let expected = u8::try_from(index + 4).unwrap();
+            assert!(sector.iter().all(|b| *b == expected));
+        }
+
+        // Everything is now cached, so a second read issues no requests
+        device.reads.lock().clear();
+        block_on(fs.read_blocks(2, &mut buffer)).unwrap();
+        assert!(device.reads.lock().is_empty());
+    }
+
+    #[test]
+    pub fn test_inode_data_timestamps() {
+        use crate::interfaces::fs::{FileSystem, INodeReference};
+
+        const INODE_TABLE_BLOCK: usize = 3;
+
+        let mut image = alloc::vec![0; 4 * 1024];
+
+        // A single block group of 16 inodes, with the inode table following the block group descriptor table
+        image[1024..1028].copy_from_slice(&16u32.to_le_bytes());
+        image[1024 + 4..1024 + 8].copy_from_slice(&4u32.to_le_bytes());
+        image[1024 + 32..1024 + 36].copy_from_slice(&8192u32.to_le_bytes());
+        image[1024 + 40..1024 + 44].copy_from_slice(&16u32.to_le_bytes());
+        image[2048 + 8..2048 + 12]
+            .copy_from_slice(&u32::try_from(INODE_TABLE_BLOCK).unwrap().to_le_bytes());
+
+        // Inode 2 is the second 128 byte inode in the table, give each of its timestamps a distinct value
+        let inode = INODE_TABLE_BLOCK * 1024 + 128;
+        for (offset, time) in [(8, 1000u32), (12, 2000), (16, 3000), (20, 4000)] {
+            image[inode + offset..inode + offset + 4].copy_from_slice(&time.to_le_bytes());
+        }
+
+        let fs = super::Ext2FileSystem::new(MemoryDevice::new(image, 1), 0);
+        let data = block_on(fs.inode_data(INodeReference {
+            inode: 2,
+            device: 0,
+        }))
+        .unwrap();
+
+        assert_eq!(data.access_time.0, 1000);
+        assert_eq!(data.change_time.0, 2000);
+        assert_eq!(data.modify_time.0, 3000);
+    }
+
+    #[test]
+    pub fn test_truncated_inode() {
+        use crate::interfaces::fs::{FileSystem, FileSystemError, INodeReference};
+
+        let mut image = alloc::vec![0; 4 * 1024];
+
+        // A revision 1 super block claiming 64 byte inodes, too small to hold an inode, in a table at block 3
+        image[1024..1028].copy_from_slice(&16u32.to_le_bytes());
+        image[1024 + 4..1024 + 8].copy_from_slice(&4u32.to_le_bytes());
+        image[1024 + 32..1024 + 36].copy_from_slice(&8192u32.to_le_bytes());
+        image[1024 + 40..1024 + 44].copy_from_slice(&16u32.to_le_bytes());
+        image[1024 + 76..1024 + 80].copy_from_slice(&1u32.to_le_bytes());
+        image[1024 + 88..1024 + 90].copy_from_slice(&64u16.to_le_bytes());
+        image[2048 + 8..2048 + 12].copy_from_slice(&3u32.to_le_bytes());
+
+        let fs = super::Ext2FileSystem::new(MemoryDevice::new(image, 1), 0);
+
+        // The last inode of the block runs past its end, which is reported rather than panicking
+        assert!(matches!(
+            block_on(fs.get_inode(16)),
+            Err(super::Ext2Error::Corrupted(_))
+        ));
+        assert_eq!(
+            block_on(fs.inode_data(INodeReference {
+                inode: 16,
+                device: 0,
+            })),
+            Err(FileSystemError::CorruptedFilesystem)
+        );
+
+        // Inodes with room left in the block still parse
+        assert!(block_on(fs.get_inode(2)).is_ok());
+    }
+
+    #[test]
+    pub fn test_read_symlink() {
+        let (_, fs, _) = directory_file_system();
+
+        // A short target is kept in the block pointers
+        let mut block_pointers = [0; 15];
+        block_pointers[0] = u32::from_le_bytes(*b"../b");
+        block_pointers[1] = u32::from_le_bytes(*b"in\0\0");
+        let fast = super::raw::Inode {
+            mode: 0xA1FF,
+            lower_32_size: 6,
+            block_pointers,
+            ..Default::default()
+        };
+        assert_eq!(block_on(fs.read_symlink(&fast)).unwrap(), b"../bin");
+
+        // A longer one is stored in a block, here the directory block, which begins with the record for `.`
+        let mut block_pointers = [0; 15];
+        block_pointers[0] = u32::try_from(DIRECTORY_BLOCK).unwrap();
+        let slow = super::raw::Inode {
+            mode: 0xA1FF,
+            lower_32_size: 64,
+            disk_sectors: 2,
+            block_pointers,
+            ..Default::default()
+        };
+        let target = block_on(fs.read_symlink(&slow)).unwrap();
+        assert_eq!(target.len(), 64);
+        assert_eq!(target[8], b'.');
+    }
+
+    #[test]
+    pub fn test_read_empty_file() {
+        use crate::interfaces::fs::{FileSystem, INodeReference};
+
+        let mut image = alloc::vec![0; 16 * 1024];
+
+        // A single block group of 16 inodes, with the inode table following the block group descriptor table. Inode
+        // 12 is left zeroed, an empty file with no blocks.
+        image[1024..1028].copy_from_slice(&16u32.to_le_bytes());
+        image[1024 + 4..1024 + 8].copy_from_slice(&4u32.to_le_bytes());
+        image[1024 + 32..1024 + 36].copy_from_slice(&8192u32.to_le_bytes());
+        image[1024 + 40..1024 + 44].copy_from_slice(&16u32.to_le_bytes());
+        image[2048 + 8..2048 + 12].copy_from_slice(&3u32.to_le_bytes());
+
+        let device = MemoryDevice::new(image, 1);
+        let fs = super::Ext2FileSystem::new(device, 16);
+        let empty = INodeReference {
+            inode: 12,
+            device: 0,
+        };
+
+        // Bring the super block and inode table into the cache, so any further read is of the file's data
+        assert_eq!(block_on(fs.inode_data(empty)).unwrap().size, 0);
+        device.reads.lock().clear();
+
+        assert!(block_on(fs.read_to_data(empty)).unwrap().is_empty());
+        assert!(device.reads.lock().is_empty());
+
+        let inode = super::raw::Inode::default();
+        block_on(fs.read_inode_data(&inode, &mut [])).unwrap();
+        assert!(device.reads.lock().is_empty());
+    }
+
+    #[test]
+    pub fn test_mount_as_vfs_root() {
+        use crate::interfaces::fs::{
+            FileSystem, INodeReference, MountingFilesystem, PathLookup, VirtualFileSystem,
+        };
+
+        let (_, fs, _) = directory_file_system();
+
+        let mut vfs = VirtualFileSystem::new();
+        let empty_root = block_on(vfs.root_inode()).unwrap();
+        vfs.mount_filesystem(empty_root, fs).unwrap();
+
+        // The ext2 file system is the second device, and its root directory is always inode 2
+        let ext2_root = INodeReference {
+            inode: 2,
+            device: 2,
+        };
+
+        assert_eq!(block_on(vfs.root_inode()).unwrap(), ext2_root);
+        assert_eq!(block_on(vfs.lookup("/")).unwrap(), ext2_root);
+        assert_ne!(block_on(vfs.lookup("/")).unwrap(), empty_root);
+    }
+
+    #[test]
+    pub fn test_list_mounted_root() {
+        use crate::interfaces::fs::{
+            FileSystem, MountingFilesystem, PathLookup, VirtualFileSystem,
+            DIRECTORY_RECORD_HEADER_SIZE,
+        };
+
+        let (device, fs, _) = directory_file_system();
+        {
+            let mut image = device.image.lock();
+
+            // A revision 1 super block with typed directory entries, and a single block group of 16 inodes whose
+            // table is in block 3
+            image[1024..1028].copy_from_slice(&16u32.to_le_bytes());
+            image[1024 + 4..1024 + 8].copy_from_slice(&8u32.to_le_bytes());
+            image[1024 + 32..1024 + 36].copy_from_slice(&8192u32.to_le_bytes());
+            image[1024 + 40..1024 + 44].copy_from_slice(&16u32.to_le_bytes());
+            image[1024 + 76..1024 + 80].copy_from_slice(&1u32.to_le_bytes());
+            image[1024 + 88..1024 + 90].copy_from_slice(&128u16.to_le_bytes());
+            image[1024 + 96..1024 + 100].copy_from_slice(&2u32.to_le_bytes());
+            image[2048 + 8..2048 + 12].copy_from_slice(&3u32.to_le_bytes());
+
+            // Inode 2, the root directory, whose only data block holds the entries
+            let inode = 3 * 1024 + 128;
+            image[inode..inode + 2].copy_from_slice(&0x41EDu16.to_le_bytes());
+            image[inode + 4..inode + 8].copy_from_slice(&1024u32.to_le_bytes());
+            image[inode + 40..inode + 44]
+                .copy_from_slice(&u32::try_from(DIRECTORY_BLOCK).unwrap().to_le_bytes());
+        }
+
+        let mut vfs = VirtualFileSystem::new();
+        let empty_root = block_on(vfs.root_inode()).unwrap();
+        vfs.mount_filesystem(empty_root, fs).unwrap();
+
+        let root = block_on(vfs.lookup("/")).unwrap();
+        let descriptor = block_on(vfs.open(root)).unwrap();
+
+        // A buffer with room for two short records at most, so the listing takes several calls
+        let mut buffer = [0; 2 * DIRECTORY_RECORD_HEADER_SIZE + 2];
+        let mut entries = alloc::vec::Vec::new();
+        loop {
+            let length = block_on(descriptor.read_directory(&mut buffer)).unwrap();
+            if length == 0 {
+                break;
+            }
+
+            let mut records = &buffer[..length];
+            while !records.is_empty() {
+                let inode = u64::from_le_bytes(records[0..8].try_into().unwrap());
+                let name_length = u16::from_le_bytes(records[9..11].try_into().unwrap()) as usize;
+                let end = DIRECTORY_RECORD_HEADER_SIZE + name_length;
+                let name = alloc::string::String::from_utf8(records[11..end].to_vec()).unwrap();
+
+                entries.push((inode, records[8], name));
+                records = &records[end..];
+            }
+        }
+
+        // Every record in the fixture is marked as a directory
+        let names = entries
+            .iter()
+            .map(|(_, _, name)| name.as_str())
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(names, [".", "..", "a", "bb"]);
+        assert_eq!(entries[2].0, 12);
+        assert!(entries.iter().all(|(_, file_type, _)| *file_type == 4));
+    }
+
+    #[test]
+    pub fn test_descriptor_sequential_read() {
+        use crate::interfaces::fs::FileSystem;
+
+        let content = pattern(2500);
+        let (_, fs) = writable_file_system(&content);
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+        assert_eq!(descriptor.inode(), Some(WRITABLE_INODE));
+
+        let mut read = alloc::vec::Vec::new();
+        let mut buffer = [0; 1000];
+        for expected in [1000, 1000, 500, 0] {
+            let length = block_on(descriptor.read(&mut buffer)).unwrap();
+            assert_eq!(length, expected);
+            read.extend_from_slice(&buffer[..length]);
+        }
+
+        assert_eq!(read, content);
+    }
+
+    #[test]
+    pub fn test_descriptor_seek_then_read() {
+        use crate::interfaces::fs::{FileSystem, FileSystemError, SeekMode};
+
+        let content = pattern(2500);
+        let (_, fs) = writable_file_system(&content);
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+
+        // A read which spans the boundary between the first two blocks
+        assert_eq!(block_on(descriptor.seek(SeekMode::Set(1020))), Ok(1020));
+        let mut buffer = [0; 10];
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(10));
+        assert_eq!(buffer, content[1020..1030]);
+
+        assert_eq!(block_on(descriptor.seek(SeekMode::Current(-5))), Ok(1025));
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(10));
+        assert_eq!(buffer, content[1025..1035]);
+
+        assert_eq!(block_on(descriptor.seek(SeekMode::End(-100))), Ok(2400));
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(10));
+        assert_eq!(buffer, content[2400..2410]);
+
+        assert_eq!(
+            block_on(descriptor.seek(SeekMode::End(-2501))),
+            Err(FileSystemError::GenericError)
+        );
+        assert_eq!(block_on(descriptor.seek(SeekMode::Current(0))), Ok(2410));
+    }
+
+    #[test]
+    pub fn test_descriptor_read_past_end() {
+        use crate::interfaces::fs::{FileSystem, SeekMode};
+
+        let content = pattern(1500);
+        let (_, fs) = writable_file_system(&content);
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+
+        // A read reaching past the end is cut short, and every read after it is empty
+        let mut buffer = [0xFF; 64];
+        block_on(descriptor.seek(SeekMode::End(-10))).unwrap();
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(10));
+        assert_eq!(buffer[..10], content[1490..]);
+        assert!(buffer[10..].iter().all(|byte| *byte == 0xFF));
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(0));
+
+        assert_eq!(block_on(descriptor.seek(SeekMode::Set(4000))), Ok(4000));
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(0));
+        assert_eq!(block_on(descriptor.seek(SeekMode::Current(0))), Ok(4000));
+    }
+
+    #[test]
+    pub fn test_descriptor_write() {
+        use crate::interfaces::fs::{FileSystem, SeekMode};
+
+        let mut content = pattern(1500);
+        let (device, fs) = writable_file_system(&content);
+        let free = free_blocks(device);
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+
+        // Overwriting within the file takes no new blocks
+        block_on(descriptor.seek(SeekMode::Set(1000))).unwrap();
+        assert_eq!(block_on(descriptor.write(&[1; 100])), Ok(100));
+        content[1000..1100].fill(1);
+        assert_eq!(block_on(fs.read_to_data(WRITABLE_INODE)).unwrap(), content);
+        assert_eq!(free_blocks(device), free);
+
+        // Extending the file from the end fills the rest of its last block, then takes a new one
+        block_on(descriptor.seek(SeekMode::End(0))).unwrap();
+        assert_eq!(block_on(descriptor.write(&[2; 1000])), Ok(1000));
+        content.extend_from_slice(&[2; 1000]);
+        assert_eq!(block_on(fs.read_to_data(WRITABLE_INODE)).unwrap(), content);
+        assert_eq!(block_on(fs.inode_data(WRITABLE_INODE)).unwrap().size, 2500);
+        assert_eq!(free_blocks(device), free - 1);
+
+        // Writing past the end leaves zeros in the gap, and the thirteenth block is reached through a new indirect
+        // block
+        block_on(descriptor.seek(SeekMode::Set(12 * 1024 + 10))).unwrap();
+        assert_eq!(block_on(descriptor.write(&[3; 20])), Ok(20));
+        content.resize(12 * 1024 + 10, 0);
+        content.extend_from_slice(&[3; 20]);
+        assert_eq!(block_on(fs.read_to_data(WRITABLE_INODE)).unwrap(), content);
+        assert_eq!(free_blocks(device), free - 12);
+
+        // The cursor is left after the data written
+        assert_eq!(
+            block_on(descriptor.seek(SeekMode::Current(0))),
+            Ok(12 * 1024 + 30)
+        );
+
+        let inode = block_on(fs.get_inode(12)).unwrap();
+        assert_eq!(inode.disk_sectors, 2 * 14);
+        assert_ne!(inode.block_pointers[12], 0);
+    }
+
+    #[test]
+    pub fn test_two_block_groups() {
+        use crate::interfaces::fs::{FileSystem, SeekMode};
+
+        let (device, fs) = writable_file_system(&pattern(100));
+
+        // Split the device into groups of 16 blocks, the second with its bitmap in block 8 of the first. Its 32 byte
+        // descriptor follows straight on from the first one.
+        {
+            let mut image = device.image.lock();
+            image[1024 + 32..1024 + 36].copy_from_slice(&16u32.to_le_bytes());
+            image[1024 + 12..1024 + 16].copy_from_slice(&23u32.to_le_bytes());
+            image[2048 + 12..2048 + 14].copy_from_slice(&8u16.to_le_bytes());
+            image[3 * 1024] |= 1 << 7;
+
+            image[2048 + 32..2048 + 36].copy_from_slice(&8u32.to_le_bytes());
+            image[2048 + 36..2048 + 40].copy_from_slice(&4u32.to_le_bytes());
+            image[2048 + 40..2048 + 44].copy_from_slice(&5u32.to_le_bytes());
+            image[2048 + 44..2048 + 46].copy_from_slice(&15u16.to_le_bytes());
+        }
+
+        let counts = || {
+            let image = device.image.lock();
+            let group_free = |group: usize| {
+                u16::from_le_bytes(image[2048 + group * 32 + 12..][..2].try_into().unwrap())
+            };
+            (
+                group_free(0),
+                group_free(1),
+                u32::from_le_bytes(image[1024 + 12..1024 + 16].try_into().unwrap()),
+            )
+        };
+
+        // Ten more blocks take the rest of the first group, then the first two of the second
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+        block_on(descriptor.seek(SeekMode::Set(1024))).unwrap();
+        assert_eq!(block_on(descriptor.write(&[1; 10 * 1024])), Ok(10 * 1024));
+        assert_eq!(counts(), (0, 13, 13));
+        assert_eq!(device.image.lock()[8 * 1024], 0b11);
+
+        let inode = block_on(fs.get_inode(12)).unwrap();
+        assert_eq!(inode.block_pointers[9..11], [17, 18]);
+
+        // Freeing the file returns each block to its own group
+        block_on(fs.clear_inode_data(12)).unwrap();
+        assert_eq!(counts(), (9, 15, 24));
+    }
+
+    #[test]
+    pub fn test_descriptor_full_device() {
+        use crate::interfaces::fs::{FileSystem, FileSystemError, SeekMode};
+
+        let (device, fs) = writable_file_system(&pattern(100));
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+
+        // Only 24 blocks are free, too few for the 30 more blocks of data along with the indirect block
+        block_on(descriptor.seek(SeekMode::Set(30 * 1024))).unwrap();
+        assert_eq!(
+            block_on(descriptor.write(&[1; 10])),
+            Err(FileSystemError::NoSpace)
+        );
+        assert_eq!(free_blocks(device), 0);
+        assert_eq!(block_on(fs.inode_data(WRITABLE_INODE)).unwrap().size, 100);
+
+        // The blocks taken before the device filled up belong to the file, so truncating it returns them
+        block_on(fs.clear_inode_data(12)).unwrap();
+        assert_eq!(free_blocks(device), 25);
+    }
+
+    #[test]
+    pub fn test_descriptor_write_too_large() {
+        use crate::interfaces::fs::{FileSystem, FileSystemError, SeekMode};
+
+        let (device, fs) = writable_file_system(&pattern(100));
+        let free = free_blocks(device);
+        let descriptor = block_on(fs.open(WRITABLE_INODE)).unwrap();
+
+        // Past the blocks reached through the single indirect block, and past the end of the address space
+        for position in [(12 + 256) * 1024, usize::MAX - 4] {
+            block_on(descriptor.seek(SeekMode::Set(position))).unwrap();
+            assert_eq!(
+                block_on(descriptor.write(&[1; 10])),
+                Err(FileSystemError::Unsupported)
+            );
+        }
+
+        // Nothing was allocated for the padding
+        assert_eq!(free_blocks(device), free);
+        assert_eq!(block_on(fs.inode_data(WRITABLE_INODE)).unwrap().size, 100);
+
+        // The last byte which can be allocated is allowed, though the device is too small to hold the padding
+        block_on(descriptor.seek(SeekMode::Set((12 + 256) * 1024 - 1))).unwrap();
+        assert_eq!(
+            block_on(descriptor.write(&[1])),
+            Err(FileSystemError::NoSpace)
+        );
+    }
+
+    #[test]
+    pub fn test_clear_bad_block() {
+        let (_, fs) = writable_file_system(&pattern(100));
+
+        let mut inode = block_on(fs.get_inode(12)).unwrap();
+        inode.block_pointers[1] = u32::try_from(WRITABLE_BLOCKS).unwrap();
+        block_on(fs.write_inode(12, &inode)).unwrap();
+
+        assert_eq!(
+            block_on(fs.clear_inode_data(12)),
+            Err(super::InodeWriteError::BadBlock(
+                u32::try_from(WRITABLE_BLOCKS).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    pub fn test_open_truncate_and_append() {
+        use crate::interfaces::fs::{FileSystem, OpenFlag, OpenFlags, SeekMode};
+
+        let content = pattern(2500);
+        let (device, fs) = writable_file_system(&content);
+        let free = free_blocks(device);
+
+        // Appending starts the cursor at the end of the file
+        let descriptor =
+            block_on(fs.open_with(WRITABLE_INODE, OpenFlags::default() | OpenFlag::Append))
+                .unwrap();
+        assert_eq!(block_on(descriptor.seek(SeekMode::Current(0))), Ok(2500));
+        block_on(descriptor.write(b"tail")).unwrap();
+        assert_eq!(
+            block_on(fs.read_to_data(WRITABLE_INODE)).unwrap()[2500..],
+            *b"tail"
+        );
+
+        // Truncating frees every block of the file
+        let descriptor =
+            block_on(fs.open_with(WRITABLE_INODE, OpenFlags::default() | OpenFlag::Truncate))
+                .unwrap();
+        assert_eq!(block_on(fs.inode_data(WRITABLE_INODE)).unwrap().size, 0);
+        assert_eq!(free_blocks(device), free + 3);
+
+        block_on(descriptor.write(b"fresh")).unwrap();
+        assert_eq!(block_on(fs.read_to_data(WRITABLE_INODE)).unwrap(), b"fresh");
+        assert_eq!(free_blocks(device), free + 2);
+    }
+}
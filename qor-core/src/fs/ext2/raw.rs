@@ -1,4 +1,7 @@
-use crate::utils::parser::Parser;
+use crate::{
+    interfaces::fs::FileType,
+    utils::parser::{ParseError, Parser},
+};
 
 const fn div_ceil(a: usize, b: usize) -> usize {
     (a + b - 1) / b
@@ -58,56 +61,55 @@ pub struct SuperBlock {
 impl SuperBlock {
     /// Parses a Ext2 Super Block from a byte buffer.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the buffer passed is an invalid size.
-    #[must_use]
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    /// Returns the offset of the first field which runs past the end of the buffer, if it is too short.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
         let mut parser = Parser::new(bytes);
 
-        let inode_count = parser.take_u32().unwrap();
-        let block_count = parser.take_u32().unwrap();
-        let super_user_blocks = parser.take_u32().unwrap();
-        let unallocated_blocks = parser.take_u32().unwrap();
-        let unallocated_inodes = parser.take_u32().unwrap();
-        let super_block_block_number = parser.take_u32().unwrap();
-        let block_size_log_2_less_10 = parser.take_u32().unwrap();
-        let fragment_size_log_2_less_10 = parser.take_u32().unwrap();
-        let blocks_per_block_group = parser.take_u32().unwrap();
-        let fragments_per_block_group = parser.take_u32().unwrap();
-        let inodes_per_block_group = parser.take_u32().unwrap();
-        let last_mount_time = parser.take_u32().unwrap();
-        let last_write_time = parser.take_u32().unwrap();
-        let mounts_since_consistency_check = parser.take_u16().unwrap();
-        let mounts_until_consistency_check = parser.take_u16().unwrap();
-        let ext2_signature = parser.take_u16().unwrap();
-        let file_system_state = parser.take_u16().unwrap();
-        let error_handle_mode = parser.take_u16().unwrap();
-        let minor_version = parser.take_u16().unwrap();
-        let last_consistency_check = parser.take_u32().unwrap();
-        let interval_between_consistency_check = parser.take_u32().unwrap();
-        let operating_system_id = parser.take_u32().unwrap();
-        let major_version = parser.take_u32().unwrap();
-        let user_id_for_reserved = parser.take_u16().unwrap();
-        let group_id_for_reserved = parser.take_u16().unwrap();
+        let inode_count = parser.try_take_u32()?;
+        let block_count = parser.try_take_u32()?;
+        let super_user_blocks = parser.try_take_u32()?;
+        let unallocated_blocks = parser.try_take_u32()?;
+        let unallocated_inodes = parser.try_take_u32()?;
+        let super_block_block_number = parser.try_take_u32()?;
+        let block_size_log_2_less_10 = parser.try_take_u32()?;
+        let fragment_size_log_2_less_10 = parser.try_take_u32()?;
+        let blocks_per_block_group = parser.try_take_u32()?;
+        let fragments_per_block_group = parser.try_take_u32()?;
+        let inodes_per_block_group = parser.try_take_u32()?;
+        let last_mount_time = parser.try_take_u32()?;
+        let last_write_time = parser.try_take_u32()?;
+        let mounts_since_consistency_check = parser.try_take_u16()?;
+        let mounts_until_consistency_check = parser.try_take_u16()?;
+        let ext2_signature = parser.try_take_u16()?;
+        let file_system_state = parser.try_take_u16()?;
+        let error_handle_mode = parser.try_take_u16()?;
+        let minor_version = parser.try_take_u16()?;
+        let last_consistency_check = parser.try_take_u32()?;
+        let interval_between_consistency_check = parser.try_take_u32()?;
+        let operating_system_id = parser.try_take_u32()?;
+        let major_version = parser.try_take_u32()?;
+        let user_id_for_reserved = parser.try_take_u16()?;
+        let group_id_for_reserved = parser.try_take_u16()?;
 
         let extended = if major_version >= 1 {
-            let first_unreserved_inode = parser.take_u32().unwrap();
-            let inode_structure_size = parser.take_u16().unwrap();
-            let block_group_for_super_block = parser.take_u16().unwrap();
-            let optional_features = parser.take_u32().unwrap();
-            let required_features = parser.take_u32().unwrap();
-            let read_only_features = parser.take_u32().unwrap();
-            let file_system_id = parser.take_u8_array().unwrap();
-            let volume_name = parser.take_u8_array().unwrap();
-            let path_volume_of_last_mount = parser.take_u8_array().unwrap();
-            let compression_algorithm = parser.take_u32().unwrap();
-            let blocks_for_file_prealloc = parser.take_u32().unwrap();
-            let blocks_for_directory_prealloc = parser.take_u32().unwrap();
-            let journal_id = parser.take_u128().unwrap();
-            let journal_inode = parser.take_u32().unwrap();
-            let journal_device = parser.take_u32().unwrap();
-            let orphan_inode_list_head = parser.take_u32().unwrap();
+            let first_unreserved_inode = parser.try_take_u32()?;
+            let inode_structure_size = parser.try_take_u16()?;
+            let block_group_for_super_block = parser.try_take_u16()?;
+            let optional_features = parser.try_take_u32()?;
+            let required_features = parser.try_take_u32()?;
+            let read_only_features = parser.try_take_u32()?;
+            let file_system_id = parser.try_take_u8_array()?;
+            let volume_name = parser.try_take_u8_array()?;
+            let path_volume_of_last_mount = parser.try_take_u8_array()?;
+            let compression_algorithm = parser.try_take_u32()?;
+            let blocks_for_file_prealloc = parser.try_take_u32()?;
+            let blocks_for_directory_prealloc = parser.try_take_u32()?;
+            let journal_id = parser.try_take_u128()?;
+            let journal_inode = parser.try_take_u32()?;
+            let journal_device = parser.try_take_u32()?;
+            let orphan_inode_list_head = parser.try_take_u32()?;
 
             Some(ExtendedSuperblock {
                 first_unreserved_inode,
@@ -131,7 +133,7 @@ impl SuperBlock {
             None
         };
 
-        Self {
+        Ok(Self {
             inode_count,
             block_count,
             super_user_blocks,
@@ -158,7 +160,7 @@ impl SuperBlock {
             user_id_for_reserved,
             group_id_for_reserved,
             extended,
-        }
+        })
     }
 
     #[must_use]
@@ -184,6 +186,19 @@ impl SuperBlock {
         }
     }
 
+    /// Get the size in bytes of each entry in the block group descriptor table, which is 32 bytes unless the 64 bit
+    /// feature is required
+    #[must_use]
+    pub const fn block_group_descriptor_size(&self) -> usize {
+        if let Some(extended) = self.extended {
+            if extended.required_features & 0x80 > 0 {
+                return 64;
+            }
+        }
+
+        32
+    }
+
     #[must_use]
     pub const fn block_group_descriptor_table_index(&self) -> usize {
         if self.block_size_log_2_less_10 == 0 {
@@ -193,6 +208,17 @@ impl SuperBlock {
         }
     }
 
+    /// Returns true if directory entries record the type of the file they refer to, in the byte which holds the high
+    /// bits of the name length in the original format
+    #[must_use]
+    pub const fn directory_entries_have_type(&self) -> bool {
+        if let Some(extended) = self.extended {
+            extended.required_features & 2 > 0
+        } else {
+            false
+        }
+    }
+
     #[must_use]
     pub const fn use_64_bit_sizes(&self) -> bool {
         if let Some(extended) = self.extended {
@@ -201,6 +227,33 @@ impl SuperBlock {
             false
         }
     }
+
+    /// Get the volume label stored in the extended super block, with any trailing NUL padding removed. Returns `None`
+    /// if there is no extended super block, the volume is unlabeled, or the label is not valid UTF-8.
+    #[must_use]
+    pub fn volume_label(&self) -> Option<&str> {
+        let volume_name = &self.extended.as_ref()?.volume_name;
+        let length = volume_name
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |index| index + 1);
+
+        if length == 0 {
+            return None;
+        }
+
+        core::str::from_utf8(&volume_name[..length]).ok()
+    }
+
+    /// Get the file system UUID stored in the extended super block, returns `None` if there is no extended super block.
+    #[must_use]
+    pub const fn uuid(&self) -> Option<[u8; 16]> {
+        if let Some(extended) = self.extended {
+            Some(extended.file_system_id)
+        } else {
+            None
+        }
+    }
 }
 
 /// Minix3 Inode
@@ -210,9 +263,14 @@ pub struct Inode {
     pub mode: u16,
     pub user_id: u16,
     pub lower_32_size: u32,
+    /// Time the file contents were last read (`i_atime`)
     pub last_access_time: u32,
-    pub create_time: u32,
+    /// Time the inode itself last changed, such as its mode, owner or link count (`i_ctime`). Despite sitting where a
+    /// creation time might be expected, ext2 does not record when an inode was created.
+    pub change_time: u32,
+    /// Time the file contents were last written (`i_mtime`)
     pub last_modify_time: u32,
+    /// Time the inode was freed, zero while it is in use (`i_dtime`)
     pub delete_time: u32,
     pub group_id: u16,
     pub hard_link_count: u16,
@@ -230,38 +288,37 @@ pub struct Inode {
 impl Inode {
     /// Parses a Minix3 Inode from a byte buffer.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the buffer passed is an invalid size.
-    #[must_use]
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    /// Returns the offset of the first field which runs past the end of the buffer, if it is too short.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
         let mut parser = Parser::new(bytes);
 
-        let mode = parser.take_u16().unwrap();
-        let user_id = parser.take_u16().unwrap();
-        let lower_32_size = parser.take_u32().unwrap();
-        let last_access_time = parser.take_u32().unwrap();
-        let create_time = parser.take_u32().unwrap();
-        let last_modify_time = parser.take_u32().unwrap();
-        let delete_time = parser.take_u32().unwrap();
-        let group_id = parser.take_u16().unwrap();
-        let hard_link_count = parser.take_u16().unwrap();
-        let disk_sectors = parser.take_u32().unwrap();
-        let flags = parser.take_u32().unwrap();
-        let os_specific_1 = parser.take_u32().unwrap();
-        let block_pointers = parser.take_u32_array().unwrap();
-        let generation_number = parser.take_u32().unwrap();
-        let extended_attribute_block = parser.take_u32().unwrap();
-        let upper_32_size = parser.take_u32().unwrap();
-        let fragment_block_address = parser.take_u32().unwrap();
-        let os_specific_2 = parser.take_u8_array().unwrap();
+        let mode = parser.try_take_u16()?;
+        let user_id = parser.try_take_u16()?;
+        let lower_32_size = parser.try_take_u32()?;
+        let last_access_time = parser.try_take_u32()?;
+        let change_time = parser.try_take_u32()?;
+        let last_modify_time = parser.try_take_u32()?;
+        let delete_time = parser.try_take_u32()?;
+        let group_id = parser.try_take_u16()?;
+        let hard_link_count = parser.try_take_u16()?;
+        let disk_sectors = parser.try_take_u32()?;
+        let flags = parser.try_take_u32()?;
+        let os_specific_1 = parser.try_take_u32()?;
+        let block_pointers = parser.try_take_u32_array()?;
+        let generation_number = parser.try_take_u32()?;
+        let extended_attribute_block = parser.try_take_u32()?;
+        let upper_32_size = parser.try_take_u32()?;
+        let fragment_block_address = parser.try_take_u32()?;
+        let os_specific_2 = parser.try_take_u8_array()?;
 
-        Self {
+        Ok(Self {
             mode,
             user_id,
             lower_32_size,
             last_access_time,
-            create_time,
+            change_time,
             last_modify_time,
             delete_time,
             group_id,
@@ -275,6 +332,55 @@ impl Inode {
             upper_32_size,
             fragment_block_address,
             os_specific_2,
+        })
+    }
+
+    /// Write the inode into the first 128 bytes of `bytes`, in the layout read by [`Inode::from_bytes`]. The rest of
+    /// a larger inode is left as it is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is shorter than 128 bytes.
+    pub fn write_bytes(&self, bytes: &mut [u8]) {
+        let mut offset = 0;
+        let mut put = |field: &[u8]| {
+            bytes[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        };
+
+        put(&self.mode.to_le_bytes());
+        put(&self.user_id.to_le_bytes());
+        put(&self.lower_32_size.to_le_bytes());
+        put(&self.last_access_time.to_le_bytes());
+        put(&self.change_time.to_le_bytes());
+        put(&self.last_modify_time.to_le_bytes());
+        put(&self.delete_time.to_le_bytes());
+        put(&self.group_id.to_le_bytes());
+        put(&self.hard_link_count.to_le_bytes());
+        put(&self.disk_sectors.to_le_bytes());
+        put(&self.flags.to_le_bytes());
+        put(&self.os_specific_1.to_le_bytes());
+        for pointer in self.block_pointers {
+            put(&pointer.to_le_bytes());
+        }
+        put(&self.generation_number.to_le_bytes());
+        put(&self.extended_attribute_block.to_le_bytes());
+        put(&self.upper_32_size.to_le_bytes());
+        put(&self.fragment_block_address.to_le_bytes());
+        put(&self.os_specific_2);
+    }
+
+    /// Set the size of the file, splitting it across the two size fields if `use_extended` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size does not fit in the size fields.
+    pub fn set_size(&mut self, size: usize, use_extended: bool) {
+        if use_extended {
+            self.lower_32_size = u32::try_from(size & 0xFFFF_FFFF).unwrap();
+            self.upper_32_size = u32::try_from(size >> 32).unwrap();
+        } else {
+            self.lower_32_size = u32::try_from(size).unwrap();
         }
     }
 
@@ -305,7 +411,7 @@ impl BlockGroupDescriptor {
     ///
     /// Panics if the buffer passed is an invalid size.
     #[must_use]
-    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
         let mut parser = Parser::new(bytes);
 
         let block_usage_bitmap = parser.take_u32().unwrap();
@@ -329,6 +435,7 @@ impl BlockGroupDescriptor {
 pub struct DirectoryEntry {
     pub inode: u32,
     pub name: alloc::vec::Vec<u8>,
+    pub type_indicator: u8,
 }
 
 impl DirectoryEntry {
@@ -345,19 +452,166 @@ impl DirectoryEntry {
         while !parser.empty() {
             let inode = parser.take_u32().unwrap();
             let total_size = parser.take_u16().unwrap();
-            let _ = parser.take_u8(); // Skip name length
-            let _ = parser.take_u8(); // Skip type indicator
+            let name_length = parser.take_u8().unwrap();
+            let type_indicator = parser.take_u8().unwrap();
 
             let name_buffer = parser.take_u8_slice(total_size as usize - 8).unwrap();
-            let name: alloc::vec::Vec<u8> = name_buffer
+
+            // Records with a zero inode are unused space within the directory
+            if inode == 0 {
+                continue;
+            }
+
+            // Only the first `name_length` bytes are the name, anything after is slack from the record's length
+            let name: alloc::vec::Vec<u8> = name_buffer[..name_length as usize]
                 .iter()
                 .take_while(|b| **b != 0)
                 .copied()
                 .collect();
 
-            result.push(Self { inode, name });
+            result.push(Self {
+                inode,
+                name,
+                type_indicator,
+            });
         }
 
         result
     }
+
+    /// Get the type of file the entry refers to from its type indicator, which is only meaningful if the file system
+    /// sets [`SuperBlock::directory_entries_have_type`]
+    #[must_use]
+    pub const fn file_type(&self) -> FileType {
+        match self.type_indicator {
+            1 => FileType::Regular,
+            2 => FileType::Directory,
+            3 => FileType::CharacterDevice,
+            4 => FileType::BlockDevice,
+            5 => FileType::Fifo,
+            6 => FileType::Socket,
+            7 => FileType::SymbolicLink,
+            _ => FileType::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{Inode, SuperBlock};
+    use crate::utils::parser::ParseError;
+
+    const MAJOR_VERSION_OFFSET: usize = 76;
+    const REQUIRED_FEATURES_OFFSET: usize = 96;
+    const FILE_SYSTEM_ID_OFFSET: usize = 104;
+    const VOLUME_NAME_OFFSET: usize = 120;
+
+    const UUID: [u8; 16] = [
+        0x4f, 0x1c, 0x6e, 0x2a, 0x93, 0x0b, 0x4d, 0x5e, 0xa1, 0x7f, 0x22, 0x38, 0xc4, 0x90, 0x0d,
+        0xe7,
+    ];
+
+    fn super_block_bytes(major_version: u32, volume_name: &[u8]) -> [u8; 1024] {
+        let mut bytes = [0; 1024];
+
+        bytes[MAJOR_VERSION_OFFSET..MAJOR_VERSION_OFFSET + 4]
+            .copy_from_slice(&major_version.to_le_bytes());
+        bytes[FILE_SYSTEM_ID_OFFSET..FILE_SYSTEM_ID_OFFSET + 16].copy_from_slice(&UUID);
+        bytes[VOLUME_NAME_OFFSET..VOLUME_NAME_OFFSET + volume_name.len()]
+            .copy_from_slice(volume_name);
+
+        bytes
+    }
+
+    #[test]
+    pub fn volume_label_test() {
+        let super_block = SuperBlock::from_bytes(&super_block_bytes(1, b"qor-root")).unwrap();
+
+        assert_eq!(super_block.volume_label(), Some("qor-root"));
+        assert_eq!(super_block.uuid(), Some(UUID));
+    }
+
+    #[test]
+    pub fn full_length_volume_label_test() {
+        let super_block = SuperBlock::from_bytes(&super_block_bytes(1, b"sixteen-chars-ok")).unwrap();
+
+        assert_eq!(super_block.volume_label(), Some("sixteen-chars-ok"));
+    }
+
+    #[test]
+    pub fn unlabeled_volume_test() {
+        let super_block = SuperBlock::from_bytes(&super_block_bytes(1, b"")).unwrap();
+
+        assert_eq!(super_block.volume_label(), None);
+        assert_eq!(super_block.uuid(), Some(UUID));
+    }
+
+    #[test]
+    pub fn inode_round_trip_test() {
+        let mut bytes = [0; 256];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::try_from(index * 7 % 251).unwrap();
+        }
+
+        let inode = Inode::from_bytes(&bytes).unwrap();
+        let mut written = [0xAA; 256];
+        inode.write_bytes(&mut written);
+
+        assert_eq!(written[..128], bytes[..128]);
+        assert!(written[128..].iter().all(|byte| *byte == 0xAA));
+    }
+
+    #[test]
+    pub fn original_revision_test() {
+        let super_block = SuperBlock::from_bytes(&super_block_bytes(0, b"ignored")).unwrap();
+
+        assert_eq!(super_block.volume_label(), None);
+        assert_eq!(super_block.uuid(), None);
+    }
+
+    #[test]
+    pub fn block_group_descriptor_size_test() {
+        let descriptor_size = |bytes: &[u8]| {
+            SuperBlock::from_bytes(bytes)
+                .unwrap()
+                .block_group_descriptor_size()
+        };
+
+        let mut bytes = super_block_bytes(1, b"");
+        assert_eq!(descriptor_size(&bytes), 32);
+
+        // Only the 64 bit feature widens the descriptors
+        bytes[REQUIRED_FEATURES_OFFSET] = 0x02;
+        assert_eq!(descriptor_size(&bytes), 32);
+        bytes[REQUIRED_FEATURES_OFFSET] = 0x82;
+        assert_eq!(descriptor_size(&bytes), 64);
+
+        let original = SuperBlock::from_bytes(&super_block_bytes(0, b"")).unwrap();
+        assert_eq!(original.block_group_descriptor_size(), 32);
+    }
+
+    #[test]
+    pub fn truncated_test() {
+        // Cut off part way through the volume name
+        let bytes = super_block_bytes(1, b"qor-root");
+        assert_eq!(
+            SuperBlock::from_bytes(&bytes[..130]),
+            Err(ParseError {
+                offset: VOLUME_NAME_OFFSET,
+                needed: 16,
+                available: 10
+            })
+        );
+
+        // Only the block pointers run past the end, starting at byte 40
+        assert_eq!(
+            Inode::from_bytes(&[0; 64]).map(|_| ()),
+            Err(ParseError {
+                offset: 40,
+                needed: 60,
+                available: 24
+            })
+        );
+    }
 }
@@ -1 +1,4 @@
+pub mod dev;
 pub mod ext2;
+pub mod proc;
+pub mod tmp;
@@ -0,0 +1,422 @@
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::fmt::Write;
+
+use crate::{
+    interfaces::fs::{
+        DirectoryEntry, DirectoryFileDescriptor, FileDescriptor, FileSystem, FileSystemError,
+        FileType, INodeData, INodeReference, MountableFileSystem, OpenFlag, OpenFlags, SeekMode,
+    },
+    structures::{
+        id::{ProcessID, PID},
+        mem::{PermissionFlag, PermissionFlags},
+    },
+    sync::Mutex,
+};
+
+/// Inode number of the root directory, listing a directory for each process
+const ROOT_INODE: usize = 1;
+
+/// Mode of the directories, read and search permission for everyone
+const DIRECTORY_MODE: u16 = 0x4000 | 0o555;
+/// Mode of the files, read permission for everyone
+const FILE_MODE: u16 = 0x8000 | 0o444;
+
+/// A range of pages mapped into a process's address space
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MappedRegion {
+    pub start: u64,
+    pub end: u64,
+    pub pages: usize,
+    pub permissions: PermissionFlags,
+    /// Name shown after the region, for regions with a special purpose such as the stack
+    pub name: Option<&'static str>,
+}
+
+/// Copy of the state of a process shown in its directory, taken so no locks are held while it is formatted
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProcessSnapshot {
+    pub pid: PID,
+    pub regions: Vec<MappedRegion>,
+}
+
+impl ProcessSnapshot {
+    /// Render the `maps` file, with one line per region giving its address range, permissions and size in pages
+    #[must_use]
+    pub fn render_maps(&self) -> String {
+        let mut maps = String::new();
+
+        for region in &self.regions {
+            let flag = |flag, c| if region.permissions & flag { c } else { '-' };
+            let _ = write!(
+                maps,
+                "{:016x}-{:016x} {}{}{} {}",
+                region.start,
+                region.end,
+                flag(PermissionFlag::Read, 'r'),
+                flag(PermissionFlag::Write, 'w'),
+                flag(PermissionFlag::Execute, 'x'),
+                region.pages
+            );
+
+            if let Some(name) = region.name {
+                let _ = write!(maps, " {name}");
+            }
+
+            maps.push('\n');
+        }
+
+        maps
+    }
+}
+
+/// Source of the processes listed by a [`ProcFileSystem`]
+pub trait ProcessSource: Send + Sync {
+    /// Get the identifiers of every process
+    fn processes(&self) -> Vec<PID>;
+
+    /// Take a snapshot of the process `pid`, or `None` if there is no such process
+    fn snapshot(&self, pid: PID) -> Option<ProcessSnapshot>;
+}
+
+/// What an inode of a [`ProcFileSystem`] refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProcNode {
+    Root,
+    Process(PID),
+    Maps(PID),
+}
+
+impl ProcNode {
+    /// Each process has a pair of inodes after the root, its directory then its `maps` file
+    const fn from_inode(inode: usize) -> Option<Self> {
+        if inode == ROOT_INODE {
+            return Some(Self::Root);
+        }
+
+        let Some(pair) = (inode / 2).checked_sub(1) else {
+            return None;
+        };
+        if pair > u16::MAX as usize {
+            return None;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let pid = ProcessID(pair as u16);
+        Some(if inode.is_multiple_of(2) {
+            Self::Process(pid)
+        } else {
+            Self::Maps(pid)
+        })
+    }
+
+    const fn inode(self) -> usize {
+        match self {
+            Self::Root => ROOT_INODE,
+            Self::Process(pid) => (pid.0 as usize + 1) * 2,
+            Self::Maps(pid) => (pid.0 as usize + 1) * 2 + 1,
+        }
+    }
+}
+
+/// Read only file system presenting the state of each running process, similar to `/proc` on Linux.
+///
+/// The root directory holds a directory for each process named by its PID, which holds a `maps` file listing the
+/// regions mapped into the process's address space. Files are rendered when they are opened or read, so they show
+/// the state of the process at that moment.
+#[allow(clippy::module_name_repetitions)]
+pub struct ProcFileSystem<S: ProcessSource> {
+    device_id: core::sync::atomic::AtomicUsize,
+    source: S,
+}
+
+impl<S: ProcessSource> ProcFileSystem<S> {
+    /// Construct a new [`ProcFileSystem`] listing the processes given by `source`.
+    pub const fn new(source: S) -> Self {
+        Self {
+            device_id: core::sync::atomic::AtomicUsize::new(0),
+            source,
+        }
+    }
+
+    fn inode_ref(&self, node: ProcNode) -> INodeReference {
+        INodeReference {
+            inode: node.inode(),
+            device: self.device_id.load(core::sync::atomic::Ordering::Acquire),
+        }
+    }
+
+    /// Find what `inode` refers to, checking it belongs to this device and its process still exists.
+    fn node(
+        &self,
+        inode: INodeReference,
+    ) -> Result<(ProcNode, Option<ProcessSnapshot>), FileSystemError> {
+        if inode.device != self.device_id.load(core::sync::atomic::Ordering::Acquire) {
+            return Err(FileSystemError::BadInodeWrongDevice(inode));
+        }
+
+        match ProcNode::from_inode(inode.inode) {
+            Some(ProcNode::Root) => Ok((ProcNode::Root, None)),
+            Some(node @ (ProcNode::Process(pid) | ProcNode::Maps(pid))) => self
+                .source
+                .snapshot(pid)
+                .map(|snapshot| (node, Some(snapshot)))
+                .ok_or(FileSystemError::BadInode(inode)),
+            None => Err(FileSystemError::BadInode(inode)),
+        }
+    }
+
+    fn entry(&self, node: ProcNode, name: String, file_type: FileType) -> DirectoryEntry<'static> {
+        DirectoryEntry {
+            inode: self.inode_ref(node),
+            name: name.into(),
+            file_type,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: ProcessSource> FileSystem for ProcFileSystem<S> {
+    async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
+        Ok(self.inode_ref(ProcNode::Root))
+    }
+
+    async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
+        let (node, snapshot) = self.node(inode)?;
+
+        let (mode, size) = match (node, snapshot) {
+            (ProcNode::Maps(_), Some(snapshot)) => (FILE_MODE, snapshot.render_maps().len()),
+            _ => (DIRECTORY_MODE, 0),
+        };
+
+        Ok(INodeData {
+            mode: mode.into(),
+            link_count: 1,
+            uid: 0.into(),
+            gid: 0.into(),
+            size,
+            access_time: 0.into(),
+            modify_time: 0.into(),
+            change_time: 0.into(),
+            reference: inode,
+        })
+    }
+
+    async fn directory_entries(
+        &self,
+        inode: INodeReference,
+    ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError> {
+        let (node, _) = self.node(inode)?;
+
+        let mut entries = alloc::vec![
+            self.entry(node, ".".into(), FileType::Directory),
+            self.entry(ProcNode::Root, "..".into(), FileType::Directory),
+        ];
+
+        match node {
+            ProcNode::Root => entries.extend(self.source.processes().into_iter().map(|pid| {
+                self.entry(
+                    ProcNode::Process(pid),
+                    alloc::format!("{}", pid.0),
+                    FileType::Directory,
+                )
+            })),
+            ProcNode::Process(pid) => {
+                entries.push(self.entry(ProcNode::Maps(pid), "maps".into(), FileType::Regular));
+            }
+            ProcNode::Maps(_) => return Err(FileSystemError::NotDirectory),
+        }
+
+        Ok(entries)
+    }
+
+    /// Open a snapshot of a process's maps or a directory listing. Nothing here can be written, so
+    /// [`OpenFlag::Truncate`] is refused, while [`OpenFlag::Append`] is ignored.
+    async fn open_with(
+        &self,
+        inode: INodeReference,
+        flags: OpenFlags,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+        if flags & OpenFlag::Truncate {
+            return Err(FileSystemError::Unsupported);
+        }
+
+        if let (ProcNode::Maps(_), Some(snapshot)) = self.node(inode)? {
+            return Ok(Arc::new(SnapshotFileDescriptor::new(
+                inode,
+                snapshot.render_maps().into_bytes(),
+            )));
+        }
+
+        let entries = self
+            .directory_entries(inode)
+            .await?
+            .into_iter()
+            .map(DirectoryEntry::into_owned)
+            .collect();
+        Ok(Arc::new(DirectoryFileDescriptor::new(inode, entries)))
+    }
+
+    async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError> {
+        match self.node(inode)? {
+            (ProcNode::Maps(_), Some(snapshot)) => Ok(snapshot.render_maps().into_bytes()),
+            _ => Err(FileSystemError::IsDirectory),
+        }
+    }
+}
+
+impl<S: ProcessSource> MountableFileSystem for ProcFileSystem<S> {
+    fn set_mount_device_id(&self, device_id: usize) {
+        self.device_id
+            .store(device_id, core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Descriptor reading from a copy of a file's contents taken when it was opened
+struct SnapshotFileDescriptor {
+    inode: INodeReference,
+    data: Vec<u8>,
+    cursor: Mutex<usize>,
+}
+
+impl SnapshotFileDescriptor {
+    const fn new(inode: INodeReference, data: Vec<u8>) -> Self {
+        Self {
+            inode,
+            data,
+            cursor: Mutex::new(0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FileDescriptor for SnapshotFileDescriptor {
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+        let remaining = self.data.get(*cursor..).unwrap_or_default();
+        let length = remaining.len().min(buffer.len());
+
+        buffer[..length].copy_from_slice(&remaining[..length]);
+        *cursor += length;
+
+        Ok(length)
+    }
+
+    async fn write(&self, _buffer: &[u8]) -> Result<usize, FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    async fn seek(&self, seek: SeekMode) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+
+        let position = match seek {
+            SeekMode::Set(position) => Some(position),
+            SeekMode::End(offset) => self.data.len().checked_add_signed(offset),
+            SeekMode::Current(offset) => cursor.checked_add_signed(offset),
+        }
+        .ok_or(FileSystemError::GenericError)?;
+
+        *cursor = position;
+        Ok(position)
+    }
+
+    fn inode(&self) -> Option<INodeReference> {
+        Some(self.inode)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{MappedRegion, ProcFileSystem, ProcessSnapshot, ProcessSource};
+    use crate::{
+        interfaces::fs::{FileSystem, MountableFileSystem},
+        structures::{
+            id::PID,
+            mem::{PermissionFlag, PermissionFlags},
+        },
+        tasks::block_on,
+    };
+
+    /// Single process with a read only executable region and a writable stack
+    struct OneProcess;
+
+    impl ProcessSource for OneProcess {
+        fn processes(&self) -> Vec<PID> {
+            alloc::vec![PID::from(7)]
+        }
+
+        fn snapshot(&self, pid: PID) -> Option<ProcessSnapshot> {
+            (pid == PID::from(7)).then(|| ProcessSnapshot {
+                pid,
+                regions: alloc::vec![
+                    MappedRegion {
+                        start: 0x1_0000,
+                        end: 0x1_2000,
+                        pages: 2,
+                        permissions: PermissionFlags::new(0)
+                            | PermissionFlag::Read
+                            | PermissionFlag::Execute,
+                        name: None,
+                    },
+                    MappedRegion {
+                        start: 0x3FFF_F000,
+                        end: 0x4000_0000,
+                        pages: 1,
+                        permissions: PermissionFlags::new(0)
+                            | PermissionFlag::Read
+                            | PermissionFlag::Write,
+                        name: Some("[stack]"),
+                    },
+                ],
+            })
+        }
+    }
+
+    #[test]
+    pub fn maps_lists_regions_test() {
+        let fs = ProcFileSystem::new(OneProcess);
+        fs.set_mount_device_id(3);
+
+        let root = block_on(fs.root_inode()).unwrap();
+        let process = block_on(fs.directory_entries(root))
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.name == "7")
+            .unwrap()
+            .inode;
+        let maps = block_on(fs.directory_entries(process))
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.name == "maps")
+            .unwrap()
+            .inode;
+
+        let descriptor = block_on(fs.open(maps)).unwrap();
+        let mut buffer = [0; 256];
+        let length = block_on(descriptor.read(&mut buffer)).unwrap();
+        let text = String::from_utf8(buffer[..length].to_vec()).unwrap();
+
+        assert_eq!(
+            text,
+            "0000000000010000-0000000000012000 r-x 2\n\
+             000000003ffff000-0000000040000000 rw- 1 [stack]\n"
+        );
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(0));
+        assert_eq!(block_on(fs.inode_data(maps)).unwrap().size, length);
+    }
+
+    #[test]
+    pub fn missing_process_test() {
+        let fs = ProcFileSystem::new(OneProcess);
+        let root = block_on(fs.root_inode()).unwrap();
+
+        // The inode of a process which does not exist, numbered as if it did
+        let mut inode = block_on(fs.directory_entries(root)).unwrap()[2].inode;
+        inode.inode += 2;
+
+        assert!(block_on(fs.directory_entries(inode)).is_err());
+        assert!(block_on(fs.read_to_data(inode)).is_err());
+    }
+}
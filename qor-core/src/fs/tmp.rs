@@ -0,0 +1,542 @@
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+
+use spin::RwLock;
+
+use crate::{
+    interfaces::fs::{
+        DirectoryEntry, DirectoryFileDescriptor, FileDescriptor, FileSystem, FileSystemError,
+        FileType, INodeData, INodeReference, MountableFileSystem, OpenFlag, OpenFlags, SeekMode,
+    },
+    sync::Mutex,
+};
+
+/// Inode number of the root directory
+const ROOT_INODE: usize = 1;
+
+/// Mode of the directories, read, write and search permission for the owner and read and search for everyone else
+const DIRECTORY_MODE: u16 = 0x4000 | 0o755;
+/// Mode of the files, read and write permission for the owner and read for everyone else
+const FILE_MODE: u16 = 0x8000 | 0o644;
+
+/// Contents of a file, shared with the descriptors open on it so they outlive the file being unlinked
+type FileData = Arc<RwLock<Vec<u8>>>;
+
+/// An inode of a [`TmpFs`]
+enum TmpNode {
+    File(FileData),
+    Directory {
+        parent: usize,
+        entries: BTreeMap<String, usize>,
+    },
+}
+
+impl TmpNode {
+    const fn file_type(&self) -> FileType {
+        match self {
+            Self::File(_) => FileType::Regular,
+            Self::Directory { .. } => FileType::Directory,
+        }
+    }
+}
+
+/// File system held entirely in memory, for scratch storage and for testing the layers above a file system without
+/// a block device.
+///
+/// Every inode lives on the heap until it is unlinked, so nothing written survives a reboot. Files are grown as they
+/// are written, and an open file keeps its contents even after it is removed from its directory.
+#[allow(clippy::module_name_repetitions)]
+pub struct TmpFs {
+    device_id: core::sync::atomic::AtomicUsize,
+    inodes: RwLock<BTreeMap<usize, TmpNode>>,
+    next_inode: core::sync::atomic::AtomicUsize,
+}
+
+impl TmpFs {
+    /// Construct a new [`TmpFs`] holding only an empty root directory.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut inodes = BTreeMap::new();
+        inodes.insert(
+            ROOT_INODE,
+            TmpNode::Directory {
+                parent: ROOT_INODE,
+                entries: BTreeMap::new(),
+            },
+        );
+
+        Self {
+            device_id: core::sync::atomic::AtomicUsize::new(0),
+            inodes: RwLock::new(inodes),
+            next_inode: core::sync::atomic::AtomicUsize::new(ROOT_INODE + 1),
+        }
+    }
+
+    fn inode_ref(&self, inode: usize) -> INodeReference {
+        INodeReference {
+            inode,
+            device: self.device_id.load(core::sync::atomic::Ordering::Acquire),
+        }
+    }
+
+    /// Get the inode number of `inode`, checking it belongs to this device.
+    fn index(&self, inode: INodeReference) -> Result<usize, FileSystemError> {
+        if inode.device == self.device_id.load(core::sync::atomic::Ordering::Acquire) {
+            Ok(inode.inode)
+        } else {
+            Err(FileSystemError::BadInodeWrongDevice(inode))
+        }
+    }
+
+    /// Get the contents of the file `inode`.
+    fn file(&self, inode: INodeReference) -> Result<FileData, FileSystemError> {
+        match self.inodes.read().get(&self.index(inode)?) {
+            Some(TmpNode::File(data)) => Ok(data.clone()),
+            Some(TmpNode::Directory { .. }) => Err(FileSystemError::IsDirectory),
+            None => Err(FileSystemError::BadInode(inode)),
+        }
+    }
+
+    /// Add `node` to the directory `parent` under `name`, returning its inode.
+    fn insert(
+        &self,
+        parent: INodeReference,
+        name: &str,
+        node: TmpNode,
+    ) -> Result<INodeReference, FileSystemError> {
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(FileSystemError::GenericError);
+        }
+
+        let parent_index = self.index(parent)?;
+        let mut inodes = self.inodes.write();
+
+        let entries = match inodes.get_mut(&parent_index) {
+            Some(TmpNode::Directory { entries, .. }) => entries,
+            Some(TmpNode::File(_)) => return Err(FileSystemError::NotDirectory),
+            None => return Err(FileSystemError::BadInode(parent)),
+        };
+
+        if entries.contains_key(name) {
+            return Err(FileSystemError::AlreadyExists);
+        }
+
+        let index = self
+            .next_inode
+            .fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+        entries.insert(name.to_string(), index);
+        inodes.insert(index, node);
+
+        Ok(self.inode_ref(index))
+    }
+}
+
+impl Default for TmpFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl FileSystem for TmpFs {
+    async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
+        Ok(self.inode_ref(ROOT_INODE))
+    }
+
+    async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
+        let (mode, size) = match self.inodes.read().get(&self.index(inode)?) {
+            Some(TmpNode::File(data)) => (FILE_MODE, data.read().len()),
+            Some(TmpNode::Directory { .. }) => (DIRECTORY_MODE, 0),
+            None => return Err(FileSystemError::BadInode(inode)),
+        };
+
+        Ok(INodeData {
+            mode: mode.into(),
+            link_count: 1,
+            uid: 0.into(),
+            gid: 0.into(),
+            size,
+            access_time: 0.into(),
+            modify_time: 0.into(),
+            change_time: 0.into(),
+            reference: inode,
+        })
+    }
+
+    async fn directory_entries(
+        &self,
+        inode: INodeReference,
+    ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError> {
+        let inodes = self.inodes.read();
+
+        let (parent, entries) = match inodes.get(&self.index(inode)?) {
+            Some(TmpNode::Directory { parent, entries }) => (*parent, entries),
+            Some(TmpNode::File(_)) => return Err(FileSystemError::NotDirectory),
+            None => return Err(FileSystemError::BadInode(inode)),
+        };
+
+        let mut listing = alloc::vec![
+            DirectoryEntry {
+                inode,
+                name: ".".into(),
+                file_type: FileType::Directory,
+            },
+            DirectoryEntry {
+                inode: self.inode_ref(parent),
+                name: "..".into(),
+                file_type: FileType::Directory,
+            },
+        ];
+
+        listing.extend(entries.iter().map(|(name, &index)| {
+            DirectoryEntry {
+                inode: self.inode_ref(index),
+                name: name.clone().into(),
+                file_type: inodes
+                    .get(&index)
+                    .map_or(FileType::Unknown, TmpNode::file_type),
+            }
+        }));
+
+        Ok(listing)
+    }
+
+    /// Open a file for reading and writing, or a listing of a directory. [`OpenFlag::Truncate`] empties a file, and
+    /// [`OpenFlag::Append`] starts the descriptor at its end.
+    async fn open_with(
+        &self,
+        inode: INodeReference,
+        flags: OpenFlags,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+        match self.file(inode) {
+            Ok(data) => {
+                if flags & OpenFlag::Truncate {
+                    data.write().clear();
+                }
+
+                let cursor = if flags & OpenFlag::Append {
+                    data.read().len()
+                } else {
+                    0
+                };
+
+                Ok(Arc::new(TmpFileDescriptor {
+                    inode,
+                    data,
+                    cursor: Mutex::new(cursor),
+                }))
+            }
+            Err(FileSystemError::IsDirectory) => {
+                if flags & OpenFlag::Truncate {
+                    return Err(FileSystemError::IsDirectory);
+                }
+
+                let entries = self
+                    .directory_entries(inode)
+                    .await?
+                    .into_iter()
+                    .map(DirectoryEntry::into_owned)
+                    .collect();
+                Ok(Arc::new(DirectoryFileDescriptor::new(inode, entries)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError> {
+        Ok(self.file(inode)?.read().clone())
+    }
+
+    async fn create(
+        &self,
+        parent: INodeReference,
+        name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        self.insert(parent, name, TmpNode::File(FileData::default()))
+    }
+
+    async fn mkdir(
+        &self,
+        parent: INodeReference,
+        name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        self.insert(
+            parent,
+            name,
+            TmpNode::Directory {
+                parent: self.index(parent)?,
+                entries: BTreeMap::new(),
+            },
+        )
+    }
+
+    async fn rename(
+        &self,
+        parent: INodeReference,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), FileSystemError> {
+        if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains('/') {
+            return Err(FileSystemError::GenericError);
+        }
+
+        let parent_index = self.index(parent)?;
+        let mut inodes = self.inodes.write();
+
+        let entries = match inodes.get_mut(&parent_index) {
+            Some(TmpNode::Directory { entries, .. }) => entries,
+            Some(TmpNode::File(_)) => return Err(FileSystemError::NotDirectory),
+            None => return Err(FileSystemError::BadInode(parent)),
+        };
+
+        if !entries.contains_key(old_name) {
+            return Err(FileSystemError::PathNotFound);
+        }
+        if entries.contains_key(new_name) {
+            return Err(FileSystemError::AlreadyExists);
+        }
+
+        let index = entries
+            .remove(old_name)
+            .expect("Entry was checked to exist");
+        entries.insert(new_name.to_string(), index);
+
+        Ok(())
+    }
+
+    async fn unlink(&self, parent: INodeReference, name: &str) -> Result<(), FileSystemError> {
+        let parent_index = self.index(parent)?;
+        let mut inodes = self.inodes.write();
+
+        let index = match inodes.get(&parent_index) {
+            Some(TmpNode::Directory { entries, .. }) => {
+                *entries.get(name).ok_or(FileSystemError::PathNotFound)?
+            }
+            Some(TmpNode::File(_)) => return Err(FileSystemError::NotDirectory),
+            None => return Err(FileSystemError::BadInode(parent)),
+        };
+
+        if let Some(TmpNode::Directory { entries, .. }) = inodes.get(&index) {
+            if !entries.is_empty() {
+                return Err(FileSystemError::NotEmpty);
+            }
+        }
+
+        if let Some(TmpNode::Directory { entries, .. }) = inodes.get_mut(&parent_index) {
+            entries.remove(name);
+        }
+        inodes.remove(&index);
+
+        Ok(())
+    }
+
+    async fn truncate(&self, inode: INodeReference, size: usize) -> Result<(), FileSystemError> {
+        self.file(inode)?.write().resize(size, 0);
+        Ok(())
+    }
+}
+
+impl MountableFileSystem for TmpFs {
+    fn set_mount_device_id(&self, device_id: usize) {
+        self.device_id
+            .store(device_id, core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Descriptor reading and writing the contents of a file of a [`TmpFs`] in place
+struct TmpFileDescriptor {
+    inode: INodeReference,
+    data: FileData,
+    cursor: Mutex<usize>,
+}
+
+#[async_trait::async_trait]
+impl FileDescriptor for TmpFileDescriptor {
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+        let data = self.data.read();
+        let remaining = data.get(*cursor..).unwrap_or_default();
+        let length = remaining.len().min(buffer.len());
+
+        buffer[..length].copy_from_slice(&remaining[..length]);
+        *cursor += length;
+
+        Ok(length)
+    }
+
+    /// Write `buffer` at the cursor, extending the file as needed. Writing past the end fills the gap with zeros.
+    async fn write(&self, buffer: &[u8]) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+        let mut data = self.data.write();
+        let end = *cursor + buffer.len();
+
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[*cursor..end].copy_from_slice(buffer);
+        *cursor = end;
+
+        Ok(buffer.len())
+    }
+
+    async fn seek(&self, seek: SeekMode) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+
+        let position = match seek {
+            SeekMode::Set(position) => Some(position),
+            SeekMode::End(offset) => self.data.read().len().checked_add_signed(offset),
+            SeekMode::Current(offset) => cursor.checked_add_signed(offset),
+        }
+        .ok_or(FileSystemError::GenericError)?;
+
+        *cursor = position;
+        Ok(position)
+    }
+
+    fn inode(&self) -> Option<INodeReference> {
+        Some(self.inode)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{string::String, sync::Arc, vec::Vec};
+
+    use super::TmpFs;
+    use crate::{
+        interfaces::fs::{
+            FileSystem, FileSystemError, FileType, MountingFilesystem, OpenFlag, OpenFlags,
+            ParentFileSystem, PathLookup, SeekMode, VirtualFileSystem,
+        },
+        tasks::block_on,
+    };
+
+    fn names(fs: &TmpFs, inode: crate::interfaces::fs::INodeReference) -> Vec<String> {
+        block_on(fs.directory_entries(inode))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.name.into_owned())
+            .collect()
+    }
+
+    #[test]
+    pub fn write_then_read_test() {
+        let fs = TmpFs::new();
+        let root = block_on(fs.root_inode()).unwrap();
+        let file = block_on(fs.create(root, "notes")).unwrap();
+
+        let descriptor = block_on(fs.open(file)).unwrap();
+        assert_eq!(block_on(descriptor.write(b"hello")), Ok(5));
+        assert_eq!(block_on(descriptor.seek(SeekMode::Set(8))), Ok(8));
+        assert_eq!(block_on(descriptor.write(b"world")), Ok(5));
+
+        assert_eq!(
+            block_on(fs.read_to_data(file)).unwrap(),
+            b"hello\0\0\0world"
+        );
+        assert_eq!(block_on(fs.inode_data(file)).unwrap().size, 13);
+
+        // A second descriptor sees the first's writes, and appending starts at the end
+        let other = block_on(fs.open_with(file, OpenFlags::default() | OpenFlag::Append)).unwrap();
+        block_on(other.write(b"!")).unwrap();
+        block_on(descriptor.seek(SeekMode::Set(8))).unwrap();
+        let mut buffer = [0; 16];
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(6));
+        assert_eq!(&buffer[..6], b"world!");
+
+        block_on(fs.open_with(file, OpenFlags::default() | OpenFlag::Truncate)).unwrap();
+        assert_eq!(block_on(fs.read_to_data(file)).unwrap(), b"");
+    }
+
+    #[test]
+    pub fn directory_test() {
+        let fs = TmpFs::new();
+        let root = block_on(fs.root_inode()).unwrap();
+        let dir = block_on(fs.mkdir(root, "dir")).unwrap();
+        block_on(fs.create(dir, "b")).unwrap();
+        block_on(fs.create(dir, "a")).unwrap();
+
+        assert_eq!(names(&fs, root), [".", "..", "dir"]);
+        assert_eq!(names(&fs, dir), [".", "..", "a", "b"]);
+        assert_eq!(block_on(fs.directory_entries(dir)).unwrap()[1].inode, root);
+        assert_eq!(
+            block_on(fs.directory_entries(root)).unwrap()[2].file_type,
+            FileType::Directory
+        );
+
+        assert_eq!(
+            block_on(fs.create(dir, "a")),
+            Err(FileSystemError::AlreadyExists)
+        );
+        assert_eq!(
+            block_on(fs.mkdir(dir, "..")),
+            Err(FileSystemError::GenericError)
+        );
+
+        block_on(fs.rename(dir, "a", "c")).unwrap();
+        assert_eq!(names(&fs, dir), [".", "..", "b", "c"]);
+        assert_eq!(
+            block_on(fs.rename(dir, "a", "d")),
+            Err(FileSystemError::PathNotFound)
+        );
+    }
+
+    #[test]
+    pub fn unlink_test() {
+        let fs = TmpFs::new();
+        let root = block_on(fs.root_inode()).unwrap();
+        let dir = block_on(fs.mkdir(root, "dir")).unwrap();
+        let file = block_on(fs.create(dir, "file")).unwrap();
+
+        let descriptor = block_on(fs.open(file)).unwrap();
+        block_on(descriptor.write(b"kept")).unwrap();
+
+        assert_eq!(
+            block_on(fs.unlink(root, "dir")),
+            Err(FileSystemError::NotEmpty)
+        );
+        block_on(fs.unlink(dir, "file")).unwrap();
+        block_on(fs.unlink(root, "dir")).unwrap();
+
+        assert_eq!(names(&fs, root), [".", ".."]);
+        assert_eq!(
+            block_on(fs.read_to_data(file)),
+            Err(FileSystemError::BadInode(file))
+        );
+
+        // The open descriptor still holds the contents
+        block_on(descriptor.seek(SeekMode::Set(0))).unwrap();
+        let mut buffer = [0; 4];
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(4));
+        assert_eq!(&buffer, b"kept");
+    }
+
+    #[test]
+    pub fn mounted_test() {
+        let mut vfs = VirtualFileSystem::new();
+        let root = block_on(vfs.root_inode()).unwrap();
+        vfs.mount_filesystem(root, Arc::new(TmpFs::new())).unwrap();
+
+        let flags = OpenFlags::default() | OpenFlag::Create;
+        let tmp = block_on(vfs.lookup("/")).unwrap();
+        block_on(vfs.mkdir(tmp, "etc")).unwrap();
+        let descriptor = block_on(vfs.open_path("/etc/motd", flags)).unwrap();
+        block_on(descriptor.write(b"welcome")).unwrap();
+
+        let motd = block_on(vfs.lookup("/etc/motd")).unwrap();
+        assert_eq!(block_on(vfs.read_to_data(motd)).unwrap(), b"welcome");
+
+        let etc = block_on(vfs.lookup("/etc")).unwrap();
+        block_on(vfs.unlink(etc, "motd")).unwrap();
+        assert!(matches!(
+            block_on(vfs.lookup("/etc/motd")),
+            Err(FileSystemError::PathNotFound)
+        ));
+    }
+}
@@ -42,3 +42,21 @@ pub trait GenericByteInterface<E>:
     GenericByteReadInterface<E> + GenericByteWriteInterface<E>
 {
 }
+
+impl<E, T: GenericByteWriteInterface<E> + ?Sized> GenericByteWriteInterface<E> for &T {
+    fn send_byte(&self, byte: u8) -> Result<(), E> {
+        (**self).send_byte(byte)
+    }
+
+    fn send_bytes(&self, bytes: &[u8]) -> Result<(), E> {
+        (**self).send_bytes(bytes)
+    }
+}
+
+impl<E, T: GenericByteReadInterface<E> + ?Sized> GenericByteReadInterface<E> for &T {
+    fn read_byte(&self) -> Result<Option<u8>, E> {
+        (**self).read_byte()
+    }
+}
+
+impl<E, T: GenericByteInterface<E> + ?Sized> GenericByteInterface<E> for &T {}
@@ -2,9 +2,9 @@ use core::marker::PhantomData;
 
 use crate::interfaces::bytes::GenericByteInterface;
 
-use super::FileSystemError;
+use super::{FileSystemError, INodeReference};
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
 
 pub enum SeekMode {
     Set(usize),
@@ -14,7 +14,7 @@ pub enum SeekMode {
 
 #[allow(clippy::module_name_repetitions)]
 #[async_trait::async_trait]
-pub trait FileDescriptor {
+pub trait FileDescriptor: Send + Sync {
     /// Read bytes from the file starting at the cursor into `buffer`. Returns the number of bytes read.
     ///
     /// # Errors
@@ -35,6 +35,96 @@ pub trait FileDescriptor {
     ///
     /// Returns an error if the operation failed.
     async fn seek(&self, seek: SeekMode) -> Result<usize, FileSystemError>;
+
+    /// Fill `buffer` with packed records for the directory entries after the cursor, advancing it past them. Returns
+    /// the number of bytes written, zero once every entry has been listed. See [`super::pack_directory_record`] for
+    /// the layout of a record.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotDirectory` if the descriptor is not for a directory, which is the default, and `BufferTooSmall`
+    /// if the next record does not fit in `buffer`.
+    async fn read_directory(&self, _buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        Err(FileSystemError::NotDirectory)
+    }
+
+    /// Get the inode the descriptor was opened from, or `None` if it is not backed by one, which is the default.
+    fn inode(&self) -> Option<INodeReference> {
+        None
+    }
+
+    /// Flush and release any resources held by the descriptor. This is called once the last reference to a shared
+    /// descriptor is released, and does nothing by default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation failed.
+    async fn close(&self) -> Result<(), FileSystemError> {
+        Ok(())
+    }
+}
+
+/// Remove every descriptor from a process's descriptor table.
+///
+/// `close` is invoked on each descriptor whose last reference is held by the table, descriptors still shared with
+/// another table are only dropped from this one.
+///
+/// # Errors
+///
+/// Every descriptor is released even if closing one fails, the first error encountered is returned.
+pub async fn release_file_descriptors(
+    descriptors: &mut BTreeMap<usize, Arc<dyn FileDescriptor>>,
+) -> Result<(), FileSystemError> {
+    let mut result = Ok(());
+
+    while let Some((_, descriptor)) = descriptors.pop_first() {
+        if let Err(e) = release_file_descriptor(descriptor).await {
+            if result.is_ok() {
+                result = Err(e);
+            }
+        }
+    }
+
+    result
+}
+
+/// Drop a descriptor which has been removed from a descriptor table, calling `close` on it if that was its last
+/// reference.
+///
+/// # Errors
+///
+/// Returns the error raised by `close`, if it was called and failed.
+pub async fn release_file_descriptor(
+    descriptor: Arc<dyn FileDescriptor>,
+) -> Result<(), FileSystemError> {
+    // The same descriptor may appear under several numbers (after a `dup`), so only the final reference closes it
+    if Arc::strong_count(&descriptor) == 1 {
+        descriptor.close().await
+    } else {
+        Ok(())
+    }
+}
+
+/// Insert `descriptor` into a descriptor table under the lowest unused number, returning that number, or `None` if
+/// the table already holds `limit` descriptors.
+pub fn insert_file_descriptor(
+    descriptors: &mut BTreeMap<usize, Arc<dyn FileDescriptor>>,
+    descriptor: Arc<dyn FileDescriptor>,
+    limit: usize,
+) -> Option<usize> {
+    if descriptors.len() >= limit {
+        return None;
+    }
+
+    // The keys are sorted, so the first number which does not match its position is the lowest gap
+    let number = descriptors
+        .keys()
+        .enumerate()
+        .find(|(expected, number)| expected != *number)
+        .map_or(descriptors.len(), |(expected, _)| expected);
+
+    descriptors.insert(number, descriptor);
+    Some(number)
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -53,14 +143,23 @@ impl <E: core::marker::Sync, Inner:  core::marker::Sync + GenericByteInterface<E
 }
 
 #[async_trait::async_trait]
-impl<E: core::marker::Sync, Inner:  core::marker::Sync + GenericByteInterface<E>> FileDescriptor for GenericDeviceFileDescriptor<E, Inner> {
+impl<E: core::marker::Send + core::marker::Sync, Inner:  core::marker::Send + core::marker::Sync + GenericByteInterface<E>> FileDescriptor for GenericDeviceFileDescriptor<E, Inner> {
     /// Read bytes from the file starting at the cursor into `buffer`. Returns the number of bytes read.
     ///
     /// # Errors
     ///
     /// Returns an error if the operation failed.
-    async fn read(&self, _buffer: &mut [u8]) -> Result<usize, FileSystemError> {
-        todo!()
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        // Only the bytes the device already has are read, so this returns early rather than waiting for more
+        for (count, byte) in buffer.iter_mut().enumerate() {
+            match self.inner.read_byte() {
+                Ok(Some(value)) => *byte = value,
+                Ok(None) => return Ok(count),
+                Err(_) => return Err(FileSystemError::GenericError)
+            }
+        }
+
+        Ok(buffer.len())
     }
 
     /// Writes bytes to the file starting at the cursor. Returns the number of bytes written.
@@ -83,4 +182,124 @@ impl<E: core::marker::Sync, Inner:  core::marker::Sync + GenericByteInterface<E>
     async fn seek(&self, _seek: SeekMode) -> Result<usize, FileSystemError> {
         Ok(0)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{boxed::Box, collections::BTreeMap, sync::Arc};
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{
+        insert_file_descriptor, release_file_descriptor, release_file_descriptors, FileDescriptor,
+        SeekMode,
+    };
+    use crate::interfaces::fs::{EmptyFileDescriptor, FileSystemError};
+    use crate::tasks::block_on;
+
+    /// Descriptor which counts the number of times it is closed.
+    struct ClosingDescriptor {
+        closed: &'static AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl FileDescriptor for ClosingDescriptor {
+        async fn read(&self, _buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+            Ok(0)
+        }
+
+        async fn write(&self, buffer: &[u8]) -> Result<usize, FileSystemError> {
+            Ok(buffer.len())
+        }
+
+        async fn seek(&self, _seek: SeekMode) -> Result<usize, FileSystemError> {
+            Ok(0)
+        }
+
+        async fn close(&self) -> Result<(), FileSystemError> {
+            self.closed.fetch_add(1, Ordering::AcqRel);
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn release_file_descriptors_test() {
+        let closed = Box::leak(Box::new(AtomicUsize::new(0)));
+
+        let private = Arc::new(ClosingDescriptor { closed }) as Arc<dyn FileDescriptor>;
+        let duplicated = Arc::new(ClosingDescriptor { closed }) as Arc<dyn FileDescriptor>;
+        let shared = Arc::new(ClosingDescriptor { closed }) as Arc<dyn FileDescriptor>;
+
+        let weak_private = Arc::downgrade(&private);
+        let weak_duplicated = Arc::downgrade(&duplicated);
+        let weak_shared = Arc::downgrade(&shared);
+
+        // Another process holds its own reference to the shared descriptor
+        let other_process = shared.clone();
+
+        let mut descriptors = BTreeMap::new();
+        descriptors.insert(0, private);
+        descriptors.insert(1, duplicated.clone());
+        descriptors.insert(2, duplicated);
+        descriptors.insert(3, shared);
+
+        crate::tasks::execute_task(crate::tasks::Task::ignore_result(
+            release_file_descriptors(&mut descriptors),
+        ));
+
+        assert!(descriptors.is_empty());
+        assert_eq!(weak_private.strong_count(), 0);
+        assert_eq!(weak_duplicated.strong_count(), 0);
+        assert_eq!(weak_shared.strong_count(), 1);
+        assert_eq!(closed.load(Ordering::Acquire), 2);
+
+        drop(other_process);
+        assert_eq!(weak_shared.strong_count(), 0);
+    }
+
+    #[test]
+    pub fn blocking_empty_descriptor_test() {
+        let descriptor = Arc::new(EmptyFileDescriptor {}) as Arc<dyn FileDescriptor>;
+
+        assert_eq!(block_on(descriptor.write(b"hello")), Ok(5));
+        assert_eq!(block_on(descriptor.write(&[])), Ok(0));
+
+        let mut buffer = [0xff; 16];
+        assert_eq!(block_on(descriptor.read(&mut buffer)), Ok(16));
+        assert_eq!(buffer, [0; 16]);
+    }
+
+    #[test]
+    pub fn insert_file_descriptor_test() {
+        let insert = |descriptors: &mut BTreeMap<usize, Arc<dyn FileDescriptor>>| {
+            insert_file_descriptor(descriptors, Arc::new(EmptyFileDescriptor {}), 4)
+        };
+        let mut descriptors = BTreeMap::new();
+
+        assert_eq!(insert(&mut descriptors), Some(0));
+        assert_eq!(insert(&mut descriptors), Some(1));
+        assert_eq!(insert(&mut descriptors), Some(2));
+
+        // Gaps left by closed descriptors are reused lowest first
+        descriptors.remove(&0);
+        descriptors.remove(&1);
+        assert_eq!(insert(&mut descriptors), Some(0));
+        assert_eq!(insert(&mut descriptors), Some(1));
+
+        assert_eq!(insert(&mut descriptors), Some(3));
+        assert_eq!(insert(&mut descriptors), None);
+        assert_eq!(descriptors.len(), 4);
+    }
+
+    #[test]
+    pub fn release_file_descriptor_test() {
+        let closed = Box::leak(Box::new(AtomicUsize::new(0)));
+        let descriptor = Arc::new(ClosingDescriptor { closed }) as Arc<dyn FileDescriptor>;
+
+        block_on(release_file_descriptor(descriptor.clone())).unwrap();
+        assert_eq!(closed.load(Ordering::Acquire), 0);
+
+        block_on(release_file_descriptor(descriptor)).unwrap();
+        assert_eq!(closed.load(Ordering::Acquire), 1);
+    }
+}
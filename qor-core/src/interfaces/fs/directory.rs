@@ -0,0 +1,195 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use super::{DirectoryEntry, FileDescriptor, FileSystemError, INodeReference, SeekMode};
+
+/// Size of the fixed portion of a packed directory record.
+pub const DIRECTORY_RECORD_HEADER_SIZE: usize = 11;
+
+/// Pack `entry` into the start of `buffer` as a directory record, returning the number of bytes used, or `None` if
+/// the record does not fit.
+///
+/// Records are laid out back to back with no padding, all fields little endian:
+///
+/// | Offset | Size  | Field                                      |
+/// |--------|-------|--------------------------------------------|
+/// | 0      | 8     | inode number                               |
+/// | 8      | 1     | `d_type`, see [`super::FileType`]          |
+/// | 9      | 2     | name length `n`                            |
+/// | 11     | `n`   | name, without a terminating NUL            |
+#[must_use]
+pub fn pack_directory_record(entry: &DirectoryEntry<'_>, buffer: &mut [u8]) -> Option<usize> {
+    let name = entry.name.as_bytes();
+    let name_length = u16::try_from(name.len()).ok()?;
+    let length = DIRECTORY_RECORD_HEADER_SIZE + name.len();
+    let record = buffer.get_mut(..length)?;
+
+    record[0..8].copy_from_slice(&(entry.inode.inode as u64).to_le_bytes());
+    record[8] = entry.file_type.directory_entry_type();
+    record[9..11].copy_from_slice(&name_length.to_le_bytes());
+    record[11..].copy_from_slice(name);
+
+    Some(length)
+}
+
+/// Descriptor for an open directory, which lists the entries it held when it was opened.
+///
+/// Each [`FileDescriptor::read_directory`] call continues from where the previous one stopped, and
+/// `SeekMode::Set(0)` rewinds to the first entry.
+#[allow(clippy::module_name_repetitions)]
+pub struct DirectoryFileDescriptor {
+    inode: INodeReference,
+    entries: Vec<DirectoryEntry<'static>>,
+    cursor: crate::sync::Mutex<usize>,
+}
+
+impl DirectoryFileDescriptor {
+    /// Construct a descriptor for the directory `inode` listing `entries`, starting from the first
+    #[must_use]
+    pub const fn new(inode: INodeReference, entries: Vec<DirectoryEntry<'static>>) -> Self {
+        Self {
+            inode,
+            entries,
+            cursor: crate::sync::Mutex::new(0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FileDescriptor for DirectoryFileDescriptor {
+    async fn read(&self, _buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        Err(FileSystemError::IsDirectory)
+    }
+
+    async fn write(&self, _buffer: &[u8]) -> Result<usize, FileSystemError> {
+        Err(FileSystemError::IsDirectory)
+    }
+
+    async fn seek(&self, seek: SeekMode) -> Result<usize, FileSystemError> {
+        match seek {
+            SeekMode::Set(0) => {
+                *self.cursor.async_lock().await = 0;
+                Ok(0)
+            }
+            _ => Err(FileSystemError::IsDirectory),
+        }
+    }
+
+    async fn read_directory(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        let mut cursor = self.cursor.async_lock().await;
+        let mut written = 0;
+
+        for entry in &self.entries[*cursor..] {
+            let Some(length) = pack_directory_record(entry, &mut buffer[written..]) else {
+                break;
+            };
+
+            written += length;
+            *cursor += 1;
+        }
+
+        // Returning zero would look like the end of the directory, so a buffer too small for the next record fails
+        if written == 0 && *cursor < self.entries.len() {
+            return Err(FileSystemError::BufferTooSmall);
+        }
+
+        Ok(written)
+    }
+
+    fn inode(&self) -> Option<INodeReference> {
+        Some(self.inode)
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{DirectoryFileDescriptor, DIRECTORY_RECORD_HEADER_SIZE};
+    use crate::interfaces::fs::{
+        DirectoryEntry, FileDescriptor, FileSystemError, FileType, INodeReference, SeekMode,
+    };
+    use crate::tasks::block_on;
+
+    /// Unpack the directory records in `buffer` into their inode, `d_type` and name.
+    fn unpack_records(mut buffer: &[u8]) -> Vec<(u64, u8, String)> {
+        let mut records = Vec::new();
+
+        while !buffer.is_empty() {
+            let inode = u64::from_le_bytes(buffer[0..8].try_into().unwrap());
+            let name_length = u16::from_le_bytes(buffer[9..11].try_into().unwrap()) as usize;
+            let end = DIRECTORY_RECORD_HEADER_SIZE + name_length;
+            let name = core::str::from_utf8(&buffer[DIRECTORY_RECORD_HEADER_SIZE..end]).unwrap();
+
+            records.push((inode, buffer[8], String::from(name)));
+            buffer = &buffer[end..];
+        }
+
+        records
+    }
+
+    fn entry(inode: usize, name: &'static str, file_type: FileType) -> DirectoryEntry<'static> {
+        DirectoryEntry {
+            inode: INodeReference { inode, device: 1 },
+            name: name.into(),
+            file_type,
+        }
+    }
+
+    #[test]
+    pub fn read_directory_pages_test() {
+        let descriptor = DirectoryFileDescriptor::new(
+            INodeReference {
+                inode: 2,
+                device: 1,
+            },
+            alloc::vec![
+                entry(2, ".", FileType::Directory),
+                entry(11, "file", FileType::Regular),
+                entry(12, "dev", FileType::CharacterDevice),
+            ],
+        );
+        assert_eq!(descriptor.inode().map(|inode| inode.inode), Some(2));
+
+        // Room for the first two records, but not the third
+        let mut buffer = [0; 2 * DIRECTORY_RECORD_HEADER_SIZE + 5 + 2];
+        let length = block_on(descriptor.read_directory(&mut buffer)).unwrap();
+        assert_eq!(
+            unpack_records(&buffer[..length]),
+            [(2, 4, String::from(".")), (11, 8, String::from("file"))]
+        );
+
+        let length = block_on(descriptor.read_directory(&mut buffer)).unwrap();
+        assert_eq!(
+            unpack_records(&buffer[..length]),
+            [(12, 2, String::from("dev"))]
+        );
+
+        assert_eq!(block_on(descriptor.read_directory(&mut buffer)), Ok(0));
+
+        block_on(descriptor.seek(SeekMode::Set(0))).unwrap();
+        let length = block_on(descriptor.read_directory(&mut buffer)).unwrap();
+        assert_eq!(unpack_records(&buffer[..length]).len(), 2);
+    }
+
+    #[test]
+    pub fn read_directory_small_buffer_test() {
+        let descriptor = DirectoryFileDescriptor::new(
+            INodeReference {
+                inode: 2,
+                device: 1,
+            },
+            alloc::vec![entry(2, "name", FileType::Directory)],
+        );
+
+        let mut buffer = [0; DIRECTORY_RECORD_HEADER_SIZE];
+        assert_eq!(
+            block_on(descriptor.read_directory(&mut buffer)),
+            Err(FileSystemError::BufferTooSmall)
+        );
+        assert_eq!(
+            block_on(descriptor.read(&mut buffer)),
+            Err(FileSystemError::IsDirectory)
+        );
+    }
+}
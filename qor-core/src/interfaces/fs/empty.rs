@@ -1,6 +1,6 @@
 use super::{
-    DirectoryEntry, FileDescriptor, FileSystem, FileSystemError, INodeData, INodeReference,
-    MountableFileSystem, SeekMode,
+    DirectoryEntry, FileDescriptor, FileSystem, FileSystemError, FileType, INodeData,
+    INodeReference, MountableFileSystem, OpenFlags, SeekMode,
 };
 
 use alloc::boxed::Box;
@@ -58,8 +58,9 @@ impl FileSystem for EmptyFileSystem {
     async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
         self.verify_ref(inode)?;
         match inode.inode {
+            // The root is an empty directory, so other file systems can be mounted over it
             0 => Ok(INodeData {
-                mode: 0.into(),
+                mode: (0x4000 | 0o555).into(),
                 link_count: 0,
                 uid: 0.into(),
                 gid: 0.into(),
@@ -82,20 +83,25 @@ impl FileSystem for EmptyFileSystem {
             0 => Ok(alloc::vec![
                 DirectoryEntry {
                     inode: self.inode_ref(0),
-                    name: ".".into()
+                    name: ".".into(),
+                    file_type: FileType::Directory,
                 },
                 DirectoryEntry {
                     inode: self.inode_ref(0),
-                    name: "..".into()
+                    name: "..".into(),
+                    file_type: FileType::Directory,
                 }
             ]),
             _ => Err(FileSystemError::BadInode(inode)),
         }
     }
 
-    async fn open(
+    /// Open the root directory. Its descriptor reads zeros and discards writes without a cursor, so every flag is
+    /// ignored.
+    async fn open_with(
         &self,
         inode: INodeReference,
+        _flags: OpenFlags,
     ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
         self.verify_ref(inode)?;
         match inode.inode {
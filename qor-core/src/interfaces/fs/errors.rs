@@ -9,4 +9,15 @@ pub enum FileSystemError {
     CorruptedFilesystem,
     PathNotFound,
     NotDirectory,
+    IsDirectory,
+    BufferTooSmall,
+    AlreadyExists,
+    AlreadyMounted,
+    NotMounted,
+    Busy,
+    NotSymbolicLink,
+    TooManySymbolicLinks,
+    NoSpace,
+    NotEmpty,
+    Unsupported,
 }
@@ -1,6 +1,9 @@
-use super::{DirectoryEntry, FileDescriptor, FileSystemError, INodeData, INodeReference};
+use super::{
+    normalize, DirectoryEntry, FileDescriptor, FileSystemError, INodeData, INodeReference,
+    OpenFlag, OpenFlags,
+};
 
-use alloc::{boxed::Box, vec::Vec, sync::Arc};
+use alloc::{boxed::Box, string::String, vec::Vec, sync::Arc};
 
 #[async_trait::async_trait]
 pub trait FileSystem {
@@ -10,9 +13,105 @@ pub trait FileSystem {
         &self,
         inode: INodeReference,
     ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError>;
-    async fn open(&self, inode: INodeReference)
-        -> Result<Arc<dyn FileDescriptor>, FileSystemError>;
+
+    /// Open `inode` with the default, empty, set of [`OpenFlags`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `inode` can not be opened.
+    async fn open(
+        &self,
+        inode: INodeReference,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+        self.open_with(inode, OpenFlags::default()).await
+    }
+
+    /// Open `inode`, emptying it first for [`OpenFlag::Truncate`] and starting the descriptor's cursor at the end of
+    /// the file for [`OpenFlag::Append`]. [`OpenFlag::Create`] is handled by [`ParentFileSystem::open_path`] before
+    /// the inode is known, so it is ignored here.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `inode` can not be opened, or can not be truncated when asked to.
+    async fn open_with(
+        &self,
+        inode: INodeReference,
+        flags: OpenFlags,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError>;
     async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError>;
+
+    /// Read the path the symbolic link `inode` points to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotSymbolicLink` if `inode` is not a symbolic link, which is the default for file systems without
+    /// them.
+    async fn readlink(&self, _inode: INodeReference) -> Result<String, FileSystemError> {
+        Err(FileSystemError::NotSymbolicLink)
+    }
+
+    /// Create an empty regular file named `name` in the directory `parent`, returning its inode.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` if the file system cannot create files, which is the default, or `AlreadyExists` if
+    /// `parent` already has an entry named `name`.
+    async fn create(
+        &self,
+        _parent: INodeReference,
+        _name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    /// Create an empty directory named `name` in the directory `parent`, returning its inode.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` if the file system cannot create directories, which is the default, or `AlreadyExists`
+    /// if `parent` already has an entry named `name`.
+    async fn mkdir(
+        &self,
+        _parent: INodeReference,
+        _name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    /// Rename the entry `old_name` in the directory `parent` to `new_name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` if the file system cannot rename entries, which is the default, `PathNotFound` if there
+    /// is no entry named `old_name`, or `AlreadyExists` if there is already one named `new_name`.
+    async fn rename(
+        &self,
+        _parent: INodeReference,
+        _old_name: &str,
+        _new_name: &str,
+    ) -> Result<(), FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    /// Remove the entry `name` from the directory `parent`, freeing the inode it named. A directory must be empty to
+    /// be removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` if the file system cannot remove entries, which is the default, `PathNotFound` if there
+    /// is no entry named `name`, or `NotEmpty` if it names a directory which still has entries.
+    async fn unlink(&self, _parent: INodeReference, _name: &str) -> Result<(), FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
+
+    /// Set the size of the file `inode` to `size` bytes, discarding data past it or extending it with zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` if the file system cannot resize files, which is the default.
+    async fn truncate(&self, _inode: INodeReference, _size: usize) -> Result<(), FileSystemError> {
+        Err(FileSystemError::Unsupported)
+    }
 }
 
 pub trait MountableFileSystem: FileSystem {
@@ -21,11 +120,15 @@ pub trait MountableFileSystem: FileSystem {
 
 pub trait MountingFilesystem: FileSystem {
     /// Mount a filesystem at a given inode.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlreadyMounted` if another file system is already mounted at `inode`.
     fn mount_filesystem(
         &mut self,
         inode: INodeReference,
         device: alloc::sync::Arc<dyn MountableFileSystem + Send + Sync + 'static>,
-    );
+    ) -> Result<(), FileSystemError>;
 }
 
 #[async_trait::async_trait]
@@ -39,4 +142,52 @@ pub trait PathLookup {
     async fn walk_children(&self, inode: INodeReference) -> Result<usize, FileSystemError>;
 }
 
-pub trait ParentFileSystem: MountingFilesystem + PathLookup {}
+#[async_trait::async_trait]
+pub trait ParentFileSystem: MountingFilesystem + PathLookup {
+    /// Mount a filesystem over the directory at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` can not be found, is not a directory, or already has a file system mounted at it.
+    async fn mount_at_path(
+        &mut self,
+        path: &str,
+        device: alloc::sync::Arc<dyn MountableFileSystem + Send + Sync + 'static>,
+    ) -> Result<(), FileSystemError>;
+
+    /// Unmount the filesystem mounted over `inode`, uncovering the directory beneath it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NotMounted` if no filesystem is mounted at `inode`, and `Busy` if the mounted filesystem still has
+    /// open descriptors or filesystems mounted within it.
+    async fn unmount(&mut self, inode: INodeReference) -> Result<(), FileSystemError>;
+
+    /// Open the file at `path` with the given flags, first creating an empty file there if it is missing and `flags`
+    /// has [`OpenFlag::Create`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` can not be found or created, or the file can not be opened.
+    async fn open_path(
+        &self,
+        path: &str,
+        flags: OpenFlags,
+    ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+        let path = normalize(path);
+
+        let inode = match self.lookup(&path).await {
+            Err(FileSystemError::PathNotFound) if flags & OpenFlag::Create => {
+                // The root always exists, so a missing path has a final component
+                let (parent, name) = path.rsplit_once('/').unwrap_or(("", &path));
+                let parent = self
+                    .lookup(if parent.is_empty() { "/" } else { parent })
+                    .await?;
+                self.create(parent, name).await?
+            }
+            result => result?,
+        };
+
+        self.open_with(inode, flags).await
+    }
+}
@@ -1,6 +1,9 @@
 pub mod descriptor;
 pub use descriptor::*;
 
+pub mod directory;
+pub use directory::*;
+
 pub mod empty;
 pub use empty::*;
 
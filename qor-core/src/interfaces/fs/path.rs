@@ -1 +1,60 @@
+use alloc::{string::String, vec::Vec};
+
 pub struct Path {}
+
+/// Rewrite `path` as an absolute path with no empty, `.` or `..` components.
+///
+/// Every way of writing a path gives the same string. `..` steps up to the parent, stopping at the root, and relative
+/// paths are taken from the root. The root itself is `/`, and no other path ends with a slash.
+#[must_use]
+pub fn normalize(path: &str) -> String {
+    let mut components = Vec::new();
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            name => components.push(name),
+        }
+    }
+
+    if components.is_empty() {
+        return String::from("/");
+    }
+
+    let mut normalized = String::with_capacity(path.len());
+    for component in components {
+        normalized.push('/');
+        normalized.push_str(component);
+    }
+
+    normalized
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::normalize;
+
+    #[test]
+    pub fn normalize_test() {
+        for (path, expected) in [
+            ("/", "/"),
+            ("", "/"),
+            ("/bin/hello", "/bin/hello"),
+            ("/bin/", "/bin"),
+            ("//bin//hello", "/bin/hello"),
+            ("/bin/./hello/.", "/bin/hello"),
+            ("/bin/../etc", "/etc"),
+            ("/bin/hello/../../etc/", "/etc"),
+            ("/../..", "/"),
+            ("/../bin", "/bin"),
+            ("bin/hello", "/bin/hello"),
+            ("/bin/...", "/bin/..."),
+        ] {
+            assert_eq!(normalize(path), expected, "normalizing {path:?}");
+        }
+    }
+}
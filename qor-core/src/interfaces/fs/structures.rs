@@ -46,8 +46,145 @@ impl INodeData {
     }
 }
 
+/// Type of the file an inode refers to, as encoded in the top four bits of its [`FileMode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Unknown,
+    Fifo,
+    CharacterDevice,
+    Directory,
+    BlockDevice,
+    Regular,
+    SymbolicLink,
+    Socket,
+}
+
+impl FileType {
+    /// Get the type of file from its mode, or [`FileType::Unknown`] if the type bits are not recognised
+    #[must_use]
+    pub const fn from_mode(mode: FileMode) -> Self {
+        match mode.0 & 0xF000 {
+            0x1000 => Self::Fifo,
+            0x2000 => Self::CharacterDevice,
+            0x4000 => Self::Directory,
+            0x6000 => Self::BlockDevice,
+            0x8000 => Self::Regular,
+            0xA000 => Self::SymbolicLink,
+            0xC000 => Self::Socket,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Get the `d_type` value used for this type in directory listings given to user space
+    #[must_use]
+    pub const fn directory_entry_type(self) -> u8 {
+        match self {
+            Self::Unknown => 0,
+            Self::Fifo => 1,
+            Self::CharacterDevice => 2,
+            Self::Directory => 4,
+            Self::BlockDevice => 6,
+            Self::Regular => 8,
+            Self::SymbolicLink => 10,
+            Self::Socket => 12,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DirectoryEntry<'a> {
     pub inode: INodeReference,
     pub name: Cow<'a, str>,
+    /// Type of the entry if the file system records it alongside the name, otherwise [`FileType::Unknown`]
+    pub file_type: FileType,
+}
+
+impl DirectoryEntry<'_> {
+    /// Take ownership of the name, so the entry no longer borrows from the file system which listed it
+    #[must_use]
+    pub fn into_owned(self) -> DirectoryEntry<'static> {
+        DirectoryEntry {
+            inode: self.inode,
+            name: Cow::Owned(self.name.into_owned()),
+            file_type: self.file_type,
+        }
+    }
+}
+
+/// Option changing how a file is opened, see [`OpenFlags`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenFlag {
+    /// Create the file if it is missing, this is handled when the path is looked up
+    Create,
+    /// Discard the contents of the file, leaving it empty
+    Truncate,
+    /// Start the descriptor's cursor at the end of the file
+    Append,
+}
+
+impl OpenFlag {
+    #[must_use]
+    pub const fn repr(&self) -> u32 {
+        match self {
+            Self::Create => 0x1,
+            Self::Truncate => 0x2,
+            Self::Append => 0x4,
+        }
+    }
+}
+
+/// Set of [`OpenFlag`]s given when opening a file, the default set is empty
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OpenFlags {
+    data: u32,
+}
+
+impl OpenFlags {
+    #[must_use]
+    pub const fn new(data: u32) -> Self {
+        Self { data }
+    }
+
+    #[must_use]
+    pub const fn data(&self) -> u32 {
+        self.data
+    }
+
+    #[must_use]
+    pub const fn flag(&self, flag: OpenFlag) -> bool {
+        self.data & flag.repr() != 0
+    }
+
+    pub const fn set_flag_state(&mut self, flag: OpenFlag, state: bool) {
+        if state {
+            self.set_flag(flag);
+        } else {
+            self.clear_flag(flag);
+        }
+    }
+
+    pub const fn set_flag(&mut self, flag: OpenFlag) {
+        self.data |= flag.repr();
+    }
+
+    pub const fn clear_flag(&mut self, flag: OpenFlag) {
+        self.data &= !flag.repr();
+    }
+}
+
+impl core::ops::BitOr<OpenFlag> for OpenFlags {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: OpenFlag) -> Self::Output {
+        self.set_flag(rhs);
+        self
+    }
+}
+
+impl core::ops::BitAnd<OpenFlag> for OpenFlags {
+    type Output = bool;
+
+    fn bitand(self, rhs: OpenFlag) -> Self::Output {
+        self.flag(rhs)
+    }
 }
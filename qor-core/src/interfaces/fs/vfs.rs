@@ -2,21 +2,38 @@ use alloc::{
     boxed::Box,
     collections::BTreeMap,
     string::{String, ToString},
-    sync::Arc,
+    sync::{Arc, Weak},
     vec::Vec,
 };
 use spin::RwLock;
 
 use super::{
-    DirectoryEntry, EmptyFileSystem, FileDescriptor, FileSystem, FileSystemError, INodeData,
-    INodeReference, MountableFileSystem, MountingFilesystem, ParentFileSystem, PathLookup,
+    normalize, DirectoryEntry, DirectoryFileDescriptor, EmptyFileSystem, FileDescriptor,
+    FileSystem, FileSystemError, FileType, INodeData, INodeReference, MountableFileSystem,
+    MountingFilesystem, OpenFlag, OpenFlags, ParentFileSystem, PathLookup,
 };
 
+/// Number of symbolic links a single lookup may follow before it is assumed to be caught in a loop
+const MAX_SYMBOLIC_LINKS: usize = 40;
+
+/// Where a walk along a path ended
+enum Walk {
+    /// The path led to this inode
+    Found(INodeReference),
+    /// The path passed through a symbolic link, and continues as this path
+    Redirect(String),
+}
+
 pub struct VirtualFileSystem {
     path_cache: RwLock<BTreeMap<alloc::string::String, INodeReference>>,
     rev_path_cache: RwLock<BTreeMap<INodeReference, alloc::string::String>>,
-    devices: Vec<Arc<dyn MountableFileSystem + Send + Sync + 'static>>,
+    /// Mounted devices, indexed by their device id less one. Unmounted devices leave an empty slot, so the ids of
+    /// the others do not change.
+    devices: Vec<Option<Arc<dyn MountableFileSystem + Send + Sync + 'static>>>,
     mounted_filesystems: BTreeMap<INodeReference, usize>,
+    /// Descriptors opened through the filesystem with the id of the device they belong to, so a device is not
+    /// unmounted from under them
+    open_descriptors: RwLock<Vec<(usize, Weak<dyn FileDescriptor>)>>,
 }
 
 impl VirtualFileSystem {
@@ -28,21 +45,77 @@ impl VirtualFileSystem {
         Self {
             path_cache: RwLock::new(BTreeMap::new()),
             rev_path_cache: RwLock::new(BTreeMap::new()),
-            devices: alloc::vec![Arc::new(empty)],
+            devices: alloc::vec![Some(Arc::new(empty))],
             mounted_filesystems: BTreeMap::new(),
+            open_descriptors: RwLock::new(Vec::new()),
         }
     }
 
-    /// Mount a filesystem at a given inode.
+    /// Mount a filesystem at a given inode, refusing to shadow a filesystem already mounted there.
     fn mount_inner(
         &mut self,
         inode: INodeReference,
         device: Arc<dyn MountableFileSystem + Send + Sync + 'static>,
-    ) {
+    ) -> Result<(), FileSystemError> {
+        if self.mounted_filesystems.contains_key(&inode) {
+            return Err(FileSystemError::AlreadyMounted);
+        }
+
         device.set_mount_device_id(self.devices.len() + 1);
-        self.devices.push(device);
+        self.devices.push(Some(device));
         self.mounted_filesystems
             .insert(inode, self.devices.len() - 1);
+
+        // Paths beneath the mount point now lead into the mounted filesystem. This is done without reading any
+        // directories, so a mount never waits on a device.
+        let path = self.rev_path_cache.read().get(&inode).cloned();
+        self.forget_beneath(path);
+
+        Ok(())
+    }
+
+    /// Get the device which owns the given inode.
+    fn device(
+        &self,
+        inode: INodeReference,
+    ) -> Result<&Arc<dyn MountableFileSystem + Send + Sync + 'static>, FileSystemError> {
+        inode
+            .device
+            .checked_sub(1)
+            .and_then(|index| self.devices.get(index)?.as_ref())
+            .ok_or(FileSystemError::BadInodeWrongDevice(inode))
+    }
+
+    /// Follow any filesystems mounted over `inode` down to the root inode of the last one mounted, so the inode
+    /// returned always belongs to the device which actually serves it. Inodes without a mount are returned unchanged.
+    async fn resolve_mounts(
+        &self,
+        mut inode: INodeReference,
+    ) -> Result<INodeReference, FileSystemError> {
+        while let Some(mounted_fs) = self.mounted_filesystems.get(&inode) {
+            inode = self
+                .devices
+                .get(*mounted_fs)
+                .and_then(Option::as_ref)
+                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
+                .root_inode()
+                .await?;
+        }
+
+        Ok(inode)
+    }
+
+    /// Remember a descriptor opened from `inode`, forgetting those which have since been dropped
+    fn track_descriptor(
+        &self,
+        inode: INodeReference,
+        descriptor: Arc<dyn FileDescriptor>,
+    ) -> Arc<dyn FileDescriptor> {
+        let mut open_descriptors = self.open_descriptors.write();
+        open_descriptors.retain(|(_, descriptor)| descriptor.strong_count() > 0);
+        open_descriptors.push((inode.device, Arc::downgrade(&descriptor)));
+
+        descriptor
     }
 }
 
@@ -55,121 +128,123 @@ impl Default for VirtualFileSystem {
 #[async_trait::async_trait]
 impl FileSystem for VirtualFileSystem {
     async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
-        if let Some(first_device) = self.devices.first() {
-            first_device.root_inode().await
+        if let Some(first_device) = self.devices.first().and_then(Option::as_ref) {
+            self.resolve_mounts(first_device.root_inode().await?).await
         } else {
             Err(FileSystemError::NoMountedFilesystem)
         }
     }
 
     async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
-        if let Some(mounted_fs) = self.mounted_filesystems.get(&inode) {
-            let mounted_root = self
-                .devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .root_inode()
-                .await?;
-            self.devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .inode_data(mounted_root)
-                .await
-        } else if inode.device >= 1 {
-            self.devices
-                .get(inode.device - 1)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .inode_data(inode)
-                .await
-        } else if inode.device == 0 {
-            Err(FileSystemError::BadInodeWrongDevice(inode))
-        } else {
-            unreachable!()
-        }
+        let inode = self.resolve_mounts(inode).await?;
+        self.device(inode)?.inode_data(inode).await
     }
 
     async fn directory_entries(
         &self,
         inode: INodeReference,
     ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError> {
-        if let Some(mounted_fs) = self.mounted_filesystems.get(&inode) {
-            let mounted_root = self
-                .devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .root_inode()
-                .await?;
-            self.devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .directory_entries(mounted_root)
-                .await
-        } else if inode.device >= 1 {
-            self.devices
-                .get(inode.device - 1)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .directory_entries(inode)
-                .await
-        } else if inode.device == 0 {
-            Err(FileSystemError::BadInodeWrongDevice(inode))
-        } else {
-            unreachable!()
-        }
+        let inode = self.resolve_mounts(inode).await?;
+        self.device(inode)?.directory_entries(inode).await
     }
 
-    async fn open(
+    async fn open_with(
         &self,
         inode: INodeReference,
+        flags: OpenFlags,
     ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
-        if let Some(mounted_fs) = self.mounted_filesystems.get(&inode) {
-            let mounted_root = self
-                .devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .root_inode()
-                .await?;
-            self.devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .open(mounted_root)
-                .await
-        } else if inode.device >= 1 {
-            self.devices
-                .get(inode.device - 1)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .open(inode)
-                .await
-        } else if inode.device == 0 {
-            Err(FileSystemError::BadInodeWrongDevice(inode))
-        } else {
-            unreachable!()
+        let inode = self.resolve_mounts(inode).await?;
+        let device = self.device(inode)?;
+
+        // Directories are listed the same way whichever file system holds them, so they are opened here
+        if device.inode_data(inode).await?.is_directory() {
+            if flags & OpenFlag::Truncate {
+                return Err(FileSystemError::IsDirectory);
+            }
+
+            let entries = device
+                .directory_entries(inode)
+                .await?
+                .into_iter()
+                .map(DirectoryEntry::into_owned)
+                .collect();
+
+            return Ok(self.track_descriptor(
+                inode,
+                Arc::new(DirectoryFileDescriptor::new(inode, entries)),
+            ));
         }
+
+        Ok(self.track_descriptor(inode, device.open_with(inode, flags).await?))
     }
 
     async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError> {
-        if let Some(mounted_fs) = self.mounted_filesystems.get(&inode) {
-            let mounted_root = self
-                .devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .root_inode()
-                .await?;
-            self.devices
-                .get(*mounted_fs)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .read_to_data(mounted_root)
-                .await
-        } else if inode.device >= 1 {
-            self.devices
-                .get(inode.device - 1)
-                .ok_or(FileSystemError::BadInodeWrongDevice(inode))?
-                .read_to_data(inode)
-                .await
-        } else if inode.device == 0 {
-            Err(FileSystemError::BadInodeWrongDevice(inode))
-        } else {
-            unreachable!()
-        }
+        let inode = self.resolve_mounts(inode).await?;
+        self.device(inode)?.read_to_data(inode).await
+    }
+
+    async fn readlink(&self, inode: INodeReference) -> Result<String, FileSystemError> {
+        let inode = self.resolve_mounts(inode).await?;
+        self.device(inode)?.readlink(inode).await
+    }
+
+    async fn create(
+        &self,
+        parent: INodeReference,
+        name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        let device_parent = self.resolve_mounts(parent).await?;
+        let inode = self
+            .device(device_parent)?
+            .create(device_parent, name)
+            .await?;
+        self.invalidate_children(parent).await?;
+
+        Ok(inode)
+    }
+
+    async fn mkdir(
+        &self,
+        parent: INodeReference,
+        name: &str,
+    ) -> Result<INodeReference, FileSystemError> {
+        let device_parent = self.resolve_mounts(parent).await?;
+        let inode = self
+            .device(device_parent)?
+            .mkdir(device_parent, name)
+            .await?;
+        self.invalidate_children(parent).await?;
+
+        Ok(inode)
+    }
+
+    async fn rename(
+        &self,
+        parent: INodeReference,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), FileSystemError> {
+        let device_parent = self.resolve_mounts(parent).await?;
+        self.device(device_parent)?
+            .rename(device_parent, old_name, new_name)
+            .await?;
+
+        self.invalidate_children(parent).await
+    }
+
+    async fn unlink(&self, parent: INodeReference, name: &str) -> Result<(), FileSystemError> {
+        let device_parent = self.resolve_mounts(parent).await?;
+        self.device(device_parent)?
+            .unlink(device_parent, name)
+            .await?;
+
+        self.invalidate_children(parent).await
+    }
+
+    async fn truncate(&self, inode: INodeReference, size: usize) -> Result<(), FileSystemError> {
+        // Only names are cached, and resizing a file changes none of them
+        let inode = self.resolve_mounts(inode).await?;
+        self.device(inode)?.truncate(inode, size).await
     }
 }
 
@@ -178,8 +253,8 @@ impl MountingFilesystem for VirtualFileSystem {
         &mut self,
         inode: INodeReference,
         device: alloc::sync::Arc<dyn MountableFileSystem + Send + Sync + 'static>,
-    ) {
-        self.mount_inner(inode, device);
+    ) -> Result<(), FileSystemError> {
+        self.mount_inner(inode, device)
     }
 }
 
@@ -193,35 +268,117 @@ impl VirtualFileSystem {
         self.rev_path_cache.write().insert(inode, path);
     }
 
+    /// Walk the normalized `path` from the root directory, following symbolic links. Only the paths actually walked
+    /// are cached, so a path through a link is read again each time.
+    ///
+    /// Paths which are missing are not cached, as entries appear in file systems such as `/proc` without going
+    /// through the VFS, and a remembered miss would hide them.
     async fn lookup_inner(&self, path: &str) -> Result<INodeReference, FileSystemError> {
-        let mut path = path.split('/');
-        let mut inode = self.root_inode().await?;
-        assert!(path.next() == Some(""));
-        let mut build_path = String::from("/");
-        self.insert_pairing("/", inode);
-
-        for dir in path {
-            self.insert_pairing(build_path.as_str(), inode);
-            let entries = self.directory_entries(inode).await?;
-            let mut found = false;
-            for entry in entries {
-                if entry.name == dir {
-                    inode = entry.inode;
-                    if !build_path.ends_with('/') {
-                        build_path += "/";
+        let mut walked = String::from(path);
+        let mut redirections = 0;
+
+        loop {
+            match self.walk(&walked).await {
+                Ok(Walk::Found(inode)) => return Ok(inode),
+                Ok(Walk::Redirect(next)) => {
+                    redirections += 1;
+                    if redirections > MAX_SYMBOLIC_LINKS {
+                        return Err(FileSystemError::TooManySymbolicLinks);
                     }
-                    build_path += dir;
-                    found = true;
-                    break;
+
+                    walked = next;
                 }
+                Err(e) => return Err(e),
             }
-            if !found {
-                return Err(FileSystemError::PathNotFound);
+        }
+    }
+
+    /// Walk the normalized `path` from the root directory, stopping at the first symbolic link to give the path with
+    /// the link replaced by its target.
+    async fn walk(&self, path: &str) -> Result<Walk, FileSystemError> {
+        if path == "/" {
+            return Ok(Walk::Found(self.root_inode().await?));
+        }
+
+        let mut components = path.split('/');
+        assert_eq!(components.next(), Some(""));
+        let components = components.collect::<Vec<_>>();
+
+        let mut inode = self.root_inode().await?;
+        let mut build_path = String::new();
+
+        for (index, dir) in components.iter().enumerate() {
+            let entry = self
+                .directory_entries(inode)
+                .await?
+                .into_iter()
+                .find(|entry| entry.name == *dir)
+                .ok_or(FileSystemError::PathNotFound)?;
+            inode = entry.inode;
+
+            let is_link = match entry.file_type {
+                FileType::SymbolicLink => true,
+                FileType::Unknown => {
+                    FileType::from_mode(self.inode_data(inode).await?.mode)
+                        == FileType::SymbolicLink
+                }
+                _ => false,
+            };
+
+            if is_link {
+                // Relative targets are taken from the directory holding the link
+                let target = self.readlink(inode).await?;
+                let base = if target.starts_with('/') {
+                    ""
+                } else {
+                    build_path.as_str()
+                };
+                let rest = components[index + 1..].join("/");
+
+                return Ok(Walk::Redirect(normalize(&alloc::format!(
+                    "{base}/{target}/{rest}"
+                ))));
+            }
+
+            build_path += "/";
+            build_path += dir;
+
+            if index + 1 < components.len() {
+                self.insert_pairing(build_path.as_str(), inode);
             }
         }
 
-        self.insert_pairing(build_path.as_str(), inode);
-        Ok(inode)
+        self.insert_pairing_owned(build_path, inode);
+        Ok(Walk::Found(inode))
+    }
+
+    /// Forget every cached path beneath the directory `parent` after an entry in it has been added, removed or renamed.
+    async fn invalidate_children(&self, parent: INodeReference) -> Result<(), FileSystemError> {
+        let path = self.reverse_lookup(parent).await?;
+        self.forget_beneath(path);
+
+        Ok(())
+    }
+
+    /// Forget every cached path beneath the directory at `path`, or every cached path at all if the directory's path
+    /// is not known.
+    fn forget_beneath(&self, path: Option<String>) {
+        let Some(path) = path else {
+            // Without the directory's path there is no telling which cached paths lie beneath it
+            self.path_cache.write().clear();
+            self.rev_path_cache.write().clear();
+            return;
+        };
+
+        let prefix = if path.ends_with('/') {
+            path
+        } else {
+            path + "/"
+        };
+        let beneath = |path: &String| path.starts_with(prefix.as_str());
+
+        self.path_cache.write().retain(|path, _| !beneath(path));
+        self.rev_path_cache.write().retain(|_, path| !beneath(path));
     }
 
     #[async_recursion::async_recursion]
@@ -267,27 +424,30 @@ impl VirtualFileSystem {
 #[async_trait::async_trait]
 impl PathLookup for VirtualFileSystem {
     async fn lookup(&self, path: &str) -> Result<INodeReference, FileSystemError> {
-        let path = if path.ends_with('/') {
-            let mut chars = path.chars();
-            chars.next_back();
-            chars.as_str()
-        } else {
-            path
-        };
+        // Every spelling of a path is cached under its normalized form
+        let path = normalize(path);
 
-        if let Some(path) = self.path_cache.read().get(path) {
+        // The root is known without reading any directories, so it is never walked or cached
+        if path == "/" {
+            return self.root_inode().await;
+        }
+
+        if let Some(path) = self.path_cache.read().get(&path) {
             return Ok(*path);
         }
 
-        let inode = self.lookup_inner(path).await?;
-        Ok(inode)
+        self.lookup_inner(&path).await
     }
 
     async fn reverse_lookup(
         &self,
         inode: INodeReference,
     ) -> Result<Option<alloc::string::String>, FileSystemError> {
-        Ok(self.rev_path_cache.read().get(&inode).cloned())
+        if let Some(path) = self.rev_path_cache.read().get(&inode) {
+            return Ok(Some(path.clone()));
+        }
+
+        Ok((inode == self.root_inode().await?).then(|| String::from("/")))
     }
 
     async fn invalidate_cache(&self, inode: INodeReference) -> Result<(), FileSystemError> {
@@ -306,16 +466,9 @@ impl PathLookup for VirtualFileSystem {
             self.path_cache.write().remove(&reversed);
             self.rev_path_cache.write().remove(&inode);
         }
-        let read = self.path_cache.read();
-        let remove = read
-            .iter()
-            .filter(|(_, this_inode)| **this_inode == inode)
-            .map(|(path, _)| path)
-            .collect::<Vec<_>>();
-
-        for rem in remove {
-            self.path_cache.write().remove(rem);
-        }
+        self.path_cache
+            .write()
+            .retain(|_, this_inode| *this_inode != inode);
 
         Ok(())
     }
@@ -325,4 +478,637 @@ impl PathLookup for VirtualFileSystem {
     }
 }
 
-impl ParentFileSystem for VirtualFileSystem {}
+#[async_trait::async_trait]
+impl ParentFileSystem for VirtualFileSystem {
+    async fn mount_at_path(
+        &mut self,
+        path: &str,
+        device: Arc<dyn MountableFileSystem + Send + Sync + 'static>,
+    ) -> Result<(), FileSystemError> {
+        // Looking up the root gives the root of the filesystem mounted over it, so the root's own mount point is
+        // used instead, and mounting over it again is refused like any other mount point
+        let inode = if normalize(path) == "/" {
+            self.devices
+                .first()
+                .and_then(Option::as_ref)
+                .ok_or(FileSystemError::NoMountedFilesystem)?
+                .root_inode()
+                .await?
+        } else {
+            self.lookup(path).await?
+        };
+
+        if !self.inode_data(inode).await?.is_directory() {
+            return Err(FileSystemError::NotDirectory);
+        }
+
+        self.mount_inner(inode, device)
+    }
+
+    async fn unmount(&mut self, inode: INodeReference) -> Result<(), FileSystemError> {
+        let index = *self
+            .mounted_filesystems
+            .get(&inode)
+            .ok_or(FileSystemError::NotMounted)?;
+        let device_id = index + 1;
+
+        let nested_mount = self
+            .mounted_filesystems
+            .keys()
+            .any(|mount_point| mount_point.device == device_id);
+        let open_descriptor = self
+            .open_descriptors
+            .read()
+            .iter()
+            .any(|(device, descriptor)| *device == device_id && descriptor.strong_count() > 0);
+        if nested_mount || open_descriptor {
+            return Err(FileSystemError::Busy);
+        }
+
+        // Paths beneath the mount point led into the unmounted filesystem
+        self.invalidate_children(inode).await?;
+
+        self.mounted_filesystems.remove(&inode);
+        self.devices[index] = None;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{
+        boxed::Box,
+        string::{String, ToString},
+        sync::Arc,
+        vec::Vec,
+    };
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::VirtualFileSystem;
+    use crate::interfaces::fs::{
+        DirectoryEntry, EmptyFileDescriptor, FileDescriptor, FileSystem, FileSystemError, FileType,
+        INodeData, INodeReference, MountableFileSystem, MountingFilesystem, OpenFlag, OpenFlags,
+        ParentFileSystem, PathLookup,
+    };
+    use crate::{sync::Mutex, tasks::block_on};
+
+    /// File system holding a single chain of directories `/d1/d2/.../dN`, where inode `i` is named `di`, which counts
+    /// how many times a directory is read. Files created in it are listed after the chain, as `(parent, name, inode)`.
+    /// Symbolic links are listed last, as `(parent, name, target)`, with the link at index `i` being inode `200 + i`.
+    struct ChainFileSystem {
+        depth: usize,
+        device: AtomicUsize,
+        directory_reads: AtomicUsize,
+        created: Mutex<Vec<(usize, String, usize)>>,
+        links: Vec<(usize, &'static str, &'static str)>,
+    }
+
+    impl ChainFileSystem {
+        fn inode_ref(&self, inode: usize) -> INodeReference {
+            INodeReference {
+                inode,
+                device: self.device.load(Ordering::Acquire),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FileSystem for ChainFileSystem {
+        async fn root_inode(&self) -> Result<INodeReference, FileSystemError> {
+            Ok(self.inode_ref(0))
+        }
+
+        async fn inode_data(&self, inode: INodeReference) -> Result<INodeData, FileSystemError> {
+            let mode: u16 = if inode.inode <= self.depth {
+                0x4000 | 0o755
+            } else if inode.inode >= 200 {
+                0xA000 | 0o777
+            } else {
+                0x8000 | 0o644
+            };
+
+            Ok(INodeData {
+                mode: mode.into(),
+                link_count: 1,
+                uid: 0.into(),
+                gid: 0.into(),
+                size: 0,
+                access_time: 0.into(),
+                modify_time: 0.into(),
+                change_time: 0.into(),
+                reference: inode,
+            })
+        }
+
+        async fn directory_entries(
+            &self,
+            inode: INodeReference,
+        ) -> Result<Vec<DirectoryEntry<'_>>, FileSystemError> {
+            self.directory_reads.fetch_add(1, Ordering::AcqRel);
+
+            let mut entries = alloc::vec![DirectoryEntry {
+                inode,
+                name: ".".into(),
+                file_type: FileType::Directory,
+            }];
+            if inode.inode < self.depth {
+                entries.push(DirectoryEntry {
+                    inode: self.inode_ref(inode.inode + 1),
+                    name: alloc::format!("d{}", inode.inode + 1).into(),
+                    file_type: FileType::Directory,
+                });
+            }
+            for (_, name, created) in self
+                .created
+                .spin_lock()
+                .iter()
+                .filter(|(parent, _, _)| *parent == inode.inode)
+            {
+                entries.push(DirectoryEntry {
+                    inode: self.inode_ref(*created),
+                    name: name.clone().into(),
+                    file_type: FileType::Regular,
+                });
+            }
+            for (index, (_, name, _)) in self
+                .links
+                .iter()
+                .enumerate()
+                .filter(|(_, (parent, _, _))| *parent == inode.inode)
+            {
+                entries.push(DirectoryEntry {
+                    inode: self.inode_ref(200 + index),
+                    name: (*name).into(),
+                    file_type: FileType::SymbolicLink,
+                });
+            }
+
+            Ok(entries)
+        }
+
+        async fn open_with(
+            &self,
+            inode: INodeReference,
+            _flags: OpenFlags,
+        ) -> Result<Arc<dyn FileDescriptor>, FileSystemError> {
+            if (100..200).contains(&inode.inode) {
+                return Ok(Arc::new(EmptyFileDescriptor {}));
+            }
+
+            Err(FileSystemError::BadInode(inode))
+        }
+
+        async fn read_to_data(&self, inode: INodeReference) -> Result<Vec<u8>, FileSystemError> {
+            Err(FileSystemError::BadInode(inode))
+        }
+
+        async fn readlink(&self, inode: INodeReference) -> Result<String, FileSystemError> {
+            inode
+                .inode
+                .checked_sub(200)
+                .and_then(|index| self.links.get(index))
+                .map(|(_, _, target)| (*target).to_string())
+                .ok_or(FileSystemError::NotSymbolicLink)
+        }
+
+        async fn create(
+            &self,
+            parent: INodeReference,
+            name: &str,
+        ) -> Result<INodeReference, FileSystemError> {
+            let mut created = self.created.spin_lock();
+            let inode = 100 + created.len();
+            created.push((parent.inode, name.to_string(), inode));
+
+            Ok(self.inode_ref(inode))
+        }
+
+        async fn rename(
+            &self,
+            parent: INodeReference,
+            old_name: &str,
+            new_name: &str,
+        ) -> Result<(), FileSystemError> {
+            let mut created = self.created.spin_lock();
+            let entry = created
+                .iter_mut()
+                .find(|(this_parent, name, _)| *this_parent == parent.inode && name == old_name)
+                .ok_or(FileSystemError::PathNotFound)?;
+            entry.1 = new_name.to_string();
+
+            Ok(())
+        }
+    }
+
+    impl MountableFileSystem for ChainFileSystem {
+        fn set_mount_device_id(&self, device_id: usize) {
+            self.device.store(device_id, Ordering::Release);
+        }
+    }
+
+    fn chain(depth: usize) -> Arc<ChainFileSystem> {
+        linked_chain(depth, Vec::new())
+    }
+
+    fn linked_chain(
+        depth: usize,
+        links: Vec<(usize, &'static str, &'static str)>,
+    ) -> Arc<ChainFileSystem> {
+        Arc::new(ChainFileSystem {
+            depth,
+            device: AtomicUsize::new(0),
+            directory_reads: AtomicUsize::new(0),
+            created: Mutex::new(Vec::new()),
+            links,
+        })
+    }
+
+    fn chain_vfs(depth: usize) -> (VirtualFileSystem, Arc<ChainFileSystem>) {
+        linked_chain_vfs(depth, Vec::new())
+    }
+
+    fn linked_chain_vfs(
+        depth: usize,
+        links: Vec<(usize, &'static str, &'static str)>,
+    ) -> (VirtualFileSystem, Arc<ChainFileSystem>) {
+        let chain = linked_chain(depth, links);
+
+        let mut vfs = VirtualFileSystem::new();
+        let empty_root = block_on(vfs.root_inode()).unwrap();
+        vfs.mount_filesystem(empty_root, chain.clone()).unwrap();
+
+        (vfs, chain)
+    }
+
+    fn cached_paths(vfs: &VirtualFileSystem) -> Vec<alloc::string::String> {
+        vfs.path_cache.read().keys().cloned().collect()
+    }
+
+    #[test]
+    pub fn lookup_root_test() {
+        let (vfs, chain) = chain_vfs(3);
+
+        let root = block_on(vfs.lookup("/")).unwrap();
+        assert_eq!(root, chain.inode_ref(0));
+        assert_eq!(chain.directory_reads.load(Ordering::Acquire), 0);
+        assert!(cached_paths(&vfs).is_empty());
+
+        assert_eq!(
+            block_on(vfs.reverse_lookup(root)).unwrap().as_deref(),
+            Some("/")
+        );
+    }
+
+    #[test]
+    pub fn lookup_single_component_test() {
+        let (vfs, chain) = chain_vfs(3);
+
+        assert_eq!(block_on(vfs.lookup("/d1")).unwrap(), chain.inode_ref(1));
+        assert_eq!(chain.directory_reads.load(Ordering::Acquire), 1);
+        assert_eq!(cached_paths(&vfs), ["/d1"]);
+
+        // The second lookup is served from the cache
+        assert_eq!(block_on(vfs.lookup("/d1/")).unwrap(), chain.inode_ref(1));
+        assert_eq!(chain.directory_reads.load(Ordering::Acquire), 1);
+
+        assert_eq!(
+            block_on(vfs.lookup("/missing")),
+            Err(FileSystemError::PathNotFound)
+        );
+    }
+
+    #[test]
+    pub fn lookup_deep_path_test() {
+        let (vfs, chain) = chain_vfs(3);
+
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d3")).unwrap(),
+            chain.inode_ref(3)
+        );
+        assert_eq!(chain.directory_reads.load(Ordering::Acquire), 3);
+        assert_eq!(cached_paths(&vfs), ["/d1", "/d1/d2", "/d1/d2/d3"]);
+        assert_eq!(
+            block_on(vfs.reverse_lookup(chain.inode_ref(2)))
+                .unwrap()
+                .as_deref(),
+            Some("/d1/d2")
+        );
+    }
+
+    #[test]
+    pub fn create_invalidates_missing_test() {
+        let (vfs, _) = chain_vfs(2);
+
+        assert_eq!(
+            block_on(vfs.lookup("/d1/file")),
+            Err(FileSystemError::PathNotFound)
+        );
+        assert!(!cached_paths(&vfs).contains(&String::from("/d1/file")));
+
+        let d1 = block_on(vfs.lookup("/d1")).unwrap();
+        let file = block_on(vfs.create(d1, "file")).unwrap();
+        assert_eq!(block_on(vfs.lookup("/d1/file")).unwrap(), file);
+    }
+
+    #[test]
+    pub fn rename_invalidates_paths_test() {
+        let (vfs, chain) = chain_vfs(2);
+
+        let d1 = block_on(vfs.lookup("/d1")).unwrap();
+        let file = block_on(vfs.create(d1, "old")).unwrap();
+        assert_eq!(block_on(vfs.lookup("/d1/old")).unwrap(), file);
+        assert_eq!(
+            block_on(vfs.lookup("/d1/new")),
+            Err(FileSystemError::PathNotFound)
+        );
+
+        block_on(vfs.rename(d1, "old", "new")).unwrap();
+        assert_eq!(
+            block_on(vfs.lookup("/d1/old")),
+            Err(FileSystemError::PathNotFound)
+        );
+        assert_eq!(block_on(vfs.lookup("/d1/new")).unwrap(), file);
+        assert_eq!(
+            block_on(vfs.reverse_lookup(file)).unwrap().as_deref(),
+            Some("/d1/new")
+        );
+
+        // Paths outside the renamed directory stay cached
+        assert!(cached_paths(&vfs).contains(&String::from("/d1")));
+        assert_eq!(block_on(vfs.lookup("/d1/d2")).unwrap(), chain.inode_ref(2));
+    }
+
+    #[test]
+    pub fn lookup_messy_path_test() {
+        let (vfs, chain) = chain_vfs(3);
+
+        for path in [
+            "/d1/d2",
+            "//d1///d2/",
+            "/d1/./d2/.",
+            "/d1/d2/d3/..",
+            "/d1/../d1/d2",
+            "/../d1/d2",
+        ] {
+            assert_eq!(block_on(vfs.lookup(path)).unwrap(), chain.inode_ref(2));
+        }
+
+        // Only the normalized path is walked and cached
+        assert_eq!(cached_paths(&vfs), ["/d1", "/d1/d2"]);
+        assert_eq!(chain.directory_reads.load(Ordering::Acquire), 2);
+        assert_eq!(block_on(vfs.lookup("/d1/..")).unwrap(), chain.inode_ref(0));
+        assert_eq!(
+            block_on(vfs.lookup("/d1/../d2")),
+            Err(FileSystemError::PathNotFound)
+        );
+    }
+
+    #[test]
+    pub fn symbolic_link_chain_test() {
+        let (vfs, chain) = linked_chain_vfs(
+            3,
+            alloc::vec![
+                (0, "a", "d1/d2"),
+                (1, "b", "/a/d3"),
+                (0, "c", "d1/b"),
+                (1, "up", "../d1/d2"),
+                (2, "top", "/"),
+                (0, "dangling", "nowhere"),
+            ],
+        );
+
+        for (path, expected) in [
+            ("/a", 2),
+            ("/d1/b", 3),
+            ("/c", 3),
+            ("/d1/up/d3", 3),
+            ("/a/top/d1", 1),
+        ] {
+            assert_eq!(
+                block_on(vfs.lookup(path)).unwrap(),
+                chain.inode_ref(expected),
+                "looking up {path:?}"
+            );
+        }
+
+        // Paths through links are not cached, as the links may change where they lead
+        assert!(!cached_paths(&vfs)
+            .iter()
+            .any(|path| path.contains(['a', 'b', 'c'])));
+        assert_eq!(
+            block_on(vfs.reverse_lookup(chain.inode_ref(3)))
+                .unwrap()
+                .as_deref(),
+            Some("/d1/d2/d3")
+        );
+
+        assert_eq!(
+            block_on(vfs.lookup("/dangling")),
+            Err(FileSystemError::PathNotFound)
+        );
+
+        // Links are read through the file system they are on
+        assert_eq!(
+            block_on(vfs.readlink(chain.inode_ref(202))).unwrap(),
+            "d1/b"
+        );
+        assert_eq!(
+            block_on(vfs.readlink(chain.inode_ref(1))),
+            Err(FileSystemError::NotSymbolicLink)
+        );
+    }
+
+    #[test]
+    pub fn symbolic_link_loop_test() {
+        let (vfs, chain) = linked_chain_vfs(
+            1,
+            alloc::vec![(0, "loop", "loop"), (0, "x", "/d1/y"), (1, "y", "../x")],
+        );
+
+        for path in ["/loop", "/x", "/d1/y/d1"] {
+            assert_eq!(
+                block_on(vfs.lookup(path)),
+                Err(FileSystemError::TooManySymbolicLinks),
+                "looking up {path:?}"
+            );
+        }
+
+        // Each hop reads a single directory before being redirected
+        assert!(chain.directory_reads.load(Ordering::Acquire) > super::MAX_SYMBOLIC_LINKS);
+    }
+
+    #[test]
+    pub fn open_path_create_test() {
+        let (vfs, chain) = chain_vfs(2);
+        let create = OpenFlags::default() | OpenFlag::Create;
+
+        assert!(matches!(
+            block_on(vfs.open_path("/d1/new", OpenFlags::default())),
+            Err(FileSystemError::PathNotFound)
+        ));
+        assert!(chain.created.spin_lock().is_empty());
+
+        // The missing file is created, and opened again rather than created twice
+        block_on(vfs.open_path("/d1/./new", create)).unwrap();
+        block_on(vfs.open_path("/d1/new", create)).unwrap();
+        block_on(vfs.open_path("/top", create)).unwrap();
+        assert_eq!(
+            *chain.created.spin_lock(),
+            [(1, "new".to_string(), 100), (0, "top".to_string(), 101)]
+        );
+        assert_eq!(
+            block_on(vfs.lookup("/d1/new")).unwrap(),
+            chain.inode_ref(100)
+        );
+
+        assert!(matches!(
+            block_on(vfs.open_path("/missing/new", create)),
+            Err(FileSystemError::PathNotFound)
+        ));
+        assert!(matches!(
+            block_on(vfs.open_path("/d1", OpenFlags::default() | OpenFlag::Truncate)),
+            Err(FileSystemError::IsDirectory)
+        ));
+    }
+
+    #[test]
+    pub fn mount_at_path_test() {
+        let (mut vfs, chain) = chain_vfs(3);
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d3")).unwrap(),
+            chain.inode_ref(3)
+        );
+
+        let inner = self::chain(1);
+        block_on(vfs.mount_at_path("/d1/./d2/", inner.clone())).unwrap();
+        assert_ne!(inner.inode_ref(0).device, chain.inode_ref(0).device);
+
+        // The mount point now shows the mounted chain, and the paths cached beneath it are forgotten
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d1")).unwrap(),
+            inner.inode_ref(1)
+        );
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d3")),
+            Err(FileSystemError::PathNotFound)
+        );
+        assert_eq!(block_on(vfs.lookup("/d1")).unwrap(), chain.inode_ref(1));
+
+        assert_eq!(
+            block_on(vfs.mount_at_path("/d1/d2", self::chain(1))),
+            Err(FileSystemError::AlreadyMounted)
+        );
+        assert_eq!(
+            block_on(vfs.mount_at_path("/", self::chain(1))),
+            Err(FileSystemError::AlreadyMounted)
+        );
+        assert_eq!(
+            block_on(vfs.mount_at_path("/missing", self::chain(1))),
+            Err(FileSystemError::PathNotFound)
+        );
+
+        let d1 = block_on(vfs.lookup("/d1")).unwrap();
+        block_on(vfs.create(d1, "file")).unwrap();
+        assert_eq!(
+            block_on(vfs.mount_at_path("/d1/file", self::chain(1))),
+            Err(FileSystemError::NotDirectory)
+        );
+    }
+
+    #[test]
+    pub fn mount_filesystem_forgets_paths_test() {
+        let (mut vfs, chain) = chain_vfs(3);
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d3")).unwrap(),
+            chain.inode_ref(3)
+        );
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d1")),
+            Err(FileSystemError::PathNotFound)
+        );
+
+        // Mounting directly over an inode forgets the paths cached beneath it
+        let inner = self::chain(1);
+        let d2 = block_on(vfs.lookup("/d1/d2")).unwrap();
+        vfs.mount_filesystem(d2, inner.clone()).unwrap();
+
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d3")),
+            Err(FileSystemError::PathNotFound)
+        );
+        assert_eq!(
+            block_on(vfs.lookup("/d1/d2/d1")).unwrap(),
+            inner.inode_ref(1)
+        );
+    }
+
+    #[test]
+    pub fn mount_at_root_test() {
+        let mut vfs = VirtualFileSystem::new();
+        let chain = self::chain(2);
+
+        block_on(vfs.mount_at_path("/", chain.clone())).unwrap();
+        assert_eq!(block_on(vfs.lookup("/d1")).unwrap(), chain.inode_ref(1));
+
+        // Every spelling of the root is refused once a filesystem is mounted there
+        for path in ["/", "//", "/./"] {
+            assert_eq!(
+                block_on(vfs.mount_at_path(path, self::chain(1))),
+                Err(FileSystemError::AlreadyMounted)
+            );
+        }
+        assert_eq!(block_on(vfs.lookup("/d1/d2")).unwrap(), chain.inode_ref(2));
+    }
+
+    #[test]
+    pub fn unmount_test() {
+        let (mut vfs, chain) = chain_vfs(3);
+        let mount_point = block_on(vfs.lookup("/d1/d2")).unwrap();
+        assert_eq!(
+            block_on(vfs.unmount(mount_point)),
+            Err(FileSystemError::NotMounted)
+        );
+
+        for _ in 0..2 {
+            let inner = self::chain(1);
+            block_on(vfs.mount_at_path("/d1/d2", inner.clone())).unwrap();
+            let inner_d1 = block_on(vfs.lookup("/d1/d2/d1")).unwrap();
+            assert_eq!(inner_d1, inner.inode_ref(1));
+
+            // An open descriptor keeps the filesystem mounted
+            let descriptor = block_on(vfs.open(inner_d1)).unwrap();
+            assert_eq!(
+                block_on(vfs.unmount(mount_point)),
+                Err(FileSystemError::Busy)
+            );
+            drop(descriptor);
+
+            block_on(vfs.unmount(mount_point)).unwrap();
+            assert_eq!(
+                block_on(vfs.lookup("/d1/d2/d1")),
+                Err(FileSystemError::PathNotFound)
+            );
+            assert_eq!(
+                block_on(vfs.lookup("/d1/d2/d3")).unwrap(),
+                chain.inode_ref(3)
+            );
+            assert_eq!(
+                block_on(vfs.inode_data(inner_d1)),
+                Err(FileSystemError::BadInodeWrongDevice(inner_d1))
+            );
+        }
+
+        // A filesystem mounted within another keeps the outer one mounted
+        block_on(vfs.mount_at_path("/d1/d2", self::chain(1))).unwrap();
+        block_on(vfs.mount_at_path("/d1/d2/d1", self::chain(1))).unwrap();
+        assert_eq!(
+            block_on(vfs.unmount(mount_point)),
+            Err(FileSystemError::Busy)
+        );
+        let inner_mount_point = block_on(vfs.lookup("/d1/d2/d1")).unwrap();
+        block_on(vfs.unmount(inner_mount_point)).unwrap();
+        block_on(vfs.unmount(mount_point)).unwrap();
+    }
+}
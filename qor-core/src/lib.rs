@@ -3,6 +3,8 @@
 #![feature(ptr_sub_ptr)]
 // Require nice atomic pointers
 #![feature(strict_provenance_atomic_ptr)]
+// Implement `Allocator` for the page allocators
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
 #[cfg(feature = "std")]
@@ -17,11 +17,110 @@ pub struct LoggerWrapper {
 
 impl core::fmt::Write for LoggerWrapper {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        capture_in_ring(s);
         (self.logger_inner)(s);
         Ok(())
     }
 }
 
+/// The largest capacity, in bytes, the ring of recent log messages can be given
+pub const MAX_RING_CAPACITY: usize = 16 * 1024;
+
+/// Fixed capacity ring of the most recently logged bytes, kept so the messages leading up to a crash can be recovered
+struct LogRing {
+    buffer: [u8; MAX_RING_CAPACITY],
+    capacity: usize,
+    start: usize,
+    len: usize,
+    overwritten: bool,
+}
+
+impl LogRing {
+    /// Construct an empty [`LogRing`] which holds up to `capacity` bytes
+    const fn new(capacity: usize) -> Self {
+        Self {
+            buffer: [0; MAX_RING_CAPACITY],
+            capacity,
+            start: 0,
+            len: 0,
+            overwritten: false,
+        }
+    }
+
+    /// Append `bytes` to the ring, overwriting the oldest bytes once it is full
+    fn push(&mut self, bytes: &[u8]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        for &byte in bytes {
+            self.buffer[(self.start + self.len) % self.capacity] = byte;
+
+            if self.len == self.capacity {
+                self.start = (self.start + 1) % self.capacity;
+                self.overwritten = true;
+            } else {
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Write the complete lines held in the ring to `writer`, oldest first, then empty the ring. If older bytes have
+    /// been overwritten, the partial line left at the start of the ring is skipped.
+    fn drain(&mut self, writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+        // Make the contents contiguous so they can be written as a single string
+        self.buffer[..self.capacity].rotate_left(self.start);
+        self.start = 0;
+
+        let mut contents = &self.buffer[..self.len];
+        if self.overwritten {
+            let line_start = contents.iter().position(|&byte| byte == b'\n').map_or(contents.len(), |i| i + 1);
+            contents = &contents[line_start..];
+        }
+
+        self.len = 0;
+        self.overwritten = false;
+
+        match core::str::from_utf8(contents) {
+            Ok(text) => writer.write_str(text),
+            Err(e) => writer.write_str(core::str::from_utf8(&contents[..e.valid_up_to()]).unwrap_or_default()),
+        }
+    }
+}
+
+static LOG_RING: crate::sync::Mutex<LogRing> = crate::sync::Mutex::new(LogRing::new(0));
+static RING_CAPACITY: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+/// Record logged text in the ring. Text logged while the ring is locked, for instance by an interrupt handler which
+/// interrupted a write to the ring, is left out rather than waiting on a lock which may never be released.
+fn capture_in_ring(s: &str) {
+    if RING_CAPACITY.load(core::sync::atomic::Ordering::Acquire) == 0 {
+        return;
+    }
+
+    if let Some(mut ring) = LOG_RING.try_lock() {
+        ring.push(s.as_bytes());
+    }
+}
+
+/// Set the number of bytes of recent log messages kept in memory, clamped to `MAX_RING_CAPACITY`, discarding any
+/// messages already held. A capacity of zero, the default, stops recording messages.
+pub fn set_ring_capacity(capacity: usize) {
+    let capacity = capacity.min(MAX_RING_CAPACITY);
+
+    *LOG_RING.spin_lock() = LogRing::new(capacity);
+    RING_CAPACITY.store(capacity, core::sync::atomic::Ordering::Release);
+}
+
+/// Write the recent log messages held in memory to `writer`, oldest first, and empty the ring.
+///
+/// # Errors
+///
+/// Returns an error if the ring is locked, as happens if the caller interrupted a write to it, or if `writer` fails.
+pub fn dump_ring(writer: &mut impl core::fmt::Write) -> core::fmt::Result {
+    LOG_RING.try_lock().ok_or(core::fmt::Error)?.drain(writer)
+}
+
 #[cfg(not(feature = "std"))]
 const fn default_value(_: &str) {}
 
@@ -33,6 +132,15 @@ fn default_value(s: &str) {
 static LOGGER_OBJECT: atomic_ref::AtomicRef<'static, fn(&str)> =
     atomic_ref::AtomicRef::new(Some(&(default_value as fn(&str))));
 static LOG_LEVEL: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(0);
+static CLOCK_SOURCE: atomic_ref::AtomicRef<'static, fn() -> u64> = atomic_ref::AtomicRef::new(None);
+static HART_ID_SOURCE: atomic_ref::AtomicRef<'static, fn() -> u64> = atomic_ref::AtomicRef::new(None);
+
+/// The number of targets which can have their own log level at once
+pub const MAX_TARGET_LEVELS: usize = 16;
+
+static TARGET_LEVELS: crate::sync::RwLock<[Option<(&'static str, LogLevel)>; MAX_TARGET_LEVELS]> =
+    crate::sync::RwLock::new([None; MAX_TARGET_LEVELS]);
+static TARGET_LEVEL_COUNT: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
 
 /// Returns a logging wrapper for the configured logger.
 pub fn get_writer() -> LoggerWrapper {
@@ -43,9 +151,44 @@ pub fn get_writer() -> LoggerWrapper {
     }
 }
 
-/// Returns true if the log level passed is to be displayed with the current settings.
-pub fn check_log_level(level: LogLevel) -> bool {
-    level as u8 >= LOG_LEVEL.load(core::sync::atomic::Ordering::Acquire)
+/// Returns true if a message at the log level passed, from the given target, is to be displayed with the current
+/// settings.
+///
+/// The level set for the longest matching target prefix takes precedence over the global level. While the target
+/// levels are being changed, the global level is used instead.
+#[must_use]
+pub fn check_log_level(target: &str, level: LogLevel) -> bool {
+    level as u8 >= minimum_log_level(target)
+}
+
+/// Get the minimum displayed log level for messages from the given target
+fn minimum_log_level(target: &str) -> u8 {
+    let global = LOG_LEVEL.load(core::sync::atomic::Ordering::Acquire);
+
+    // Avoid taking the lock in the common case where no target has its own level
+    if TARGET_LEVEL_COUNT.load(core::sync::atomic::Ordering::Acquire) == 0 {
+        return global;
+    }
+
+    // Logging may happen in interrupt context, where spinning on a writer the interrupt preempted would deadlock
+    let Some(levels) = TARGET_LEVELS.try_read() else {
+        return global;
+    };
+
+    levels
+        .iter()
+        .flatten()
+        .filter(|(prefix, _)| target_matches(target, prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(global, |(_, level)| *level as u8)
+}
+
+/// Returns true if `target` is `prefix` or lies within it, where a target lies within another if it continues with a
+/// `::` separated path
+fn target_matches(target: &str, prefix: &str) -> bool {
+    target
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
 }
 
 /// Set the currently configured writing function for logging.
@@ -58,6 +201,105 @@ pub fn set_log_level(level: LogLevel) {
     LOG_LEVEL.store(level as u8, core::sync::atomic::Ordering::Release);
 }
 
+/// Set the minimum displayed log level for messages from `target` and any targets within it.
+///
+/// This overrides the global level. Targets are module paths, such as `qor_os::drivers::virtio`, unless a target is
+/// given to the logging macro. Returns false if `MAX_TARGET_LEVELS` targets already have their own level.
+pub fn set_target_log_level(target: &'static str, level: LogLevel) -> bool {
+    let mut levels = TARGET_LEVELS.write();
+
+    if let Some(entry) = levels.iter_mut().flatten().find(|(prefix, _)| *prefix == target) {
+        entry.1 = level;
+        return true;
+    }
+
+    let Some(slot) = levels.iter_mut().find(|entry| entry.is_none()) else {
+        return false;
+    };
+
+    *slot = Some((target, level));
+    TARGET_LEVEL_COUNT.fetch_add(1, core::sync::atomic::Ordering::AcqRel);
+    true
+}
+
+/// Remove the log level set for `target`, so its messages are filtered by the global level again.
+pub fn clear_target_log_level(target: &str) {
+    let mut levels = TARGET_LEVELS.write();
+
+    if let Some(slot) = levels.iter_mut().find(|entry| entry.is_some_and(|(prefix, _)| prefix == target)) {
+        *slot = None;
+        TARGET_LEVEL_COUNT.fetch_sub(1, core::sync::atomic::Ordering::AcqRel);
+    }
+}
+
+/// Write a report of a panic at `location` with `message` to `writer`. Nothing is allocated, so the report can still
+/// be written when the allocator is what panicked.
+///
+/// # Errors
+///
+/// Returns an error if `writer` fails.
+pub fn write_panic_report(
+    writer: &mut impl core::fmt::Write,
+    location: Option<&core::panic::Location<'_>>,
+    message: impl core::fmt::Display,
+) -> core::fmt::Result {
+    write!(writer, "{ERROR}Kernel panic")?;
+
+    if let Some(location) = location {
+        write!(
+            writer,
+            " at {}:{}:{}",
+            location.file(),
+            location.line(),
+            location.column()
+        )?;
+    }
+
+    writeln!(writer, ": {message}")
+}
+
+/// Set the clock used to timestamp log messages, which returns the number of microseconds since boot.
+pub fn set_clock_source(clock: &'static fn() -> u64) {
+    CLOCK_SOURCE.store(Some(clock), core::sync::atomic::Ordering::Release);
+}
+
+/// Stop timestamping log messages.
+pub fn clear_clock_source() {
+    CLOCK_SOURCE.store(None, core::sync::atomic::Ordering::Release);
+}
+
+/// Set the function giving the id of the hart logging a message, so lines logged by different harts can be told apart
+///
+/// Until this is set, which is only worthwhile on systems with more than one hart, the hart id is left out.
+pub fn set_hart_id_source(hart_id: &'static fn() -> u64) {
+    HART_ID_SOURCE.store(Some(hart_id), core::sync::atomic::Ordering::Release);
+}
+
+/// Stop including the hart id in log messages.
+pub fn clear_hart_id_source() {
+    HART_ID_SOURCE.store(None, core::sync::atomic::Ordering::Release);
+}
+
+/// The prefix written before each log message, made up of the time since boot and the id of the logging hart, each
+/// only if their source is set, followed by the level tag.
+#[derive(Clone, Copy)]
+pub struct Prefix(pub &'static str);
+
+impl core::fmt::Display for Prefix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(clock) = CLOCK_SOURCE.load(core::sync::atomic::Ordering::Acquire) {
+            let micros = clock();
+            write!(f, "[{:>5}.{:06}] ", micros / 1_000_000, micros % 1_000_000)?;
+        }
+
+        if let Some(hart_id) = HART_ID_SOURCE.load(core::sync::atomic::Ordering::Acquire) {
+            write!(f, "[hart {}] ", hart_id())?;
+        }
+
+        write!(f, "{}", self.0)
+    }
+}
+
 // Flag to set if the output should be colored
 pub const COLORED: bool = true;
 
@@ -77,89 +319,270 @@ pub const ERROR: &str = const_format::formatcp!("[{}ERROR{}] ", RED, CLEAR);
 /// Log a trace message to the configured logger
 #[macro_export]
 macro_rules! trace {
-    ($fmt: literal, $($args:tt)+) => {{
+    (target: $target: expr, $fmt: literal, $($args:tt)+) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Trace) {
-            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::TRACE, $($args)+);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Trace) {
+            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::Prefix($crate::logging::TRACE), $($args)+);
         }
     }};
 
-    ($fmt: literal) => {{
+    (target: $target: expr, $fmt: literal) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Trace) {
-            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::TRACE, $fmt);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Trace) {
+            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::Prefix($crate::logging::TRACE), $fmt);
         }
     }};
+
+    ($fmt: literal, $($args:tt)+) => {{
+        $crate::trace!(target: module_path!(), $fmt, $($args)+)
+    }};
+
+    ($fmt: literal) => {{
+        $crate::trace!(target: module_path!(), $fmt)
+    }};
 }
 
 /// Log a debug message to the configured logger
 #[macro_export]
 macro_rules! debug {
-    ($fmt: literal, $($args:tt)+) => {{
+    (target: $target: expr, $fmt: literal, $($args:tt)+) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Debug) {
-            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::DEBUG, $($args)+);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Debug) {
+            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::Prefix($crate::logging::DEBUG), $($args)+);
         }
     }};
 
-    ($fmt: literal) => {{
+    (target: $target: expr, $fmt: literal) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Debug) {
-            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::DEBUG, $fmt);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Debug) {
+            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::Prefix($crate::logging::DEBUG), $fmt);
         }
     }};
+
+    ($fmt: literal, $($args:tt)+) => {{
+        $crate::debug!(target: module_path!(), $fmt, $($args)+)
+    }};
+
+    ($fmt: literal) => {{
+        $crate::debug!(target: module_path!(), $fmt)
+    }};
 }
 
 /// Log an info message to the configured logger
 #[macro_export]
 macro_rules! info {
-    ($fmt: literal, $($args:tt)+) => {{
+    (target: $target: expr, $fmt: literal, $($args:tt)+) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Info) {
-            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::INFO, $($args)+);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Info) {
+            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::Prefix($crate::logging::INFO), $($args)+);
         }
     }};
 
-    ($fmt: literal) => {{
+    (target: $target: expr, $fmt: literal) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Info) {
-            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::INFO, $fmt);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Info) {
+            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::Prefix($crate::logging::INFO), $fmt);
         }
     }};
+
+    ($fmt: literal, $($args:tt)+) => {{
+        $crate::info!(target: module_path!(), $fmt, $($args)+)
+    }};
+
+    ($fmt: literal) => {{
+        $crate::info!(target: module_path!(), $fmt)
+    }};
 }
 
 /// Log a warning message to the configured logger
 #[macro_export]
 macro_rules! warn {
-    ($fmt: literal, $($args:tt)+) => {{
+    (target: $target: expr, $fmt: literal, $($args:tt)+) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Warn) {
-            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::WARN, $($args)+);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Warn) {
+            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::Prefix($crate::logging::WARN), $($args)+);
         }
     }};
 
-    ($fmt: literal) => {{
+    (target: $target: expr, $fmt: literal) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Warn) {
-            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::WARN, $fmt);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Warn) {
+            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::Prefix($crate::logging::WARN), $fmt);
         }
     }};
+
+    ($fmt: literal, $($args:tt)+) => {{
+        $crate::warn!(target: module_path!(), $fmt, $($args)+)
+    }};
+
+    ($fmt: literal) => {{
+        $crate::warn!(target: module_path!(), $fmt)
+    }};
 }
 
 /// Log an error message to the configured logger
 #[macro_export]
 macro_rules! error {
-    ($fmt: literal, $($args:tt)+) => {{
+    (target: $target: expr, $fmt: literal, $($args:tt)+) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Error) {
-            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::ERROR, $($args)+);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Error) {
+            let _ = write!($crate::logging::get_writer(), concat!("{}", $fmt, "\n"), $crate::logging::Prefix($crate::logging::ERROR), $($args)+);
         }
     }};
 
-    ($fmt: literal) => {{
+    (target: $target: expr, $fmt: literal) => {{
         use core::fmt::Write;
-        if $crate::logging::check_log_level($crate::logging::LogLevel::Error) {
-            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::ERROR, $fmt);
+        if $crate::logging::check_log_level($target, $crate::logging::LogLevel::Error) {
+            let _ = write!($crate::logging::get_writer(), "{}{}\n", $crate::logging::Prefix($crate::logging::ERROR), $fmt);
         }
     }};
+
+    ($fmt: literal, $($args:tt)+) => {{
+        $crate::error!(target: module_path!(), $fmt, $($args)+)
+    }};
+
+    ($fmt: literal) => {{
+        $crate::error!(target: module_path!(), $fmt)
+    }};
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::string::String;
+
+    use super::{
+        check_log_level, clear_clock_source, clear_hart_id_source, clear_target_log_level, set_clock_source,
+        set_hart_id_source, set_target_log_level, write_panic_report, LogLevel, LogRing, Prefix, ERROR, INFO,
+        TARGET_LEVELS,
+    };
+
+    /// Held by tests which install a clock or hart id source, so they do not see each other's prefixes
+    static PREFIX_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Held by tests which change the target levels, so one holding them does not hide the levels from another
+    static TARGET_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    pub fn panic_report_test() {
+        let location = core::panic::Location::caller();
+
+        let mut output = String::new();
+        write_panic_report(&mut output, Some(location), format_args!("index {} out of range", 7)).unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "{ERROR}Kernel panic at {}:{}:{}: index 7 out of range\n",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        );
+
+        output.clear();
+        write_panic_report(&mut output, None, "out of memory").unwrap();
+        assert_eq!(output, format!("{ERROR}Kernel panic: out of memory\n"));
+    }
+
+    #[test]
+    pub fn ring_test() {
+        let mut ring = LogRing::new(32);
+        ring.push(b"first\n");
+        ring.push(b"second\n");
+
+        let mut output = String::new();
+        ring.drain(&mut output).unwrap();
+        assert_eq!(output, "first\nsecond\n");
+
+        // Draining empties the ring
+        output.clear();
+        ring.drain(&mut output).unwrap();
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    pub fn ring_wraparound_test() {
+        let mut ring = LogRing::new(16);
+        ring.push(b"alpha\n");
+        ring.push(b"bravo\n");
+        ring.push(b"charlie\n");
+
+        // Only the end of `alpha` is left, so it is skipped as a partial line
+        let mut output = String::new();
+        ring.drain(&mut output).unwrap();
+        assert_eq!(output, "bravo\ncharlie\n");
+
+        ring.push(b"delta\necho\nfoxtrot\n");
+        output.clear();
+        ring.drain(&mut output).unwrap();
+        assert_eq!(output, "echo\nfoxtrot\n");
+
+        let mut disabled = LogRing::new(0);
+        disabled.push(b"ignored\n");
+        output.clear();
+        disabled.drain(&mut output).unwrap();
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    pub fn timestamp_prefix_test() {
+        let _guard = PREFIX_LOCK.lock().unwrap();
+        assert_eq!(format!("{}", Prefix(INFO)), INFO);
+
+        set_clock_source(&((|| 12_345_678) as fn() -> u64));
+        assert_eq!(format!("{}", Prefix(INFO)), format!("[   12.345678] {INFO}"));
+
+        clear_clock_source();
+        assert_eq!(format!("{}", Prefix(INFO)), INFO);
+    }
+
+    #[test]
+    pub fn hart_id_prefix_test() {
+        let _guard = PREFIX_LOCK.lock().unwrap();
+
+        set_hart_id_source(&((|| 3) as fn() -> u64));
+        assert_eq!(format!("{}", Prefix(INFO)), format!("[hart 3] {INFO}"));
+
+        set_clock_source(&((|| 1_500_000) as fn() -> u64));
+        assert_eq!(format!("{}", Prefix(INFO)), format!("[    1.500000] [hart 3] {INFO}"));
+
+        clear_clock_source();
+        clear_hart_id_source();
+        assert_eq!(format!("{}", Prefix(INFO)), INFO);
+    }
+
+    #[test]
+    pub fn target_log_level_test() {
+        let _guard = TARGET_LOCK.lock().unwrap();
+        assert!(check_log_level("logging_test::quiet", LogLevel::Trace));
+
+        assert!(set_target_log_level("logging_test::quiet", LogLevel::Warn));
+        assert!(set_target_log_level("logging_test::quiet::loud", LogLevel::Trace));
+
+        assert!(!check_log_level("logging_test::quiet", LogLevel::Debug));
+        assert!(check_log_level("logging_test::quiet", LogLevel::Error));
+        assert!(!check_log_level("logging_test::quiet::inner", LogLevel::Info));
+        assert!(check_log_level("logging_test::quiet::loud::inner", LogLevel::Trace));
+        assert!(check_log_level("logging_test::quieter", LogLevel::Trace));
+
+        clear_target_log_level("logging_test::quiet");
+        clear_target_log_level("logging_test::quiet::loud");
+        assert!(check_log_level("logging_test::quiet", LogLevel::Trace));
+    }
+
+    #[test]
+    pub fn target_log_level_contended_test() {
+        let _guard = TARGET_LOCK.lock().unwrap();
+        assert!(set_target_log_level("logging_test::contended", LogLevel::Error));
+        assert!(!check_log_level("logging_test::contended", LogLevel::Warn));
+
+        // With a writer holding the levels, the global level is used rather than waiting on it
+        {
+            let _levels = TARGET_LEVELS.write();
+            assert!(check_log_level("logging_test::contended", LogLevel::Warn));
+        }
+
+        assert!(!check_log_level("logging_test::contended", LogLevel::Warn));
+        clear_target_log_level("logging_test::contended");
+    }
 }
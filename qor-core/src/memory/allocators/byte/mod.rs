@@ -1,33 +1,39 @@
 use core::{
     mem::size_of,
-    sync::atomic::{AtomicPtr, AtomicU32, Ordering},
+    sync::atomic::{AtomicPtr, AtomicU32, AtomicUsize, Ordering},
 };
 
 const LOCKED_BIT_MASK: u32 = 0x8000_0000;
 const VALID_BIT_MASK: u32 = 0x4000_0000;
 const ALLOCATED_BIT_MASK: u32 = 0x2000_0000;
-const ALLOCATION_LENGTH_MASK: u32 = 0x1FFF_0000;
 const NEXT_ENTRY_INDEX_MASK: u32 = 0x0000_FFFF;
 
+/// Number of immediate attempts to lock a contended entry before backing off
+const LOCK_SPIN_RETRIES: usize = 64;
+/// Longest wait between attempts to lock a contended entry, in spin loop iterations
+const LOCK_MAX_BACKOFF: usize = 1024;
+
 /// The `AllocationTableEntry`is a structure that represents a single entry into the byte allocator table. The data contained needs to include some bit flags.
 /// ```
-/// +--------+-------+-----------+-------------------+------------------+--------------------------+
-/// | 1 Bit  | 1 Bit |   1 Bit   |      13 Bits      |     16 Bits      |         32 Bits          |
-/// +--------+-------+-----------+-------------------+------------------+--------------------------+
-/// | Locked | Valid | Allocated | Allocation Length | Next Entry Index | Lower 32 Bits of Pointer |
-/// +--------+-------+-----------+-------------------+------------------+--------------------------+
+/// +--------+-------+-----------+----------+------------------+-------------------+--------------------------+
+/// | 1 Bit  | 1 Bit |   1 Bit   | 13 Bits  |     16 Bits      |      32 Bits      |         32 Bits          |
+/// +--------+-------+-----------+----------+------------------+-------------------+--------------------------+
+/// | Locked | Valid | Allocated | Reserved | Next Entry Index | Allocation Length | Lower 32 Bits of Pointer |
+/// +--------+-------+-----------+----------+------------------+-------------------+--------------------------+
 /// ```
 ///
 /// - The locked bit is used to denote that the entry is locked (and thus being used by another thread).
 /// - The valid bit is used to denote if the entry is valid and thus contains a reference to an allocation.
 /// - The allocated bit is used to denote that the entry is referring to an allocated section of memory.
-/// - The allocation length is the number of bytes contained in the allocation.
 /// - The next entry index is the index into the allocation tables of the next entry.
+/// - The allocation length is the number of bytes contained in the allocation, held in a word of its own so a single
+///   entry can describe a region of up to 4 GiB.
 /// - The lower 32 bits of the pointer is combined with the upper 32 bits of the pointer from the `AllocationTable` to
 ///   form a full pointer to the allocation.
 #[derive(Debug)]
 pub struct AllocationTableEntry {
     flags: AtomicU32,
+    length: AtomicU32,
     ptr_offset: AtomicU32,
 }
 
@@ -37,6 +43,7 @@ impl AllocationTableEntry {
     pub const fn empty() -> Self {
         Self {
             flags: AtomicU32::new(0),
+            length: AtomicU32::new(0),
             ptr_offset: AtomicU32::new(0),
         }
     }
@@ -49,6 +56,30 @@ impl AllocationTableEntry {
             None
         }
     }
+
+    /// Lock this [`AllocationTableEntry`], waiting for any other holder to release it. The lock is retried
+    /// immediately a bounded number of times, after which the wait between attempts backs off exponentially to
+    /// reduce contention on the entry.
+    pub fn spin_lock(&self) -> AllocationTableEntryGuard<'_> {
+        for _ in 0..LOCK_SPIN_RETRIES {
+            if let Some(guard) = self.lock() {
+                return guard;
+            }
+            core::hint::spin_loop();
+        }
+
+        let mut backoff = 1;
+        loop {
+            if let Some(guard) = self.lock() {
+                return guard;
+            }
+
+            for _ in 0..backoff {
+                core::hint::spin_loop();
+            }
+            backoff = (backoff * 2).min(LOCK_MAX_BACKOFF);
+        }
+    }
 }
 
 pub struct AllocationTableEntryGuard<'a> {
@@ -103,16 +134,17 @@ impl<'a> AllocationTableEntryGuard<'a> {
     /// Get the allocation length
     #[must_use]
     pub fn allocation_length(&self) -> usize {
-        ((self.entry.flags.load(Ordering::Relaxed) & ALLOCATION_LENGTH_MASK) >> 16) as usize
+        self.entry.length.load(Ordering::Relaxed) as usize
     }
 
     /// Set the allocation length
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the length does not fit in 32 bits.
     pub fn set_allocation_length(&self, length: usize) {
-        self.entry
-            .flags
-            .fetch_and(!ALLOCATION_LENGTH_MASK, Ordering::Relaxed);
-        self.entry.flags.fetch_or(
-            u32::try_from(length & 0x1FFF).expect("Length Too Big") << 16,
+        self.entry.length.store(
+            u32::try_from(length).expect("Length Too Big"),
             Ordering::Relaxed,
         );
     }
@@ -169,8 +201,38 @@ impl<'a> core::ops::Drop for AllocationTableEntryGuard<'a> {
     }
 }
 
+/// Number of words at the start of an [`AllocationTable`] before its entries
+const ALLOCATION_TABLE_HEADER_WORDS: usize = 8;
+
 const ALLOCATION_TABLE_LENGTH: usize =
-    (4096 - 4 * size_of::<usize>()) / size_of::<AllocationTableEntry>();
+    (4096 - ALLOCATION_TABLE_HEADER_WORDS * size_of::<usize>()) / size_of::<AllocationTableEntry>();
+
+/// Snapshot of how the regions managed by an [`AllocationTable`] are used
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationUsage {
+    /// Total length of the allocated regions
+    pub allocated_bytes: usize,
+    /// Total length of the free regions
+    pub free_bytes: usize,
+    /// Number of allocated regions
+    pub allocations: usize,
+}
+
+/// Snapshot of the counters an [`AllocationTable`] keeps as memory is allocated and freed.
+///
+/// The counters are read independently, so a snapshot taken while other users are allocating may not be exactly
+/// consistent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationStats {
+    /// Total length of the live allocations, including any padding for alignment
+    pub allocated_bytes: usize,
+    /// Total length of the regions added to the table which are not allocated
+    pub free_bytes: usize,
+    /// Number of live allocations
+    pub live_allocations: usize,
+    /// Largest value `allocated_bytes` has reached
+    pub peak_allocated_bytes: usize,
+}
 
 #[repr(align(4096))]
 #[derive(Debug)]
@@ -184,6 +246,11 @@ pub struct AllocationTable {
     next: AtomicPtr<AllocationTable>,     // Treat like: Option<&'static mut AllocationTable>
     pointer_upper_32: u32,
     start_index: usize,
+    // Usage counters, only maintained by the table allocations are made through
+    total_bytes: AtomicUsize,
+    allocated_bytes: AtomicUsize,
+    live_allocations: AtomicUsize,
+    peak_allocated_bytes: AtomicUsize,
     entries: [AllocationTableEntry; ALLOCATION_TABLE_LENGTH],
 }
 
@@ -240,6 +307,10 @@ impl AllocationTable {
             next: AtomicPtr::new(core::ptr::null_mut()),
             pointer_upper_32: 0,
             start_index: 0,
+            total_bytes: AtomicUsize::new(0),
+            allocated_bytes: AtomicUsize::new(0),
+            live_allocations: AtomicUsize::new(0),
+            peak_allocated_bytes: AtomicUsize::new(0),
             entries: [EMPTY; ALLOCATION_TABLE_LENGTH],
         }
     }
@@ -261,47 +332,40 @@ impl AllocationTable {
     ///
     /// # Panics
     ///
-    /// This function will panic if it is unable to add a region due to being blocked by another thread.
+    /// This function will panic if the table has no free entries left, or the region does not share the upper 32 bits of
+    /// its address with the regions already added.
     pub fn add_region(&mut self, region: &'static mut [u8]) {
         let pointer = region.as_mut_ptr() as u64;
 
         let low = (pointer & 0xffff_ffff) as u32;
         let high = (pointer >> 32) as u32;
 
-        let mut current = self
+        let mut guard = self
             .index(0)
-            .expect("Need to be able to access the first entry");
-        let guard = current.lock().unwrap();
-        #[allow(clippy::if_not_else)]
-        if !guard.valid() {
+            .expect("Need to be able to access the first entry")
+            .spin_lock();
+        if guard.valid() {
+            // Walk to the end of the list, and link a new entry for the region after it
+            while let Some(next_guard) = self.lock_next(&guard) {
+                guard = next_guard;
+            }
+
+            let (free_guard_index, _, free_guard) = self
+                .find_first_invalid(region.as_mut_ptr() as usize)
+                .unwrap();
+            free_guard.set_next(0);
+            free_guard.update(true, false, region.len(), low);
+            guard.set_next(free_guard_index);
+        } else {
             guard.update(true, false, region.len(), low);
             guard.set_next(0);
             core::mem::drop(guard);
             self.pointer_upper_32 = high;
-        } else {
-            core::mem::drop(guard);
-            loop {
-                if let Some(guard) = current.lock() {
-                    assert!(guard.valid());
-                    let next_index = guard.next();
-                    if next_index > 0 {
-                        current = self.index(next_index as usize).expect("Bad Link");
-                    } else {
-                        let (free_guard_index, _, free_guard) = self
-                            .find_first_invalid(region.as_mut_ptr() as usize)
-                            .unwrap();
-                        free_guard.set_next(0);
-                        free_guard.update(true, false, region.len(), low);
-                        guard.set_next(free_guard_index);
-                        break;
-                    }
-                } else {
-                    todo!();
-                }
-            }
         }
 
         assert_eq!(self.pointer_upper_32, high);
+
+        self.total_bytes.fetch_add(region.len(), Ordering::Relaxed);
     }
 
     /// Get a reference to the entry at the given index, walking the linked list of `AllocationTable`s if necessary.
@@ -389,60 +453,127 @@ impl AllocationTable {
         }
     }
 
+    /// Count `bytes` more allocated bytes in `allocations` more allocations, raising the peak if it has been passed
+    fn record_allocation(&self, bytes: usize, allocations: usize) {
+        let allocated = self.allocated_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.live_allocations
+            .fetch_add(allocations, Ordering::Relaxed);
+        self.peak_allocated_bytes
+            .fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    /// Snapshot the usage counters of the table. Unlike [`AllocationTable::try_usage`] this never touches an entry, so
+    /// it is always available.
+    #[must_use]
+    pub fn stats(&self) -> AllocationStats {
+        let allocated_bytes = self.allocated_bytes.load(Ordering::Relaxed);
+
+        AllocationStats {
+            allocated_bytes,
+            free_bytes: self
+                .total_bytes
+                .load(Ordering::Relaxed)
+                .saturating_sub(allocated_bytes),
+            live_allocations: self.live_allocations.load(Ordering::Relaxed),
+            peak_allocated_bytes: self.peak_allocated_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Total the allocated and free regions of the table without waiting on any entry, returning `None` if an entry
+    /// is locked by another user. This makes it safe to call from contexts which may have interrupted a lock holder.
+    #[must_use]
+    pub fn try_usage(&self) -> Option<AllocationUsage> {
+        let mut usage = AllocationUsage::default();
+        let mut guard = self.index(0)?.lock()?;
+
+        loop {
+            if !guard.valid() {
+                return Some(usage);
+            }
+
+            if guard.allocated() {
+                usage.allocated_bytes += guard.allocation_length();
+                usage.allocations += 1;
+            } else {
+                usage.free_bytes += guard.allocation_length();
+            }
+
+            let next = guard.next();
+            if next == 0 {
+                return Some(usage);
+            }
+
+            guard = self.index(next as usize)?.lock()?;
+        }
+    }
+
+    /// Lock the entry following the one held by `guard`, returning `None` at the end of the list. The lock on the
+    /// following entry is taken before `guard` is released by the caller, so the entry cannot be unlinked in between.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the link is to an invalid entry.
+    fn lock_next(
+        &self,
+        guard: &AllocationTableEntryGuard,
+    ) -> Option<AllocationTableEntryGuard<'_>> {
+        let next_index = guard.next();
+        if next_index == 0 {
+            return None;
+        }
+
+        let next = self
+            .index(next_index as usize)
+            .expect("Bad Link")
+            .spin_lock();
+        assert!(next.valid());
+
+        Some(next)
+    }
+
     /// Search for a region of memory with the given alignment and size.
     ///
     /// # Panics
     ///
     /// This function will panic if it encounters an invalid state.
     pub fn alloc(&self, size: usize, align: usize) -> Option<*mut u8> {
-        let mut current = self.index(0)?;
+        let mut guard = self.index(0)?.spin_lock();
+        assert!(guard.valid());
+
         loop {
-            if let Some(guard) = current.lock() {
-                assert!(guard.valid());
-
-                if !guard.allocated() {
-                    let low_ptr = guard.low_pointer() as usize;
-                    let align_slack = (align - (low_ptr % align)) % align;
-                    #[allow(clippy::comparison_chain)]
-                    if guard.allocation_length() == align_slack + size {
-                        guard.set_allocated(true);
-                        return Some(
-                            (guard.pointer(self.pointer_upper_32) + align_slack) as *mut u8,
+            if !guard.allocated() {
+                let low_ptr = guard.low_pointer() as usize;
+                let align_slack = (align - (low_ptr % align)) % align;
+                #[allow(clippy::comparison_chain)]
+                if guard.allocation_length() == align_slack + size {
+                    guard.set_allocated(true);
+                    self.record_allocation(align_slack + size, 1);
+                    return Some((guard.pointer(self.pointer_upper_32) + align_slack) as *mut u8);
+                } else if guard.allocation_length() > align_slack + size {
+                    if let Some((free_index, upper_32, free)) = self.find_first_invalid(
+                        guard.pointer(self.pointer_upper_32) + align_slack + size,
+                    ) {
+                        free.update(
+                            true,
+                            false,
+                            guard.allocation_length() - align_slack - size,
+                            guard
+                                .low_pointer()
+                                .wrapping_add((align_slack + size).try_into().unwrap()),
                         );
-                    } else if guard.allocation_length() > align_slack + size {
-                        if let Some((free_index, upper_32, free)) = self.find_first_invalid(
-                            guard.pointer(self.pointer_upper_32) + align_slack + size,
-                        ) {
-                            free.update(
-                                true,
-                                false,
-                                guard.allocation_length() - align_slack - size,
-                                guard
-                                    .low_pointer()
-                                    .wrapping_add((align_slack + size).try_into().unwrap()),
-                            );
-                            free.set_next(guard.next());
-
-                            guard.update(true, true, align_slack + size, guard.low_pointer());
-                            guard.set_next(free_index);
-
-                            return Some((guard.pointer(upper_32) + align_slack) as *mut u8);
-                        }
-                    }
-                }
+                        free.set_next(guard.next());
 
-                let next_index = guard.next();
-                if next_index > 0 {
-                    current = self.index(next_index as usize)?;
-                } else {
-                    break;
+                        guard.update(true, true, align_slack + size, guard.low_pointer());
+                        guard.set_next(free_index);
+                        self.record_allocation(align_slack + size, 1);
+
+                        return Some((guard.pointer(upper_32) + align_slack) as *mut u8);
+                    }
                 }
-            } else {
-                return None;
             }
-        }
 
-        None
+            guard = self.lock_next(&guard)?;
+        }
     }
 
     /// Free a region of allocated memory. Note that this function does not assume that the pointer given is at the start of the memory region, only that it is within the region.
@@ -451,35 +582,88 @@ impl AllocationTable {
     ///
     /// This function will panic if it encounters an invalid state.
     pub fn free(&self, ptr: usize) {
-        let mut current = self
+        let mut guard = self
             .index(0)
-            .expect("Need to be able to access the first entry");
+            .expect("Need to be able to access the first entry")
+            .spin_lock();
+        assert!(guard.valid());
+
         loop {
-            if let Some(guard) = current.lock() {
-                assert!(guard.valid());
+            if guard.allocated() {
+                let ptr_start = guard.pointer(self.pointer_upper_32);
+                let length = guard.allocation_length();
+
+                if ptr >= ptr_start && ptr < ptr_start + length {
+                    guard.set_allocated(false);
+                    self.allocated_bytes.fetch_sub(length, Ordering::Relaxed);
+                    self.live_allocations.fetch_sub(1, Ordering::Relaxed);
+                    return;
+                }
+            }
 
-                if guard.allocated() {
-                    let ptr_start = guard.pointer(self.pointer_upper_32);
-                    let length = guard.allocation_length();
+            guard = self
+                .lock_next(&guard)
+                .expect("Pointer is not within an allocation");
+        }
+    }
 
-                    if ptr >= ptr_start && ptr < ptr_start + length {
-                        guard.set_allocated(false);
-                        return;
-                    }
+    /// Attempt to grow the allocation containing `ptr` in place, so that `new_size` bytes starting at `ptr` are
+    /// allocated, by taking space from the free region directly following it. Returns true if the allocation is now
+    /// large enough, which it always is for a shrinking allocation.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it encounters an invalid state, or `ptr` is not within an allocation.
+    pub fn try_grow(&self, ptr: usize, new_size: usize) -> bool {
+        let mut guard = self
+            .index(0)
+            .expect("Need to be able to access the first entry")
+            .spin_lock();
+        assert!(guard.valid());
+
+        loop {
+            let ptr_start = guard.pointer(self.pointer_upper_32);
+            let length = guard.allocation_length();
+            let next_guard = self.lock_next(&guard);
+
+            if guard.allocated() && ptr >= ptr_start && ptr < ptr_start + length {
+                let required = ptr - ptr_start + new_size;
+                if required <= length {
+                    return true;
+                }
+
+                let Some(next_guard) = next_guard else {
+                    return false;
+                };
+
+                let extra = required - length;
+                if next_guard.allocated()
+                    || guard.low_pointer().wrapping_add(length.try_into().unwrap())
+                        != next_guard.low_pointer()
+                    || next_guard.allocation_length() < extra
+                {
+                    return false;
                 }
 
-                let next_index = guard.next();
-                if next_index > 0 {
-                    current = self.index(next_index as usize).expect("Bad Link");
+                if next_guard.allocation_length() == extra {
+                    guard.set_next(next_guard.next());
+                    next_guard.set_valid(false);
                 } else {
-                    break;
+                    next_guard.set_allocation_length(next_guard.allocation_length() - extra);
+                    next_guard.set_low_pointer(
+                        next_guard
+                            .low_pointer()
+                            .wrapping_add(extra.try_into().unwrap()),
+                    );
                 }
-            } else {
-                todo!();
+                guard.set_allocation_length(required);
+                self.record_allocation(extra, 0);
+
+                return true;
             }
-        }
 
-        panic!()
+            guard = next_guard.expect("Pointer is not within an allocation");
+        }
     }
 
     /// Coalesce sequential free regions
@@ -488,48 +672,28 @@ impl AllocationTable {
     ///
     /// This function will panic if it reaches an invalid state.
     pub fn coalesce_free_regions(&self) {
-        let mut current = self.index(0).expect("Unable to access first entry");
-        loop {
-            if let Some(guard) = current.lock() {
-                assert!(guard.valid());
-
-                let next_index = guard.next();
-                let next = if next_index > 0 {
-                    self.index(next_index as usize)
-                } else {
-                    None
-                };
-
-                if let Some(next) = next {
-                    let mut expected_next = next;
-                    #[allow(clippy::option_if_let_else)]
-                    if let Some(next_guard) = next.lock() {
-                        if !guard.allocated()
-                            && !next_guard.allocated()
-                            && next_guard.valid()
-                            && guard
-                                .low_pointer()
-                                .wrapping_add(guard.allocation_length().try_into().unwrap())
-                                == next_guard.low_pointer()
-                            && guard.allocation_length() + next_guard.allocation_length() <= 4096
-                        {
-                            guard.set_allocation_length(
-                                guard.allocation_length() + next_guard.allocation_length(),
-                            );
-                            guard.set_next(next_guard.next());
-                            next_guard.set_valid(false);
-                            expected_next = current;
-                        }
-                    } else {
-                        todo!()
-                    }
-
-                    current = expected_next;
-                } else {
-                    break;
-                }
+        let mut guard = self
+            .index(0)
+            .expect("Unable to access first entry")
+            .spin_lock();
+        assert!(guard.valid());
+
+        while let Some(next_guard) = self.lock_next(&guard) {
+            if !guard.allocated()
+                && !next_guard.allocated()
+                && guard
+                    .low_pointer()
+                    .wrapping_add(guard.allocation_length().try_into().unwrap())
+                    == next_guard.low_pointer()
+                && u32::try_from(guard.allocation_length() + next_guard.allocation_length()).is_ok()
+            {
+                guard.set_allocation_length(
+                    guard.allocation_length() + next_guard.allocation_length(),
+                );
+                guard.set_next(next_guard.next());
+                next_guard.set_valid(false);
             } else {
-                return;
+                guard = next_guard;
             }
         }
     }
@@ -541,6 +705,202 @@ impl Default for AllocationTable {
     }
 }
 
+#[test]
+pub fn try_usage_test() {
+    use std::boxed::Box;
+
+    let table = AllocationTable::construct_with_region(Box::leak(Box::new([0u8; 4096])));
+    let empty = table.try_usage().unwrap();
+    assert_eq!(empty.allocations, 0);
+
+    let a = table.alloc(64, 8).unwrap();
+    let b = table.alloc(32, 8).unwrap();
+    let usage = table.try_usage().unwrap();
+    assert_eq!(usage.allocations, 2);
+    assert_eq!(usage.allocated_bytes + usage.free_bytes, empty.free_bytes);
+
+    // A held entry lock means the snapshot is skipped rather than waited on
+    let guard = table.index(0).unwrap().lock().unwrap();
+    assert_eq!(table.try_usage(), None);
+    drop(guard);
+
+    table.free(a as usize);
+    table.free(b as usize);
+    table.coalesce_free_regions();
+    assert_eq!(table.try_usage().unwrap().allocations, 0);
+}
+
+#[test]
+pub fn large_allocation_test() {
+    use std::boxed::Box;
+
+    // Sixteen pages, added one at a time as the global allocator does
+    let memory = Box::leak(std::vec![0u8; 16 * 4096].into_boxed_slice());
+    let mut table = AllocationTable::new();
+    for page in memory.chunks_exact_mut(4096) {
+        table.add_region(page);
+    }
+    table.coalesce_free_regions();
+
+    let empty = table.try_usage().unwrap();
+    assert_eq!(empty.free_bytes, 16 * 4096);
+
+    let length = 32 * 1024;
+    let allocation = table.alloc(length, 8).unwrap();
+    // Safety: The allocation is `length` bytes long
+    unsafe { allocation.write_bytes(0xaa, length) };
+
+    let usage = table.try_usage().unwrap();
+    assert_eq!(usage.allocations, 1);
+    assert_eq!(usage.allocated_bytes, length);
+    assert_eq!(usage.free_bytes, 16 * 4096 - length);
+
+    table.free(allocation as usize);
+    table.coalesce_free_regions();
+    assert_eq!(table.try_usage().unwrap(), empty);
+}
+
+#[test]
+pub fn try_grow_test() {
+    use std::boxed::Box;
+
+    let table = AllocationTable::construct_with_region(Box::leak(Box::new([0u8; 4096])));
+
+    // Grow a buffer by doubling its capacity as a `Vec` does, falling back to moving it
+    let mut capacity = 4;
+    let mut buffer = table.alloc(capacity, 4).unwrap();
+    let mut reused = 0;
+    for length in 0..=255u8 {
+        if usize::from(length) == capacity {
+            if table.try_grow(buffer as usize, capacity * 2) {
+                reused += 1;
+            } else {
+                let moved = table.alloc(capacity * 2, 4).unwrap();
+                // Safety: Both allocations are at least `capacity` bytes long, and are distinct
+                unsafe { moved.copy_from_nonoverlapping(buffer, capacity) };
+                table.free(buffer as usize);
+                buffer = moved;
+            }
+            capacity *= 2;
+        }
+
+        // Safety: `length` is less than the capacity of the buffer
+        unsafe { buffer.add(usize::from(length)).write(length) };
+    }
+
+    assert!(reused > 0);
+    // Safety: The first 256 bytes of the buffer have been written
+    let contents = unsafe { core::slice::from_raw_parts(buffer, 256) };
+    assert!(contents.iter().copied().eq(0..=255));
+
+    // An allocation directly following the buffer blocks it from growing
+    let usage = table.try_usage().unwrap();
+    let blocker = table.alloc(16, 4).unwrap();
+    assert_eq!(blocker as usize, buffer as usize + capacity);
+    assert!(!table.try_grow(buffer as usize, capacity * 2));
+    assert!(table.try_grow(buffer as usize, capacity / 2));
+
+    table.free(blocker as usize);
+    table.coalesce_free_regions();
+    assert_eq!(table.try_usage().unwrap(), usage);
+}
+
+#[test]
+pub fn stats_test() {
+    use std::boxed::Box;
+
+    assert_eq!(size_of::<AllocationTable>(), 4096);
+
+    let table = AllocationTable::construct_with_region(Box::leak(Box::new([0u8; 4096])));
+    let empty = table.stats();
+    assert_eq!(
+        empty,
+        AllocationStats {
+            allocated_bytes: 0,
+            free_bytes: 4096,
+            live_allocations: 0,
+            peak_allocated_bytes: 0,
+        }
+    );
+
+    let a = table.alloc(64, 8).unwrap();
+    let b = table.alloc(100, 4).unwrap();
+    let stats = table.stats();
+    assert_eq!(stats.live_allocations, 2);
+    assert_eq!(stats.allocated_bytes + stats.free_bytes, 4096);
+    assert!(stats.allocated_bytes >= 164);
+    assert_eq!(stats.peak_allocated_bytes, stats.allocated_bytes);
+
+    // The counters agree with a walk of the table
+    let usage = table.try_usage().unwrap();
+    assert_eq!(usage.allocated_bytes, stats.allocated_bytes);
+    assert_eq!(usage.allocations, stats.live_allocations);
+
+    table.free(b as usize);
+    table.free(a as usize);
+    table.coalesce_free_regions();
+    assert_eq!(
+        table.stats(),
+        AllocationStats {
+            peak_allocated_bytes: stats.allocated_bytes,
+            ..empty
+        }
+    );
+}
+
+#[test]
+pub fn concurrent_test() {
+    use std::{boxed::Box, vec::Vec};
+
+    let memory = Box::leak(std::vec![0u8; 16 * 4096].into_boxed_slice());
+    let table: &'static AllocationTable =
+        Box::leak(Box::new(AllocationTable::construct_with_region(memory)));
+    let empty = table.try_usage().unwrap();
+
+    let threads = (0..4u8)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                let mut live = Vec::new();
+                for round in 0..2000usize {
+                    let size = 8 + (round * 37 + usize::from(thread) * 11) % 200;
+                    let pattern = thread.wrapping_add(round.to_le_bytes()[0]);
+
+                    let ptr = table
+                        .alloc(size, 8)
+                        .expect("Allocation failed under contention");
+                    // Safety: The allocation is `size` bytes long
+                    unsafe { ptr.write_bytes(pattern, size) };
+                    live.push((ptr, size, pattern));
+
+                    if live.len() > 8 || round % 3 == 0 {
+                        let (ptr, size, pattern) = live.remove(round % live.len());
+                        // Safety: The allocation is `size` bytes long, and no other thread was handed it
+                        let contents = unsafe { core::slice::from_raw_parts(ptr, size) };
+                        assert!(contents.iter().all(|byte| *byte == pattern));
+
+                        table.free(ptr as usize);
+                    }
+
+                    if round % 16 == 0 {
+                        table.coalesce_free_regions();
+                    }
+                }
+
+                for (ptr, _, _) in live {
+                    table.free(ptr as usize);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    table.coalesce_free_regions();
+    assert_eq!(table.try_usage().unwrap(), empty);
+}
+
 #[test]
 pub fn test() {
     use std::boxed::Box;
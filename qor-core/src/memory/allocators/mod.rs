@@ -1,2 +1,3 @@
 pub mod byte;
 pub mod page;
+pub mod slab;
@@ -11,6 +11,16 @@ pub struct PageBitmapAllocator<Page> {
     //      at that index is free to be allocated.
     bitmap: BitmapLock,
     start_pointer: core::sync::atomic::AtomicPtr<Page>,
+    best_fit: core::sync::atomic::AtomicBool,
+}
+
+/// Strategy used to choose which run of free pages an allocation is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationPolicy {
+    /// Take the first run long enough for the request, the fastest option
+    FirstFit,
+    /// Take the shortest run long enough for the request, leaving long runs for long requests
+    BestFit,
 }
 
 /// Errors possible to be returned by the allocator
@@ -20,6 +30,17 @@ pub enum AllocationError {
     Uninitialized,
 }
 
+/// Problems with the page type or memory given to [`PageBitmapAllocator::try_from_pages`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitmapInitError {
+    /// `Page` is aligned to fewer bytes than the `AtomicU64`s the bitmap is stored in
+    UnderAligned { alignment: usize, required: usize },
+    /// `Page` is too small to hold even a single `u64` of the bitmap
+    PageTooSmall { size: usize },
+    /// After the pages holding the bitmap, no pages are left to allocate
+    NoAllocatablePages { pages: usize },
+}
+
 /// Bitmap allocated smart pointer
 pub struct PageBox<'a, Page: 'static, T> {
     // Safety Requirements:
@@ -91,22 +112,39 @@ impl<Page> PageBitmapAllocator<Page> {
         Self {
             bitmap: BitmapLock::new(),
             start_pointer: core::sync::atomic::AtomicPtr::new(core::ptr::null_mut()),
+            best_fit: core::sync::atomic::AtomicBool::new(false),
         }
     }
 
-    /// Construct a [`PageBitmapAllocator<Page>`] to refer to a slice of pages
+    /// Construct a [`PageBitmapAllocator<Page>`] to refer to a slice of pages. The first few pages are used to hold
+    /// the bitmap, and the rest are available to allocate.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if `Page` is not aligned to 8 byte boundaries or more.
-    pub fn from_pages(data: &'static mut [Page]) -> Self {
-        // Immediately assert that `Page` is properly aligned (that is, its alignment is greater than or equal to that of `AtomicU64`).
-        assert!(align_of::<Page>() >= align_of::<core::sync::atomic::AtomicU64>());
+    /// Returns an error if `Page` is not aligned to 8 byte boundaries or more, is too small to hold part of the
+    /// bitmap, or `data` has no pages left over for allocation once the bitmap is stored.
+    pub fn try_from_pages(data: &'static mut [Page]) -> Result<Self, BitmapInitError> {
+        // The bitmap is stored in the pages as `AtomicU64`s, so `Page` must be at least as aligned
+        if align_of::<Page>() < align_of::<core::sync::atomic::AtomicU64>() {
+            return Err(BitmapInitError::UnderAligned {
+                alignment: align_of::<Page>(),
+                required: align_of::<core::sync::atomic::AtomicU64>(),
+            });
+        }
 
         // Calculate the number of `u64`s that can be put into a `Page`
         let u64s_per_page = size_of::<Page>() / 8;
+        if u64s_per_page == 0 {
+            return Err(BitmapInitError::PageTooSmall {
+                size: size_of::<Page>(),
+            });
+        }
+
         let denominator = 64 * u64s_per_page + 1;
         let pages_for_bitmap = (data.len() + denominator - 1) / denominator;
+        if pages_for_bitmap >= data.len() {
+            return Err(BitmapInitError::NoAllocatablePages { pages: data.len() });
+        }
 
         // Split the available data into the space for the bitmap, and the space for the allocations
         let (for_bitmap, for_allocation) = data.split_at_mut(pages_for_bitmap);
@@ -130,7 +168,7 @@ impl<Page> PageBitmapAllocator<Page> {
         // - This is a single allocated object as we have derived it from a static mutable slice.
         // - Because `bitmap_pointer_as_au64` came from `slice::as_mut_ptr()` we know it must be non-zero.
         // - `bitmap_pointer_as_au64` must be aligned because it came from a `&mut [Page]`'s pointer. Thus, it must be properly aligned
-        //   for a `Page`, and this function begins with an assertion that `Page` has a greater or equal alignment as `AtomicU64`.
+        //   for a `Page`, and this function begins by checking that `Page` has a greater or equal alignment as `AtomicU64`.
         // - `AtomicU64` has the same memory layout as `u64` (https://doc.rust-lang.org/core/sync/atomic/struct.AtomicU64.html), and `u64`
         //   is valid for any 8 byte sequence. Thus, no matter what the contents of the array previously, because it had to be initialized
         //   to be a valid slice of `Page`s, it must be a valid slice of `AtomicU64`s.
@@ -159,12 +197,49 @@ impl<Page> PageBitmapAllocator<Page> {
         // Safety requirements:
         // `start_pointer` must not be null because it originated from `slice::as_mut_ptr()`.
         // `bitmap` was initialized to be of the length of `for_allocation`, and is all free for allocation.
-        Self {
+        Ok(Self {
             bitmap,
             start_pointer,
+            best_fit: core::sync::atomic::AtomicBool::new(false),
+        })
+    }
+
+    /// Set the policy used to place later allocations. Allocators start out using [`AllocationPolicy::FirstFit`].
+    pub fn set_policy(&self, policy: AllocationPolicy) {
+        self.best_fit.store(
+            policy == AllocationPolicy::BestFit,
+            core::sync::atomic::Ordering::Release,
+        );
+    }
+
+    /// Get the policy used to place allocations
+    #[must_use]
+    pub fn policy(&self) -> AllocationPolicy {
+        if self.best_fit.load(core::sync::atomic::Ordering::Acquire) {
+            AllocationPolicy::BestFit
+        } else {
+            AllocationPolicy::FirstFit
         }
     }
 
+    /// Get the number of free pages
+    #[must_use]
+    pub fn free_pages(&self) -> usize {
+        self.bitmap.cleared()
+    }
+
+    /// Get the number of pages available to allocate, excluding those holding the bitmap
+    #[must_use]
+    pub const fn total_pages(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    /// Get the length in pages of the longest run of free pages, the largest allocation which could currently succeed
+    #[must_use]
+    pub fn largest_free_run(&self) -> usize {
+        self.bitmap.largest_free_run()
+    }
+
     /// Allocate a number of pages from the [`PageBitmapAllocator<Page>`] and return a pointer to the start of that
     /// memory region.
     ///
@@ -191,9 +266,14 @@ impl<Page> PageBitmapAllocator<Page> {
         // Additionally, if the corresponding bit in the `bitmap` is cleared, then the `Page` at that index is free to be allocated.
 
         // Reserve a sequence of bits in the `bitmap`.
-        match self.bitmap.reserve_sequence(page_count) {
+        let reserved = match self.policy() {
+            AllocationPolicy::FirstFit => self.bitmap.reserve_sequence(page_count),
+            AllocationPolicy::BestFit => self.bitmap.reserve_best_fit(page_count),
+        };
+
+        match reserved {
             // Safety:
-            // - `reserve_sequence` guarantees that `sequence_index` will be
+            // - Both `reserve_sequence` and `reserve_best_fit` guarantee that `sequence_index` will be
             //   less than `self.bitmap.length`, which as noted above means
             //   this offset is within the allocation alloted to this allocator.
             // - The region alloted can only be constructed from a slice, which
@@ -210,6 +290,81 @@ impl<Page> PageBitmapAllocator<Page> {
         }
     }
 
+    /// Allocate a number of pages from the [`PageBitmapAllocator<Page>`] whose address is a multiple of
+    /// `align_pages` pages, and return a pointer to the start of that memory region. The alignment is counted from
+    /// address zero, not from the first allocatable page, so it holds for the physical address wherever the
+    /// allocator's memory starts. The allocation policy is not used, the first aligned run long enough is taken.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the allocator is not initialized, the allocatable pages do not start on
+    /// a page boundary, or there is not enough memory to complete the requested allocation at an aligned address.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `page_count` or `align_pages` is set to zero.
+    pub fn allocate_aligned(
+        &self,
+        page_count: usize,
+        align_pages: usize,
+    ) -> Result<*mut Page, AllocationError> {
+        assert!(align_pages > 0);
+
+        let page_size = size_of::<Page>();
+        let start_address = self
+            .start_pointer
+            .load(core::sync::atomic::Ordering::Acquire) as usize;
+        if !start_address.is_multiple_of(page_size) {
+            return Err(AllocationError::OutOfMemory {
+                requested: page_count,
+            });
+        }
+
+        // Count the alignment in pages from address zero, so an aligned index gives an aligned address
+        self.allocate_offset_aligned(
+            page_count,
+            align_pages,
+            (start_address / page_size) % align_pages,
+        )
+    }
+
+    /// Allocate a number of pages whose first page has an index which becomes a multiple of `align_pages` once
+    /// `offset_pages` is added to it.
+    fn allocate_offset_aligned(
+        &self,
+        page_count: usize,
+        align_pages: usize,
+        offset_pages: usize,
+    ) -> Result<*mut Page, AllocationError> {
+        assert!(page_count > 0);
+        assert!(align_pages > 0);
+
+        let start_pointer = self
+            .start_pointer
+            .load(core::sync::atomic::Ordering::Acquire);
+        if start_pointer.is_null() {
+            return Err(AllocationError::Uninitialized);
+        }
+
+        match self
+            .bitmap
+            .reserve_aligned_sequence(page_count, align_pages, offset_pages)
+        {
+            // Safety:
+            // - `reserve_aligned_sequence` guarantees that `sequence_index` will be less than `self.bitmap.length`,
+            //   so this offset is within the allocation alloted to this allocator.
+            // - The region alloted can only be constructed from a slice, which must not be greater than `isize::MAX`
+            //   bytes, and is continuous, so the sum will not wrap.
+            Ok(sequence_index) => Ok(unsafe { start_pointer.add(sequence_index) }),
+            Err(BitmapError::RangeOutOfBounds { .. }) => {
+                unreachable!()
+            }
+            Err(BitmapError::UnableToAllocate { length }) => {
+                Err(AllocationError::OutOfMemory { requested: length })
+            }
+        }
+    }
+
     /// Free a number of pages allocated by the [`PageBitmapAllocator<Page>`]
     ///
     /// # Errors
@@ -278,6 +433,72 @@ impl<Page> PageBitmapAllocator<Page> {
     }
 }
 
+impl<Page> PageBitmapAllocator<Page> {
+    /// Get the number of pages needed to hold an allocation with the given layout, at least one so every allocation
+    /// has an address of its own
+    #[cfg(feature = "allocator_api")]
+    const fn pages_for(layout: core::alloc::Layout) -> usize {
+        let pages = layout.size().div_ceil(size_of::<Page>());
+        if pages == 0 {
+            1
+        } else {
+            pages
+        }
+    }
+
+    /// Allocate a number of pages whose address is a multiple of `align` bytes. Alignments past that of `Page` are
+    /// only possible when they are a whole number of pages, and the allocatable pages start on a page boundary.
+    #[cfg(feature = "allocator_api")]
+    fn allocate_address_aligned(
+        &self,
+        page_count: usize,
+        align: usize,
+    ) -> Result<*mut Page, AllocationError> {
+        if align <= align_of::<Page>() {
+            return self.allocate(page_count);
+        }
+
+        let page_size = size_of::<Page>();
+        if !align.is_multiple_of(page_size) {
+            return Err(AllocationError::OutOfMemory {
+                requested: page_count,
+            });
+        }
+
+        self.allocate_aligned(page_count, align / page_size)
+    }
+}
+
+// Safety:
+// - Allocations are made from pages which are reserved in the bitmap until they are deallocated, so they stay valid
+//   and are not handed out again while in use.
+// - `deallocate` frees the same number of pages `allocate` reserved, as both are found from the layout.
+#[cfg(feature = "allocator_api")]
+unsafe impl<Page> core::alloc::Allocator for PageBitmapAllocator<Page> {
+    fn allocate(
+        &self,
+        layout: core::alloc::Layout,
+    ) -> Result<core::ptr::NonNull<[u8]>, core::alloc::AllocError> {
+        let page_count = Self::pages_for(layout);
+        let ptr = self
+            .allocate_address_aligned(page_count, layout.align())
+            .map_err(|_| core::alloc::AllocError)?;
+
+        core::ptr::NonNull::new(ptr.cast::<u8>())
+            .map(|ptr| {
+                core::ptr::NonNull::slice_from_raw_parts(ptr, page_count * size_of::<Page>())
+            })
+            .ok_or(core::alloc::AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: core::ptr::NonNull<u8>, layout: core::alloc::Layout) {
+        // Safety: The caller guarantees `ptr` was allocated by this allocator with `layout`, so it is the start of
+        // `pages_for(layout)` pages
+        unsafe { self.free(ptr.as_ptr().cast(), Self::pages_for(layout)) }
+            .expect("Allocation freed to an uninitialized allocator");
+    }
+}
+
 impl<Page> Default for PageBitmapAllocator<Page> {
     fn default() -> Self {
         Self::new()
@@ -292,7 +513,7 @@ unsafe impl<'a, Page, T> Send for PageBox<'a, Page, T> where T: Send {}
 mod test {
     use std::prelude::rust_2021::*;
 
-    use super::PageBitmapAllocator;
+    use super::{AllocationPolicy, BitmapInitError, PageBitmapAllocator};
 
     #[derive(Debug, Clone, Copy)]
     #[repr(align(16))]
@@ -301,20 +522,26 @@ mod test {
     #[test]
     pub fn simple_allocator_test() {
         let alloc_space = Box::leak(Box::new([Page([0; 128]); 1024]));
-        let allocator = PageBitmapAllocator::from_pages(alloc_space);
+        let allocator = PageBitmapAllocator::try_from_pages(alloc_space).unwrap();
+
+        assert_eq!(allocator.total_pages(), 1023);
+        assert_eq!(allocator.free_pages(), 1023);
 
         let mem = allocator.allocate(512).unwrap();
+        assert_eq!(allocator.free_pages(), 511);
 
         assert!(allocator.allocate(512).is_err());
 
-        unsafe { allocator.free(mem, 512) };
+        unsafe { allocator.free(mem, 512) }.unwrap();
+        assert_eq!(allocator.free_pages(), 1023);
     }
 
     #[test]
     pub fn allocator_test() {
         let alloc_space = Box::leak(Box::new([Page([0; 128]); 4096]));
-        let allocator = Box::leak(Box::new(PageBitmapAllocator::from_pages(alloc_space)))
-            as &PageBitmapAllocator<_>;
+        let allocator = Box::leak(Box::new(
+            PageBitmapAllocator::try_from_pages(alloc_space).unwrap(),
+        )) as &PageBitmapAllocator<_>;
 
         const THREAD_COUNT: usize = 4;
         const ALLOCATIONS: usize = 128;
@@ -337,11 +564,121 @@ mod test {
         }
     }
 
+    /// Construct an allocator with every page allocated except for free runs of 32, 8 and 16 pages, in that order.
+    fn fragmented_allocator(policy: AllocationPolicy) -> &'static PageBitmapAllocator<Page> {
+        let alloc_space = Box::leak(Box::new([Page([0; 128]); 1024]));
+        let allocator = Box::leak(Box::new(
+            PageBitmapAllocator::try_from_pages(alloc_space).unwrap(),
+        )) as &PageBitmapAllocator<_>;
+
+        let mut pages = Vec::new();
+        while let Ok(page) = allocator.allocate(1) {
+            pages.push(page);
+        }
+
+        for (start, length) in [(0, 32), (40, 8), (64, 16)] {
+            for page in &pages[start..start + length] {
+                unsafe { allocator.free(*page, 1) }.unwrap();
+            }
+        }
+
+        allocator.set_policy(policy);
+        allocator
+    }
+
+    #[test]
+    pub fn best_fit_fragmentation_test() {
+        let first_fit = fragmented_allocator(AllocationPolicy::FirstFit);
+        let best_fit = fragmented_allocator(AllocationPolicy::BestFit);
+        assert_eq!(first_fit.largest_free_run(), 32);
+        assert_eq!(best_fit.largest_free_run(), 32);
+
+        // First fit carves both requests out of the 32 page run, best fit places each in the run it exactly fills
+        for allocator in [first_fit, best_fit] {
+            allocator.allocate(8).unwrap();
+            allocator.allocate(16).unwrap();
+        }
+
+        assert_eq!(first_fit.largest_free_run(), 16);
+        assert_eq!(best_fit.largest_free_run(), 32);
+        assert_eq!(first_fit.free_pages(), best_fit.free_pages());
+
+        // Only best fit still has room for a large request
+        assert!(first_fit.allocate(32).is_err());
+        assert!(best_fit.allocate(32).is_ok());
+    }
+
+    #[test]
+    pub fn aligned_allocation_test() {
+        #[derive(Debug, Clone, Copy)]
+        #[repr(align(128))]
+        struct AlignedPage {
+            _bytes: [u8; 128],
+        }
+        const ALIGN: usize = 512 * size_of::<AlignedPage>();
+
+        // Place the data so the first allocatable page, after the two pages of the bitmap, lies one page past a 512
+        // page boundary, making the aligned addresses those at indices 511, 1023 and 1535
+        let backing = Box::leak(vec![AlignedPage { _bytes: [0; 128] }; 3072].into_boxed_slice());
+        let skip = backing.as_ptr().align_offset(ALIGN) + 511;
+        let alloc_space = &mut backing[skip..skip + 2048];
+        let allocator = PageBitmapAllocator::try_from_pages(alloc_space).unwrap();
+        let start = allocator.allocate(1).unwrap();
+        assert_eq!(start as usize % ALIGN, size_of::<AlignedPage>());
+
+        // The run starts at the first aligned address, not the first index which is a multiple of 512
+        let aligned = allocator.allocate_aligned(16, 512).unwrap();
+        assert_eq!(unsafe { aligned.offset_from(start) }, 511);
+        assert!((aligned as usize).is_multiple_of(ALIGN));
+
+        // Only the runs at 1023 and 1535 are left, and 1024 pages from 1023 would run past the last page
+        assert!(allocator.allocate_aligned(1024, 512).is_err());
+        assert!(allocator.allocate_aligned(512, 512).is_ok());
+        let last = allocator.allocate_aligned(16, 512).unwrap();
+        assert_eq!(unsafe { last.offset_from(start) }, 1535);
+        assert!(allocator.allocate_aligned(16, 512).is_err());
+
+        // Unaligned allocations can still use the space before the aligned runs
+        assert!(allocator.allocate(256).is_ok());
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    pub fn allocator_api_test() {
+        #[derive(Clone, Copy)]
+        #[repr(align(128))]
+        struct AlignedPage {
+            _bytes: [u8; 128],
+        }
+
+        #[repr(align(1024))]
+        struct OverAligned(u64);
+
+        let alloc_space = Box::leak(Box::new([AlignedPage { _bytes: [0; 128] }; 64]));
+        let allocator = PageBitmapAllocator::try_from_pages(alloc_space).unwrap();
+        let total = allocator.free_pages();
+
+        // Growing the vector moves it into larger runs of pages
+        let mut values = Vec::new_in(&allocator);
+        values.extend(0..100u64);
+        assert_eq!(values.iter().sum::<u64>(), 4950);
+        assert_eq!(allocator.free_pages(), total - 7);
+
+        let boxed = Box::new_in(OverAligned(42), &allocator);
+        assert!(core::ptr::from_ref(&*boxed).is_aligned());
+        assert_eq!(boxed.0, 42);
+
+        drop(values);
+        drop(boxed);
+        assert_eq!(allocator.free_pages(), total);
+    }
+
     #[test]
     pub fn alloc_box_test() {
         let alloc_space = Box::leak(Box::new([Page([0; 128]); 4096]));
-        let allocator = Box::leak(Box::new(PageBitmapAllocator::from_pages(alloc_space)))
-            as &PageBitmapAllocator<_>;
+        let allocator = Box::leak(Box::new(
+            PageBitmapAllocator::try_from_pages(alloc_space).unwrap(),
+        )) as &PageBitmapAllocator<_>;
 
         let data_a = [0u64; 128];
         let data_b = [42u32; 8];
@@ -357,4 +694,40 @@ mod test {
 
         core::mem::drop(box_a);
     }
+
+    #[test]
+    pub fn under_aligned_page_test() {
+        #[derive(Clone, Copy)]
+        #[repr(align(4))]
+        struct UnalignedPage([u8; 128]);
+
+        let alloc_space = Box::leak(Box::new([UnalignedPage([0; 128]); 16]));
+        assert_eq!(
+            PageBitmapAllocator::try_from_pages(alloc_space).err(),
+            Some(BitmapInitError::UnderAligned {
+                alignment: 4,
+                required: 8
+            })
+        );
+    }
+
+    #[test]
+    pub fn unusable_pages_test() {
+        #[derive(Clone, Copy)]
+        #[repr(align(8))]
+        struct EmptyPage;
+
+        let alloc_space = Box::leak(Box::new([EmptyPage; 16]));
+        assert_eq!(
+            PageBitmapAllocator::try_from_pages(alloc_space).err(),
+            Some(BitmapInitError::PageTooSmall { size: 0 })
+        );
+
+        // A single page is taken up entirely by the bitmap
+        let alloc_space = Box::leak(Box::new([Page([0; 128]); 1]));
+        assert_eq!(
+            PageBitmapAllocator::try_from_pages(alloc_space).err(),
+            Some(BitmapInitError::NoAllocatablePages { pages: 1 })
+        );
+    }
 }
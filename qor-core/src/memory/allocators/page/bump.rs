@@ -115,49 +115,58 @@ impl<Page: 'static> PageBumpAllocator<Page> {
             Err(AllocationError::Uninitialized)
         } else {
             // Once control makes it here, `end_pointer` must not be null
-            let walking_pointer = self
+            let mut walking_pointer = self
                 .walking_pointer
-                .fetch_ptr_add(page_count, core::sync::atomic::Ordering::Relaxed);
-
-            // If `walking_pointer` is greater than or equal to `end_pointer`, there is a memory exhaustion error.
-            if walking_pointer >= end_pointer {
-                Err(AllocationError::OutOfMemory {
-                    remaining: 0,
-                    total: self.total_pages.load(core::sync::atomic::Ordering::Acquire),
-                    requested: page_count,
-                })
-            } else {
-                // Once control makes it here, `walking_pointer` is known to be less than
-                // `end_pointer`, thus every page at `walking_pointer`,
-                // `walking_pointer.add(1)`, `walking_pointer.add(2)`, and so on up to and
-                // not including `end_pointer` is aligned and points to an unallocated page.
+                .load(core::sync::atomic::Ordering::Relaxed);
+
+            loop {
+                // `walking_pointer` is only advanced when the allocation fits, so it never passes `end_pointer`, and
+                // every page at `walking_pointer`, `walking_pointer.add(1)`, `walking_pointer.add(2)`, and so on up to
+                // and not including `end_pointer` is aligned and points to an unallocated page.
 
                 // Compute the pages remaining free in the allocator
                 // Safety:
-                // - The distance between pointers is known to be non-negative as `walking_pointer < end_pointer`.
+                // - The distance between pointers is known to be non-negative as `walking_pointer <= end_pointer`.
                 // - `end_pointer` is non null and thus known to be properly aligned and pointing to just after the assigned range.
-                // - `walking_pointer` is known to be valid and part of the assigned range.
+                // - `walking_pointer` is known to be valid and part of the assigned range, or directly after it.
                 // - Both are known to be properly aligned, and thus the difference will be a multiple of the size of `Page`.
                 // - The only way to initialize `walking_pointer` was to have it have a distance of less than `isize::MAX` bytes
-                // from `end_pointer`, `walking_pointer` only increases, and is less than `end_pointer`, and is thus within that distance.
+                // from `end_pointer`, `walking_pointer` only increases, and is not past `end_pointer`, and is thus within that distance.
                 // - A similar argument prevents wrapping.
                 let free_pages = unsafe { end_pointer.sub_ptr(walking_pointer) };
 
-                if free_pages >= page_count {
+                // A request which does not fit leaves the allocator untouched, so smaller requests can still succeed
+                if free_pages < page_count {
+                    return Err(AllocationError::OutOfMemory {
+                        remaining: free_pages,
+                        total: self.total_pages.load(core::sync::atomic::Ordering::Acquire),
+                        requested: page_count,
+                    });
+                }
+
+                // Safety: At least `page_count` pages remain after `walking_pointer`, so the result is at most `end_pointer`
+                let next_pointer = unsafe { walking_pointer.add(page_count) };
+
+                match self.walking_pointer.compare_exchange_weak(
+                    walking_pointer,
+                    next_pointer,
+                    core::sync::atomic::Ordering::Relaxed,
+                    core::sync::atomic::Ordering::Relaxed,
+                ) {
                     // Safety:
                     // - At least `page_count` pages remain between `walking_pointer` and `end_pointer`, and all pages between the two are known to be valid for being allocated.
                     // - All pages in that range are known to be valid representations of `Page`.
-                    // - This memory will never again be accessed, because `page_count` was added to `walking_pointer`, and it is thus out of this range. Because `walking_pointer`
+                    // - This memory will never again be accessed, because `walking_pointer` was exchanged for `next_pointer`, which is out of this range. Because `walking_pointer`
                     // is never decreased, it can not return to within this range. Thus exclusive access is achieved.
                     // - Because the distance between `walking_pointer` and `end_pointer` was known to be less than `isize::MAX` bytes, then this allocation, which is known to be
                     // less than or equal to that size must also be less than `isize::MAX` bytes.
-                    Ok(unsafe { core::slice::from_raw_parts_mut(walking_pointer, page_count) })
-                } else {
-                    Err(AllocationError::OutOfMemory {
-                        remaining: free_pages,
-                        total: self.total_pages.load(core::sync::atomic::Ordering::Acquire),
-                        requested: page_count,
-                    })
+                    Ok(_) => {
+                        return Ok(unsafe {
+                            core::slice::from_raw_parts_mut(walking_pointer, page_count)
+                        })
+                    }
+                    // Another allocation moved the pointer first, so try again from where it left it
+                    Err(current) => walking_pointer = current,
                 }
             }
         }
@@ -224,4 +233,34 @@ mod test {
         }
         allocator.allocate(54).unwrap();
     }
+
+    #[test]
+    pub fn failed_allocation_test() {
+        let mem = std::boxed::Box::leak(std::boxed::Box::new([0usize; 16]));
+        let allocator = super::PageBumpAllocator::new();
+        unsafe {
+            allocator.assign_region(mem.as_mut_ptr_range());
+        }
+        allocator.allocate(10).unwrap();
+
+        assert_eq!(
+            allocator.allocate(7).err(),
+            Some(super::AllocationError::OutOfMemory {
+                remaining: 6,
+                total: 16,
+                requested: 7
+            })
+        );
+
+        // The failed request does not use up the pages which remain
+        assert_eq!(allocator.allocate(6).unwrap().len(), 6);
+        assert_eq!(
+            allocator.allocate(1).err(),
+            Some(super::AllocationError::OutOfMemory {
+                remaining: 0,
+                total: 16,
+                requested: 1
+            })
+        );
+    }
 }
@@ -0,0 +1,224 @@
+use core::mem::{align_of, size_of, ManuallyDrop};
+use core::ptr::NonNull;
+
+use crate::memory::allocators::page::bitmap::{AllocationError, PageBitmapAllocator};
+use crate::sync::Mutex;
+
+/// Space for a single object in a slab, which holds the link to the next free slot while it is not in use
+#[repr(C)]
+union Slot<T> {
+    next: Option<NonNull<Slot<T>>>,
+    value: ManuallyDrop<T>,
+}
+
+/// Head of the list of free slots, kept behind the allocator's lock
+struct FreeList<T> {
+    head: Option<NonNull<Slot<T>>>,
+}
+
+/// Allocator for many objects of the same type, which carves pages taken from a [`PageBitmapAllocator`] into fixed
+/// size slots.
+///
+/// Pages are taken one slab at a time as the free slots run out, and are kept by the slab allocator rather than
+/// being returned to the page allocator when their objects are freed.
+pub struct SlabAllocator<'a, Page, T> {
+    // Safety Requirements:
+    // - Every slot on `free_list` lies in a slab taken from `pages`, is properly aligned for a `Slot<T>`, and is not
+    //   referenced by anything other than the list.
+    pages: &'a PageBitmapAllocator<Page>,
+    free_list: Mutex<FreeList<T>>,
+    slab_count: core::sync::atomic::AtomicUsize,
+}
+
+impl<'a, Page, T> SlabAllocator<'a, Page, T> {
+    /// Number of slots carved from each slab
+    const SLOTS_PER_SLAB: usize = Self::PAGES_PER_SLAB * size_of::<Page>() / size_of::<Slot<T>>();
+
+    /// Number of pages in each slab, enough to hold at least one slot
+    const PAGES_PER_SLAB: usize = size_of::<Slot<T>>().div_ceil(size_of::<Page>());
+
+    /// Construct a new `SlabAllocator` which takes its pages from `pages`, no pages are taken until the first
+    /// allocation.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `Page` is zero sized, or `T` has a greater alignment requirement than `Page`.
+    #[must_use]
+    pub const fn new(pages: &'a PageBitmapAllocator<Page>) -> Self {
+        assert!(size_of::<Page>() > 0);
+        assert!(align_of::<Slot<T>>() <= align_of::<Page>());
+
+        Self {
+            pages,
+            free_list: Mutex::new(FreeList { head: None }),
+            slab_count: core::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the number of pages taken from the page allocator so far
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.slab_count.load(core::sync::atomic::Ordering::Relaxed) * Self::PAGES_PER_SLAB
+    }
+
+    /// Take a new slab from the page allocator, and link its slots together, returning the first
+    fn grow(&self) -> Result<NonNull<Slot<T>>, AllocationError> {
+        let slab = self.pages.allocate(Self::PAGES_PER_SLAB)?.cast::<Slot<T>>();
+
+        // Link each slot to the one after it, with the last ending the list
+        for index in 0..Self::SLOTS_PER_SLAB {
+            let next = if index + 1 < Self::SLOTS_PER_SLAB {
+                // Safety: `index + 1` is a slot within the slab, which is a single allocation
+                NonNull::new(unsafe { slab.add(index + 1) })
+            } else {
+                None
+            };
+
+            // Safety:
+            // - `index` is a slot within the slab, which holds `SLOTS_PER_SLAB` slots.
+            // - The slab is aligned to `Page`, whose alignment is at least that of `Slot<T>`, and slots follow each
+            //   other directly, so each is properly aligned.
+            unsafe { slab.add(index).write(Slot { next }) };
+        }
+
+        self.slab_count
+            .fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+
+        Ok(NonNull::new(slab).expect("Page allocator returned a null pointer"))
+    }
+
+    /// Allocate a slot for `object` and move it there, taking a new slab from the page allocator if every slot is in
+    /// use.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a new slab is needed and the page allocator can not provide one.
+    pub fn alloc(&self, object: T) -> Result<&'a mut T, AllocationError> {
+        let mut free_list = self.free_list.spin_lock();
+
+        // The lock is held while growing, so only one slab is taken when several allocations find the list empty
+        let slot = match free_list.head {
+            Some(slot) => slot,
+            None => self.grow()?,
+        };
+
+        // Safety: Slots on the free list hold the link to the next free slot
+        free_list.head = unsafe { slot.as_ref().next };
+        drop(free_list);
+
+        let object_ptr = slot.as_ptr().cast::<T>();
+
+        // Safety:
+        // - The slot was taken off the free list, so nothing else refers to it.
+        // - `Slot<T>` is `repr(C)`, so `value` is at the start of the slot, and the slot is aligned for a `T`.
+        // - Slabs are never returned to the page allocator, so the slot lives for `'a`.
+        unsafe {
+            object_ptr.write(object);
+            Ok(&mut *object_ptr)
+        }
+    }
+
+    /// Drop the object in a slot allocated by the `SlabAllocator`, and put the slot back on the free list.
+    ///
+    /// # Safety
+    ///
+    /// `object` must have been returned by [`SlabAllocator::alloc`] on this allocator, and must not be used again
+    /// once freed.
+    pub unsafe fn free(&self, object: &mut T) {
+        let object_ptr = core::ptr::from_mut(object);
+
+        // Safety: `object` is a valid `T` which will not be used again, as required of the caller
+        unsafe { object_ptr.drop_in_place() };
+
+        let slot = object_ptr.cast::<Slot<T>>();
+        let mut free_list = self.free_list.spin_lock();
+
+        // Safety: The caller guarantees `object` came from this allocator, so it is the start of one of its slots
+        unsafe {
+            slot.write(Slot {
+                next: free_list.head,
+            });
+        }
+        free_list.head = NonNull::new(slot);
+    }
+}
+
+// Safety: The free slots are owned by the list, and only hold the space for a `T` rather than a value of it
+unsafe impl<T> Send for FreeList<T> where T: Send {}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::prelude::rust_2021::*;
+
+    use super::SlabAllocator;
+    use crate::memory::allocators::page::bitmap::PageBitmapAllocator;
+
+    /// Stand in for a page, only its size and alignment matter
+    #[derive(Debug, Clone, Copy)]
+    #[repr(align(16))]
+    struct Page {
+        _bytes: [u8; 128],
+    }
+
+    fn page_allocator() -> &'static PageBitmapAllocator<Page> {
+        let alloc_space = Box::leak(Box::new([Page { _bytes: [0; 128] }; 64]));
+        Box::leak(Box::new(
+            PageBitmapAllocator::try_from_pages(alloc_space).unwrap(),
+        ))
+    }
+
+    #[test]
+    pub fn multiple_slab_test() {
+        let pages = page_allocator();
+        let slab = SlabAllocator::<_, [u64; 3]>::new(pages);
+        assert_eq!(slab.page_count(), 0);
+
+        // Five 24 byte objects fit in each 128 byte page
+        let objects = (0..12u64)
+            .map(|i| slab.alloc([i, i * 2, i * 3]).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(slab.page_count(), 3);
+        assert_eq!(pages.free_pages(), pages.total_pages() - 3);
+
+        for (i, object) in (0..12u64).zip(&objects) {
+            assert_eq!(**object, [i, i * 2, i * 3]);
+        }
+    }
+
+    #[test]
+    pub fn scrambled_free_test() {
+        let pages = page_allocator();
+        let slab = SlabAllocator::<_, (u64, std::sync::Arc<()>)>::new(pages);
+        assert_eq!(size_of::<(u64, std::sync::Arc<()>)>(), 16);
+        let shared = std::sync::Arc::new(());
+
+        let mut objects = (0..20u64)
+            .map(|i| Some(slab.alloc((i, shared.clone())).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(slab.page_count(), 3);
+
+        // Free every object in an order unrelated to the one they were allocated in
+        for i in (0..20).map(|i| (i * 7) % 20) {
+            let object = objects[i].take().unwrap();
+            assert_eq!(object.0, i as u64);
+            unsafe { slab.free(object) };
+        }
+
+        // Every object was dropped, and the slots are reused rather than taking more pages
+        assert_eq!(std::sync::Arc::strong_count(&shared), 1);
+
+        let objects = (0..20u64)
+            .map(|i| slab.alloc((i, shared.clone())).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(slab.page_count(), 3);
+
+        let mut addresses = objects
+            .iter()
+            .map(|object| core::ptr::from_ref(&**object) as usize)
+            .collect::<Vec<_>>();
+        addresses.sort_unstable();
+        addresses.dedup();
+        assert_eq!(addresses.len(), 20);
+    }
+}
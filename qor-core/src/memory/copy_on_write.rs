@@ -0,0 +1,219 @@
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+
+/// How a write to a page of a [`CopyOnWritePages`] which was mapped read only has been resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteResolution {
+    /// The page at this index was given a private copy, which must be mapped writable in place of the shared page
+    Copied(usize),
+    /// No other process refers to the backing of these pages any longer, so they can be mapped writable where they are
+    TakenOver(Vec<usize>),
+}
+
+/// The pages of a single mapping, which may be shared copy-on-write with forked processes.
+///
+/// The pages start out backed by one allocation, `B`. Sharing them with a fork leaves both sides mapping every page
+/// read only, and the first write to a page on either side gives that side a private copy of just that page, so the
+/// rest stay shared.
+#[derive(Debug)]
+pub struct CopyOnWritePages<B> {
+    original: Arc<B>,
+    copies: BTreeMap<usize, Arc<B>>,
+    read_only: Vec<bool>,
+}
+
+impl<B> CopyOnWritePages<B> {
+    /// Construct a mapping of `pages` pages backed by `original`, which is not shared with anything
+    #[must_use]
+    pub fn new(original: B, pages: usize) -> Self {
+        Self {
+            original: Arc::new(original),
+            copies: BTreeMap::new(),
+            read_only: alloc::vec![false; pages],
+        }
+    }
+
+    /// Get the number of pages in the mapping
+    #[must_use]
+    pub const fn pages(&self) -> usize {
+        self.read_only.len()
+    }
+
+    /// Get the number of pages which are mapped read only as they are shared
+    #[must_use]
+    pub fn shared_pages(&self) -> usize {
+        self.read_only.iter().filter(|read_only| **read_only).count()
+    }
+
+    /// Returns true if the page at `index` is mapped read only as it is shared
+    #[must_use]
+    pub fn is_read_only(&self, index: usize) -> bool {
+        self.read_only[index]
+    }
+
+    /// Get the allocation backing the whole mapping, if no page has been given a private copy. Only then are the pages
+    /// of the mapping laid out contiguously in it.
+    #[must_use]
+    pub fn contiguous(&self) -> Option<&B> {
+        self.copies.is_empty().then_some(&*self.original)
+    }
+
+    /// Get the allocation holding the page at `index`, along with the index of the page within that allocation
+    #[must_use]
+    pub fn source(&self, index: usize) -> (&B, usize) {
+        self.copies
+            .get(&index)
+            .map_or((&*self.original, index), |copy| (&**copy, 0))
+    }
+
+    /// Share the pages with a forked process, returning its side of the mapping. Every page becomes read only on both
+    /// sides, until it is written.
+    #[must_use]
+    pub fn share(&mut self) -> Self {
+        self.read_only.fill(true);
+
+        Self {
+            original: self.original.clone(),
+            copies: self.copies.clone(),
+            read_only: self.read_only.clone(),
+        }
+    }
+
+    /// Resolve a write to the page at `index`, returning `None` if the page is not read only.
+    ///
+    /// If another process still refers to the page's backing, `copy` is called with the backing and the index of the
+    /// page within it, to make a single page private copy. Otherwise every read only page this side holds in that
+    /// backing is taken over without copying.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `copy`, in which case the mapping is left untouched.
+    pub fn resolve_write<E>(
+        &mut self,
+        index: usize,
+        copy: impl FnOnce(&B, usize) -> Result<B, E>,
+    ) -> Result<Option<WriteResolution>, E> {
+        if !self.read_only[index] {
+            return Ok(None);
+        }
+
+        if let Some(existing) = self.copies.get(&index) {
+            if Arc::strong_count(existing) == 1 {
+                self.read_only[index] = false;
+                return Ok(Some(WriteResolution::TakenOver(alloc::vec![index])));
+            }
+        } else if Arc::strong_count(&self.original) == 1 {
+            let taken = (0..self.pages())
+                .filter(|page| self.read_only[*page] && !self.copies.contains_key(page))
+                .collect::<Vec<_>>();
+            for page in &taken {
+                self.read_only[*page] = false;
+            }
+
+            return Ok(Some(WriteResolution::TakenOver(taken)));
+        }
+
+        let (backing, within) = self.source(index);
+        let private = copy(backing, within)?;
+
+        self.copies.insert(index, Arc::new(private));
+        self.read_only[index] = false;
+
+        Ok(Some(WriteResolution::Copied(index)))
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{CopyOnWritePages, WriteResolution};
+
+    /// Stand in for an allocation of pages, holding one byte per page
+    type Backing = Vec<u8>;
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn copy_page(backing: &Backing, index: usize) -> Result<Backing, ()> {
+        Ok(alloc::vec![backing[index]])
+    }
+
+    fn read(pages: &CopyOnWritePages<Backing>, index: usize) -> u8 {
+        let (backing, within) = pages.source(index);
+        backing[within]
+    }
+
+    #[test]
+    pub fn forked_write_copies_one_page_test() {
+        let mut parent = CopyOnWritePages::new(alloc::vec![10, 11, 12, 13], 4);
+        assert_eq!(parent.resolve_write(1, copy_page), Ok(None));
+
+        let mut child = parent.share();
+        assert_eq!((parent.shared_pages(), child.shared_pages()), (4, 4));
+
+        // The child writing page 2 gets its own copy of just that page, the others stay shared
+        assert_eq!(
+            child.resolve_write(2, copy_page),
+            Ok(Some(WriteResolution::Copied(2)))
+        );
+        assert!(!child.is_read_only(2));
+        assert_eq!(child.shared_pages(), 3);
+        assert_eq!(parent.shared_pages(), 4);
+        assert!(child.contiguous().is_none());
+        assert!(parent.contiguous().is_some());
+
+        let (backing, within) = child.source(2);
+        assert_eq!((backing.len(), within, backing[0]), (1, 0, 12));
+        assert!(core::ptr::eq(parent.source(3).0, child.source(3).0));
+        assert!(!core::ptr::eq(parent.source(2).0, child.source(2).0));
+
+        // A page already written is not copied again
+        assert_eq!(child.resolve_write(2, copy_page), Ok(None));
+
+        // The parent still refers to the original while the child does, so its writes copy too
+        assert_eq!(
+            parent.resolve_write(0, copy_page),
+            Ok(Some(WriteResolution::Copied(0)))
+        );
+        assert_eq!(read(&parent, 0), 10);
+    }
+
+    #[test]
+    pub fn take_over_after_exit_test() {
+        let mut parent = CopyOnWritePages::new(alloc::vec![10, 11, 12], 3);
+        let mut child = parent.share();
+        child.resolve_write(1, copy_page).unwrap();
+
+        // Once the child has gone the parent holds the only reference, so its read only pages are taken over
+        drop(child);
+        assert_eq!(
+            parent.resolve_write(0, |_, _| Err(())),
+            Ok(Some(WriteResolution::TakenOver(alloc::vec![0, 1, 2])))
+        );
+        assert_eq!(parent.shared_pages(), 0);
+        assert_eq!((read(&parent, 0), read(&parent, 2)), (10, 12));
+    }
+
+    #[test]
+    pub fn shared_copies_test() {
+        let mut parent = CopyOnWritePages::new(alloc::vec![10, 11], 2);
+        let mut child = parent.share();
+        parent.resolve_write(1, copy_page).unwrap();
+
+        // Forking again shares the parent's private copy with the new child as well
+        let mut grandchild = parent.share();
+        assert_eq!(
+            parent.resolve_write(1, copy_page),
+            Ok(Some(WriteResolution::Copied(1)))
+        );
+
+        // The copy made before the second fork is now only held by the new child, which takes it over
+        assert_eq!(
+            grandchild.resolve_write(1, copy_page),
+            Ok(Some(WriteResolution::TakenOver(alloc::vec![1])))
+        );
+
+        // A failed copy leaves the page read only
+        assert_eq!(child.resolve_write(0, |_, _| Err(())), Err(()));
+        assert!(child.is_read_only(0));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod allocators;
+pub mod copy_on_write;
+pub mod statistics;
 pub mod units;
 pub use units::*;
@@ -0,0 +1,152 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use alloc::sync::{Arc, Weak};
+
+/// Counts of the pages held by a process.
+///
+/// Pages are counted through [`TrackedPages`] handles, which uncount them when dropped, so the counts fall back to
+/// zero once everything the process held has been freed.
+#[derive(Debug, Default)]
+pub struct MemoryStatistics {
+    size: AtomicUsize,
+    resident: AtomicUsize,
+    shared: AtomicUsize,
+}
+
+impl MemoryStatistics {
+    /// Construct statistics for a process holding no pages
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            size: AtomicUsize::new(0),
+            resident: AtomicUsize::new(0),
+            shared: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the size of the process's address space in pages
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Get the number of pages the process holds
+    pub fn resident(&self) -> usize {
+        self.resident.load(Ordering::Acquire)
+    }
+
+    /// Get the number of the process's pages which are shared with another process
+    pub fn shared(&self) -> usize {
+        self.shared.load(Ordering::Acquire)
+    }
+
+    /// Count `pages` pages as held by the process until the returned handle is dropped
+    #[must_use]
+    pub fn track(self: &Arc<Self>, pages: usize) -> TrackedPages {
+        self.resident.fetch_add(pages, Ordering::AcqRel);
+
+        TrackedPages {
+            pages,
+            shared: 0,
+            tracker: Arc::downgrade(self),
+        }
+    }
+}
+
+/// Pages counted against a [`MemoryStatistics`], which are uncounted when this is dropped. The statistics are not kept
+/// alive by the handle.
+#[derive(Debug)]
+pub struct TrackedPages {
+    pages: usize,
+    shared: usize,
+    tracker: Weak<MemoryStatistics>,
+}
+
+impl TrackedPages {
+    /// Get the number of pages counted
+    #[must_use]
+    pub const fn pages(&self) -> usize {
+        self.pages
+    }
+
+    /// Get the number of pages counted as shared with another process
+    #[must_use]
+    pub const fn shared(&self) -> usize {
+        self.shared
+    }
+
+    /// Count `shared` of the pages as shared with another process, in place of the number counted before.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `shared` is more than the number of pages counted.
+    pub fn set_shared(&mut self, shared: usize) {
+        assert!(shared <= self.pages, "More pages shared than are tracked");
+
+        if let Some(tracker) = self.tracker.upgrade() {
+            tracker.shared.fetch_add(shared, Ordering::AcqRel);
+            tracker.shared.fetch_sub(self.shared, Ordering::AcqRel);
+        }
+
+        self.shared = shared;
+    }
+}
+
+impl Drop for TrackedPages {
+    fn drop(&mut self) {
+        if let Some(tracker) = self.tracker.upgrade() {
+            tracker.resident.fetch_sub(self.pages, Ordering::AcqRel);
+            tracker.shared.fetch_sub(self.shared, Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::sync::Arc;
+
+    use super::MemoryStatistics;
+
+    #[test]
+    pub fn tracked_pages_test() {
+        let stats = Arc::new(MemoryStatistics::new());
+
+        let mut pages = stats.track(4);
+        assert_eq!(stats.resident(), 4);
+
+        pages.set_shared(3);
+        assert_eq!(stats.shared(), 3);
+        pages.set_shared(1);
+        assert_eq!(stats.shared(), 1);
+        assert_eq!((pages.pages(), pages.shared()), (4, 1));
+
+        drop(pages);
+        assert_eq!((stats.resident(), stats.shared()), (0, 0));
+    }
+
+    #[test]
+    pub fn resident_returns_to_baseline_test() {
+        let parent = Arc::new(MemoryStatistics::new());
+
+        // A process holding its trap frame, page table and stack, along with its program image and heap
+        let held = [1, 1, 4, 3, 2].map(|pages| parent.track(pages));
+        assert_eq!(parent.resident(), 11);
+
+        // Forking shares the image and heap with a child, which counts them as its own too
+        let child = Arc::new(MemoryStatistics::new());
+        let mut child_held = [3, 2].map(|pages| child.track(pages));
+        for pages in &mut child_held {
+            pages.set_shared(pages.pages());
+        }
+        assert_eq!((child.resident(), child.shared()), (5, 5));
+
+        // Once the parent exits and is reaped, everything it held is uncounted, leaving the child's counts alone
+        drop(held);
+        assert_eq!((parent.resident(), parent.shared()), (0, 0));
+        assert_eq!((child.resident(), child.shared()), (5, 5));
+
+        // Handles outliving their statistics do nothing when dropped
+        drop(child);
+        drop(child_held);
+    }
+}
@@ -41,9 +41,35 @@ impl<const SCALE: usize> MemoryUnit<SCALE> {
 }
 
 impl<const SRC: usize> MemoryUnit<SRC> {
+    /// Convert to a different unit, rounding up to a whole number of the new units. This is the same as
+    /// [`MemoryUnit::convert_ceil`].
     #[must_use]
     pub const fn convert<const DEST: usize>(&self) -> MemoryUnit<DEST> {
-        MemoryUnit((self.raw_bytes() + DEST - 1) / DEST)
+        self.convert_ceil()
+    }
+
+    /// Convert to a different unit, rounding up so the result covers every byte, such as for an allocation which must
+    /// hold the whole size. A single byte converts to one page.
+    #[must_use]
+    pub const fn convert_ceil<const DEST: usize>(&self) -> MemoryUnit<DEST> {
+        MemoryUnit(self.raw_bytes().div_ceil(DEST))
+    }
+
+    /// Convert to a different unit, rounding down so the result never covers more than the original size, such as
+    /// for counting the whole pages within a region. A single byte converts to zero pages.
+    #[must_use]
+    pub const fn convert_floor<const DEST: usize>(&self) -> MemoryUnit<DEST> {
+        MemoryUnit(self.raw_bytes() / DEST)
+    }
+
+    /// Convert to a different unit, returning `None` unless the size is an exact number of the new units
+    #[must_use]
+    pub const fn checked_convert<const DEST: usize>(&self) -> Option<MemoryUnit<DEST>> {
+        if self.raw_bytes().is_multiple_of(DEST) {
+            Some(MemoryUnit(self.raw_bytes() / DEST))
+        } else {
+            None
+        }
     }
 }
 
@@ -62,3 +88,50 @@ impl<const SIZE: usize> core::fmt::Display for MemoryUnit<SIZE> {
         }
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{ByteCount, KiByteCount, MemoryUnit};
+
+    type PageCount = MemoryUnit<4096>;
+
+    #[test]
+    pub fn convert_ceil_test() {
+        let pages = |bytes| ByteCount::new(bytes).convert_ceil::<4096>();
+
+        assert_eq!(pages(0), PageCount::new(0));
+        assert_eq!(pages(1), PageCount::new(1));
+        assert_eq!(pages(4095), PageCount::new(1));
+        assert_eq!(pages(4096), PageCount::new(1));
+        assert_eq!(pages(4097), PageCount::new(2));
+
+        assert_eq!(ByteCount::new(1).convert::<4096>(), PageCount::new(1));
+    }
+
+    #[test]
+    pub fn convert_floor_test() {
+        let pages = |bytes| ByteCount::new(bytes).convert_floor::<4096>();
+
+        assert_eq!(pages(1), PageCount::new(0));
+        assert_eq!(pages(4095), PageCount::new(0));
+        assert_eq!(pages(4096), PageCount::new(1));
+        assert_eq!(pages(4097), PageCount::new(1));
+        assert_eq!(pages(8191), PageCount::new(1));
+    }
+
+    #[test]
+    pub fn checked_convert_test() {
+        let pages = |bytes| ByteCount::new(bytes).checked_convert::<4096>();
+
+        assert_eq!(pages(4095), None);
+        assert_eq!(pages(4096), Some(PageCount::new(1)));
+        assert_eq!(pages(4097), None);
+
+        // Converting to a smaller unit is always exact
+        assert_eq!(
+            PageCount::new(3).checked_convert::<1024>(),
+            Some(KiByteCount::new(12))
+        );
+    }
+}
@@ -0,0 +1,139 @@
+/// Errors which can occur while parsing a [`BootConfig`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootConfigError<'a> {
+    /// An entry was not of the form `key=value`
+    MissingValue(&'a str),
+    /// The key is not one of the recognised options
+    UnknownKey(&'a str),
+    /// The value given for the key was not a valid number
+    InvalidNumber(&'a str),
+}
+
+/// Selection of the root file system and first program to run, made at boot.
+///
+/// The configuration is written as whitespace separated `key=value` entries, any key which is left out keeps its
+/// default:
+///
+/// | Key              | Value                                                                 | Default      |
+/// |------------------|-----------------------------------------------------------------------|--------------|
+/// | `root_device`    | index of the block device holding the root                            | `0`          |
+/// | `root_partition` | MBR partition on that device counting from 1, or `none` for all of it | `none`       |
+/// | `root_delay`     | milliseconds to wait for the block device before mounting the root    | `0`          |
+/// | `init`           | absolute path of the program to launch                                | `/bin/hello` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootConfig<'a> {
+    pub root_device: usize,
+    pub root_partition: Option<usize>,
+    pub root_delay: u64,
+    pub init: &'a str,
+}
+
+impl<'a> BootConfig<'a> {
+    /// Construct the default configuration, mounting the whole of the first block device and launching `/bin/hello`
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            root_device: 0,
+            root_partition: None,
+            root_delay: 0,
+            init: "/bin/hello",
+        }
+    }
+
+    /// Parse a configuration from `key=value` entries, later entries overriding earlier ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first entry which is malformed, has an unknown key, or has a value that is not a
+    /// number where one is expected.
+    pub fn parse(config: &'a str) -> Result<Self, BootConfigError<'a>> {
+        let mut result = Self::new();
+
+        for entry in config.split_whitespace() {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or(BootConfigError::MissingValue(entry))?;
+
+            match key {
+                "root_device" => result.root_device = parse_number(key, value)?,
+                "root_partition" => {
+                    result.root_partition = if value == "none" {
+                        None
+                    } else {
+                        Some(parse_number(key, value)?)
+                    };
+                }
+                "root_delay" => result.root_delay = parse_number(key, value)?,
+                "init" => result.init = value,
+                _ => return Err(BootConfigError::UnknownKey(key)),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl Default for BootConfig<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_number<'a, T: core::str::FromStr>(
+    key: &'a str,
+    value: &str,
+) -> Result<T, BootConfigError<'a>> {
+    value
+        .parse()
+        .map_err(|_| BootConfigError::InvalidNumber(key))
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{BootConfig, BootConfigError};
+
+    #[test]
+    pub fn parse_test() {
+        let config =
+            BootConfig::parse("root_device=1 root_partition=2\ninit=/sbin/init root_delay=250")
+                .unwrap();
+
+        assert_eq!(config.root_device, 1);
+        assert_eq!(config.root_partition, Some(2));
+        assert_eq!(config.root_delay, 250);
+        assert_eq!(config.init, "/sbin/init");
+    }
+
+    #[test]
+    pub fn parse_defaults_test() {
+        assert_eq!(BootConfig::parse(""), Ok(BootConfig::default()));
+        assert_eq!(
+            BootConfig::parse("init=/bin/sh root_partition=none"),
+            Ok(BootConfig {
+                init: "/bin/sh",
+                ..BootConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    pub fn parse_error_test() {
+        assert_eq!(
+            BootConfig::parse("root_device"),
+            Err(BootConfigError::MissingValue("root_device"))
+        );
+        assert_eq!(
+            BootConfig::parse("root=1"),
+            Err(BootConfigError::UnknownKey("root"))
+        );
+        assert_eq!(
+            BootConfig::parse("root_device=first"),
+            Err(BootConfigError::InvalidNumber("root_device"))
+        );
+        assert_eq!(
+            BootConfig::parse("root_delay=-1"),
+            Err(BootConfigError::InvalidNumber("root_delay"))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utils::parser::ByteOrder;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OsABI {
     SystemV,
@@ -295,6 +297,15 @@ impl core::convert::From<Endian> for u8 {
     }
 }
 
+impl core::convert::From<Endian> for ByteOrder {
+    fn from(value: Endian) -> Self {
+        match value {
+            Endian::Little => Self::Little,
+            Endian::Big => Self::Big,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BitWidth {
     Bit32,
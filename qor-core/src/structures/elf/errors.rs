@@ -0,0 +1,32 @@
+use super::enums::{Architecture, BitWidth};
+
+/// Errors which can occur while parsing an ELF file.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfParseError {
+    /// The file is too short to hold an ELF header.
+    TruncatedHeader,
+    /// The file does not start with the `\x7fELF` magic number.
+    BadMagic,
+    /// The class byte is neither 32 nor 64 bit.
+    UnsupportedClass(u8),
+    /// The data byte is neither little nor big endian.
+    UnsupportedEndian(u8),
+    /// The program header table extends past the end of the file.
+    ProgramHeaderOffsetOutOfBounds,
+    /// The section header table extends past the end of the file.
+    SectionHeaderOffsetOutOfBounds,
+    /// A program header could not be parsed.
+    MalformedProgramHeader,
+    /// A section header could not be parsed.
+    MalformedSectionHeader,
+}
+
+/// Reasons an ELF file cannot be run on a given target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetMismatch {
+    /// The file was built for a different instruction set architecture.
+    Architecture(Architecture),
+    /// The file uses a different word size to the target.
+    Class(BitWidth),
+}
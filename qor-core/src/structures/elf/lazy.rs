@@ -0,0 +1,110 @@
+use crate::utils::parser::Parser;
+
+use super::{
+    enums::BitWidth,
+    errors::ElfParseError,
+    raw,
+    structures::{ElfHeader, ProgramHeader, SectionHeader},
+};
+
+/// View of an ELF file which only parses the file header up front.
+///
+/// Program and section headers are parsed on demand by index, avoiding allocation for callers which only need a subset
+/// of the file's metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyElf<'a> {
+    pub header: ElfHeader,
+    pub data: &'a [u8],
+}
+
+impl<'a> LazyElf<'a> {
+    /// Parse the header of an ELF file from a slice of bytes
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ELF header is truncated or malformed.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ElfParseError> {
+        let mut parser = Parser::new(data);
+        let header: ElfHeader = raw::RawElfHeader::parse(&mut parser)
+            .ok_or(ElfParseError::TruncatedHeader)?
+            .try_into()?;
+
+        Ok(Self { header, data })
+    }
+
+    /// Get the number of program headers in the file.
+    #[must_use]
+    pub const fn program_header_count(&self) -> usize {
+        self.header.ph_entry_count as usize
+    }
+
+    /// Get the number of section headers in the file.
+    #[must_use]
+    pub const fn section_header_count(&self) -> usize {
+        self.header.sh_entry_count as usize
+    }
+
+    /// Get the bytes of the `index`th entry of a header table, if it lies within the file.
+    fn table_entry(&self, table_offset: u64, entry_size: u16, index: usize) -> Option<&'a [u8]> {
+        let start = usize::try_from(table_offset)
+            .ok()?
+            .checked_add(index.checked_mul(entry_size as usize)?)?;
+
+        self.data
+            .get(start..start.checked_add(entry_size as usize)?)
+    }
+
+    /// Parse the `index`th program header, returns `None` if the index is out of range or the header is malformed.
+    #[must_use]
+    pub fn program_header(&self, index: usize) -> Option<ProgramHeader> {
+        if index >= self.program_header_count() {
+            return None;
+        }
+
+        let mut parser = Parser::with_byte_order(
+            self.table_entry(self.header.ph_offset, self.header.ph_entry_size, index)?,
+            self.header.endian.into(),
+        );
+
+        let raw = if self.header.class == BitWidth::Bit64 {
+            raw::RawProgramHeader::parse64(&mut parser)
+        } else {
+            raw::RawProgramHeader::parse32(&mut parser)
+        }?;
+
+        raw.try_into().ok()
+    }
+
+    /// Parse the `index`th section header, returns `None` if the index is out of range or the header is malformed.
+    #[must_use]
+    pub fn section_header(&self, index: usize) -> Option<SectionHeader> {
+        if index >= self.section_header_count() {
+            return None;
+        }
+
+        let mut parser = Parser::with_byte_order(
+            self.table_entry(self.header.sh_offset, self.header.sh_entry_size, index)?,
+            self.header.endian.into(),
+        );
+
+        let raw = if self.header.class == BitWidth::Bit64 {
+            raw::RawSectionHeader::parse64(&mut parser)
+        } else {
+            raw::RawSectionHeader::parse32(&mut parser)
+        }?;
+
+        raw.try_into().ok()
+    }
+
+    /// Iterate over the program headers of the file, parsing each as it is reached. Headers which fail to parse are
+    /// yielded as `None`.
+    pub fn program_headers(&self) -> impl Iterator<Item = Option<ProgramHeader>> + '_ {
+        (0..self.program_header_count()).map(|index| self.program_header(index))
+    }
+
+    /// Iterate over the section headers of the file, parsing each as it is reached. Headers which fail to parse are
+    /// yielded as `None`.
+    pub fn section_headers(&self) -> impl Iterator<Item = Option<SectionHeader>> + '_ {
+        (0..self.section_header_count()).map(|index| self.section_header(index))
+    }
+}
@@ -5,10 +5,15 @@ use crate::utils::parser::Parser;
 use self::structures::ElfHeader;
 
 pub mod enums;
+pub mod errors;
 pub mod flags;
+pub mod lazy;
 pub mod raw;
 pub mod structures;
 
+pub use errors::{ElfParseError, TargetMismatch};
+pub use lazy::LazyElf;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Elf<'a> {
     pub header: structures::ElfHeader,
@@ -18,27 +23,52 @@ pub struct Elf<'a> {
 }
 
 impl<'a> Elf<'a> {
-    #[must_use]
+    /// Parse only the header of an ELF file from a slice of bytes, returning a view which parses program and section
+    /// headers on demand rather than collecting them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ELF header is truncated or malformed.
+    pub fn parse_lazy(data: &'a [u8]) -> Result<LazyElf<'a>, ElfParseError> {
+        LazyElf::parse(data)
+    }
+
     /// Parse an ELF file from a slice of bytes
     ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ELF header is truncated or malformed, or if the program or section
+    /// header tables extend past the end of the data or contain malformed headers.
+    ///
     /// # Panics
     ///
-    /// This function will panic if the ELF file is malformed or a 64 bit file with > 32 bit pointers is attempted to
-    /// be read on a 32 bit machine.
-    pub fn parse(data: &'a [u8]) -> Option<Self> {
+    /// This function will panic if a 64 bit file with > 32 bit pointers is attempted to be read on a 32 bit machine.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ElfParseError> {
         let mut parser = Parser::new(data);
 
-        let header: ElfHeader = raw::RawElfHeader::parse(&mut parser)?.try_into().unwrap();
+        let header: ElfHeader = raw::RawElfHeader::parse(&mut parser)
+            .ok_or(ElfParseError::TruncatedHeader)?
+            .try_into()?;
+
+        let program_header_table = table_bytes(
+            data,
+            header.ph_offset,
+            header.ph_entry_size,
+            header.ph_entry_count,
+        )
+        .ok_or(ElfParseError::ProgramHeaderOffsetOutOfBounds)?;
 
-        let program_header_offset = header.ph_offset.try_into().unwrap();
-        let program_header_size = header.ph_entry_size as usize * header.ph_entry_count as usize;
+        let section_header_table = table_bytes(
+            data,
+            header.sh_offset,
+            header.sh_entry_size,
+            header.sh_entry_count,
+        )
+        .ok_or(ElfParseError::SectionHeaderOffsetOutOfBounds)?;
 
-        let section_header_offset = header.sh_offset.try_into().unwrap();
-        let section_header_size = header.sh_entry_size as usize * header.sh_entry_count as usize;
+        let byte_order = header.endian.into();
 
-        let mut parser = Parser::new(
-            data[program_header_offset..program_header_offset + program_header_size].as_ref(),
-        );
+        let mut parser = Parser::with_byte_order(program_header_table, byte_order);
         let program_headers = (0..header.ph_entry_count)
             .map(|_| {
                 if header.class == enums::BitWidth::Bit64 {
@@ -46,12 +76,12 @@ impl<'a> Elf<'a> {
                 } else {
                     raw::RawProgramHeader::parse32(&mut parser)
                 }
+                .and_then(|header| header.try_into().ok())
+                .ok_or(ElfParseError::MalformedProgramHeader)
             })
-            .collect::<Option<Vec<_>>>()?;
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let mut parser = Parser::new(
-            data[section_header_offset..section_header_offset + section_header_size].as_ref(),
-        );
+        let mut parser = Parser::with_byte_order(section_header_table, byte_order);
         let section_headers = (0..header.sh_entry_count)
             .map(|_| {
                 if header.class == enums::BitWidth::Bit64 {
@@ -59,20 +89,748 @@ impl<'a> Elf<'a> {
                 } else {
                     raw::RawSectionHeader::parse32(&mut parser)
                 }
+                .and_then(|header| header.try_into().ok())
+                .ok_or(ElfParseError::MalformedSectionHeader)
             })
-            .collect::<Option<Vec<_>>>()?;
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Some(Self {
+        Ok(Self {
             header,
-            program_headers: program_headers
-                .into_iter()
-                .map(|header| header.try_into().unwrap())
-                .collect::<Vec<_>>(),
-            section_headers: section_headers
-                .into_iter()
-                .map(|header| header.try_into().unwrap())
-                .collect::<Vec<_>>(),
+            program_headers,
+            section_headers,
             data,
         })
     }
+
+    /// Resolve the name of a section using the section header string table, returns `None` if the string table index
+    /// in the header is out of range, or the name is out of bounds, unterminated, or not valid UTF-8.
+    #[must_use]
+    pub fn section_name(&self, header: &structures::SectionHeader) -> Option<&'a str> {
+        let string_table = self
+            .section_headers
+            .get(self.header.sh_str_index as usize)?
+            .data(self.data)?;
+
+        nul_terminated(string_table.get(header.name_offset() as usize..)?)
+    }
+
+    /// Get the path of the interpreter (dynamic linker) requested by the `Interpreter` program header, returns `None`
+    /// if the file is statically linked, or the path is out of bounds, unterminated, or not valid UTF-8.
+    #[must_use]
+    pub fn interpreter(&self) -> Option<&'a str> {
+        let header = self
+            .program_headers
+            .iter()
+            .find(|header| header.header_type == enums::ProgramHeaderType::Interpreter)?;
+
+        nul_terminated(header.data(self.data)?)
+    }
+
+    /// Returns true if the entry point lies within a `Load` segment which is executable, so starting execution there
+    /// runs code from the file rather than faulting on unmapped or non-executable memory.
+    #[must_use]
+    pub fn entry_is_valid(&self) -> bool {
+        let entry = self.header.entry;
+
+        self.program_headers.iter().any(|header| {
+            header.header_type == enums::ProgramHeaderType::Load
+                && header.flags.flag(flags::ProgramHeaderFlag::Execute)
+                && header.virtual_addr <= entry
+                && header
+                    .virtual_addr
+                    .checked_add(header.memory_size)
+                    .is_some_and(|end| entry < end)
+        })
+    }
+
+    /// Iterate over the sections of the file along with their resolved names, sections whose name cannot be resolved
+    /// (including when the string table is missing) are given an empty name.
+    pub fn sections_named(
+        &self,
+    ) -> impl Iterator<Item = (&'a str, &structures::SectionHeader)> + '_ {
+        self.section_headers
+            .iter()
+            .map(|header| (self.section_name(header).unwrap_or(""), header))
+    }
+
+    /// Find the first section with the given name
+    #[must_use]
+    pub fn section_by_name(&self, name: &str) -> Option<&structures::SectionHeader> {
+        self.section_headers
+            .iter()
+            .find(|header| self.section_name(header) == Some(name))
+    }
+
+    /// Find the symbol table of the file, preferring the full `SymTab` over the dynamic `DynSym` table
+    #[must_use]
+    pub fn symbol_table(&self) -> Option<&structures::SectionHeader> {
+        let find = |section_type| {
+            self.section_headers
+                .iter()
+                .find(|header| header.section_type() == section_type)
+        };
+
+        find(enums::SectionHeaderType::SymTab).or_else(|| find(enums::SectionHeaderType::DynSym))
+    }
+
+    /// Parse every entry of the symbol table, returning an empty `Vec` if the file has no symbol table or it does not
+    /// lie within the file. Any partial entry at the end of the table is ignored.
+    #[must_use]
+    pub fn symbols(&self) -> Vec<structures::Symbol> {
+        let Some(table) = self
+            .symbol_table()
+            .and_then(|header| header.data(self.data))
+        else {
+            return Vec::new();
+        };
+
+        let entry_size = if self.header.class == enums::BitWidth::Bit64 {
+            24
+        } else {
+            16
+        };
+
+        table
+            .chunks_exact(entry_size)
+            .filter_map(|entry| {
+                let mut parser = Parser::with_byte_order(entry, self.header.endian.into());
+
+                if self.header.class == enums::BitWidth::Bit64 {
+                    raw::RawSymbol::parse64(&mut parser)
+                } else {
+                    raw::RawSymbol::parse32(&mut parser)
+                }
+            })
+            .map(structures::Symbol::from)
+            .collect()
+    }
+
+    /// Resolve the name of a symbol using the string table linked to by the symbol table, returns `None` if there is
+    /// no such string table, or the name is out of bounds, unterminated, or not valid UTF-8.
+    #[must_use]
+    pub fn symbol_name(&self, symbol: &structures::Symbol) -> Option<&'a str> {
+        let string_table = self
+            .section_headers
+            .get(self.symbol_table()?.link() as usize)?
+            .data(self.data)?;
+
+        nul_terminated(string_table.get(symbol.name as usize..)?)
+    }
+}
+
+/// Get the string stored at the start of `bytes` up to the first NUL byte, if it is terminated and valid UTF-8
+fn nul_terminated(bytes: &[u8]) -> Option<&str> {
+    let length = bytes.iter().position(|b| *b == 0)?;
+
+    core::str::from_utf8(&bytes[..length]).ok()
+}
+
+/// Get the bytes of a header table starting at `offset` holding `count` entries of `entry_size` bytes, returning
+/// `None` if the table does not lie entirely within `data`.
+fn table_bytes(data: &[u8], offset: u64, entry_size: u16, count: u16) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?;
+    let length = (entry_size as usize).checked_mul(count as usize)?;
+
+    data.get(start..start.checked_add(length)?)
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{
+        enums::{Architecture, BitWidth, Endian, ProgramHeaderType},
+        Elf, ElfParseError, TargetMismatch,
+    };
+
+    const ELF_HEADER_SIZE: u16 = 64;
+    const PROGRAM_HEADER_SIZE: u16 = 56;
+    const SECTION_HEADER_SIZE: u16 = 64;
+
+    /// Program header used to construct a test ELF file, the offset is relative to the start of the payload.
+    #[derive(Clone, Copy)]
+    struct TestSegment {
+        header_type: u32,
+        flags: u32,
+        offset: u64,
+        virtual_addr: u64,
+        file_size: u64,
+        memory_size: u64,
+    }
+
+    /// Section header used to construct a test ELF file, the offset is relative to the start of the payload.
+    struct TestSection {
+        name: u32,
+        section_type: u32,
+        offset: u64,
+        size: u64,
+        link: u32,
+        entry_size: u64,
+    }
+
+    /// Construct a little endian 64 bit RISC-V executable with the given program headers, followed by the payload.
+    fn build_elf(entry: u64, segments: &[TestSegment], payload: &[u8]) -> Vec<u8> {
+        build_elf_with_sections(entry, segments, &[], 0, payload)
+    }
+
+    /// Construct a little endian 64 bit RISC-V executable with the given program and section headers, followed by the
+    /// payload.
+    fn build_elf_with_sections(
+        entry: u64,
+        segments: &[TestSegment],
+        sections: &[TestSection],
+        sh_str_index: u16,
+        payload: &[u8],
+    ) -> Vec<u8> {
+        let sh_offset = u64::from(ELF_HEADER_SIZE)
+            + u64::from(PROGRAM_HEADER_SIZE) * u64::try_from(segments.len()).unwrap();
+        let payload_offset =
+            sh_offset + u64::from(SECTION_HEADER_SIZE) * u64::try_from(sections.len()).unwrap();
+        let mut data = Vec::new();
+
+        data.extend_from_slice(&[0x7F, 0x45, 0x4C, 0x46, 2, 1, 1, 0, 0]);
+        data.extend_from_slice(&[0; 7]);
+        data.extend_from_slice(&2u16.to_le_bytes()); // Executable
+        data.extend_from_slice(&0xF3u16.to_le_bytes()); // RISC-V
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&entry.to_le_bytes());
+        data.extend_from_slice(&u64::from(ELF_HEADER_SIZE).to_le_bytes());
+        data.extend_from_slice(&sh_offset.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&ELF_HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&PROGRAM_HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&u16::try_from(segments.len()).unwrap().to_le_bytes());
+        data.extend_from_slice(&SECTION_HEADER_SIZE.to_le_bytes());
+        data.extend_from_slice(&u16::try_from(sections.len()).unwrap().to_le_bytes());
+        data.extend_from_slice(&sh_str_index.to_le_bytes());
+
+        for segment in segments {
+            data.extend_from_slice(&segment.header_type.to_le_bytes());
+            data.extend_from_slice(&segment.flags.to_le_bytes());
+            data.extend_from_slice(&(payload_offset + segment.offset).to_le_bytes());
+            data.extend_from_slice(&segment.virtual_addr.to_le_bytes());
+            data.extend_from_slice(&segment.virtual_addr.to_le_bytes());
+            data.extend_from_slice(&segment.file_size.to_le_bytes());
+            data.extend_from_slice(&segment.memory_size.to_le_bytes());
+            data.extend_from_slice(&0x1000u64.to_le_bytes());
+        }
+
+        for section in sections {
+            data.extend_from_slice(&section.name.to_le_bytes());
+            data.extend_from_slice(&section.section_type.to_le_bytes());
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.extend_from_slice(&0u64.to_le_bytes());
+            data.extend_from_slice(&(payload_offset + section.offset).to_le_bytes());
+            data.extend_from_slice(&section.size.to_le_bytes());
+            data.extend_from_slice(&section.link.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&1u64.to_le_bytes());
+            data.extend_from_slice(&section.entry_size.to_le_bytes());
+        }
+
+        data.extend_from_slice(payload);
+        data
+    }
+
+    #[test]
+    pub fn load_segment_zeroes_bss_test() {
+        let data = build_elf(
+            0x1_0000,
+            &[TestSegment {
+                header_type: 1,
+                flags: 0b110,
+                offset: 0,
+                virtual_addr: 0x1_0000,
+                file_size: 8,
+                memory_size: 32,
+            }],
+            &[1, 2, 3, 4, 5, 6, 7, 8],
+        );
+
+        let elf = Elf::parse(&data).unwrap();
+        let header = elf.program_headers[0];
+        assert_eq!(header.header_type, ProgramHeaderType::Load);
+
+        // Start from memory which is not zeroed, as freshly mapped pages are not guaranteed to be
+        let mut memory = [0xFF; 32];
+        header.load_segment(elf.data, &mut memory);
+
+        assert_eq!(memory[..8], [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(memory[8..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    pub fn entry_point_test() {
+        let segments = [
+            TestSegment {
+                header_type: 1,
+                flags: 0b101,
+                offset: 0,
+                virtual_addr: 0x1_0000,
+                file_size: 8,
+                memory_size: 8,
+            },
+            TestSegment {
+                header_type: 1,
+                flags: 0b110,
+                offset: 8,
+                virtual_addr: 0x2_0000,
+                file_size: 8,
+                memory_size: 0x100,
+            },
+        ];
+        let entry_is_valid = |entry| {
+            Elf::parse(&build_elf(entry, &segments, &[0; 16]))
+                .unwrap()
+                .entry_is_valid()
+        };
+
+        assert!(entry_is_valid(0x1_0000));
+        assert!(entry_is_valid(0x1_0004));
+
+        // Within the writable data segment, which is not executable
+        assert!(!entry_is_valid(0x2_0000));
+        assert!(!entry_is_valid(0x2_00FC));
+
+        // Outside every segment
+        assert!(!entry_is_valid(0x1_0008));
+        assert!(!entry_is_valid(0));
+        assert!(!entry_is_valid(0x3_0000));
+    }
+
+    #[test]
+    pub fn parse_error_test() {
+        let segment = TestSegment {
+            header_type: 1,
+            flags: 0b101,
+            offset: 0,
+            virtual_addr: 0x1_0000,
+            file_size: 4,
+            memory_size: 4,
+        };
+        let data = build_elf(0x1_0000, &[segment], &[0; 4]);
+
+        assert_eq!(Elf::parse(&data[..32]), Err(ElfParseError::TruncatedHeader));
+
+        let mut bad_magic = data.clone();
+        bad_magic[1] = b'X';
+        assert_eq!(Elf::parse(&bad_magic), Err(ElfParseError::BadMagic));
+
+        let mut bad_class = data.clone();
+        bad_class[4] = 3;
+        assert_eq!(
+            Elf::parse(&bad_class),
+            Err(ElfParseError::UnsupportedClass(3))
+        );
+
+        // Cut the file off part way through the program header table
+        assert_eq!(
+            Elf::parse(&data[..100]),
+            Err(ElfParseError::ProgramHeaderOffsetOutOfBounds)
+        );
+
+        let mut bad_offset = data;
+        bad_offset[32..40].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            Elf::parse(&bad_offset),
+            Err(ElfParseError::ProgramHeaderOffsetOutOfBounds)
+        );
+    }
+
+    #[test]
+    pub fn big_endian_test() {
+        // A big endian 64 bit executable for an S390 hart, with one loadable segment
+        let mut data = Vec::new();
+        data.extend_from_slice(&[0x7F, 0x45, 0x4C, 0x46, 2, 2, 1, 0, 0]);
+        data.extend_from_slice(&[0; 7]);
+        data.extend_from_slice(&2u16.to_be_bytes()); // Executable
+        data.extend_from_slice(&0x16u16.to_be_bytes()); // S390
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(&0x1_0000u64.to_be_bytes());
+        data.extend_from_slice(&u64::from(ELF_HEADER_SIZE).to_be_bytes());
+        data.extend_from_slice(&0u64.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&ELF_HEADER_SIZE.to_be_bytes());
+        data.extend_from_slice(&PROGRAM_HEADER_SIZE.to_be_bytes());
+        data.extend_from_slice(&1u16.to_be_bytes());
+        data.extend_from_slice(&SECTION_HEADER_SIZE.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes());
+
+        data.extend_from_slice(&1u32.to_be_bytes()); // Load
+        data.extend_from_slice(&0b101u32.to_be_bytes());
+        data.extend_from_slice(&0x78u64.to_be_bytes());
+        data.extend_from_slice(&0x1_0000u64.to_be_bytes());
+        data.extend_from_slice(&0x1_0000u64.to_be_bytes());
+        data.extend_from_slice(&4u64.to_be_bytes());
+        data.extend_from_slice(&8u64.to_be_bytes());
+        data.extend_from_slice(&0x1000u64.to_be_bytes());
+        data.extend_from_slice(&[0; 4]);
+
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(elf.header.endian, Endian::Big);
+        assert_eq!(elf.header.machine, Architecture::S390);
+        assert_eq!(elf.header.entry, 0x1_0000);
+        assert_eq!(elf.header.ph_entry_count, 1);
+
+        let segment = elf.program_headers[0];
+        assert_eq!(segment.header_type, ProgramHeaderType::Load);
+        assert_eq!(segment.offset, 0x78);
+        assert_eq!(segment.virtual_addr, 0x1_0000);
+        assert_eq!(segment.file_size, 4);
+        assert_eq!(segment.memory_size, 8);
+        assert!(elf.entry_is_valid());
+
+        let lazy = Elf::parse_lazy(&data).unwrap();
+        assert_eq!(lazy.header, elf.header);
+        assert_eq!(lazy.program_header(0), Some(segment));
+
+        // The same bytes read as little endian are nonsense, rather than silently accepted
+        let mut little = data;
+        little[5] = 1;
+        assert_eq!(
+            Elf::parse(&little),
+            Err(ElfParseError::ProgramHeaderOffsetOutOfBounds)
+        );
+    }
+
+    #[test]
+    pub fn lazy_program_header_test() {
+        let segments = [
+            TestSegment {
+                header_type: 1,
+                flags: 0b101,
+                offset: 0,
+                virtual_addr: 0x1_0000,
+                file_size: 4,
+                memory_size: 4,
+            },
+            TestSegment {
+                header_type: 4,
+                flags: 0b100,
+                offset: 4,
+                virtual_addr: 0,
+                file_size: 4,
+                memory_size: 4,
+            },
+            TestSegment {
+                header_type: 1,
+                flags: 0b110,
+                offset: 8,
+                virtual_addr: 0x2_0000,
+                file_size: 4,
+                memory_size: 64,
+            },
+        ];
+        let data = build_elf(0x1_0000, &segments, &[0; 12]);
+
+        let eager = Elf::parse(&data).unwrap();
+        let lazy = Elf::parse_lazy(&data).unwrap();
+
+        assert_eq!(lazy.header, eager.header);
+        assert_eq!(lazy.program_header_count(), eager.program_headers.len());
+
+        for (index, header) in eager.program_headers.iter().enumerate() {
+            assert_eq!(lazy.program_header(index), Some(*header));
+        }
+
+        assert_eq!(lazy.program_header(segments.len()), None);
+        assert_eq!(lazy.section_header(0), None);
+    }
+
+    /// Payload holding a section header string table for `.text`, `.shstrtab`, and an unterminated trailing name.
+    const SECTION_NAMES: &[u8] = b"\0.text\0.shstrtab\0.bad";
+
+    fn named_sections(sh_str_index: u16) -> Vec<u8> {
+        let sections = [
+            TestSection {
+                name: 0,
+                section_type: 0,
+                offset: 0,
+                size: 0,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 1,
+                section_type: 1,
+                offset: 0,
+                size: 0,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 7,
+                section_type: 3,
+                offset: 0,
+                size: SECTION_NAMES.len() as u64,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 17,
+                section_type: 1,
+                offset: 0,
+                size: 0,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 200,
+                section_type: 1,
+                offset: 0,
+                size: 0,
+                link: 0,
+                entry_size: 0,
+            },
+        ];
+
+        build_elf_with_sections(0x1_0000, &[], &sections, sh_str_index, SECTION_NAMES)
+    }
+
+    #[test]
+    pub fn section_name_test() {
+        let data = named_sections(2);
+        let elf = Elf::parse(&data).unwrap();
+
+        let names = elf
+            .section_headers
+            .iter()
+            .map(|header| elf.section_name(header))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            [Some(""), Some(".text"), Some(".shstrtab"), None, None]
+        );
+        assert_eq!(elf.section_by_name(".text"), Some(&elf.section_headers[1]));
+        assert_eq!(elf.section_by_name(".data"), None);
+    }
+
+    #[test]
+    pub fn section_name_bad_string_table_test() {
+        let data = named_sections(42);
+        let elf = Elf::parse(&data).unwrap();
+
+        assert!(elf
+            .section_headers
+            .iter()
+            .all(|header| elf.section_name(header).is_none()));
+    }
+
+    #[test]
+    pub fn sections_named_test() {
+        const NAMES: &[u8] = b"\0.text\0.data\0.bss\0.shstrtab\0";
+        let section = |name: u32, section_type: u32, size: u64| TestSection {
+            name,
+            section_type,
+            offset: 0,
+            size,
+            link: 0,
+            entry_size: 0,
+        };
+        let sections = [
+            section(0, 0, 0),
+            section(1, 1, 0x10),
+            section(7, 1, 0x20),
+            section(13, 8, 0x30),
+            section(18, 3, NAMES.len() as u64),
+        ];
+
+        let data = build_elf_with_sections(0x1_0000, &[], &sections, 4, NAMES);
+        let elf = Elf::parse(&data).unwrap();
+
+        let named = elf.sections_named().collect::<Vec<_>>();
+        assert_eq!(
+            named.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ["", ".text", ".data", ".bss", ".shstrtab"]
+        );
+        for ((_, header), expected) in named.iter().zip(&elf.section_headers) {
+            assert!(core::ptr::eq(*header, expected));
+        }
+        assert_eq!(named[3].1.size(), 0x30);
+
+        // Without a string table every section is still yielded, with an empty name
+        let data = build_elf_with_sections(0x1_0000, &[], &sections, 42, NAMES);
+        let elf = Elf::parse(&data).unwrap();
+        assert!(elf.sections_named().all(|(name, _)| name.is_empty()));
+        assert_eq!(elf.sections_named().count(), sections.len());
+    }
+
+    #[test]
+    pub fn symbol_table_test() {
+        let string_table: &[u8] = b"\0_start\0counter\0";
+        let section_names: &[u8] = b"\0.symtab\0.strtab\0.shstrtab\0";
+
+        let symbol = |name: u32, info: u8, section_index: u16, value: u64, size: u64| {
+            let mut entry = Vec::new();
+            entry.extend_from_slice(&name.to_le_bytes());
+            entry.push(info);
+            entry.push(0);
+            entry.extend_from_slice(&section_index.to_le_bytes());
+            entry.extend_from_slice(&value.to_le_bytes());
+            entry.extend_from_slice(&size.to_le_bytes());
+            entry
+        };
+
+        let mut payload = Vec::new();
+        payload.extend(symbol(0, 0, 0, 0, 0));
+        payload.extend(symbol(1, 0x12, 1, 0x1_0000, 16));
+        payload.extend(symbol(8, 0x11, 2, 0x2_0000, 8));
+        let symbol_table_length = payload.len() as u64;
+        payload.extend_from_slice(string_table);
+        payload.extend_from_slice(section_names);
+
+        let sections = [
+            TestSection {
+                name: 0,
+                section_type: 0,
+                offset: 0,
+                size: 0,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 1,
+                section_type: 2,
+                offset: 0,
+                size: symbol_table_length,
+                link: 2,
+                entry_size: 24,
+            },
+            TestSection {
+                name: 9,
+                section_type: 3,
+                offset: symbol_table_length,
+                size: string_table.len() as u64,
+                link: 0,
+                entry_size: 0,
+            },
+            TestSection {
+                name: 17,
+                section_type: 3,
+                offset: symbol_table_length + string_table.len() as u64,
+                size: section_names.len() as u64,
+                link: 0,
+                entry_size: 0,
+            },
+        ];
+
+        let data = build_elf_with_sections(0x1_0000, &[], &sections, 3, &payload);
+        let elf = Elf::parse(&data).unwrap();
+
+        assert_eq!(
+            elf.symbol_table()
+                .and_then(|header| elf.section_name(header)),
+            Some(".symtab")
+        );
+
+        let symbols = elf.symbols();
+        assert_eq!(symbols.len(), 3);
+
+        assert_eq!(elf.symbol_name(&symbols[1]), Some("_start"));
+        assert_eq!(symbols[1].value, 0x1_0000);
+        assert_eq!(symbols[1].size, 16);
+        assert_eq!(symbols[1].binding(), 1);
+        assert_eq!(symbols[1].symbol_type(), 2);
+
+        assert_eq!(elf.symbol_name(&symbols[2]), Some("counter"));
+        assert_eq!(symbols[2].section_index, 2);
+        assert_eq!(symbols[2].symbol_type(), 1);
+    }
+
+    #[test]
+    pub fn no_symbol_table_test() {
+        let data = named_sections(2);
+        let elf = Elf::parse(&data).unwrap();
+
+        assert!(elf.symbol_table().is_none());
+        assert!(elf.symbols().is_empty());
+    }
+
+    #[test]
+    pub fn check_target_test() {
+        let mut data = build_elf(0x1_0000, &[], &[]);
+
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(
+            elf.header
+                .check_target(Architecture::RISCV, BitWidth::Bit64),
+            Ok(())
+        );
+        assert_eq!(
+            elf.header
+                .check_target(Architecture::RISCV, BitWidth::Bit32),
+            Err(TargetMismatch::Class(BitWidth::Bit64))
+        );
+
+        // Rewrite the machine field to x86-64
+        data[18..20].copy_from_slice(&0x3Eu16.to_le_bytes());
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(
+            elf.header
+                .check_target(Architecture::RISCV, BitWidth::Bit64),
+            Err(TargetMismatch::Architecture(Architecture::X86_64))
+        );
+    }
+
+    #[test]
+    pub fn interpreter_test() {
+        let load = TestSegment {
+            header_type: 1,
+            flags: 0b101,
+            offset: 0,
+            virtual_addr: 0x1_0000,
+            file_size: 4,
+            memory_size: 4,
+        };
+
+        let data = build_elf(0x1_0000, &[load], &[0; 4]);
+        assert_eq!(Elf::parse(&data).unwrap().interpreter(), None);
+
+        let path = b"/lib/ld-linux-riscv64-lp64d.so.1\0";
+        let interpreter = TestSegment {
+            header_type: 3,
+            flags: 0b100,
+            offset: 4,
+            virtual_addr: 0x1_0004,
+            file_size: path.len() as u64,
+            memory_size: path.len() as u64,
+        };
+
+        let mut payload = alloc::vec![0; 4];
+        payload.extend_from_slice(path);
+
+        let data = build_elf(0x1_0000, &[interpreter, load], &payload);
+        assert_eq!(
+            Elf::parse(&data).unwrap().interpreter(),
+            Some("/lib/ld-linux-riscv64-lp64d.so.1")
+        );
+    }
+
+    #[test]
+    pub fn interpreter_malformed_test() {
+        let path = b"/lib/ld.so";
+        let interpreter = |file_size| TestSegment {
+            header_type: 3,
+            flags: 0b100,
+            offset: 0,
+            virtual_addr: 0x1_0000,
+            file_size,
+            memory_size: file_size,
+        };
+
+        // Missing the NUL terminator
+        let data = build_elf(0x1_0000, &[interpreter(path.len() as u64)], path);
+        assert_eq!(Elf::parse(&data).unwrap().interpreter(), None);
+
+        // Extends past the end of the file
+        let data = build_elf(0x1_0000, &[interpreter(0x1000)], path);
+        assert_eq!(Elf::parse(&data).unwrap().interpreter(), None);
+
+        // Not valid UTF-8
+        let data = build_elf(0x1_0000, &[interpreter(3)], b"\xff\xfe\0");
+        assert_eq!(Elf::parse(&data).unwrap().interpreter(), None);
+    }
 }
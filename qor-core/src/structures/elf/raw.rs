@@ -1,5 +1,7 @@
 use crate::utils::parser::Parser;
 
+use super::enums::Endian;
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RawElfHeader {
@@ -26,11 +28,22 @@ pub struct RawElfHeader {
 }
 
 impl RawElfHeader {
+    /// Parse an ELF header, switching `parser` to the byte order the header declares before the fields which depend on
+    /// it. The parser is left in that byte order, ready for the rest of the file.
     pub fn parse(parser: &mut Parser<'_>) -> Option<Self> {
+        let magic = parser.take_u8_array()?;
+        let class = parser.take_u8()?;
+        let data = parser.take_u8()?;
+
+        // An unsupported byte order is rejected when the header is validated, so the fields read here are unused
+        if let Ok(endian) = Endian::try_from(data) {
+            parser.set_byte_order(endian.into());
+        }
+
         Some(Self {
-            magic: parser.take_u8_array()?,
-            class: parser.take_u8()?,
-            data: parser.take_u8()?,
+            magic,
+            class,
+            data,
             version: parser.take_u8()?,
             os_abi: parser.take_u8()?,
             abi_version: parser.take_u8()?,
@@ -141,3 +154,44 @@ impl RawSectionHeader {
         })
     }
 }
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawSymbol {
+    pub st_name: u32,
+    pub st_info: u8,
+    pub st_other: u8,
+    pub st_shndx: u16,
+    pub st_value: u64,
+    pub st_size: u64,
+}
+
+impl RawSymbol {
+    /// Parse a symbol table entry as a 32-bit ELF File
+    pub fn parse32(parser: &mut Parser<'_>) -> Option<Self> {
+        let st_name = parser.take_u32()?;
+        let st_value = parser.take_u32()?.into();
+        let st_size = parser.take_u32()?.into();
+
+        Some(Self {
+            st_name,
+            st_info: parser.take_u8()?,
+            st_other: parser.take_u8()?,
+            st_shndx: parser.take_u16()?,
+            st_value,
+            st_size,
+        })
+    }
+
+    /// Parse a symbol table entry as a 64-bit ELF File
+    pub fn parse64(parser: &mut Parser<'_>) -> Option<Self> {
+        Some(Self {
+            st_name: parser.take_u32()?,
+            st_info: parser.take_u8()?,
+            st_other: parser.take_u8()?,
+            st_shndx: parser.take_u16()?,
+            st_value: parser.take_u64()?,
+            st_size: parser.take_u64()?,
+        })
+    }
+}
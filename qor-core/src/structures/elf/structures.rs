@@ -1,6 +1,7 @@
 use super::{
+    errors::{ElfParseError, TargetMismatch},
     enums::{Architecture, BitWidth, Endian, ObjectFileType, OsABI, ProgramHeaderType, SectionHeaderType},
-    raw::{RawElfHeader, RawProgramHeader, RawSectionHeader}, flags::{ProgramHeaderFlags, SectionHeaderFlags},
+    raw::{RawElfHeader, RawProgramHeader, RawSectionHeader, RawSymbol}, flags::{ProgramHeaderFlags, SectionHeaderFlags},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,22 +26,45 @@ pub struct ElfHeader {
     pub sh_str_index: u16,
 }
 
+impl ElfHeader {
+    /// Check that the file was built for the `machine` architecture with the word size `class`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the mismatched field if the file targets a different architecture or word size.
+    pub fn check_target(
+        &self,
+        machine: Architecture,
+        class: BitWidth,
+    ) -> Result<(), TargetMismatch> {
+        if self.machine != machine {
+            return Err(TargetMismatch::Architecture(self.machine));
+        }
+
+        if self.class != class {
+            return Err(TargetMismatch::Class(self.class));
+        }
+
+        Ok(())
+    }
+}
+
 impl core::convert::TryFrom<RawElfHeader> for ElfHeader {
-    type Error = ();
+    type Error = ElfParseError;
 
-    fn try_from(value: RawElfHeader) -> Result<Self, ()> {
+    fn try_from(value: RawElfHeader) -> Result<Self, ElfParseError> {
         if value.magic != [0x7F, 0x45, 0x4C, 0x46] {
-            return Err(());
+            return Err(ElfParseError::BadMagic);
         }
 
         Ok(Self {
-            class: BitWidth::try_from(value.class).map_err(|_| ())?,
-            endian: Endian::try_from(value.data).map_err(|_| ())?,
+            class: BitWidth::try_from(value.class).map_err(ElfParseError::UnsupportedClass)?,
+            endian: Endian::try_from(value.data).map_err(ElfParseError::UnsupportedEndian)?,
             version: value.version,
-            os_abi: OsABI::try_from(value.os_abi).map_err(|_| ())?,
+            os_abi: OsABI::from(value.os_abi),
             abi_version: value.abi_version,
-            elf_type: ObjectFileType::try_from(value.elf_type).map_err(|_| ())?,
-            machine: Architecture::try_from(value.machine).map_err(|_| ())?,
+            elf_type: ObjectFileType::from(value.elf_type),
+            machine: Architecture::from(value.machine),
             version2: value.version2,
             entry: value.entry,
             ph_offset: value.ph_offset,
@@ -85,6 +109,40 @@ impl core::convert::TryFrom<RawProgramHeader> for ProgramHeader {
     }
 }
 
+impl ProgramHeader {
+    /// Copy the segment described by this header out of the ELF file `data` into `destination`, the memory image of
+    /// the segment. Any bytes past the end of the file image (the `.bss` region when `memory_size > file_size`) are
+    /// explicitly zeroed rather than left with whatever the destination previously held.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `destination` is shorter than the memory size of the segment, the file image is
+    /// larger than the memory image, or the file image extends past the end of `data`.
+    pub fn load_segment(&self, data: &[u8], destination: &mut [u8]) {
+        let file_offset: usize = self.offset.try_into().unwrap();
+        let file_length: usize = self.file_size.try_into().unwrap();
+        let memory_length: usize = self.memory_size.try_into().unwrap();
+
+        assert!(
+            file_length <= memory_length,
+            "Segment file image is larger than its memory image"
+        );
+
+        destination[..file_length].copy_from_slice(&data[file_offset..file_offset + file_length]);
+        destination[file_length..memory_length].fill(0);
+    }
+
+    /// Get the bytes of this segment's file image from the ELF file `data`, returning `None` if the segment does not
+    /// lie within the file.
+    #[must_use]
+    pub fn data<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        let start = usize::try_from(self.offset).ok()?;
+        let length = usize::try_from(self.file_size).ok()?;
+
+        data.get(start..start.checked_add(length)?)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SectionHeader {
     name: u32,
@@ -99,6 +157,73 @@ pub struct SectionHeader {
     entry_size: u64,
 }
 
+impl SectionHeader {
+    /// Offset of the section's name within the section header string table
+    #[must_use]
+    pub const fn name_offset(&self) -> u32 {
+        self.name
+    }
+
+    #[must_use]
+    pub const fn section_type(&self) -> SectionHeaderType {
+        self.section_type
+    }
+
+    #[must_use]
+    pub const fn flags(&self) -> SectionHeaderFlags {
+        self.flags
+    }
+
+    #[must_use]
+    pub const fn virtual_addr(&self) -> u64 {
+        self.virtual_addr
+    }
+
+    /// Offset of the section's data within the file
+    #[must_use]
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Size of the section's data within the file
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Index of the section this section links to, the meaning of which depends on the section type
+    #[must_use]
+    pub const fn link(&self) -> u32 {
+        self.link
+    }
+
+    #[must_use]
+    pub const fn info(&self) -> u32 {
+        self.info
+    }
+
+    #[must_use]
+    pub const fn align(&self) -> u64 {
+        self.align
+    }
+
+    /// Size of each entry for sections holding a table of fixed size entries
+    #[must_use]
+    pub const fn entry_size(&self) -> u64 {
+        self.entry_size
+    }
+
+    /// Get the bytes of this section's data from the ELF file `data`, returning `None` if the section does not lie
+    /// within the file.
+    #[must_use]
+    pub fn data<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        let start = usize::try_from(self.offset).ok()?;
+        let length = usize::try_from(self.size).ok()?;
+
+        data.get(start..start.checked_add(length)?)
+    }
+}
+
 impl core::convert::TryFrom<RawSectionHeader> for SectionHeader {
     type Error = ();
 
@@ -116,4 +241,43 @@ impl core::convert::TryFrom<RawSectionHeader> for SectionHeader {
             entry_size: value.sh_entsize,
         })
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    /// Offset of the symbol's name within the string table linked to by the symbol table
+    pub name: u32,
+    pub value: u64,
+    pub size: u64,
+    pub info: u8,
+    pub other: u8,
+    /// Index of the section the symbol is defined relative to
+    pub section_index: u16,
+}
+
+impl Symbol {
+    /// Binding of the symbol (local, global, weak, ...), stored in the upper four bits of `info`
+    #[must_use]
+    pub const fn binding(&self) -> u8 {
+        self.info >> 4
+    }
+
+    /// Type of the symbol (object, function, section, ...), stored in the lower four bits of `info`
+    #[must_use]
+    pub const fn symbol_type(&self) -> u8 {
+        self.info & 0xF
+    }
+}
+
+impl core::convert::From<RawSymbol> for Symbol {
+    fn from(value: RawSymbol) -> Self {
+        Self {
+            name: value.st_name,
+            value: value.st_value,
+            size: value.st_size,
+            info: value.st_info,
+            other: value.st_other,
+            section_index: value.st_shndx,
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use core::ops::Range;
+
+/// Magic number at the start of every flattened device tree
+pub const FDT_MAGIC: u32 = 0xd00d_feed;
+
+/// Device tree format version this parser reads, the first to give the size of the structure block
+const SUPPORTED_VERSION: u32 = 17;
+
+/// Size of the header, up to and including the size of the structure block
+const HEADER_SIZE: usize = 40;
+
+/// Deepest nesting of nodes which can be walked
+const MAX_DEPTH: usize = 16;
+
+// Structure block tokens
+const FDT_BEGIN_NODE: u32 = 1;
+const FDT_END_NODE: u32 = 2;
+const FDT_PROP: u32 = 3;
+const FDT_NOP: u32 = 4;
+const FDT_END: u32 = 9;
+
+/// Errors which can occur while parsing a flattened device tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdtError {
+    /// The blob does not start with [`FDT_MAGIC`]
+    BadMagic(u32),
+    /// The blob is in a format incompatible with version 17
+    UnsupportedVersion(u32),
+    /// The blob is shorter than its header claims
+    Truncated,
+    /// The structure block is not a valid tree of nodes and properties
+    Malformed,
+}
+
+/// Big endian `u32` at `offset` in `data`
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+/// Null terminated string at the start of `data`
+fn read_str(data: &[u8]) -> Option<&str> {
+    let length = data.iter().position(|byte| *byte == 0)?;
+    core::str::from_utf8(&data[..length]).ok()
+}
+
+/// Single token of the structure block
+enum Token<'a> {
+    BeginNode(&'a str),
+    EndNode,
+    Property(&'a str, &'a [u8]),
+    End,
+}
+
+/// Flattened device tree, as handed to the kernel by the firmware
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceTree<'a> {
+    structure: &'a [u8],
+    strings: &'a [u8],
+}
+
+impl<'a> DeviceTree<'a> {
+    /// Parse the flattened device tree in `data`, checking that the structure block is well formed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `data` does not hold a valid device tree of a supported version.
+    pub fn parse(data: &'a [u8]) -> Result<Self, FdtError> {
+        let header = |index: usize| read_u32(data, index * 4).ok_or(FdtError::Truncated);
+
+        let magic = header(0)?;
+        if magic != FDT_MAGIC {
+            return Err(FdtError::BadMagic(magic));
+        }
+
+        let version = header(5)?;
+        if version < SUPPORTED_VERSION || header(6)? > SUPPORTED_VERSION {
+            return Err(FdtError::UnsupportedVersion(version));
+        }
+
+        let block = |offset: u32, size: u32| {
+            data.get(offset as usize..offset as usize + size as usize)
+                .ok_or(FdtError::Truncated)
+        };
+        let tree = Self {
+            structure: block(header(2)?, header(9)?)?,
+            strings: block(header(3)?, header(8)?)?,
+        };
+
+        tree.validate()?;
+
+        Ok(tree)
+    }
+
+    /// Parse the flattened device tree at `address`, such as the one passed by the firmware in `a1` at boot.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to a device tree blob, or at least to readable memory as long as its header, which is not
+    /// modified while the returned tree is in use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `address` does not hold a valid device tree of a supported version.
+    pub unsafe fn from_raw(address: *const u8) -> Result<DeviceTree<'static>, FdtError> {
+        // Safety: The caller guarantees the header is readable
+        let header = unsafe { core::slice::from_raw_parts(address, 8) };
+
+        let magic = read_u32(header, 0).ok_or(FdtError::Truncated)?;
+        if magic != FDT_MAGIC {
+            return Err(FdtError::BadMagic(magic));
+        }
+
+        let total_size = read_u32(header, 4).ok_or(FdtError::Truncated)? as usize;
+        if total_size < HEADER_SIZE {
+            return Err(FdtError::Truncated);
+        }
+
+        // Safety: The magic number matched, so the caller guarantees the whole blob is readable
+        DeviceTree::parse(unsafe { core::slice::from_raw_parts(address, total_size) })
+    }
+
+    /// Read the token at `offset` in the structure block, returning it along with the offset of the next token. Any
+    /// `FDT_NOP` tokens are skipped.
+    fn token(&self, mut offset: usize) -> Option<(Token<'a>, usize)> {
+        loop {
+            let tag = read_u32(self.structure, offset)?;
+            offset += 4;
+
+            return Some(match tag {
+                FDT_BEGIN_NODE => {
+                    let name = read_str(self.structure.get(offset..)?)?;
+                    (
+                        Token::BeginNode(name),
+                        (offset + name.len() + 1).next_multiple_of(4),
+                    )
+                }
+                FDT_END_NODE => (Token::EndNode, offset),
+                FDT_PROP => {
+                    let length = read_u32(self.structure, offset)? as usize;
+                    let name_offset = read_u32(self.structure, offset + 4)? as usize;
+
+                    let name = read_str(self.strings.get(name_offset..)?)?;
+                    let value = self.structure.get(offset + 8..offset + 8 + length)?;
+                    (
+                        Token::Property(name, value),
+                        (offset + 8 + length).next_multiple_of(4),
+                    )
+                }
+                FDT_NOP => continue,
+                FDT_END => (Token::End, offset),
+                _ => return None,
+            });
+        }
+    }
+
+    /// Walk the whole structure block, checking it holds a single root node nested no deeper than [`MAX_DEPTH`].
+    fn validate(&self) -> Result<(), FdtError> {
+        let mut offset = 0;
+        let mut depth = 0;
+        let mut seen_root = false;
+
+        loop {
+            let (token, next) = self.token(offset).ok_or(FdtError::Malformed)?;
+
+            match token {
+                Token::BeginNode(_) if depth < MAX_DEPTH && (depth > 0 || !seen_root) => {
+                    depth += 1;
+                    seen_root = true;
+                }
+                Token::EndNode if depth > 0 => depth -= 1,
+                Token::Property(..) if depth > 0 => {}
+                Token::End if depth == 0 && seen_root => return Ok(()),
+                _ => return Err(FdtError::Malformed),
+            }
+
+            offset = next;
+        }
+    }
+
+    /// Iterate over every node with a `compatible` property, in the order they appear in the tree.
+    #[must_use]
+    pub const fn devices(&self) -> Devices<'a> {
+        Devices {
+            tree: *self,
+            offset: 0,
+            depth: 0,
+            cells: [(2, 1); MAX_DEPTH + 1],
+        }
+    }
+
+    /// Find the first device compatible with `compatible`.
+    #[must_use]
+    pub fn find_compatible(&self, compatible: &str) -> Option<Device<'a>> {
+        self.devices()
+            .find(|device| device.is_compatible(compatible))
+    }
+}
+
+/// Big endian cells of a property value, such as the interrupts of a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cells<'a>(&'a [u8]);
+
+impl Iterator for Cells<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = read_u32(self.0, 0)?;
+        self.0 = &self.0[4..];
+
+        Some(cell)
+    }
+}
+
+/// Node of the device tree which describes a device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device<'a> {
+    /// Name of the node, such as `serial@10000000`
+    pub name: &'a str,
+    compatible: &'a [u8],
+    /// First register range of the device, if it has any and its address and size each fit in two cells
+    pub reg: Option<Range<u64>>,
+    /// Interrupts raised by the device, given by its `interrupts` property
+    pub interrupts: Cells<'a>,
+}
+
+impl<'a> Device<'a> {
+    /// Iterate over the entries of the `compatible` property, most specific first
+    pub fn compatible(&self) -> impl Iterator<Item = &'a str> {
+        self.compatible
+            .split(|byte| *byte == 0)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| core::str::from_utf8(entry).ok())
+    }
+
+    /// Returns true if any entry of the `compatible` property matches `compatible`
+    #[must_use]
+    pub fn is_compatible(&self, compatible: &str) -> bool {
+        self.compatible().any(|entry| entry == compatible)
+    }
+}
+
+/// Iterator over the devices of a [`DeviceTree`]
+pub struct Devices<'a> {
+    tree: DeviceTree<'a>,
+    offset: usize,
+    depth: usize,
+    /// `#address-cells` and `#size-cells` given to the children of the node at each depth
+    cells: [(u32, u32); MAX_DEPTH + 1],
+}
+
+/// Combine the big endian cells at the start of `data` into a single value, returning it along with the remaining
+/// data. Values wider than two cells are not supported.
+fn read_cells(data: &[u8], cells: u32) -> Option<(u64, &[u8])> {
+    if cells > 2 {
+        return None;
+    }
+
+    let mut value = 0;
+    for index in 0..cells as usize {
+        value = (value << 32) | u64::from(read_u32(data, index * 4)?);
+    }
+
+    Some((value, &data[cells as usize * 4..]))
+}
+
+impl<'a> Iterator for Devices<'a> {
+    type Item = Device<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (token, next) = self.tree.token(self.offset)?;
+            self.offset = next;
+
+            let name = match token {
+                Token::BeginNode(name) => name,
+                Token::EndNode => {
+                    self.depth -= 1;
+                    continue;
+                }
+                Token::Property(..) => continue,
+                Token::End => return None,
+            };
+
+            self.depth += 1;
+            self.cells[self.depth] = (2, 1);
+
+            // Properties always come before the children of a node
+            let mut compatible = None;
+            let mut reg = None;
+            let mut interrupts = Cells(&[]);
+            while let Some((Token::Property(property, value), next)) = self.tree.token(self.offset)
+            {
+                self.offset = next;
+
+                match property {
+                    "compatible" => compatible = Some(value),
+                    "reg" => reg = Some(value),
+                    "interrupts" => interrupts = Cells(value),
+                    "#address-cells" => self.cells[self.depth].0 = read_u32(value, 0)?,
+                    "#size-cells" => self.cells[self.depth].1 = read_u32(value, 0)?,
+                    _ => {}
+                }
+            }
+
+            if let Some(compatible) = compatible {
+                let (address_cells, size_cells) = self.cells[self.depth - 1];
+                let reg = reg.and_then(|reg| {
+                    let (base, reg) = read_cells(reg, address_cells)?;
+                    let (size, _) = read_cells(reg, size_cells)?;
+                    Some(base..base.checked_add(size)?)
+                });
+
+                return Some(Device {
+                    name,
+                    compatible,
+                    reg,
+                    interrupts,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{DeviceTree, FdtError};
+
+    /// Device tree laid out as QEMU generates it for a single hart `virt` machine
+    const QEMU_VIRT: &[u8] = include_bytes!("qemu_virt.dtb");
+
+    #[test]
+    pub fn qemu_virt_test() {
+        let tree = DeviceTree::parse(QEMU_VIRT).unwrap();
+
+        let uart = tree.find_compatible("ns16550a").unwrap();
+        assert_eq!(uart.name, "serial@10000000");
+        assert_eq!(uart.reg, Some(0x1000_0000..0x1000_0100));
+        assert_eq!(uart.interrupts.collect::<Vec<_>>(), [10]);
+
+        // Later entries of the compatible list also match
+        let clint = tree.find_compatible("riscv,clint0").unwrap();
+        assert_eq!(
+            clint.compatible().collect::<Vec<_>>(),
+            ["sifive,clint0", "riscv,clint0"]
+        );
+        assert_eq!(clint.reg, Some(0x200_0000..0x201_0000));
+        assert_eq!(clint.interrupts.count(), 0);
+
+        let virtio = tree
+            .devices()
+            .filter(|device| device.is_compatible("virtio,mmio"))
+            .map(|device| {
+                (
+                    device.reg.unwrap().start,
+                    device.interrupts.collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            virtio,
+            (1..=8)
+                .rev()
+                .map(|index| (
+                    0x1000_0000 + index * 0x1000,
+                    vec![u32::try_from(index).unwrap()]
+                ))
+                .collect::<Vec<_>>()
+        );
+
+        // The CPU is addressed with one cell and no size
+        let cpu = tree.find_compatible("riscv").unwrap();
+        assert_eq!(cpu.name, "cpu@0");
+        assert_eq!(cpu.reg, Some(0..0));
+    }
+
+    #[test]
+    pub fn malformed_test() {
+        assert_eq!(
+            DeviceTree::parse(&[0; 64]).unwrap_err(),
+            FdtError::BadMagic(0)
+        );
+        assert_eq!(
+            DeviceTree::parse(&QEMU_VIRT[..100]).unwrap_err(),
+            FdtError::Truncated
+        );
+
+        // Drop the final `FDT_END_NODE` of the root node
+        let mut blob = QEMU_VIRT.to_vec();
+        let structure_end = u32::from_be_bytes(blob[8..12].try_into().unwrap()) as usize
+            + u32::from_be_bytes(blob[36..40].try_into().unwrap()) as usize;
+        blob[structure_end - 8..structure_end - 4].copy_from_slice(&4u32.to_be_bytes());
+        assert_eq!(DeviceTree::parse(&blob).unwrap_err(), FdtError::Malformed);
+    }
+}
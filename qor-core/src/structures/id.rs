@@ -20,6 +20,25 @@ impl core::convert::From<u16> for ProcessID {
     }
 }
 
+impl ProcessID {
+    /// Get the PID of the process whose address space is tagged with `asid`. Each process's page table is installed
+    /// with its PID as the address space identifier, so the mapping is the identity.
+    ///
+    /// The ASID field of an Sv39 `satp` is 16 bits wide, so every PID fits, though harts implementing fewer ASID bits
+    /// will only keep the low bits of the PIDs they are given.
+    #[must_use]
+    pub const fn from_asid(asid: u16) -> Self {
+        Self(asid)
+    }
+
+    /// Get the address space identifier to install this process's page table with, the inverse of
+    /// [`ProcessID::from_asid`].
+    #[must_use]
+    pub const fn asid(self) -> u16 {
+        self.0
+    }
+}
+
 pub type PID = ProcessID;
 
 /// User Identifier
@@ -0,0 +1,164 @@
+/// Signature held in the last two bytes of a master boot record
+pub const MBR_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+
+/// Offset of the first partition entry within the master boot record
+const PARTITION_TABLE_OFFSET: usize = 0x1BE;
+/// Size of each partition entry
+const PARTITION_ENTRY_SIZE: usize = 16;
+
+/// Errors which can occur while parsing a master boot record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MbrError {
+    /// The sector does not end in [`MBR_SIGNATURE`], so does not hold a partition table
+    BadSignature([u8; 2]),
+    /// The partition entry with the given index, counting from zero, ends past the last sector a 32 bit LBA can
+    /// address
+    PartitionOutOfRange(usize),
+}
+
+/// A primary partition described by a master boot record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partition {
+    /// Partition type identifier, such as `0x83` for a Linux file system
+    pub partition_type: u8,
+    /// Whether the partition is marked as the one to boot from
+    pub bootable: bool,
+    /// First 512 byte sector of the partition
+    pub start_lba: u32,
+    /// Length of the partition in 512 byte sectors
+    pub sector_count: u32,
+}
+
+impl Partition {
+    /// Parse a 16 byte partition entry, returning `None` if the entry is unused
+    ///
+    /// # Errors
+    ///
+    /// Returns `PartitionOutOfRange` with the given `index` if the partition ends past the last addressable sector.
+    fn parse(index: usize, entry: &[u8]) -> Result<Option<Self>, MbrError> {
+        let word = |offset: usize| {
+            u32::from_le_bytes([
+                entry[offset],
+                entry[offset + 1],
+                entry[offset + 2],
+                entry[offset + 3],
+            ])
+        };
+
+        let partition = Self {
+            partition_type: entry[4],
+            bootable: entry[0] & 0x80 != 0,
+            start_lba: word(8),
+            sector_count: word(12),
+        };
+
+        if partition.partition_type == 0 || partition.sector_count == 0 {
+            return Ok(None);
+        }
+
+        if partition
+            .start_lba
+            .checked_add(partition.sector_count)
+            .is_none()
+        {
+            return Err(MbrError::PartitionOutOfRange(index));
+        }
+
+        Ok(Some(partition))
+    }
+}
+
+/// Parse the four primary partition entries of the master boot record in `sector`, the first sector of a device.
+/// Unused entries are `None`.
+///
+/// # Errors
+///
+/// Returns `BadSignature` if the sector does not hold a master boot record, and `PartitionOutOfRange` if a partition
+/// runs past the last sector a 32 bit LBA can address.
+pub fn parse_partition_table(sector: &[u8; 512]) -> Result<[Option<Partition>; 4], MbrError> {
+    let signature = [sector[510], sector[511]];
+    if signature != MBR_SIGNATURE {
+        return Err(MbrError::BadSignature(signature));
+    }
+
+    let mut partitions = [None; 4];
+    for (index, partition) in partitions.iter_mut().enumerate() {
+        let offset = PARTITION_TABLE_OFFSET + index * PARTITION_ENTRY_SIZE;
+        *partition = Partition::parse(index, &sector[offset..offset + PARTITION_ENTRY_SIZE])?;
+    }
+
+    Ok(partitions)
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{parse_partition_table, MbrError, Partition};
+
+    /// Write a partition entry into the table of `sector`
+    fn write_entry(sector: &mut [u8; 512], index: usize, status: u8, partition: (u8, u32, u32)) {
+        let entry = &mut sector[0x1BE + index * 16..0x1BE + (index + 1) * 16];
+        entry[0] = status;
+        entry[4] = partition.0;
+        entry[8..12].copy_from_slice(&partition.1.to_le_bytes());
+        entry[12..16].copy_from_slice(&partition.2.to_le_bytes());
+    }
+
+    #[test]
+    pub fn parse_partition_table_test() {
+        let mut sector = [0; 512];
+        sector[510..].copy_from_slice(&[0x55, 0xAA]);
+        write_entry(&mut sector, 0, 0x80, (0x83, 2048, 0x1_0000));
+        write_entry(&mut sector, 2, 0, (0x0C, 0x1_0800, 0x2000));
+        // A type without any sectors is not a partition
+        write_entry(&mut sector, 3, 0, (0x83, 0x1_2800, 0));
+
+        assert_eq!(
+            parse_partition_table(&sector),
+            Ok([
+                Some(Partition {
+                    partition_type: 0x83,
+                    bootable: true,
+                    start_lba: 2048,
+                    sector_count: 0x1_0000,
+                }),
+                None,
+                Some(Partition {
+                    partition_type: 0x0C,
+                    bootable: false,
+                    start_lba: 0x1_0800,
+                    sector_count: 0x2000,
+                }),
+                None,
+            ])
+        );
+    }
+
+    #[test]
+    pub fn bad_signature_test() {
+        let mut sector = [0; 512];
+        write_entry(&mut sector, 0, 0x80, (0x83, 2048, 0x1_0000));
+
+        assert_eq!(
+            parse_partition_table(&sector),
+            Err(MbrError::BadSignature([0, 0]))
+        );
+    }
+
+    #[test]
+    pub fn partition_out_of_range_test() {
+        let mut sector = [0; 512];
+        sector[510..].copy_from_slice(&[0x55, 0xAA]);
+        write_entry(&mut sector, 0, 0, (0x83, 2048, 0x1_0000));
+
+        // Ending exactly at the last addressable sector is allowed
+        write_entry(&mut sector, 1, 0, (0x83, 0xFFFF_0000, 0xFFFF));
+        assert!(parse_partition_table(&sector).is_ok());
+
+        write_entry(&mut sector, 1, 0, (0x83, 0xFFFF_0000, 0x1_0000));
+        assert_eq!(
+            parse_partition_table(&sector),
+            Err(MbrError::PartitionOutOfRange(1))
+        );
+    }
+}
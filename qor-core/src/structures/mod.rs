@@ -1,5 +1,14 @@
+pub mod boot_config;
 pub mod elf;
+pub mod fdt;
 pub mod id;
+pub mod mbr;
 pub mod mem;
+pub mod process;
+pub mod program_break;
+pub mod region;
+pub mod stack;
+pub mod stat;
 pub mod syscall_error;
-pub mod time;
\ No newline at end of file
+pub mod time;
+pub mod transfer;
\ No newline at end of file
@@ -0,0 +1,279 @@
+use alloc::collections::BTreeMap;
+
+use super::id::{HartID, PID};
+
+/// Scheduling state of a process.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    /// Ready to run, but not currently executing on any hart
+    Active,
+    /// Currently executing on the given hart
+    Running(HartID),
+    Sleeping,
+    Waiting,
+    Terminated,
+}
+
+impl ProcessState {
+    /// Returns true if a process in this state can be switched to by the scheduler
+    #[must_use]
+    pub const fn is_runnable(self) -> bool {
+        matches!(self, Self::Active | Self::Running(_))
+    }
+}
+
+/// A process as seen by the scheduler, which only needs its identity and scheduling state.
+pub trait Schedulable {
+    /// Get the PID of the process
+    fn pid(&self) -> PID;
+
+    /// Get the scheduling state of the process
+    fn state(&self) -> ProcessState;
+
+    /// Change the scheduling state of the process
+    fn set_state(&mut self, state: ProcessState);
+}
+
+/// Find the next runnable process after `cursor` in PID order, wrapping around to the start of the table.
+///
+/// The process at the cursor itself is only chosen if no other process can run. Returns `None` if no process is
+/// runnable.
+pub fn next_runnable<P: Schedulable>(table: &BTreeMap<PID, P>, cursor: Option<PID>) -> Option<PID> {
+    let runnable = |proc: &&P| proc.state().is_runnable();
+
+    let after_cursor = cursor.and_then(|cursor| {
+        table
+            .range((
+                core::ops::Bound::Excluded(cursor),
+                core::ops::Bound::Unbounded,
+            ))
+            .map(|(_, proc)| proc)
+            .find(runnable)
+    });
+
+    after_cursor
+        .or_else(|| table.values().find(runnable))
+        .map(Schedulable::pid)
+}
+
+/// Mark the process `pid` as running on `hart`, returning any other process which was running on that hart to
+/// `Active`. Returns false, leaving the table untouched, if there is no such process.
+pub fn mark_running<P: Schedulable>(table: &mut BTreeMap<PID, P>, pid: PID, hart: HartID) -> bool {
+    if !table.contains_key(&pid) {
+        return false;
+    }
+
+    for proc in table.values_mut() {
+        if proc.pid() == pid {
+            proc.set_state(ProcessState::Running(hart));
+        } else if proc.state() == ProcessState::Running(hart) {
+            proc.set_state(ProcessState::Active);
+        }
+    }
+
+    true
+}
+
+/// Choose the process to run next on `hart` for one scheduling tick, in round robin order after `cursor`, and mark it
+/// as running there.
+///
+/// The cursor is moved to the chosen process. Returns `None`, leaving the table and cursor untouched, if no process is
+/// runnable.
+pub fn schedule_next<P: Schedulable>(
+    table: &mut BTreeMap<PID, P>,
+    cursor: &mut Option<PID>,
+    hart: HartID,
+) -> Option<PID> {
+    let next = next_runnable(table, *cursor)?;
+    mark_running(table, next, hart);
+    *cursor = Some(next);
+
+    Some(next)
+}
+
+/// Write a line for each process in `table` to `writer` for a postmortem, with its state and the parent given by
+/// `parent`.
+///
+/// The table is never waited on, as the code which panicked may be holding it, so a locked table is reported as
+/// unavailable instead. Nothing is allocated.
+///
+/// # Errors
+///
+/// Returns an error if `writer` fails.
+pub fn write_process_table<P: Schedulable>(
+    writer: &mut impl core::fmt::Write,
+    table: &crate::sync::Mutex<BTreeMap<PID, P>>,
+    parent: impl Fn(&P) -> Option<PID>,
+) -> core::fmt::Result {
+    let Some(table) = table.try_lock() else {
+        return writeln!(writer, "Process table: unavailable");
+    };
+
+    writeln!(writer, "Process table: {} processes", table.len())?;
+    for (pid, proc) in table.iter() {
+        writeln!(
+            writer,
+            "  {:?}: {:?}, parent {:?}",
+            pid,
+            proc.state(),
+            parent(proc)
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::collections::BTreeMap;
+
+    use super::{
+        mark_running, next_runnable, schedule_next, write_process_table, ProcessState, Schedulable,
+    };
+    use crate::structures::id::{HartID, PID};
+
+    struct TestProcess(PID, ProcessState);
+
+    impl Schedulable for TestProcess {
+        fn pid(&self) -> PID {
+            self.0
+        }
+
+        fn state(&self) -> ProcessState {
+            self.1
+        }
+
+        fn set_state(&mut self, state: ProcessState) {
+            self.1 = state;
+        }
+    }
+
+    fn table(states: &[ProcessState]) -> BTreeMap<PID, TestProcess> {
+        (1..)
+            .zip(states)
+            .map(|(pid, state)| (PID::from(pid), TestProcess(PID::from(pid), *state)))
+            .collect()
+    }
+
+    fn running_on(table: &BTreeMap<PID, TestProcess>, hart: HartID) -> alloc::vec::Vec<PID> {
+        table
+            .values()
+            .filter(|proc| proc.state() == ProcessState::Running(hart))
+            .map(Schedulable::pid)
+            .collect()
+    }
+
+    #[test]
+    pub fn next_runnable_test() {
+        let mut table = table(&[
+            ProcessState::Active,
+            ProcessState::Sleeping,
+            ProcessState::Active,
+            ProcessState::Terminated,
+        ]);
+
+        assert_eq!(next_runnable(&table, None), Some(PID::from(1)));
+        assert_eq!(
+            next_runnable(&table, Some(PID::from(1))),
+            Some(PID::from(3))
+        );
+
+        // Wraps around past the end of the table, skipping processes which can not run
+        assert_eq!(
+            next_runnable(&table, Some(PID::from(3))),
+            Some(PID::from(1))
+        );
+
+        // The process at the cursor is only chosen again if it is the only one left
+        table.get_mut(&PID::from(1)).unwrap().1 = ProcessState::Waiting;
+        assert_eq!(
+            next_runnable(&table, Some(PID::from(3))),
+            Some(PID::from(3))
+        );
+
+        table.get_mut(&PID::from(3)).unwrap().1 = ProcessState::Terminated;
+        assert_eq!(next_runnable(&table, Some(PID::from(3))), None);
+    }
+
+    #[test]
+    pub fn one_running_per_hart_test() {
+        let mut table = table(&[ProcessState::Active; 4]);
+
+        assert!(mark_running(&mut table, PID::from(1), HartID(0)));
+        assert!(mark_running(&mut table, PID::from(2), HartID(1)));
+        assert_eq!(running_on(&table, HartID(0)), [PID::from(1)]);
+        assert_eq!(running_on(&table, HartID(1)), [PID::from(2)]);
+
+        // Switching hart 0 to another process returns its previous one to `Active`, and leaves hart 1 alone
+        assert!(mark_running(&mut table, PID::from(3), HartID(0)));
+        assert_eq!(running_on(&table, HartID(0)), [PID::from(3)]);
+        assert_eq!(running_on(&table, HartID(1)), [PID::from(2)]);
+        assert_eq!(table[&PID::from(1)].state(), ProcessState::Active);
+        assert_eq!(table[&PID::from(4)].state(), ProcessState::Active);
+
+        // An unknown process leaves the table untouched
+        assert!(!mark_running(&mut table, PID::from(9), HartID(0)));
+        assert_eq!(running_on(&table, HartID(0)), [PID::from(3)]);
+    }
+
+    #[test]
+    pub fn busy_processes_interleave_test() {
+        let mut table = table(&[ProcessState::Active, ProcessState::Active]);
+        let mut cursor = None;
+
+        // Neither process ever gives up the hart, so each timer tick switches to the other
+        let order = (0..6)
+            .map(|_| schedule_next(&mut table, &mut cursor, HartID(0)).unwrap())
+            .collect::<alloc::vec::Vec<_>>();
+        assert_eq!(order, [1, 2, 1, 2, 1, 2].map(PID::from));
+        assert_eq!(running_on(&table, HartID(0)), [PID::from(2)]);
+        assert_eq!(table[&PID::from(1)].state(), ProcessState::Active);
+
+        // Once one exits the other keeps the hart on every tick
+        table.get_mut(&PID::from(1)).unwrap().1 = ProcessState::Terminated;
+        for _ in 0..2 {
+            assert_eq!(
+                schedule_next(&mut table, &mut cursor, HartID(0)),
+                Some(PID::from(2))
+            );
+        }
+
+        table.get_mut(&PID::from(2)).unwrap().1 = ProcessState::Terminated;
+        assert_eq!(schedule_next(&mut table, &mut cursor, HartID(0)), None);
+        assert_eq!(cursor, Some(PID::from(2)));
+    }
+
+    #[test]
+    pub fn write_process_table_test() {
+        use std::string::String;
+
+        let table = crate::sync::Mutex::new(table(&[
+            ProcessState::Running(HartID(0)),
+            ProcessState::Terminated,
+        ]));
+        let parent = |proc: &TestProcess| (proc.0 != PID::from(1)).then(|| PID::from(1));
+
+        let mut dump = String::new();
+        write_process_table(&mut dump, &table, parent).unwrap();
+        assert!(dump.starts_with("Process table: 2 processes\n"));
+        assert_eq!(dump.lines().count(), 3);
+        assert!(dump
+            .lines()
+            .nth(2)
+            .unwrap()
+            .contains("Terminated, parent Some("));
+
+        // A table held elsewhere is skipped rather than waited on
+        let guard = table.try_lock().unwrap();
+        let mut dump = String::new();
+        write_process_table(&mut dump, &table, parent).unwrap();
+        assert_eq!(dump, "Process table: unavailable\n");
+
+        drop(guard);
+        let mut dump = String::new();
+        write_process_table(&mut dump, &table, parent).unwrap();
+        assert_eq!(dump.lines().count(), 3);
+    }
+}
@@ -0,0 +1,159 @@
+/// Errors which can occur while moving a [`ProgramBreak`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramBreakError {
+    /// The break would move below the start of the heap
+    BelowStart,
+    /// The heap would grow past its limit, into the region reserved for the stack
+    Collision,
+}
+
+/// Result of moving a [`ProgramBreak`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakAdjustment {
+    /// The break before it was moved
+    pub previous: u64,
+    /// Page aligned range of addresses which the heap now covers but were not mapped before, empty if the existing
+    /// pages already cover the new break
+    pub new_pages: core::ops::Range<u64>,
+}
+
+/// The program break of a process, the end of its heap.
+///
+/// The heap starts on the first page boundary above the process's loaded segments and grows upwards. Pages are never
+/// released when the break moves down, so the pages up to the highest break so far stay mapped and are reused when it
+/// grows again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramBreak<const PAGE_SIZE: usize> {
+    start: u64,
+    current: u64,
+    mapped_end: u64,
+}
+
+impl<const PAGE_SIZE: usize> ProgramBreak<PAGE_SIZE> {
+    /// Construct an empty heap starting at the first page boundary at or above `start`
+    #[must_use]
+    pub const fn new(start: u64) -> Self {
+        let start = start.next_multiple_of(PAGE_SIZE as u64);
+
+        Self {
+            start,
+            current: start,
+            mapped_end: start,
+        }
+    }
+
+    /// Get the address the heap starts at
+    #[must_use]
+    pub const fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Get the current break
+    #[must_use]
+    pub const fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Move the break by `delta` bytes, without letting the heap's pages extend past `limit`. On success, the pages
+    /// in the returned [`BreakAdjustment::new_pages`] must be mapped before the heap is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BelowStart` if the break would move below the start of the heap, and `Collision` if the pages needed
+    /// to cover the new break would extend past `limit`. The break is left unchanged on error.
+    pub fn adjust(&mut self, delta: i64, limit: u64) -> Result<BreakAdjustment, ProgramBreakError> {
+        let new_break = self
+            .current
+            .checked_add_signed(delta)
+            .ok_or(ProgramBreakError::Collision)?;
+
+        if new_break < self.start {
+            return Err(ProgramBreakError::BelowStart);
+        }
+
+        let new_end = new_break
+            .checked_next_multiple_of(PAGE_SIZE as u64)
+            .filter(|end| *end <= limit)
+            .ok_or(ProgramBreakError::Collision)?;
+
+        let new_pages = self.mapped_end..new_end.max(self.mapped_end);
+        let previous = self.current;
+
+        self.current = new_break;
+        self.mapped_end = new_pages.end;
+
+        Ok(BreakAdjustment {
+            previous,
+            new_pages,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{BreakAdjustment, ProgramBreak, ProgramBreakError};
+
+    const LIMIT: u64 = 0x1_0000;
+
+    #[test]
+    pub fn incremental_growth_test() {
+        let mut heap = ProgramBreak::<0x1000>::new(0x4321);
+        assert_eq!(heap.start(), 0x5000);
+
+        // Querying the break does not map anything
+        assert_eq!(
+            heap.adjust(0, LIMIT),
+            Ok(BreakAdjustment {
+                previous: 0x5000,
+                new_pages: 0x5000..0x5000
+            })
+        );
+
+        assert_eq!(heap.adjust(0x10, LIMIT).unwrap().new_pages, 0x5000..0x6000);
+        assert_eq!(heap.adjust(0x10, LIMIT).unwrap().new_pages, 0x6000..0x6000);
+
+        let grown = heap.adjust(0x2000, LIMIT).unwrap();
+        assert_eq!(grown.previous, 0x5020);
+        assert_eq!(grown.new_pages, 0x6000..0x8000);
+        assert_eq!(heap.current(), 0x7020);
+    }
+
+    #[test]
+    pub fn shrink_reuses_pages_test() {
+        let mut heap = ProgramBreak::<0x1000>::new(0x5000);
+        heap.adjust(0x3000, LIMIT).unwrap();
+
+        assert_eq!(heap.adjust(-0x2800, LIMIT).unwrap().previous, 0x8000);
+        assert_eq!(heap.current(), 0x5800);
+
+        // The pages kept from before the shrink cover the regrown heap
+        assert_eq!(
+            heap.adjust(0x2000, LIMIT).unwrap().new_pages,
+            0x8000..0x8000
+        );
+
+        assert_eq!(
+            heap.adjust(-0x4000, LIMIT),
+            Err(ProgramBreakError::BelowStart)
+        );
+        assert_eq!(heap.current(), 0x7800);
+    }
+
+    #[test]
+    pub fn collision_test() {
+        let mut heap = ProgramBreak::<0x1000>::new(0xe000);
+
+        assert_eq!(
+            heap.adjust(0x2001, LIMIT),
+            Err(ProgramBreakError::Collision)
+        );
+        assert_eq!(heap.current(), 0xe000);
+
+        assert_eq!(heap.adjust(0x2000, LIMIT).unwrap().new_pages, 0xe000..LIMIT);
+        assert_eq!(
+            heap.adjust(i64::MAX, LIMIT),
+            Err(ProgramBreakError::Collision)
+        );
+    }
+}
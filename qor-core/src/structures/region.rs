@@ -0,0 +1,132 @@
+use core::ops::Range;
+
+/// Returns true if the two half open ranges share at least one address
+#[must_use]
+pub const fn overlaps(a: &Range<u64>, b: &Range<u64>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Round `length` up to a multiple of `alignment`, such as a whole number of pages.
+///
+/// Returns `None` if the rounded length would be larger than `limit`, or `alignment` is zero. The limit is checked
+/// before rounding, so a length close to `u64::MAX` can not overflow.
+#[must_use]
+pub const fn aligned_length(length: u64, alignment: u64, limit: u64) -> Option<u64> {
+    if length > limit {
+        return None;
+    }
+
+    match length.checked_next_multiple_of(alignment) {
+        Some(rounded) if rounded <= limit => Some(rounded),
+        _ => None,
+    }
+}
+
+/// Find the lowest `alignment` aligned address in `window` at which `length` bytes fit without overlapping any of the
+/// `used` ranges.
+///
+/// Returns `None` if there is no such gap or `alignment` is zero. The `used` ranges may be given in any order.
+#[must_use]
+pub fn find_free_region(
+    used: &(impl Iterator<Item = Range<u64>> + Clone),
+    window: Range<u64>,
+    length: u64,
+    alignment: u64,
+) -> Option<u64> {
+    let mut candidate = window.start.checked_next_multiple_of(alignment)?;
+
+    loop {
+        let end = candidate.checked_add(length)?;
+        if end > window.end {
+            return None;
+        }
+
+        let region = candidate..end;
+
+        // Skip past every range in the way, any range overlapping the candidate ends after it starts, so the candidate
+        // always moves forward
+        match used
+            .clone()
+            .filter(|range| overlaps(range, &region))
+            .map(|range| range.end)
+            .max()
+        {
+            Some(blocked_until) => candidate = blocked_until.checked_next_multiple_of(alignment)?,
+            None => return Some(candidate),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{aligned_length, find_free_region, overlaps};
+
+    #[test]
+    pub fn overlaps_test() {
+        assert!(overlaps(&(0..10), &(9..20)));
+        assert!(overlaps(&(5..6), &(0..10)));
+        assert!(!overlaps(&(0..10), &(10..20)));
+        assert!(!overlaps(&(0..0), &(0..10)));
+    }
+
+    #[test]
+    pub fn aligned_length_test() {
+        assert_eq!(aligned_length(0, 0x1000, 0x4000), Some(0));
+        assert_eq!(aligned_length(1, 0x1000, 0x4000), Some(0x1000));
+        assert_eq!(aligned_length(0x1000, 0x1000, 0x4000), Some(0x1000));
+        assert_eq!(aligned_length(0x4000, 0x1000, 0x4000), Some(0x4000));
+
+        // Too long, either before or only after rounding
+        assert_eq!(aligned_length(0x4001, 0x1000, 0x4000), None);
+        assert_eq!(aligned_length(0x3001, 0x1000, 0x3800), None);
+
+        // Rounding would overflow without the limit
+        assert_eq!(aligned_length(u64::MAX, 0x1000, u64::MAX), None);
+        assert_eq!(aligned_length(u64::MAX - 1, 0x1000, 0x4000), None);
+
+        assert_eq!(aligned_length(1, 0, 0x4000), None);
+    }
+
+    #[test]
+    pub fn empty_window_test() {
+        let used = core::iter::empty();
+
+        assert_eq!(
+            find_free_region(&used, 0x1001..0x10000, 0x2000, 0x1000),
+            Some(0x2000)
+        );
+        assert_eq!(
+            find_free_region(&used, 0x1000..0x2000, 0x2000, 0x1000),
+            None
+        );
+    }
+
+    #[test]
+    pub fn find_gap_test() {
+        let used = [0x3000..0x5000, 0x1000..0x2000, 0x6000..0x7000];
+
+        assert_eq!(
+            find_free_region(&used.iter().cloned(), 0x1000..0x10000, 0x1000, 0x1000),
+            Some(0x2000)
+        );
+        assert_eq!(
+            find_free_region(&used.iter().cloned(), 0x1000..0x10000, 0x2000, 0x1000),
+            Some(0x7000)
+        );
+        assert_eq!(
+            find_free_region(&used.iter().cloned(), 0x1000..0x8000, 0x2000, 0x1000),
+            None
+        );
+    }
+
+    #[test]
+    pub fn unaligned_used_range_test() {
+        let used = core::iter::once(0x1000..0x1800);
+
+        assert_eq!(
+            find_free_region(&used, 0x1000..0x4000, 0x1000, 0x1000),
+            Some(0x2000)
+        );
+    }
+}
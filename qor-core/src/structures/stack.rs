@@ -0,0 +1,147 @@
+/// The stack of a user thread, which grows downwards from a fixed top.
+///
+/// Only the pages from the current bottom up to the top are mapped. An access in the window below the bottom, down to
+/// the maximum size of the stack, grows the stack to cover it, while one beyond the maximum size is a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackRegion<const PAGE_SIZE: usize> {
+    top: u64,
+    bottom: u64,
+    maximum_size: u64,
+}
+
+impl<const PAGE_SIZE: usize> StackRegion<PAGE_SIZE> {
+    /// Construct a stack whose pages from `bottom` to `top` are mapped, and which may grow until it is `maximum_size`
+    /// bytes long. A maximum smaller than the mapped pages is raised to cover them.
+    #[must_use]
+    pub fn new(bottom: u64, top: u64, maximum_size: u64) -> Self {
+        Self {
+            top,
+            bottom,
+            maximum_size: maximum_size.max(top - bottom),
+        }
+    }
+
+    /// Get the address just past the highest byte of the stack
+    #[must_use]
+    pub const fn top(&self) -> u64 {
+        self.top
+    }
+
+    /// Get the lowest mapped address of the stack
+    #[must_use]
+    pub const fn bottom(&self) -> u64 {
+        self.bottom
+    }
+
+    /// Get the lowest address the stack may grow down to, the start of the region reserved for it
+    #[must_use]
+    pub const fn limit(&self) -> u64 {
+        self.top.saturating_sub(self.maximum_size)
+    }
+
+    /// Get the range of addresses reserved for the stack, whether mapped yet or not
+    #[must_use]
+    pub const fn reserved(&self) -> core::ops::Range<u64> {
+        self.limit()..self.top
+    }
+
+    /// Set the largest size in bytes the stack may grow to. The pages already mapped are kept, even if they reach past
+    /// the new maximum.
+    pub const fn set_maximum_size(&mut self, maximum_size: u64) {
+        self.maximum_size = maximum_size;
+    }
+
+    /// Get the page aligned range which must be mapped below the bottom of the stack to cover an access at `address`,
+    /// or `None` if `address` is not in the window the stack may still grow into.
+    #[must_use]
+    pub fn growth_for(&self, address: u64) -> Option<core::ops::Range<u64>> {
+        (self.limit()..self.bottom)
+            .contains(&address)
+            .then(|| address & !(PAGE_SIZE as u64 - 1)..self.bottom)
+    }
+
+    /// Record that the pages down to `bottom` have been mapped
+    pub const fn grow_to(&mut self, bottom: u64) {
+        self.bottom = bottom;
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::StackRegion;
+
+    const TOP: u64 = 0x1_0000_4000;
+
+    #[test]
+    pub fn growth_window_test() {
+        let stack = StackRegion::<0x1000>::new(TOP - 0x4000, TOP, 0x10000);
+        assert_eq!(stack.limit(), TOP - 0x10000);
+        assert_eq!(stack.reserved(), TOP - 0x10000..TOP);
+
+        // Mapped pages and anything above the top are not growth
+        assert_eq!(stack.growth_for(TOP - 0x4000), None);
+        assert_eq!(stack.growth_for(TOP), None);
+
+        // Just below the bottom grows by a page, further down maps every page in between
+        assert_eq!(
+            stack.growth_for(TOP - 0x4008),
+            Some(TOP - 0x5000..TOP - 0x4000)
+        );
+        assert_eq!(
+            stack.growth_for(TOP - 0x10000),
+            Some(TOP - 0x10000..TOP - 0x4000)
+        );
+
+        // Beyond the maximum size is a fault
+        assert_eq!(stack.growth_for(TOP - 0x10001), None);
+        assert_eq!(stack.growth_for(0), None);
+    }
+
+    #[test]
+    pub fn deep_recursion_test() {
+        let mut stack = StackRegion::<0x1000>::new(TOP - 0x1000, TOP, 0x8000);
+
+        // Each call pushes a 0x180 byte frame, faulting whenever it reaches below the mapped pages
+        let mut stack_pointer = TOP;
+        let mut grown_pages = 0;
+        let fault = loop {
+            stack_pointer -= 0x180;
+            if stack_pointer >= stack.bottom() {
+                continue;
+            }
+
+            let Some(growth) = stack.growth_for(stack_pointer) else {
+                break stack_pointer;
+            };
+            assert_eq!(growth.end - growth.start, 0x1000);
+
+            grown_pages += 1;
+            stack.grow_to(growth.start);
+        };
+
+        // The stack grew a page at a time up to its maximum size, and the first frame past it faulted
+        assert_eq!(grown_pages, 7);
+        assert_eq!(stack.bottom(), stack.limit());
+        assert!(fault < stack.limit());
+        assert!(fault + 0x180 >= stack.limit());
+    }
+
+    #[test]
+    pub fn maximum_size_test() {
+        // The maximum is raised to cover the initial stack
+        let mut stack = StackRegion::<0x1000>::new(TOP - 0x4000, TOP, 0x1000);
+        assert_eq!(stack.limit(), TOP - 0x4000);
+        assert_eq!(stack.growth_for(TOP - 0x4001), None);
+
+        stack.set_maximum_size(0x5000);
+        assert_eq!(
+            stack.growth_for(TOP - 0x4001),
+            Some(TOP - 0x5000..TOP - 0x4000)
+        );
+
+        // The maximum can not reach below address zero
+        stack.set_maximum_size(u64::MAX);
+        assert_eq!(stack.limit(), 0);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::interfaces::fs::INodeData;
+
+/// File status returned to user space by the `stat` and `fstat` syscalls.
+///
+/// The layout is fixed so user programs can declare a matching C struct, every field is native endian and there is
+/// no padding:
+///
+/// | Offset | Size | Field         |
+/// |--------|------|---------------|
+/// | 0      | 8    | `device`      |
+/// | 8      | 8    | `inode`       |
+/// | 16     | 4    | `mode`        |
+/// | 20     | 4    | `link_count`  |
+/// | 24     | 4    | `uid`         |
+/// | 28     | 4    | `gid`         |
+/// | 32     | 8    | `size`        |
+/// | 40     | 8    | `access_time` |
+/// | 48     | 8    | `modify_time` |
+/// | 56     | 8    | `change_time` |
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stat {
+    pub device: u64,
+    pub inode: u64,
+    pub mode: u32,
+    pub link_count: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u64,
+    pub access_time: u64,
+    pub modify_time: u64,
+    pub change_time: u64,
+}
+
+impl Stat {
+    /// Size of the struct as seen by user space
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// View the struct as the bytes to copy to user space
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8] {
+        // Safety: `Stat` is `repr(C)` and made only of integers with no padding between them, so every byte is
+        // initialized
+        unsafe { core::slice::from_raw_parts(core::ptr::from_ref(self).cast(), Self::SIZE) }
+    }
+}
+
+impl core::convert::From<&INodeData> for Stat {
+    fn from(value: &INodeData) -> Self {
+        Self {
+            device: value.reference.device as u64,
+            inode: value.reference.inode as u64,
+            mode: u32::from(u16::from(value.mode)),
+            // Saturate rather than wrap, a count that large is already meaningless to a C program
+            link_count: u32::try_from(value.link_count).unwrap_or(u32::MAX),
+            uid: u32::from(value.uid.0),
+            gid: u32::from(value.gid.0),
+            size: value.size as u64,
+            access_time: value.access_time.0,
+            modify_time: value.modify_time.0,
+            change_time: value.change_time.0,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use core::mem::offset_of;
+
+    use super::Stat;
+    use crate::interfaces::fs::{INodeData, INodeReference};
+    use crate::structures::{
+        id::{GroupID, UserID},
+        time::UnixTimestamp,
+    };
+
+    #[test]
+    pub fn layout_test() {
+        assert_eq!(Stat::SIZE, 64);
+        assert_eq!(offset_of!(Stat, device), 0);
+        assert_eq!(offset_of!(Stat, inode), 8);
+        assert_eq!(offset_of!(Stat, mode), 16);
+        assert_eq!(offset_of!(Stat, link_count), 20);
+        assert_eq!(offset_of!(Stat, uid), 24);
+        assert_eq!(offset_of!(Stat, gid), 28);
+        assert_eq!(offset_of!(Stat, size), 32);
+        assert_eq!(offset_of!(Stat, access_time), 40);
+        assert_eq!(offset_of!(Stat, modify_time), 48);
+        assert_eq!(offset_of!(Stat, change_time), 56);
+    }
+
+    #[test]
+    pub fn from_inode_data_test() {
+        let data = INodeData {
+            mode: 0x81A4.into(),
+            link_count: 2,
+            uid: UserID(1000),
+            gid: GroupID(100),
+            size: 0x1234,
+            access_time: UnixTimestamp(10),
+            modify_time: UnixTimestamp(20),
+            change_time: UnixTimestamp(30),
+            reference: INodeReference {
+                inode: 12,
+                device: 3,
+            },
+        };
+        let stat = Stat::from(&data);
+
+        assert_eq!(
+            stat,
+            Stat {
+                device: 3,
+                inode: 12,
+                mode: 0x81A4,
+                link_count: 2,
+                uid: 1000,
+                gid: 100,
+                size: 0x1234,
+                access_time: 10,
+                modify_time: 20,
+                change_time: 30,
+            }
+        );
+
+        let bytes = stat.as_bytes();
+        assert_eq!(bytes.len(), Stat::SIZE);
+        assert_eq!(&bytes[16..20], &0x81A4u32.to_ne_bytes());
+        assert_eq!(&bytes[56..64], &30u64.to_ne_bytes());
+    }
+}
@@ -1,14 +1,54 @@
+use crate::interfaces::fs::FileSystemError;
+
 #[derive(Debug)]
 pub enum SyscallError {
+    NoEntry,
+    /// The path or descriptor does not name an inode, reported to user space as `ENOENT`
+    BadInode,
+    Io,
     BadFileDescriptor,
-    Fault
+    OutOfMemory,
+    Fault,
+    AlreadyExists,
+    NotDirectory,
+    IsDirectory,
+    InvalidArgument,
+    TooManyOpenFiles,
+    NameTooLong,
+    NotImplemented,
 }
 
 impl core::convert::From<SyscallError> for isize {
     fn from(value: SyscallError) -> Self {
         match value {
+            SyscallError::NoEntry | SyscallError::BadInode => 2,
+            SyscallError::Io => 5,
             SyscallError::BadFileDescriptor => 9,
+            SyscallError::OutOfMemory => 12,
             SyscallError::Fault => 14,
+            SyscallError::AlreadyExists => 17,
+            SyscallError::NotDirectory => 20,
+            SyscallError::IsDirectory => 21,
+            SyscallError::InvalidArgument => 22,
+            SyscallError::TooManyOpenFiles => 24,
+            SyscallError::NameTooLong => 36,
+            SyscallError::NotImplemented => 38,
         }
     }
-}
\ No newline at end of file
+}
+
+impl core::convert::From<FileSystemError> for SyscallError {
+    fn from(value: FileSystemError) -> Self {
+        match value {
+            FileSystemError::PathNotFound => Self::NoEntry,
+            FileSystemError::BadInode(_) | FileSystemError::BadInodeWrongDevice(_) => {
+                Self::BadInode
+            }
+            FileSystemError::NotDirectory => Self::NotDirectory,
+            FileSystemError::IsDirectory => Self::IsDirectory,
+            FileSystemError::BufferTooSmall => Self::InvalidArgument,
+            FileSystemError::AlreadyExists => Self::AlreadyExists,
+            _ => Self::Io,
+        }
+    }
+}
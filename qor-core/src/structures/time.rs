@@ -7,6 +7,115 @@ pub struct Hertz(pub u64);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct UnixTimestamp(pub u64);
 
+impl Microseconds {
+    /// Construct a duration of `millis` milliseconds, saturating if it does not fit.
+    #[must_use]
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis.saturating_mul(1_000))
+    }
+
+    /// Construct a duration of `secs` seconds, saturating if it does not fit.
+    #[must_use]
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(1_000_000))
+    }
+
+    /// Get the number of whole milliseconds in this duration.
+    #[must_use]
+    pub const fn as_millis(self) -> u64 {
+        self.0 / 1_000
+    }
+
+    /// Get the number of whole seconds in this duration.
+    #[must_use]
+    pub const fn as_secs(self) -> u64 {
+        self.0 / 1_000_000
+    }
+
+    /// Get the duration of `ticks` cycles of a clock running at `frequency`, saturating if it does not fit.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `frequency` is zero.
+    #[must_use]
+    pub fn from_ticks(ticks: u64, frequency: Hertz) -> Self {
+        // Widened so a long run of a fast clock does not overflow before the division
+        let micros = u128::from(ticks) * 1_000_000 / u128::from(frequency.0);
+        Self(u64::try_from(micros).unwrap_or(u64::MAX))
+    }
+
+    /// Get the number of whole cycles of a clock running at `frequency` which fit in this duration, saturating if it
+    /// does not fit. This converts a duration into a count of raw `mtime` ticks, the inverse of [`Self::from_ticks`].
+    #[must_use]
+    pub fn to_ticks(self, frequency: Hertz) -> u64 {
+        let ticks = u128::from(self.0) * u128::from(frequency.0) / 1_000_000;
+        u64::try_from(ticks).unwrap_or(u64::MAX)
+    }
+
+    /// Subtract `rhs`, returning `None` if it is longer than this duration.
+    #[must_use]
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(micros) => Some(Self(micros)),
+            None => None,
+        }
+    }
+
+    /// Subtract `rhs`, stopping at zero if it is longer than this duration.
+    #[must_use]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Add `rhs`, stopping at the longest representable duration rather than overflowing.
+    #[must_use]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Hertz {
+    /// Get the frequency of an event which repeats every `period`, or zero if the period is zero.
+    #[must_use]
+    pub const fn from_period(period: Microseconds) -> Self {
+        match 1_000_000u64.checked_div(period.0) {
+            Some(frequency) => Self(frequency),
+            None => Self(0),
+        }
+    }
+
+    /// Get the time between events repeating at this frequency, rounded down to a whole microsecond.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the frequency is zero.
+    #[must_use]
+    pub const fn period(self) -> Microseconds {
+        Microseconds(1_000_000 / self.0)
+    }
+
+    /// Get the number of ticks of a clock running at `timebase` between events repeating at this frequency, the value
+    /// to advance a timer compare register by to fire at this frequency. Returns `None` if the frequency is zero.
+    #[must_use]
+    pub const fn reload_ticks(self, timebase: Self) -> Option<u64> {
+        timebase.0.checked_div(self.0)
+    }
+}
+
+impl UnixTimestamp {
+    /// Get the time since the epoch as a duration, saturating if it does not fit.
+    #[must_use]
+    pub const fn since_epoch(self) -> Microseconds {
+        Microseconds::from_secs(self.0)
+    }
+
+    /// Construct the timestamp `duration` after the epoch, rounded down to a whole second.
+    #[must_use]
+    pub const fn from_duration(duration: Microseconds) -> Self {
+        Self(duration.as_secs())
+    }
+}
+
 impl core::convert::From<u64> for UnixTimestamp {
     fn from(value: u64) -> Self {
         Self(value)
@@ -20,3 +129,94 @@ impl core::ops::Add for Microseconds {
         Self(self.0 + rhs.0)
     }
 }
+
+impl core::ops::AddAssign for Microseconds {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl core::ops::Sub for Microseconds {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl core::ops::SubAssign for Microseconds {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl core::ops::Add<Microseconds> for UnixTimestamp {
+    type Output = Self;
+
+    /// Move the timestamp on by `rhs`, rounded down to a whole second
+    fn add(self, rhs: Microseconds) -> Self::Output {
+        Self(self.0 + rhs.as_secs())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{Hertz, Microseconds, UnixTimestamp};
+
+    /// Frequency of the `mtime` counter on the QEMU `virt` machine
+    const QEMU_TIMEBASE: Hertz = Hertz(10_000_000);
+
+    #[test]
+    pub fn reload_ticks_test() {
+        assert_eq!(Hertz(2).reload_ticks(QEMU_TIMEBASE), Some(5_000_000));
+        assert_eq!(Hertz(100).reload_ticks(QEMU_TIMEBASE), Some(100_000));
+        assert_eq!(Hertz(0).reload_ticks(QEMU_TIMEBASE), None);
+
+        assert_eq!(Hertz(2).period(), Microseconds(500_000));
+        assert_eq!(Hertz(2).period().to_ticks(QEMU_TIMEBASE), 5_000_000);
+    }
+
+    #[test]
+    pub fn ticks_round_trip_test() {
+        let duration = Microseconds::from_millis(1_500);
+        assert_eq!(duration, Microseconds(1_500_000));
+
+        let ticks = duration.to_ticks(QEMU_TIMEBASE);
+        assert_eq!(ticks, 15_000_000);
+        assert_eq!(Microseconds::from_ticks(ticks, QEMU_TIMEBASE), duration);
+
+        assert_eq!(Microseconds(u64::MAX).to_ticks(QEMU_TIMEBASE), u64::MAX);
+    }
+
+    #[test]
+    pub fn arithmetic_test() {
+        let mut deadline = Microseconds::from_millis(20);
+        deadline += Microseconds::from_millis(5);
+        assert_eq!(deadline, Microseconds(25_000));
+        assert_eq!(deadline - Microseconds(5_000), Microseconds::from_millis(20));
+        assert!(Microseconds::from_millis(20) < deadline);
+
+        assert_eq!(deadline.checked_sub(Microseconds::from_secs(1)), None);
+        assert_eq!(
+            deadline.saturating_sub(Microseconds::from_secs(1)),
+            Microseconds(0)
+        );
+        assert_eq!(
+            Microseconds(u64::MAX).saturating_add(deadline),
+            Microseconds(u64::MAX)
+        );
+        assert_eq!(Microseconds::from_millis(u64::MAX), Microseconds(u64::MAX));
+
+        let timestamp = UnixTimestamp(1_700_000_000);
+        assert_eq!(
+            UnixTimestamp::from_duration(timestamp.since_epoch()),
+            timestamp
+        );
+        assert_eq!(
+            timestamp + Microseconds::from_millis(2_500),
+            UnixTimestamp(1_700_000_002)
+        );
+        assert!(timestamp.since_epoch() > Microseconds::from_secs(1_699_999_999));
+    }
+}
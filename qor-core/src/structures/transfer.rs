@@ -0,0 +1,92 @@
+/// Largest number of bytes a single system call copies between user and kernel memory. Reads and writes of more are
+/// cut short to this length, as the kernel buffers the whole transfer.
+pub const MAXIMUM_TRANSFER: usize = 64 * 1024;
+
+/// Clamp the length of a transfer requested by user space to [`MAXIMUM_TRANSFER`]
+#[must_use]
+pub const fn clamp_transfer(length: usize) -> usize {
+    if length > MAXIMUM_TRANSFER {
+        MAXIMUM_TRANSFER
+    } else {
+        length
+    }
+}
+
+/// Iterator over the pieces of a range of virtual addresses which lie on separate pages, see [`page_chunks`]
+#[derive(Debug, Clone)]
+pub struct PageChunks<const PAGE_SIZE: usize> {
+    address: usize,
+    length: usize,
+    offset: usize,
+}
+
+impl<const PAGE_SIZE: usize> Iterator for PageChunks<PAGE_SIZE> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.length {
+            return None;
+        }
+
+        let current = self.address.wrapping_add(self.offset);
+        let chunk = (PAGE_SIZE - current % PAGE_SIZE).min(self.length - self.offset);
+
+        let item = (self.offset, chunk);
+        self.offset += chunk;
+
+        Some(item)
+    }
+}
+
+/// Split the `length` byte range starting at `address` at page boundaries, yielding the offset into the range and the
+/// length of each piece.
+///
+/// Consecutive virtual pages need not be physically contiguous, so each piece must be looked up separately.
+#[must_use]
+pub const fn page_chunks<const PAGE_SIZE: usize>(
+    address: usize,
+    length: usize,
+) -> PageChunks<PAGE_SIZE> {
+    PageChunks {
+        address,
+        length,
+        offset: 0,
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{clamp_transfer, page_chunks, MAXIMUM_TRANSFER};
+
+    #[test]
+    pub fn clamp_transfer_test() {
+        assert_eq!(clamp_transfer(0), 0);
+        assert_eq!(clamp_transfer(100), 100);
+        assert_eq!(clamp_transfer(MAXIMUM_TRANSFER), MAXIMUM_TRANSFER);
+        assert_eq!(clamp_transfer(usize::MAX), MAXIMUM_TRANSFER);
+    }
+
+    #[test]
+    pub fn page_chunks_test() {
+        // Within a single page
+        assert_eq!(
+            page_chunks::<0x1000>(0x1010, 0x20).collect::<alloc::vec::Vec<_>>(),
+            [(0, 0x20)]
+        );
+
+        // Crossing two page boundaries
+        assert_eq!(
+            page_chunks::<0x1000>(0x1800, 0x2000).collect::<alloc::vec::Vec<_>>(),
+            [(0, 0x800), (0x800, 0x1000), (0x1800, 0x800)]
+        );
+
+        // Page aligned
+        assert_eq!(
+            page_chunks::<0x1000>(0x2000, 0x2000).collect::<alloc::vec::Vec<_>>(),
+            [(0, 0x1000), (0x1000, 0x1000)]
+        );
+
+        assert_eq!(page_chunks::<0x1000>(0x1234, 0).count(), 0);
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+use core::task::Waker;
+
+use super::Mutex;
+
+/// One-shot signal which tasks can wait on until it is set. Once set, an `Event` stays set, and all current and future
+/// waiters proceed immediately.
+pub struct Event {
+    is_set: core::sync::atomic::AtomicBool,
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl Event {
+    /// Create a new `Event` which has not yet been set
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            is_set: core::sync::atomic::AtomicBool::new(false),
+            waiters: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set the `Event`, waking every task waiting on it. Setting an `Event` which is already set has no effect.
+    pub fn set(&self) {
+        if !self.is_set.swap(true, core::sync::atomic::Ordering::AcqRel) {
+            let waiters = core::mem::take(&mut *self.waiters.spin_lock());
+
+            for waker in waiters {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns true if the `Event` has been set
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.is_set.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Asynchronously wait until the `Event` is set
+    #[must_use]
+    pub const fn wait(&self) -> EventFuture<'_> {
+        EventFuture { event: self }
+    }
+
+    /// Spin until the `Event` is set
+    pub fn wait_blocking(&self) {
+        while !self.is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl core::default::Default for Event {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A future implementor for the `Event` which completes once the `Event` is set
+#[allow(clippy::module_name_repetitions)]
+pub struct EventFuture<'a> {
+    event: &'a Event,
+}
+
+impl core::future::Future for EventFuture<'_> {
+    type Output = ();
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        if self.event.is_set() {
+            return core::task::Poll::Ready(());
+        }
+
+        {
+            let mut waiters = self.event.waiters.spin_lock();
+            if !waiters.iter().any(|waker| waker.will_wake(cx.waker())) {
+                waiters.push(cx.waker().clone());
+            }
+        }
+
+        // The event may have been set between the first check and registering the waker, in which case the waker may
+        // have been missed by `set`
+        if self.event.is_set() {
+            core::task::Poll::Ready(())
+        } else {
+            core::task::Poll::Pending
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::Event;
+    use crate::tasks::{Executor, Task};
+
+    #[test]
+    pub fn wake_all_waiters_test() {
+        let event = Event::new();
+        let proceeded = AtomicUsize::new(0);
+
+        let mut executor = Executor::new();
+        for _ in 0..3 {
+            executor.spawn(Task::new(async {
+                event.wait().await;
+                proceeded.fetch_add(1, Ordering::AcqRel);
+            }));
+        }
+
+        executor.run_until_pending();
+        assert_eq!(proceeded.load(Ordering::Acquire), 0);
+
+        event.set();
+        executor.run();
+        assert_eq!(proceeded.load(Ordering::Acquire), 3);
+    }
+
+    #[test]
+    pub fn already_set_test() {
+        let event = Event::new();
+        event.set();
+        event.set();
+
+        let mut executor = Executor::new();
+        executor.spawn(Task::new(event.wait()));
+
+        // A single poll is enough to complete the wait
+        assert_eq!(executor.step(), Some(true));
+        event.wait_blocking();
+    }
+}
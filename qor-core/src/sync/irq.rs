@@ -0,0 +1,166 @@
+use super::{Mutex, MutexGuard};
+
+/// Masking of interrupts on the current hart, used by an [`IrqSpinLock`] to keep interrupt handlers out of its
+/// critical sections.
+pub trait InterruptControl {
+    /// Disable interrupts, returning whether they were enabled beforehand
+    fn disable(&self) -> bool;
+
+    /// Enable interrupts
+    fn enable(&self);
+}
+
+impl<C: InterruptControl + ?Sized> InterruptControl for &C {
+    fn disable(&self) -> bool {
+        (**self).disable()
+    }
+
+    fn enable(&self) {
+        (**self).enable();
+    }
+}
+
+/// Spin lock for data shared between normal code and interrupt handlers.
+///
+/// Interrupts are disabled while the lock is held, so a handler can never interrupt the holder and then spin forever
+/// waiting for the lock the interrupted code holds.
+#[allow(clippy::module_name_repetitions)]
+pub struct IrqSpinLock<T, C: InterruptControl> {
+    inner: Mutex<T>,
+    control: C,
+}
+
+impl<T, C: InterruptControl> IrqSpinLock<T, C> {
+    /// Create a new `IrqSpinLock` around an inner object, masking interrupts through `control`
+    pub const fn new(inner: T, control: C) -> Self {
+        Self {
+            inner: Mutex::new(inner),
+            control,
+        }
+    }
+
+    /// Disable interrupts then spin until the lock can be acquired. Interrupts are restored to their previous state
+    /// once the returned guard is dropped.
+    pub fn lock(&self) -> IrqSpinLockGuard<'_, T, C> {
+        let restore = RestoreInterrupts {
+            control: &self.control,
+            was_enabled: self.control.disable(),
+        };
+
+        IrqSpinLockGuard {
+            guard: self.inner.spin_lock(),
+            _restore: restore,
+        }
+    }
+
+    /// Spin until the lock can be acquired, without touching the interrupt state. This is for interrupt handlers,
+    /// which already run with interrupts disabled, and can never interrupt a holder which took the lock with
+    /// [`Self::lock`].
+    pub fn lock_from_interrupt(&self) -> MutexGuard<'_, T> {
+        self.inner.spin_lock()
+    }
+}
+
+/// Re-enables interrupts when dropped, if they were enabled when the lock was taken
+struct RestoreInterrupts<'a, C: InterruptControl> {
+    control: &'a C,
+    was_enabled: bool,
+}
+
+impl<C: InterruptControl> Drop for RestoreInterrupts<'_, C> {
+    fn drop(&mut self) {
+        if self.was_enabled {
+            self.control.enable();
+        }
+    }
+}
+
+/// Guard giving access to the data inside an [`IrqSpinLock`], keeping interrupts disabled until it is dropped
+#[allow(clippy::module_name_repetitions)]
+pub struct IrqSpinLockGuard<'a, T, C: InterruptControl> {
+    // Fields are dropped in order, so the lock is released before interrupts are restored
+    guard: MutexGuard<'a, T>,
+    _restore: RestoreInterrupts<'a, C>,
+}
+
+impl<T, C: InterruptControl> core::ops::Deref for IrqSpinLockGuard<'_, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T, C: InterruptControl> core::ops::DerefMut for IrqSpinLockGuard<'_, T, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use super::{InterruptControl, IrqSpinLock};
+
+    /// Interrupt enable bit of a pretend hart
+    struct MockInterrupts {
+        enabled: AtomicBool,
+    }
+
+    impl InterruptControl for MockInterrupts {
+        fn disable(&self) -> bool {
+            self.enabled.swap(false, Ordering::AcqRel)
+        }
+
+        fn enable(&self) {
+            self.enabled.store(true, Ordering::Release);
+        }
+    }
+
+    fn enabled<T>(lock: &IrqSpinLock<T, MockInterrupts>) -> bool {
+        lock.control.enabled.load(Ordering::Acquire)
+    }
+
+    #[test]
+    pub fn guard_disables_interrupts_test() {
+        let lock = IrqSpinLock::new(
+            0,
+            MockInterrupts {
+                enabled: AtomicBool::new(true),
+            },
+        );
+
+        {
+            let mut guard = lock.lock();
+            assert!(!enabled(&lock));
+            *guard += 1;
+            assert!(!enabled(&lock));
+        }
+        assert!(enabled(&lock));
+
+        // The lock was released before interrupts were restored
+        assert!(lock.inner.try_lock().is_some());
+        assert_eq!(*lock.lock(), 1);
+
+        // Handlers take the lock with interrupts as they found them
+        *lock.lock_from_interrupt() += 1;
+        assert!(enabled(&lock));
+        assert_eq!(*lock.lock(), 2);
+    }
+
+    #[test]
+    pub fn guard_keeps_disabled_interrupts_test() {
+        let lock = IrqSpinLock::new(
+            (),
+            MockInterrupts {
+                enabled: AtomicBool::new(false),
+            },
+        );
+
+        // Interrupts masked by the caller stay masked after the guard is dropped
+        drop(lock.lock());
+        assert!(!enabled(&lock));
+    }
+}
@@ -1,2 +1,18 @@
+#[cfg(feature = "alloc")]
+pub mod event;
+#[cfg(feature = "alloc")]
+pub use event::*;
+
+pub mod irq;
+pub use irq::*;
+
 pub mod mutex;
 pub use mutex::*;
+
+pub mod rwlock;
+pub use rwlock::*;
+
+#[cfg(feature = "alloc")]
+pub mod semaphore;
+#[cfg(feature = "alloc")]
+pub use semaphore::*;
@@ -1,5 +1,8 @@
 use core::cell::UnsafeCell;
 
+/// Largest number of spin hints issued between attempts to take a held `Mutex`
+const MAX_BACKOFF: usize = 64;
+
 /// A `Mutex` implementation for the Qor kernel, simply wraps an `UnsafeCell`
 /// with an `AtomicBool` used as a flag to denote if the `Mutex` is locked or
 /// not. The wrapped object can be accessed by `lock`ing the `Mutex` which
@@ -19,15 +22,29 @@ impl<T> Mutex<T> {
         }
     }
 
-    /// Spin until the lock can be acquired, returning a `MutexGuard` for the wrapped data
+    /// Spin until the lock can be acquired, returning a `MutexGuard` for the wrapped data.
+    ///
+    /// While the lock is held, the wait between attempts doubles up to [`MAX_BACKOFF`] spin hints, and only reads of
+    /// the flag are made, so waiting harts do not keep claiming the cache line from the holder.
     pub fn spin_lock(&self) -> MutexGuard<T> {
-        while !self.acquire_lock() {}
+        let mut backoff = 1;
+
+        while !self.acquire_lock() {
+            while self.is_locked.load(core::sync::atomic::Ordering::Relaxed) {
+                for _ in 0..backoff {
+                    core::hint::spin_loop();
+                }
+
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
 
         MutexGuard { reference: self }
     }
 
-    /// Attempt to get the lock on the `Mutex`, returning `None` if it is not possible
-    pub fn attempt_lock(&self) -> Option<MutexGuard<T>> {
+    /// Attempt to get the lock on the `Mutex`, returning `None` if it is held. This makes a single attempt and never
+    /// spins, so it is safe to use where the holder could be interrupted, such as in an interrupt handler.
+    pub fn try_lock(&self) -> Option<MutexGuard<T>> {
         if self.acquire_lock() {
             Some(MutexGuard { reference: self })
         } else {
@@ -99,10 +116,57 @@ impl<'a, T> core::future::Future for MutexFuture<'a, T> {
 
     fn poll(
         self: core::pin::Pin<&mut Self>,
-        _: &mut core::task::Context<'_>,
+        cx: &mut core::task::Context<'_>,
     ) -> core::task::Poll<Self::Output> {
-        self.mutex
-            .attempt_lock()
-            .map_or(core::task::Poll::Pending, core::task::Poll::Ready)
+        self.mutex.try_lock().map_or_else(
+            || {
+                // Releasing the lock does not wake anyone, so ask to be polled again to retry
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            },
+            core::task::Poll::Ready,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::prelude::rust_2021::*;
+
+    use super::Mutex;
+
+    #[test]
+    pub fn try_lock_test() {
+        let mutex = Mutex::new(3);
+
+        let mut guard = mutex.try_lock().unwrap();
+        assert!(mutex.try_lock().is_none());
+        *guard += 1;
+        drop(guard);
+
+        assert_eq!(mutex.try_lock().map(|guard| *guard), Some(4));
+    }
+
+    #[test]
+    pub fn contended_spin_lock_test() {
+        let mutex = std::sync::Arc::new(Mutex::new(0usize));
+
+        let threads = (0..8)
+            .map(|_| {
+                let mutex = mutex.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..2000 {
+                        *mutex.spin_lock() += 1;
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(*mutex.spin_lock(), 16000);
     }
 }
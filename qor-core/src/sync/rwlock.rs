@@ -0,0 +1,271 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bit of the lock state set while a writer holds the lock, the bits below it count the readers
+const WRITER: usize = 1 << (usize::BITS - 1);
+
+/// A reader-writer lock for the Qor kernel, which allows any number of readers or a single writer to access the
+/// wrapped object at once.
+///
+/// The state is an `AtomicUsize` holding a count of the readers, with the top bit set while a writer holds the lock.
+/// Neither readers nor writers are given priority, so a steady stream of readers can hold off a writer.
+pub struct RwLock<T> {
+    inner: UnsafeCell<T>,
+    state: AtomicUsize,
+}
+
+impl<T> RwLock<T> {
+    /// Create a new `RwLock` around an inner object
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner: UnsafeCell::new(inner),
+            state: AtomicUsize::new(0),
+        }
+    }
+
+    /// Spin until the lock can be shared with other readers, returning a `RwLockReadGuard` for the wrapped data
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_read() {
+                return guard;
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Spin until the lock is held exclusively, returning a `RwLockWriteGuard` for the wrapped data
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_write() {
+                return guard;
+            }
+
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Attempt to share the lock with other readers, returning `None` if a writer holds it
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the count of readers would overflow into the writer bit.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.load(Ordering::Relaxed);
+
+        loop {
+            if state & WRITER != 0 {
+                return None;
+            }
+
+            assert!(state + 1 < WRITER, "Too many readers of a RwLock");
+
+            match self.state.compare_exchange_weak(
+                state,
+                state + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(RwLockReadGuard { reference: self }),
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Attempt to hold the lock exclusively, returning `None` if any reader or writer holds it
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.state
+            .compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| RwLockWriteGuard { reference: self })
+    }
+
+    /// Asynchronously share the lock with other readers
+    pub const fn async_read(&self) -> RwLockReadFuture<'_, T> {
+        RwLockReadFuture { lock: self }
+    }
+
+    /// Asynchronously hold the lock exclusively
+    pub const fn async_write(&self) -> RwLockWriteFuture<'_, T> {
+        RwLockWriteFuture { lock: self }
+    }
+}
+
+unsafe impl<T> Send for RwLock<T> where T: Send {}
+unsafe impl<T> Sync for RwLock<T> where T: Send + Sync {}
+
+/// `RwLockReadGuard` object which gives shared access to the wrapped object, can only be constructed from the
+/// `RwLock` which owns the wrapped data.
+#[allow(clippy::module_name_repetitions)]
+pub struct RwLockReadGuard<'a, T> {
+    reference: &'a RwLock<T>,
+}
+
+impl<T> core::ops::Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The guard holds a reader's share of the lock, so no writer can access the data
+        unsafe { &*self.reference.inner.get() }
+    }
+}
+
+impl<T> core::ops::Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.reference.state.fetch_sub(1, Ordering::Release);
+    }
+}
+
+/// `RwLockWriteGuard` object which gives exclusive access to the wrapped object, can only be constructed from the
+/// `RwLock` which owns the wrapped data.
+#[allow(clippy::module_name_repetitions)]
+pub struct RwLockWriteGuard<'a, T> {
+    reference: &'a RwLock<T>,
+}
+
+impl<T> core::ops::Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: The guard holds the lock exclusively
+        unsafe { &*self.reference.inner.get() }
+    }
+}
+
+impl<T> core::ops::DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: The guard holds the lock exclusively
+        unsafe { &mut *self.reference.inner.get() }
+    }
+}
+
+impl<T> core::ops::Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.reference.state.store(0, Ordering::Release);
+    }
+}
+
+/// A future implementor for the `RwLock` which allows async shared locking
+#[allow(clippy::module_name_repetitions)]
+pub struct RwLockReadFuture<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> core::future::Future for RwLockReadFuture<'a, T> {
+    type Output = RwLockReadGuard<'a, T>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        self.lock.try_read().map_or_else(
+            || {
+                // Releasing the lock does not wake anyone, so ask to be polled again to retry
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            },
+            core::task::Poll::Ready,
+        )
+    }
+}
+
+/// A future implementor for the `RwLock` which allows async exclusive locking
+#[allow(clippy::module_name_repetitions)]
+pub struct RwLockWriteFuture<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> core::future::Future for RwLockWriteFuture<'a, T> {
+    type Output = RwLockWriteGuard<'a, T>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        self.lock.try_write().map_or_else(
+            || {
+                // Releasing the lock does not wake anyone, so ask to be polled again to retry
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            },
+            core::task::Poll::Ready,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::prelude::rust_2021::*;
+
+    use super::RwLock;
+    use crate::tasks::block_on;
+
+    #[test]
+    pub fn exclusion_test() {
+        let lock = RwLock::new(5);
+
+        let first = lock.try_read().unwrap();
+        let second = lock.read();
+        assert_eq!(*first + *second, 10);
+        assert!(lock.try_write().is_none());
+
+        drop(first);
+        assert!(lock.try_write().is_none());
+        drop(second);
+
+        let mut writer = lock.try_write().unwrap();
+        *writer += 1;
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+        drop(writer);
+
+        assert_eq!(*lock.read(), 6);
+    }
+
+    #[test]
+    pub fn async_test() {
+        let lock = RwLock::new(Vec::new());
+
+        block_on(async {
+            lock.async_write().await.push(1);
+
+            let first = lock.async_read().await;
+            let second = lock.async_read().await;
+            assert_eq!(*first, *second);
+        });
+
+        assert_eq!(*lock.read(), [1]);
+    }
+
+    #[test]
+    pub fn threaded_test() {
+        // Writers keep both halves equal, so a reader seeing them differ shares the lock with a writer
+        let lock = std::sync::Arc::new(RwLock::new((0usize, 0usize)));
+
+        let threads = (0..8)
+            .map(|i| {
+                let lock = lock.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..2000 {
+                        if i % 2 == 0 {
+                            let mut pair = lock.write();
+                            pair.0 += 1;
+                            std::hint::spin_loop();
+                            pair.1 += 1;
+                        } else {
+                            let pair = lock.read();
+                            assert_eq!(pair.0, pair.1);
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(*lock.read(), (8000, 8000));
+    }
+}
@@ -0,0 +1,338 @@
+use alloc::collections::VecDeque;
+use core::task::Waker;
+
+use super::Mutex;
+
+/// Counting semaphore which hands out up to a fixed number of permits at once, such as one for each slot of a queue
+/// shared with a device.
+///
+/// Tasks waiting for a permit are parked until one is released, rather than being polled repeatedly, and are woken in
+/// the order they started waiting.
+pub struct Semaphore {
+    permits: core::sync::atomic::AtomicUsize,
+    waiters: Mutex<VecDeque<Waker>>,
+}
+
+impl Semaphore {
+    /// Create a new `Semaphore` with `permits` permits available
+    #[must_use]
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            permits: core::sync::atomic::AtomicUsize::new(permits),
+            waiters: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Get the number of permits which are not currently held
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.permits.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Take a permit if one is available, returning `true` if it was taken. This never waits.
+    pub fn try_acquire(&self) -> bool {
+        self.permits
+            .fetch_update(
+                core::sync::atomic::Ordering::AcqRel,
+                core::sync::atomic::Ordering::Acquire,
+                |permits| permits.checked_sub(1),
+            )
+            .is_ok()
+    }
+
+    /// Asynchronously wait until a permit can be taken
+    #[must_use]
+    pub const fn acquire(&self) -> SemaphoreFuture<'_> {
+        SemaphoreFuture {
+            semaphore: self,
+            waker: None,
+        }
+    }
+
+    /// Spin until a permit can be taken
+    pub fn acquire_blocking(&self) {
+        while !self.try_acquire() {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Return a permit taken by one of the `acquire` functions, waking the task which has waited longest for one.
+    pub fn release(&self) {
+        self.permits
+            .fetch_add(1, core::sync::atomic::Ordering::AcqRel);
+
+        self.wake_next();
+    }
+
+    /// Wake the task which has waited longest for a permit, if any
+    fn wake_next(&self) {
+        let waiter = self.waiters.spin_lock().pop_front();
+        if let Some(waker) = waiter {
+            waker.wake();
+        }
+    }
+
+    /// Stop waiting with `waker`, returning `false` if it was no longer queued, as a release has already woken it
+    fn deregister(&self, waker: &Waker) -> bool {
+        let mut waiters = self.waiters.spin_lock();
+        waiters
+            .iter()
+            .position(|queued| queued.will_wake(waker))
+            .and_then(|index| waiters.remove(index))
+            .is_some()
+    }
+}
+
+/// A future implementor for the `Semaphore` which completes once a permit has been taken. Dropping it before it
+/// completes gives up its place in the queue of waiting tasks.
+#[allow(clippy::module_name_repetitions)]
+pub struct SemaphoreFuture<'a> {
+    semaphore: &'a Semaphore,
+    /// The waker queued for this future, if it has started waiting
+    waker: Option<Waker>,
+}
+
+impl SemaphoreFuture<'_> {
+    /// Stop waiting once a permit has been taken, so the next release wakes a task which still needs one
+    fn stop_waiting(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            self.semaphore.deregister(&waker);
+        }
+    }
+}
+
+impl core::future::Future for SemaphoreFuture<'_> {
+    type Output = ();
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if this.semaphore.try_acquire() {
+            this.stop_waiting();
+            return core::task::Poll::Ready(());
+        }
+
+        {
+            let mut waiters = this.semaphore.waiters.spin_lock();
+            let queued = this
+                .waker
+                .as_ref()
+                .and_then(|waker| waiters.iter().position(|queued| queued.will_wake(waker)));
+
+            // A future still in the queue keeps its place, but is woken through the waker it was last polled with
+            if let Some(index) = queued {
+                waiters[index].clone_from(cx.waker());
+            } else {
+                waiters.push_back(cx.waker().clone());
+            }
+        }
+        this.waker = Some(cx.waker().clone());
+
+        // A permit may have been released between the first attempt and registering the waker, in which case the
+        // waker may have been missed by `release`
+        if this.semaphore.try_acquire() {
+            this.stop_waiting();
+            core::task::Poll::Ready(())
+        } else {
+            core::task::Poll::Pending
+        }
+    }
+}
+
+impl Drop for SemaphoreFuture<'_> {
+    fn drop(&mut self) {
+        // A release which already woke this future would otherwise be lost, so it is passed on to the next waiter
+        if let Some(waker) = self.waker.take() {
+            if !self.semaphore.deregister(&waker) {
+                self.semaphore.wake_next();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use core::{
+        future::Future,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Waker},
+    };
+    use std::{prelude::rust_2021::*, sync::Arc, task::Wake};
+
+    use super::Semaphore;
+    use crate::tasks::{Executor, Task};
+
+    #[test]
+    pub fn try_acquire_test() {
+        let semaphore = Semaphore::new(2);
+
+        assert!(semaphore.try_acquire());
+        assert!(semaphore.try_acquire());
+        assert!(!semaphore.try_acquire());
+        assert_eq!(semaphore.available(), 0);
+
+        semaphore.release();
+        assert!(semaphore.try_acquire());
+        semaphore.release();
+        semaphore.release();
+        assert_eq!(semaphore.available(), 2);
+    }
+
+    #[test]
+    pub fn parked_waiter_test() {
+        let semaphore = Semaphore::new(1);
+        let proceeded = AtomicUsize::new(0);
+
+        let mut executor = Executor::new();
+        for _ in 0..3 {
+            executor.spawn(Task::new(async {
+                semaphore.acquire().await;
+                proceeded.fetch_add(1, Ordering::AcqRel);
+            }));
+        }
+
+        // Only one task gets the permit, the others stay parked rather than being polled again
+        executor.run_until_pending();
+        assert_eq!(proceeded.load(Ordering::Acquire), 1);
+        assert_eq!(executor.step(), None);
+
+        semaphore.release();
+        executor.run_until_pending();
+        assert_eq!(proceeded.load(Ordering::Acquire), 2);
+
+        semaphore.release();
+        executor.run();
+        assert_eq!(proceeded.load(Ordering::Acquire), 3);
+        assert_eq!(semaphore.available(), 0);
+    }
+
+    /// Waker which counts the number of times it is woken
+    struct CountingWaker {
+        woken: AtomicUsize,
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.woken.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+        let counter = Arc::new(CountingWaker {
+            woken: AtomicUsize::new(0),
+        });
+        (counter.clone(), Waker::from(counter))
+    }
+
+    #[test]
+    pub fn fifo_waiters_test() {
+        let semaphore = Semaphore::new(0);
+        let order = std::sync::Mutex::new(Vec::new());
+
+        let mut executor = Executor::new();
+        for index in 0..3 {
+            let semaphore = &semaphore;
+            let order = &order;
+            executor.spawn(Task::new(async move {
+                semaphore.acquire().await;
+                order.lock().unwrap().push(index);
+            }));
+        }
+        executor.run_until_pending();
+
+        // Each release goes to the task which has waited longest
+        for _ in 0..3 {
+            semaphore.release();
+            executor.run_until_pending();
+        }
+        assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+    }
+
+    #[test]
+    pub fn dropped_waiter_test() {
+        let semaphore = Semaphore::new(0);
+        let (first_counter, first_waker) = counting_waker();
+        let (second_counter, second_waker) = counting_waker();
+
+        let mut first = Box::pin(semaphore.acquire());
+        let mut second = Box::pin(semaphore.acquire());
+        assert_eq!(
+            first.as_mut().poll(&mut Context::from_waker(&first_waker)),
+            Poll::Pending
+        );
+        assert_eq!(
+            second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker)),
+            Poll::Pending
+        );
+
+        // A waiter dropped before its turn leaves the queue, so the release goes to the one still waiting
+        drop(first);
+        semaphore.release();
+        assert_eq!(first_counter.woken.load(Ordering::Acquire), 0);
+        assert_eq!(second_counter.woken.load(Ordering::Acquire), 1);
+        assert_eq!(
+            second
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker)),
+            Poll::Ready(())
+        );
+
+        // A waiter dropped after being woken, without taking the permit, passes the wakeup on
+        let mut third = Box::pin(semaphore.acquire());
+        let mut fourth = Box::pin(semaphore.acquire());
+        assert!(third
+            .as_mut()
+            .poll(&mut Context::from_waker(&first_waker))
+            .is_pending());
+        assert!(fourth
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker))
+            .is_pending());
+
+        semaphore.release();
+        assert_eq!(first_counter.woken.load(Ordering::Acquire), 1);
+        drop(third);
+        assert_eq!(second_counter.woken.load(Ordering::Acquire), 2);
+        assert_eq!(
+            fourth
+                .as_mut()
+                .poll(&mut Context::from_waker(&second_waker)),
+            Poll::Ready(())
+        );
+        assert_eq!(semaphore.available(), 0);
+    }
+
+    #[test]
+    pub fn threaded_test() {
+        let semaphore = std::sync::Arc::new(Semaphore::new(3));
+        let holders = std::sync::Arc::new(AtomicUsize::new(0));
+
+        let threads = (0..8)
+            .map(|_| {
+                let semaphore = semaphore.clone();
+                let holders = holders.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        semaphore.acquire_blocking();
+                        assert!(holders.fetch_add(1, Ordering::AcqRel) < 3);
+                        std::hint::spin_loop();
+                        holders.fetch_sub(1, Ordering::AcqRel);
+                        semaphore.release();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(semaphore.available(), 3);
+    }
+}
@@ -1,95 +1,318 @@
 use super::Task;
-use alloc::collections::VecDeque;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use core::task::Context;
 use core::task::Poll;
-use core::task::RawWaker;
-use core::task::RawWakerVTable;
 use core::task::Waker;
 
-/// Kernel executor
+/// Identifier of a task within a single [`Executor`]
+type TaskId = usize;
+
+/// Number of times [`Executor::run`] spins waiting for a wake before polling every waiting task again
+const IDLE_SPINS: usize = 1 << 16;
+
+/// Waker handed to a task while it is polled, waking it marks the task as ready to be polled again.
+///
+/// Waking only touches atomics, so tasks can be woken from interrupt handlers, including ones which interrupt the
+/// executor itself.
+struct TaskWaker {
+    queued: AtomicBool,
+    /// Number of tasks of the executor which are queued, shared by every waker of the executor
+    pending: Arc<AtomicUsize>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // A task woken several times before it is next polled only needs to be polled once
+        if !self.queued.swap(true, Ordering::AcqRel) {
+            self.pending.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+}
+
+/// Kernel executor.
+///
+/// Tasks are only polled after they have been woken, a task is woken once when it is spawned, and after that only
+/// when a future it is waiting on calls its [`Waker`]. Tasks waiting on hardware are therefore left alone until the
+/// driver wakes them, rather than being polled in a loop. Woken tasks are polled in turn, starting after the last task
+/// polled.
+///
+/// An executor run with interrupts masked, such as from a trap handler, may never see the wake a task is waiting on,
+/// so if no task is woken for a while every waiting task is polled again, letting futures check their source
+/// themselves.
 #[allow(clippy::module_name_repetitions)]
-pub struct SimpleExecutor<'a> {
-    task_queue: VecDeque<Task<'a>>,
+pub struct Executor<'a> {
+    tasks: BTreeMap<TaskId, (Task<'a>, Arc<TaskWaker>)>,
+    pending: Arc<AtomicUsize>,
+    next_id: TaskId,
+    /// Task to start looking for a woken task from
+    cursor: TaskId,
 }
 
-impl<'a> SimpleExecutor<'a> {
+impl<'a> Executor<'a> {
     /// Construct a new empty executor
     #[must_use]
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         Self {
-            task_queue: VecDeque::new(),
+            tasks: BTreeMap::new(),
+            pending: Arc::new(AtomicUsize::new(0)),
+            next_id: 0,
+            cursor: 0,
         }
     }
 
-    /// Add a new task to the spawn
+    /// Add a new task to the executor, it is polled on the next step
     pub fn spawn(&mut self, task: Task<'a>) {
-        self.task_queue.push_back(task);
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let waker = Arc::new(TaskWaker {
+            queued: AtomicBool::new(false),
+            pending: self.pending.clone(),
+        });
+        waker.wake_by_ref();
+
+        self.tasks.insert(id, (task, waker));
     }
 
-    /// Single step, returns true when there is at least one task in the queue
+    /// Poll the next woken task. Returns `None` if no task has been woken, otherwise whether the polled task completed.
     pub fn step(&mut self) -> Option<bool> {
-        if let Some(mut task) = self.task_queue.pop_front() {
-            let waker = dummy_waker();
-            let mut context = Context::from_waker(&waker);
-            match task.poll(&mut context) {
-                Poll::Ready(()) => Some(true),
-                Poll::Pending => {
-                    self.task_queue.push_back(task);
-                    Some(false)
+        if self.pending.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+
+        let id = self
+            .tasks
+            .range(self.cursor..)
+            .chain(self.tasks.range(..self.cursor))
+            .find(|(_, (_, waker))| waker.queued.load(Ordering::Acquire))
+            .map(|(&id, _)| id)?;
+        self.cursor = id + 1;
+        let (task, waker) = self.tasks.get_mut(&id)?;
+
+        // Cleared before polling, so a wake during the poll queues the task again
+        waker.queued.store(false, Ordering::Release);
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+        let context_waker = Waker::from(waker.clone());
+        let mut context = Context::from_waker(&context_waker);
+
+        match task.poll(&mut context) {
+            Poll::Ready(()) => {
+                // Wakers can outlive their task, leaving the task marked as queued keeps later wakes from counting it
+                if let Some((_, waker)) = self.tasks.remove(&id) {
+                    if waker.queued.swap(true, Ordering::AcqRel) {
+                        self.pending.fetch_sub(1, Ordering::AcqRel);
+                    }
                 }
+                Some(true)
             }
-        } else {
-            None
+            Poll::Pending => Some(false),
         }
     }
 
-    /// Run to exhaustion
+    /// Run until every task has completed, waiting for a task to be woken whenever none are ready. Every waiting task
+    /// is polled again after [`IDLE_SPINS`] spins without a wake.
     pub fn run(&mut self) {
-        while self.run_until_pending() {}
-    }
+        while self.run_until_pending() {
+            let mut spins = 0;
+            while self.pending.load(Ordering::Acquire) == 0 {
+                if spins == IDLE_SPINS {
+                    self.wake_all();
+                    break;
+                }
 
-    /// Run through the queue until all tasks are pending
-    pub fn run_until_pending(&mut self) -> bool {
-        if self.task_queue.is_empty() {
-            return false;
+                spins += 1;
+                core::hint::spin_loop();
+            }
         }
+    }
 
-        'outer: loop {
-            let remaining = self.task_queue.len();
-
-            let mut flag = false;
+    /// Wake every task, so each is polled on the following steps
+    fn wake_all(&self) {
+        for (_, waker) in self.tasks.values() {
+            waker.wake_by_ref();
+        }
+    }
 
-            for _ in 0..remaining {
-                if let Some(b) = self.step() {
-                    flag |= b;
-                } else {
-                    break 'outer false;
-                }
-            }
+    /// Poll woken tasks until none are left ready, returns true if there are tasks still waiting to be woken
+    pub fn run_until_pending(&mut self) -> bool {
+        while self.step().is_some() {}
 
-            if !flag {
-                break true;
-            }
-        }
+        !self.tasks.is_empty()
     }
 }
 
-impl<'a> Default for SimpleExecutor<'a> {
+impl Default for Executor<'_> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-fn dummy_raw_waker() -> RawWaker {
-    const fn no_op(_: *const ()) {}
-    fn clone(_: *const ()) -> RawWaker {
-        dummy_raw_waker()
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{sync::Arc, vec::Vec};
+    use core::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::Executor;
+    use crate::{sync::Mutex, tasks::Task};
+
+    /// Future which stays pending until it is completed by hand, counting the number of times it is polled
+    #[derive(Default)]
+    struct Manual {
+        complete: bool,
+        polls: usize,
+        waker: Option<Waker>,
     }
 
-    let vtable = &RawWakerVTable::new(clone, no_op, no_op, no_op);
-    RawWaker::new(core::ptr::null::<()>(), vtable)
-}
+    struct ManualFuture(Arc<Mutex<Manual>>);
+
+    impl Future for ManualFuture {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut state = self.0.spin_lock();
+            state.polls += 1;
+
+            if state.complete {
+                Poll::Ready(())
+            } else {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    fn complete(state: &Mutex<Manual>) {
+        let waker = {
+            let mut state = state.spin_lock();
+            state.complete = true;
+            state.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    #[test]
+    pub fn only_woken_tasks_polled_test() {
+        let first = Arc::new(Mutex::new(Manual::default()));
+        let second = Arc::new(Mutex::new(Manual::default()));
+
+        let mut executor = Executor::new();
+        executor.spawn(Task::new(ManualFuture(first.clone())));
+        executor.spawn(Task::new(ManualFuture(second.clone())));
+
+        assert!(executor.run_until_pending());
+        assert!(executor.run_until_pending());
+        assert_eq!(first.spin_lock().polls, 1);
+        assert_eq!(second.spin_lock().polls, 1);
+
+        // Waking the second task polls it alone
+        complete(&second);
+        assert_eq!(executor.step(), Some(true));
+        assert_eq!(executor.step(), None);
+        assert_eq!(first.spin_lock().polls, 1);
+        assert_eq!(second.spin_lock().polls, 2);
+
+        complete(&first);
+        executor.run();
+        assert_eq!(first.spin_lock().polls, 2);
+    }
+
+    #[test]
+    pub fn repeated_wakes_poll_once_test() {
+        let state = Arc::new(Mutex::new(Manual::default()));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut executor = Executor::new();
+        executor.spawn(Task::new(ManualFuture(state.clone())));
+        executor.spawn(Task::new({
+            let order = order.clone();
+            async move { order.spin_lock().push(1) }
+        }));
+        assert!(executor.run_until_pending());
+        assert_eq!(*order.spin_lock(), [1]);
+
+        let waker = state.spin_lock().waker.clone().unwrap();
+        waker.wake_by_ref();
+        waker.wake_by_ref();
+        assert_eq!(executor.step(), Some(false));
+        assert_eq!(executor.step(), None);
+        assert_eq!(state.spin_lock().polls, 2);
+
+        // Waking a task after it has completed is ignored
+        complete(&state);
+        assert!(!executor.run_until_pending());
+        waker.wake();
+        assert_eq!(executor.step(), None);
+    }
 
-fn dummy_waker() -> Waker {
-    unsafe { Waker::from_raw(dummy_raw_waker()) }
+    /// Future which never registers its waker, completing once it has been polled `remaining` more times, as a device
+    /// whose completion is only seen by checking it
+    struct Polled {
+        remaining: usize,
+    }
+
+    impl Future for Polled {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.remaining == 0 {
+                Poll::Ready(())
+            } else {
+                self.remaining -= 1;
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    pub fn polled_wake_source_test() {
+        let done = Arc::new(Mutex::new(false));
+
+        let mut executor = Executor::new();
+        executor.spawn(Task::new({
+            let done = done.clone();
+            async move {
+                Polled { remaining: 3 }.await;
+                *done.spin_lock() = true;
+            }
+        }));
+
+        // Nothing ever wakes the task, so it is only finished by being polled again while the executor is idle
+        executor.run();
+        assert!(*done.spin_lock());
+    }
+
+    #[test]
+    pub fn wake_during_poll_test() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut executor = Executor::new();
+        for id in 0..2 {
+            let order = order.clone();
+            executor.spawn(Task::new(async move {
+                for _ in 0..2 {
+                    order.spin_lock().push(id);
+                    crate::tasks::task_yield().await;
+                }
+            }));
+        }
+
+        // A task which wakes itself while being polled is queued again, after the other woken tasks
+        executor.run();
+        assert_eq!(*order.spin_lock(), [0, 1, 0, 1]);
+    }
 }
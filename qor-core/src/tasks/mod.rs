@@ -12,7 +12,20 @@ pub use r#yield::task_yield;
 
 /// Spawn a new executor to run a task to completion synchronously
 pub fn execute_task(task: Task) {
-    let mut executor = SimpleExecutor::new();
+    let mut executor = Executor::new();
     executor.spawn(task);
     executor.run();
-}
\ No newline at end of file
+}
+/// Spawn a new executor to run a future to completion synchronously, returning its output
+///
+/// # Panics
+///
+/// This function will panic if the executor stops before the future completes.
+pub fn block_on<'a, T: 'a>(future: impl core::future::Future<Output = T> + 'a) -> T {
+    let mut result = None;
+    execute_task(Task::new(async {
+        result = Some(future.await);
+    }));
+
+    result.expect("Executor finished without completing the future")
+}
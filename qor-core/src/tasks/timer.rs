@@ -1,9 +1,67 @@
-use crate::{drivers::timer::HardwareTimerDriver, structures::time::Microseconds};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::task::Waker;
+
+use crate::{
+    drivers::timer::HardwareTimerDriver,
+    structures::time::Microseconds,
+    sync::{InterruptControl, IrqSpinLock},
+};
+
+/// Set of wakers waiting on deadlines, ordered by deadline.
+///
+/// The timer interrupt drains the expired entries on every tick, so a sleeping task is woken within one tick of its
+/// deadline however rarely the executor polls it.
+#[allow(clippy::module_name_repetitions)]
+pub struct TimerWheel {
+    deadlines:
+        IrqSpinLock<BTreeMap<Microseconds, Vec<Waker>>, &'static (dyn InterruptControl + Sync)>,
+}
+
+impl TimerWheel {
+    /// Construct a new `TimerWheel` with nothing waiting on it, which masks the timer interrupt through `control`
+    /// while registering a deadline
+    #[must_use]
+    pub const fn new(control: &'static (dyn InterruptControl + Sync)) -> Self {
+        Self {
+            deadlines: IrqSpinLock::new(BTreeMap::new(), control),
+        }
+    }
+
+    /// Wake `waker` once the time passes `deadline`
+    pub fn register(&self, deadline: Microseconds, waker: &Waker) {
+        let mut deadlines = self.deadlines.lock();
+        let wakers = deadlines.entry(deadline).or_default();
+        if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    /// Wake every task whose deadline is before `now`, returning the number of tasks woken. This is called from the
+    /// timer interrupt, which can never interrupt a task registering a deadline, as registering masks the interrupt.
+    pub fn wake_expired(&self, now: Microseconds) -> usize {
+        let expired = {
+            let mut deadlines = self.deadlines.lock_from_interrupt();
+
+            let pending = deadlines.split_off(&now);
+            core::mem::replace(&mut *deadlines, pending)
+        };
+
+        // Wake outside of the lock, as a woken task may register a new deadline straight away
+        let mut woken = 0;
+        for waker in expired.into_values().flatten() {
+            waker.wake();
+            woken += 1;
+        }
+
+        woken
+    }
+}
 
 #[allow(clippy::module_name_repetitions)]
 pub struct TimerFuture<'a, E: Copy> {
     wake_time: Microseconds,
     timer: &'a dyn HardwareTimerDriver<HardwareTimerError = E>,
+    wheel: Option<&'a TimerWheel>,
     error: Option<E>,
 }
 
@@ -21,9 +79,23 @@ impl<'a, E: Copy> TimerFuture<'a, E> {
         Self {
             wake_time,
             timer,
+            wheel: None,
             error,
         }
     }
+
+    /// Construct a new timer future which waits to be woken by `wheel` rather than asking to be polled again
+    /// immediately while the deadline has not passed.
+    pub fn with_wheel(
+        timer: &'a dyn HardwareTimerDriver<HardwareTimerError = E>,
+        wheel: &'a TimerWheel,
+        duration: Microseconds,
+    ) -> Self {
+        Self {
+            wheel: Some(wheel),
+            ..Self::new(timer, duration)
+        }
+    }
 }
 
 pub fn wait<E: Copy>(
@@ -33,6 +105,15 @@ pub fn wait<E: Copy>(
     TimerFuture::new(timer, duration)
 }
 
+/// Wait for `duration`, with the task being woken by `wheel` once the deadline passes.
+pub fn wait_on<'a, E: Copy>(
+    timer: &'a dyn HardwareTimerDriver<HardwareTimerError = E>,
+    wheel: &'a TimerWheel,
+    duration: Microseconds,
+) -> TimerFuture<'a, E> {
+    TimerFuture::with_wheel(timer, wheel, duration)
+}
+
 impl<'a, E: Copy> core::future::Future for TimerFuture<'a, E> {
     type Output = Result<(), E>;
 
@@ -48,7 +129,11 @@ impl<'a, E: Copy> core::future::Future for TimerFuture<'a, E> {
                     if t.0 > self.wake_time.0 {
                         core::task::Poll::Ready(Ok(()))
                     } else {
-                        cx.waker().wake_by_ref();
+                        if let Some(wheel) = self.wheel {
+                            wheel.register(self.wake_time, cx.waker());
+                        } else {
+                            cx.waker().wake_by_ref();
+                        }
                         core::task::Poll::Pending
                     }
                 }
@@ -57,3 +142,176 @@ impl<'a, E: Copy> core::future::Future for TimerFuture<'a, E> {
         }
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::{boxed::Box, sync::Arc, task::Wake, vec::Vec};
+    use core::{
+        future::Future,
+        sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        task::{Context, Poll, Waker},
+    };
+
+    use super::{wait_on, TimerWheel};
+    use crate::{
+        drivers::timer::HardwareTimerDriver,
+        structures::{
+            id::HartID,
+            time::{Hertz, Microseconds},
+        },
+        sync::InterruptControl,
+        tasks::{Executor, Task},
+    };
+
+    /// Interrupt control for tests, where there are no interrupts to mask
+    struct NoInterrupts;
+
+    impl InterruptControl for NoInterrupts {
+        fn disable(&self) -> bool {
+            false
+        }
+
+        fn enable(&self) {}
+    }
+
+    /// Interrupt control which counts the critical sections it is asked to mask interrupts for
+    struct CountingInterrupts {
+        masked: AtomicUsize,
+    }
+
+    impl InterruptControl for CountingInterrupts {
+        fn disable(&self) -> bool {
+            self.masked.fetch_add(1, Ordering::AcqRel);
+            true
+        }
+
+        fn enable(&self) {}
+    }
+
+    /// Clock which only moves when told to
+    struct MockClock {
+        now: AtomicU64,
+    }
+
+    impl HardwareTimerDriver for MockClock {
+        type HardwareTimerError = ();
+
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn initialize(&self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn set_time(&self, _id: HartID, _time: Microseconds) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn time(&self, _id: HartID) -> Result<Microseconds, ()> {
+            Ok(Microseconds(self.now.load(Ordering::Acquire)))
+        }
+
+        fn reset(&self, _id: HartID) -> Result<(), ()> {
+            self.now.store(0, Ordering::Release);
+            Ok(())
+        }
+    }
+
+    /// Waker which counts the number of times it is woken
+    struct CountingWaker {
+        woken: AtomicUsize,
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.woken.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    #[test]
+    pub fn wake_expired_deadlines_test() {
+        static INTERRUPTS: CountingInterrupts = CountingInterrupts {
+            masked: AtomicUsize::new(0),
+        };
+
+        let clock = MockClock {
+            now: AtomicU64::new(0),
+        };
+        let wheel = TimerWheel::new(&INTERRUPTS);
+
+        let mut sleeps = [10, 20, 30, 100]
+            .map(|duration| Box::pin(wait_on(&clock, &wheel, Microseconds(duration))));
+        let counters = (0..sleeps.len())
+            .map(|_| {
+                Arc::new(CountingWaker {
+                    woken: AtomicUsize::new(0),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for (sleep, counter) in sleeps.iter_mut().zip(&counters) {
+            let waker = Waker::from(counter.clone());
+            let poll = sleep.as_mut().poll(&mut Context::from_waker(&waker));
+            assert_eq!(poll, Poll::Pending);
+        }
+
+        // Nothing is woken by a pending sleep, the wheel owns the wakers until their deadline passes
+        assert!(counters
+            .iter()
+            .all(|c| c.woken.load(Ordering::Acquire) == 0));
+
+        // Registering a deadline masks the timer interrupt, draining them from the interrupt does not
+        assert_eq!(INTERRUPTS.masked.load(Ordering::Acquire), 4);
+
+        // A single tick well past the first three deadlines wakes all of them, and only them
+        clock.now.store(50, Ordering::Release);
+        assert_eq!(wheel.wake_expired(Microseconds(50)), 3);
+        assert_eq!(INTERRUPTS.masked.load(Ordering::Acquire), 4);
+
+        let woken = counters
+            .iter()
+            .map(|c| c.woken.load(Ordering::Acquire))
+            .collect::<Vec<_>>();
+        assert_eq!(woken, [1, 1, 1, 0]);
+
+        for (sleep, counter) in sleeps.iter_mut().zip(&counters).take(3) {
+            let waker = Waker::from(counter.clone());
+            let poll = sleep.as_mut().poll(&mut Context::from_waker(&waker));
+            assert_eq!(poll, Poll::Ready(Ok(())));
+        }
+
+        // The expired entries were drained, so the same tick wakes nothing further
+        assert_eq!(wheel.wake_expired(Microseconds(50)), 0);
+    }
+
+    #[test]
+    pub fn sleep_waits_for_wheel_test() {
+        let clock = MockClock {
+            now: AtomicU64::new(0),
+        };
+        let wheel = TimerWheel::new(&NoInterrupts);
+        let done = AtomicBool::new(false);
+
+        // 50 ticks of a 100 kHz clock
+        let duration = Microseconds::from_ticks(50, Hertz(100_000));
+        assert_eq!(duration, Microseconds(500));
+
+        let mut executor = Executor::new();
+        executor.spawn(Task::new(async {
+            wait_on(&clock, &wheel, duration).await.unwrap();
+            done.store(true, Ordering::Release);
+        }));
+        assert!(executor.run_until_pending());
+
+        // The deadline passing is not enough, the sleeping task is only polled once the wheel wakes it
+        clock.now.store(600, Ordering::Release);
+        assert_eq!(executor.step(), None);
+        assert!(!done.load(Ordering::Acquire));
+
+        assert_eq!(wheel.wake_expired(Microseconds(600)), 1);
+        assert!(!executor.run_until_pending());
+        assert!(done.load(Ordering::Acquire));
+    }
+}
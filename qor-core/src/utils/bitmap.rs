@@ -3,6 +3,7 @@
 pub struct BitmapLock {
     bitmap: &'static [core::sync::atomic::AtomicU64],
     length: usize,
+    cleared: core::sync::atomic::AtomicUsize,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -25,23 +26,27 @@ impl BitmapLock {
         Self {
             bitmap: &[],
             length: 0,
+            cleared: core::sync::atomic::AtomicUsize::new(0),
         }
     }
 
-    /// Construct a new `BitmapLock` from a buffer of `AtomicU64`s and a length
+    /// Construct a new `BitmapLock` from a buffer of cleared `AtomicU64`s and a length, which is capped at the
+    /// number of bits the buffer can hold
     pub const fn from_data(
         bitmap: &'static [core::sync::atomic::AtomicU64],
         length: usize,
     ) -> Self {
+        // Bits past `length` do not correspond to anything, so they must never be handed out
         let use_length = if bitmap.len() * 64 >= length {
-            bitmap.len() * 64
-        } else {
             length
+        } else {
+            bitmap.len() * 64
         };
 
         Self {
             bitmap,
             length: use_length,
+            cleared: core::sync::atomic::AtomicUsize::new(use_length),
         }
     }
 
@@ -51,11 +56,16 @@ impl BitmapLock {
         // If there were bits set that overlap our write, we need to leave those bits intact, and remove the excess bits
         if read & mask != 0 {
             let excess = mask & (!read);
-            self.clear_in_entry(entry_index, excess);
+            self.bitmap[entry_index].fetch_and(!excess, core::sync::atomic::Ordering::AcqRel);
+            return false;
         }
 
-        // If none of the bits which are set in the mask were set when we wrote to the entry, we were the ones to acquire those bits.
-        read & mask == 0
+        // None of the bits which are set in the mask were set when we wrote to the entry, so we acquired those bits.
+        self.cleared.fetch_sub(
+            mask.count_ones() as usize,
+            core::sync::atomic::Ordering::AcqRel,
+        );
+        true
     }
 
     /// Attempts to set a sequence of `count` bits starting at *bit* index `index`. Note that any index returned will
@@ -65,7 +75,7 @@ impl BitmapLock {
     ///
     /// This function will return an error if `count` bits after `index` does not fit within the bitmap.
     pub fn try_set(&self, index: usize, count: usize) -> Result<bool, BitmapError> {
-        if index + count >= self.length {
+        if index + count > self.length {
             Err(BitmapError::RangeOutOfBounds {
                 start: index,
                 end: index + count,
@@ -109,6 +119,11 @@ impl BitmapLock {
         let result =
             self.bitmap[entry_index].fetch_and(!mask, core::sync::atomic::Ordering::AcqRel);
 
+        self.cleared.fetch_add(
+            (result & mask).count_ones() as usize,
+            core::sync::atomic::Ordering::AcqRel,
+        );
+
         if result & mask != mask {
             warn!("Attempted to clear bits with mask {:x} at entry {} in bitmap lock, some bits were already cleared");
         }
@@ -120,7 +135,7 @@ impl BitmapLock {
     ///
     /// This function will return an error if `count` bits after index does not fit within the bitmap.
     pub fn clear(&self, index: usize, count: usize) -> Result<(), BitmapError> {
-        if index + count >= self.length {
+        if index + count > self.length {
             Err(BitmapError::RangeOutOfBounds {
                 start: index,
                 end: index + count,
@@ -173,8 +188,9 @@ impl BitmapLock {
                     Ok(true) => {
                         return Ok(index);
                     }
+                    // Every later index would run past the end as well
                     Err(BitmapError::RangeOutOfBounds { .. }) => {
-                        break;
+                        return Err(BitmapError::UnableToAllocate { length: count });
                     }
                     _ => {}
                 }
@@ -185,4 +201,189 @@ impl BitmapLock {
 
         Err(BitmapError::UnableToAllocate { length: count })
     }
+
+    /// Request a sequence of `count` bits to be locked, starting at a *bit* index which becomes a multiple of `align`
+    /// once `offset` is added to it. Returns the index of the first lock, which will be less than `length`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it was unable to allocate `count` bits at an aligned index in the
+    /// bitmap.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `align` is zero.
+    pub fn reserve_aligned_sequence(
+        &self,
+        count: usize,
+        align: usize,
+        offset: usize,
+    ) -> Result<usize, BitmapError> {
+        assert!(align > 0);
+
+        loop {
+            // Find the first run which still holds `count` bits once its start is rounded up to the alignment
+            let aligned = self.free_runs().find_map(|(start, length)| {
+                let aligned_start = (start + offset).next_multiple_of(align) - offset;
+                (aligned_start + count <= start + length).then_some(aligned_start)
+            });
+
+            let Some(start) = aligned else {
+                return Err(BitmapError::UnableToAllocate { length: count });
+            };
+
+            // Another holder may have taken part of the run since it was read, in which case look again
+            if self.try_set(start, count)? {
+                return Ok(start);
+            }
+        }
+    }
+
+    /// Returns true if the bit at `index` is set
+    fn is_set(&self, index: usize) -> bool {
+        self.bitmap[index / 64].load(core::sync::atomic::Ordering::Relaxed) & (1 << (index % 64))
+            != 0
+    }
+
+    /// Iterate over the runs of cleared bits as `(start, length)` pairs, in order of their start index. As the bitmap
+    /// is read without locking, the runs are only a snapshot.
+    fn free_runs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let end = self.length.min(self.bitmap.len() * 64);
+        let mut index = 0;
+
+        core::iter::from_fn(move || {
+            while index < end && self.is_set(index) {
+                index += 1;
+            }
+
+            let start = index;
+            while index < end && !self.is_set(index) {
+                index += 1;
+            }
+
+            (index > start).then_some((start, index - start))
+        })
+    }
+
+    /// Get the number of bits in the bitmap
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns true if the bitmap holds no bits
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Get the number of cleared bits, kept as bits are set and cleared. While a sequence is part way through being
+    /// set, the bits it has already taken are counted as set.
+    #[must_use]
+    pub fn cleared(&self) -> usize {
+        self.cleared.load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Count the cleared bits by scanning the bitmap, see [`BitmapLock::cleared`] for a count which does not need
+    /// the scan
+    #[must_use]
+    pub fn count_clear(&self) -> usize {
+        self.free_runs().map(|(_, length)| length).sum()
+    }
+
+    /// Get the length of the longest run of cleared bits, a measure of how fragmented the bitmap is.
+    #[must_use]
+    pub fn largest_free_run(&self) -> usize {
+        self.free_runs()
+            .map(|(_, length)| length)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Request a sequence of `count` bits to be locked, choosing the shortest run of cleared bits which can hold it
+    /// rather than the first, so large runs are kept intact for large requests. Returns the *bit* index of the first
+    /// lock, which will be less than `length`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it was unable to allocate `count` bits in the bitmap.
+    pub fn reserve_best_fit(&self, count: usize) -> Result<usize, BitmapError> {
+        loop {
+            let best = self
+                .free_runs()
+                .filter(|(_, length)| *length >= count)
+                .min_by_key(|(_, length)| *length);
+
+            let Some((start, _)) = best else {
+                return Err(BitmapError::UnableToAllocate { length: count });
+            };
+
+            // Another holder may have taken part of the run since it was read, in which case look again
+            if self.try_set(start, count)? {
+                return Ok(start);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::prelude::rust_2021::*;
+
+    use super::{BitmapError, BitmapLock};
+
+    fn bitmap(length: usize) -> BitmapLock {
+        let data = (0..length.div_ceil(64))
+            .map(|_| core::sync::atomic::AtomicU64::new(0))
+            .collect::<Vec<_>>();
+        BitmapLock::from_data(Vec::leak(data), length)
+    }
+
+    #[test]
+    pub fn full_reservation_test() {
+        // Lengths which fill whole entries and which end part way through one
+        for length in [128, 100] {
+            let lock = bitmap(length);
+            assert_eq!(lock.reserve_sequence(length), Ok(0));
+            assert_eq!(lock.count_clear(), 0);
+            assert_eq!(lock.cleared(), 0);
+            assert_eq!(
+                lock.reserve_sequence(1),
+                Err(BitmapError::UnableToAllocate { length: 1 })
+            );
+
+            lock.clear(0, length).unwrap();
+            assert_eq!(lock.reserve_best_fit(length), Ok(0));
+            lock.clear(0, length).unwrap();
+            assert_eq!(lock.reserve_aligned_sequence(length, 64, 0), Ok(0));
+        }
+
+        let lock = bitmap(100);
+        assert_eq!(lock.try_set(90, 10), Ok(true));
+        assert_eq!(lock.cleared(), 90);
+        assert!(lock.try_set(95, 6).is_err());
+        assert_eq!(
+            lock.reserve_sequence(101),
+            Err(BitmapError::UnableToAllocate { length: 101 })
+        );
+    }
+
+    #[test]
+    pub fn cleared_count_test() {
+        let lock = bitmap(200);
+        assert_eq!(lock.cleared(), 200);
+
+        // Runs spanning entries, and a failed set which overlaps a held run, keep the count in step with the bitmap
+        assert_eq!(lock.try_set(60, 80), Ok(true));
+        assert_eq!(lock.try_set(10, 60), Ok(false));
+        assert_eq!(lock.try_set(130, 20), Ok(false));
+        assert_eq!(lock.cleared(), 120);
+        assert_eq!(lock.cleared(), lock.count_clear());
+
+        lock.clear(60, 40).unwrap();
+        assert_eq!(lock.reserve_sequence(70), Ok(0));
+        assert_eq!(lock.cleared(), lock.count_clear());
+        assert_eq!(lock.cleared(), 90);
+    }
 }
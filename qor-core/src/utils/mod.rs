@@ -1,3 +1,4 @@
 pub mod bitmap;
 pub mod parser;
 pub mod rawstr;
+pub mod ring_buffer;
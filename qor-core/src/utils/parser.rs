@@ -1,190 +1,289 @@
+/// Order in which the bytes of multi-byte integers are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Error returned when a field runs past the end of the data being parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// Offset of the field from the start of the data
+    pub offset: usize,
+    /// Number of bytes the field needed
+    pub needed: usize,
+    /// Number of bytes left at the offset
+    pub available: usize,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "field at offset {} needs {} bytes, but only {} remain",
+            self.offset, self.needed, self.available
+        )
+    }
+}
+
 pub struct Parser<'a> {
     data: &'a [u8],
+    position: usize,
+    byte_order: ByteOrder,
 }
 
 impl<'a> Parser<'a> {
-    /// Construct a new parser for a slice of `u8`'s
+    /// Construct a new parser for a slice of `u8`'s, which reads integers as little endian
     #[must_use]
     pub const fn new(data: &'a [u8]) -> Self {
-        Self { data }
+        Self::with_byte_order(data, ByteOrder::Little)
     }
 
-    /// Take a `u8` from the slice.
+    /// Construct a new parser for a slice of `u8`'s, which reads integers in the given byte order
     #[must_use]
-    pub fn take_u8(&mut self) -> Option<u8> {
-        if self.data.is_empty() {
-            None
-        } else {
-            let result = self.data[0];
-            self.data = &self.data[1..];
-
-            Some(result)
+    pub const fn with_byte_order(data: &'a [u8], byte_order: ByteOrder) -> Self {
+        Self {
+            data,
+            position: 0,
+            byte_order,
         }
     }
 
-    /// Take a `u16` from the slice.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if the slice is of the wrong size.
+    /// Get the byte order integers are read in
     #[must_use]
-    pub fn take_u16(&mut self) -> Option<u16> {
-        if self.data.len() >= 2 {
-            let result = u16::from_le_bytes(self.data[0..2].try_into().unwrap());
-            self.data = &self.data[2..];
+    pub const fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
 
-            Some(result)
-        } else {
-            None
+    /// Read the integers which follow in the given byte order, for formats which record their byte order in a header
+    pub const fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.byte_order = byte_order;
+    }
+
+    /// Get the offset of the next byte to be taken from the start of the data
+    #[must_use]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Take the next `length` bytes, leaving the parser untouched if there are not enough left.
+    ///
+    /// # Errors
+    ///
+    /// Returns where the bytes were needed and how many were left if there are fewer than `length` bytes left.
+    pub const fn try_take(&mut self, length: usize) -> Result<&'a [u8], ParseError> {
+        if self.data.len() < length {
+            return Err(ParseError {
+                offset: self.position,
+                needed: length,
+                available: self.data.len(),
+            });
         }
+
+        let (result, rest) = self.data.split_at(length);
+        self.data = rest;
+        self.position += length;
+
+        Ok(result)
     }
 
-    /// Take a `u32` from the slice.
+    /// Take an array of `u8`'s of a given length.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    #[must_use]
-    pub fn take_u32(&mut self) -> Option<u32> {
-        if self.data.len() >= 4 {
-            let result = u32::from_le_bytes(self.data[0..4].try_into().unwrap());
-            self.data = &self.data[4..];
+    /// Returns where the array starts if it runs past the end of the data.
+    pub fn try_take_u8_array<const L: usize>(&mut self) -> Result<[u8; L], ParseError> {
+        let mut result = [0; L];
+        result.copy_from_slice(self.try_take(L)?);
 
-            Some(result)
-        } else {
-            None
-        }
+        Ok(result)
     }
 
-    /// Take a `u64` from the slice.
+    /// Take a `u8` from the slice.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    #[must_use]
-    pub fn take_u64(&mut self) -> Option<u64> {
-        if self.data.len() >= 8 {
-            let result = u64::from_le_bytes(self.data[0..8].try_into().unwrap());
-            self.data = &self.data[8..];
+    /// Returns where the field starts if the data is empty.
+    pub fn try_take_u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.try_take(1)?[0])
+    }
 
-            Some(result)
-        } else {
-            None
-        }
+    /// Take a `u16` from the slice, in the parser's byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns where the field starts if it runs past the end of the data.
+    pub fn try_take_u16(&mut self) -> Result<u16, ParseError> {
+        let bytes = self.try_take_u8_array()?;
+
+        Ok(match self.byte_order {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        })
     }
 
-    /// Take a `u128` from the slice.
+    /// Take a `u32` from the slice, in the parser's byte order.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    #[must_use]
-    pub fn take_u128(&mut self) -> Option<u128> {
-        if self.data.len() >= 16 {
-            let result = u128::from_le_bytes(self.data[0..16].try_into().unwrap());
-            self.data = &self.data[16..];
+    /// Returns where the field starts if it runs past the end of the data.
+    pub fn try_take_u32(&mut self) -> Result<u32, ParseError> {
+        let bytes = self.try_take_u8_array()?;
 
-            Some(result)
-        } else {
-            None
-        }
+        Ok(match self.byte_order {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        })
     }
 
-    /// Take an array of `u8`'s of a given length.
+    /// Take a `u64` from the slice, in the parser's byte order.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    pub fn take_u8_array<const L: usize>(&mut self) -> Option<[u8; L]> {
-        if self.data.len() >= L {
-            let result = self.data[0..L].try_into().unwrap();
-            self.data = &self.data[L..];
+    /// Returns where the field starts if it runs past the end of the data.
+    pub fn try_take_u64(&mut self) -> Result<u64, ParseError> {
+        let bytes = self.try_take_u8_array()?;
 
-            Some(result)
-        } else {
-            None
-        }
+        Ok(match self.byte_order {
+            ByteOrder::Little => u64::from_le_bytes(bytes),
+            ByteOrder::Big => u64::from_be_bytes(bytes),
+        })
     }
 
-    /// Take a slice of `u8`'s.
+    /// Take a `u128` from the slice, in the parser's byte order.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    pub fn take_u8_slice(&mut self, length: usize) -> Option<&'_ [u8]> {
-        if self.data.len() >= length {
-            let result = &self.data[0..length];
-            self.data = &self.data[length..];
+    /// Returns where the field starts if it runs past the end of the data.
+    pub fn try_take_u128(&mut self) -> Result<u128, ParseError> {
+        let bytes = self.try_take_u8_array()?;
 
-            Some(result)
-        } else {
-            None
-        }
+        Ok(match self.byte_order {
+            ByteOrder::Little => u128::from_le_bytes(bytes),
+            ByteOrder::Big => u128::from_be_bytes(bytes),
+        })
     }
 
     /// Take an array of `u16`'s of a given length.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    pub fn take_u16_array<const L: usize>(&mut self) -> Option<[u16; L]> {
-        if self.data.len() >= L {
-            let mut result = [0; L];
-            for slot in result.iter_mut() {
-                *slot = self.take_u16().unwrap();
-            }
+    /// Returns where the array starts if any of it runs past the end of the data, in which case nothing is taken.
+    pub fn try_take_u16_array<const L: usize>(&mut self) -> Result<[u16; L], ParseError> {
+        self.check_remaining(L * 2)?;
 
-            Some(result)
-        } else {
-            None
+        let mut result = [0; L];
+        for slot in &mut result {
+            *slot = self.try_take_u16()?;
         }
+
+        Ok(result)
     }
 
     /// Take an array of `u32`'s of a given length.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    pub fn take_u32_array<const L: usize>(&mut self) -> Option<[u32; L]> {
-        if self.data.len() >= L {
-            let mut result = [0; L];
-            for slot in result.iter_mut() {
-                *slot = self.take_u32().unwrap();
-            }
+    /// Returns where the array starts if any of it runs past the end of the data, in which case nothing is taken.
+    pub fn try_take_u32_array<const L: usize>(&mut self) -> Result<[u32; L], ParseError> {
+        self.check_remaining(L * 4)?;
 
-            Some(result)
-        } else {
-            None
+        let mut result = [0; L];
+        for slot in &mut result {
+            *slot = self.try_take_u32()?;
         }
+
+        Ok(result)
     }
 
     /// Take an array of `u64`'s of a given length.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// This function will panic if the slice is of the wrong size.
-    pub fn take_u64_array<const L: usize>(&mut self) -> Option<[u64; L]> {
-        if self.data.len() >= L {
-            let mut result = [0; L];
-            for slot in result.iter_mut() {
-                *slot = self.take_u64().unwrap();
-            }
+    /// Returns where the array starts if any of it runs past the end of the data, in which case nothing is taken.
+    pub fn try_take_u64_array<const L: usize>(&mut self) -> Result<[u64; L], ParseError> {
+        self.check_remaining(L * 8)?;
 
-            Some(result)
+        let mut result = [0; L];
+        for slot in &mut result {
+            *slot = self.try_take_u64()?;
+        }
+
+        Ok(result)
+    }
+
+    /// Check that at least `length` bytes are left, without taking them
+    const fn check_remaining(&self, length: usize) -> Result<(), ParseError> {
+        if self.data.len() < length {
+            Err(ParseError {
+                offset: self.position,
+                needed: length,
+                available: self.data.len(),
+            })
         } else {
-            None
+            Ok(())
         }
     }
 
+    /// Take a `u8` from the slice.
+    #[must_use]
+    pub fn take_u8(&mut self) -> Option<u8> {
+        self.try_take_u8().ok()
+    }
+
+    /// Take a `u16` from the slice, in the parser's byte order.
+    #[must_use]
+    pub fn take_u16(&mut self) -> Option<u16> {
+        self.try_take_u16().ok()
+    }
+
+    /// Take a `u32` from the slice, in the parser's byte order.
+    #[must_use]
+    pub fn take_u32(&mut self) -> Option<u32> {
+        self.try_take_u32().ok()
+    }
+
+    /// Take a `u64` from the slice, in the parser's byte order.
+    #[must_use]
+    pub fn take_u64(&mut self) -> Option<u64> {
+        self.try_take_u64().ok()
+    }
+
+    /// Take a `u128` from the slice, in the parser's byte order.
+    #[must_use]
+    pub fn take_u128(&mut self) -> Option<u128> {
+        self.try_take_u128().ok()
+    }
+
+    /// Take an array of `u8`'s of a given length.
+    pub fn take_u8_array<const L: usize>(&mut self) -> Option<[u8; L]> {
+        self.try_take_u8_array().ok()
+    }
+
+    /// Take a slice of `u8`'s.
+    pub fn take_u8_slice(&mut self, length: usize) -> Option<&'_ [u8]> {
+        self.try_take(length).ok()
+    }
+
+    /// Take an array of `u16`'s of a given length.
+    pub fn take_u16_array<const L: usize>(&mut self) -> Option<[u16; L]> {
+        self.try_take_u16_array().ok()
+    }
+
+    /// Take an array of `u32`'s of a given length.
+    pub fn take_u32_array<const L: usize>(&mut self) -> Option<[u32; L]> {
+        self.try_take_u32_array().ok()
+    }
+
+    /// Take an array of `u64`'s of a given length.
+    pub fn take_u64_array<const L: usize>(&mut self) -> Option<[u64; L]> {
+        self.try_take_u64_array().ok()
+    }
+
     /// Skip a certain number of bytes
     pub fn skip(&mut self, length: usize) -> Option<()> {
-        if self.data.len() >= length {
-            self.data = &self.data[length..];
-
-            Some(())
-        } else {
-            None
-        }
+        self.try_take(length).ok().map(|_| ())
     }
 
     /// Returns true if the buffer is empty
@@ -201,3 +300,57 @@ impl<'a> core::iter::Iterator for Parser<'a> {
         self.take_u8()
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{ByteOrder, ParseError, Parser};
+
+    #[test]
+    pub fn byte_order_test() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+        let mut parser = Parser::new(&data);
+        assert_eq!(parser.take_u16(), Some(0x0201));
+        assert_eq!(parser.take_u32(), Some(0x0605_0403));
+
+        let mut parser = Parser::with_byte_order(&data, ByteOrder::Big);
+        assert_eq!(parser.take_u16(), Some(0x0102));
+        parser.set_byte_order(ByteOrder::Little);
+        assert_eq!(parser.take_u16(), Some(0x0403));
+        assert_eq!(parser.take_u32(), None);
+    }
+
+    #[test]
+    pub fn position_test() {
+        let data = [0; 10];
+        let mut parser = Parser::new(&data);
+
+        assert_eq!(parser.position(), 0);
+        assert_eq!(parser.try_take_u32(), Ok(0));
+        assert_eq!(parser.try_take_u16(), Ok(0));
+        assert_eq!(parser.position(), 6);
+
+        // A failed take reports where the field started, and leaves the parser where it was
+        assert_eq!(
+            parser.try_take_u64(),
+            Err(ParseError {
+                offset: 6,
+                needed: 8,
+                available: 4
+            })
+        );
+        assert_eq!(parser.position(), 6);
+
+        assert_eq!(
+            parser.try_take_u32_array::<2>(),
+            Err(ParseError {
+                offset: 6,
+                needed: 8,
+                available: 4
+            })
+        );
+        assert_eq!(parser.try_take_u32_array::<1>(), Ok([0]));
+        assert!(parser.empty());
+    }
+}
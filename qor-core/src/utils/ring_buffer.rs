@@ -0,0 +1,139 @@
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// Fixed size queue of bytes, filled by a single producer, such as an interrupt handler, and drained by a single
+/// consumer, without either ever waiting on the other.
+///
+/// Bytes pushed while the buffer is full are dropped, keeping the oldest bytes, and counted.
+#[allow(clippy::module_name_repetitions)]
+pub struct ByteRingBuffer<const N: usize> {
+    data: [AtomicU8; N],
+    /// Number of bytes ever pushed, only written by the producer
+    head: AtomicUsize,
+    /// Number of bytes ever popped, only written by the consumer
+    tail: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl<const N: usize> ByteRingBuffer<N> {
+    /// Construct a new empty `ByteRingBuffer`
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `N` is not a power of two, which keeps the slot of each byte consistent when the
+    /// counters wrap.
+    #[must_use]
+    pub const fn new() -> Self {
+        assert!(N.is_power_of_two());
+
+        Self {
+            data: [const { AtomicU8::new(0) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add `byte` to the end of the buffer, returning false if the buffer was full and the byte was dropped. Only one
+    /// caller may push at a time.
+    pub fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+
+        if head.wrapping_sub(tail) >= N {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        self.data[head % N].store(byte, Ordering::Relaxed);
+        // Publishes the byte to the consumer
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+
+        true
+    }
+
+    /// Take the oldest byte from the buffer, if there is one. Only one caller may pop at a time.
+    pub fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+
+        if tail == head {
+            return None;
+        }
+
+        let byte = self.data[tail % N].load(Ordering::Relaxed);
+        // Hands the slot back to the producer
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+
+        Some(byte)
+    }
+
+    /// Get the number of bytes waiting in the buffer
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.head
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.tail.load(Ordering::Acquire))
+    }
+
+    /// Returns true if there are no bytes waiting in the buffer
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of bytes dropped because the buffer was full
+    #[must_use]
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> Default for ByteRingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::ByteRingBuffer;
+
+    #[test]
+    pub fn push_pop_test() {
+        let buffer = ByteRingBuffer::<4>::new();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.pop(), None);
+
+        // Interleaved pushes and pops wrap around the end of the storage
+        for round in 0..3u8 {
+            assert!(buffer.push(round * 2));
+            assert!(buffer.push(round * 2 + 1));
+            assert_eq!(buffer.len(), 2);
+            assert_eq!(buffer.pop(), Some(round * 2));
+            assert_eq!(buffer.pop(), Some(round * 2 + 1));
+        }
+
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.dropped(), 0);
+    }
+
+    #[test]
+    pub fn overflow_test() {
+        let buffer = ByteRingBuffer::<4>::new();
+
+        let accepted = (0..6).map(|byte| buffer.push(byte)).collect::<Vec<_>>();
+        assert_eq!(accepted, [true, true, true, true, false, false]);
+        assert_eq!(buffer.len(), 4);
+        assert_eq!(buffer.dropped(), 2);
+
+        // The oldest bytes are kept, and the freed space is usable again
+        assert_eq!(buffer.pop(), Some(0));
+        assert!(buffer.push(6));
+        let drained = core::iter::from_fn(|| buffer.pop()).collect::<Vec<_>>();
+        assert_eq!(drained, [1, 2, 3, 6]);
+        assert_eq!(buffer.dropped(), 2);
+    }
+}
@@ -19,6 +19,9 @@ _start:
     # If we are not on hart 0, we will jump to a loop
     bnez t0, _wfi_loop
 
+    # Keep the address of the device tree given by the firmware, as `a1` is used below
+    mv s1, a1
+
     # Clear the BSS section by writing 8 byte double words to it
 
     # Load the start and end pointers
@@ -58,6 +61,9 @@ _start_init:
     # Set up the return address for when `kinit` returns
     la ra, _after_kinit
 
+    # Pass the device tree address to `kinit`
+    mv a0, s1
+
     # Call `kinit`
     mret
 
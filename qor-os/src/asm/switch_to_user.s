@@ -25,6 +25,12 @@ switch_to_user:
         .set i, i + 1
     .endr
 
+    # The floating point registers now match the trap frame, so mark them as clean
+    li t0, 3 << 13
+    csrc mstatus, t0
+    li t0, 2 << 13
+    csrs mstatus, t0
+
     .set i, 1
     .rept 31
         load_gp %i, t6
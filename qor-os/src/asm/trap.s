@@ -41,6 +41,7 @@ asm_trap_vector:
     csrr t6, mscratch
     save_gp 31, t5
 
+    # Only save the floating point registers if the FS field of mstatus marks them as dirty
     csrr t1, mstatus
     srli t0, t1, 13
     andi t0, t0, 3
@@ -52,6 +53,10 @@ asm_trap_vector:
         .set i, i+1
     .endr
 
+    # Mark the floating point registers as clean, they now match the trap frame
+    li t3, 1 << 13
+    csrc mstatus, t3
+
 skip_float_save:
     csrw mscratch, t5
 
@@ -72,6 +77,23 @@ skip_float_save:
     csrw mepc, a0
     csrr t6, mscratch
 
+    # The floating point registers only need to be restored if they were written while handling the trap
+    csrr t1, mstatus
+    srli t0, t1, 13
+    andi t0, t0, 3
+    li t3, 3
+    bne t0, t3, skip_float_restore
+    .set i, 0
+    .rept 32
+        load_fp %i
+        .set i, i+1
+    .endr
+
+    li t3, 1 << 13
+    csrc mstatus, t3
+
+skip_float_restore:
+
     .set i, 1
     .rept 31
         load_gp %i
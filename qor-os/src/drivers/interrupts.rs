@@ -1,5 +1,5 @@
 use qor_core::structures::id::HartID;
-use qor_riscv::drivers::plic::InterruptSource;
+use qor_riscv::drivers::plic::{InterruptPriority, InterruptSource};
 
 pub const UART_INTERRUPT: InterruptSource = InterruptSource::Source10;
 
@@ -30,25 +30,12 @@ pub fn initialize_plic(boot_hart: HartID) {
     let plic = &crate::drivers::PLIC_DRIVER;
     plic.initialize().expect("Unable to initialize PLIC");
     for int in VIRTIO_INTERRUPTS {
-        plic.set_interrupt_priority(int, qor_riscv::drivers::plic::InterruptPriority::Priority7)
-            .expect("Unable to set VirtIO interrupt priority");
-
-        plic.enable_interrupt_source(boot_hart, int)
-            .expect("Unable to enable VirtIO interrupts");
+        plic.set_priority(int, InterruptPriority::Priority7);
+        plic.enable_source(boot_hart, int);
     }
 
-    plic.set_interrupt_priority(
-        UART_INTERRUPT,
-        qor_riscv::drivers::plic::InterruptPriority::Priority7,
-    )
-    .expect("Unable to set UART interrupt priority");
-
-    plic.enable_interrupt_source(boot_hart, UART_INTERRUPT)
-        .expect("Unable to enable UART interrupts");
+    plic.set_priority(UART_INTERRUPT, InterruptPriority::Priority7);
+    plic.enable_source(boot_hart, UART_INTERRUPT);
 
-    plic.set_hart_threshold(
-        boot_hart,
-        qor_riscv::drivers::plic::InterruptPriority::Priority1,
-    )
-    .expect("Unable to set PLIC threshold");
+    plic.set_threshold(boot_hart, InterruptPriority::Priority1);
 }
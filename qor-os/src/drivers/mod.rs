@@ -1,4 +1,12 @@
-use qor_core::drivers::uart::UARTDriverInterface;
+use qor_core::drivers::{
+    plic::PLICDriverInterface,
+    status::{DeviceRegistry, DeviceStatus},
+    uart::UARTDriverInterface,
+};
+use qor_core::structures::{
+    fdt::DeviceTree,
+    time::{Hertz, Microseconds},
+};
 use qor_riscv::drivers::{clint::HardwareTimer, plic::PLICDriver, uart::UARTDriver};
 
 pub mod interrupts;
@@ -14,6 +22,18 @@ pub static UART_DRIVER: UARTDriver = unsafe { UARTDriver::new(0x1000_0000) };
 // Safety: This is the base address given in the specification for the `virt` platform by QEMU (https://github.com/qemu/qemu/blob/master/hw/riscv/virt.c)
 pub static CLINT_DRIVER: HardwareTimer = unsafe { HardwareTimer::new(0x200_0000) };
 
+/// Deadlines of sleeping kernel tasks, drained by the CLINT timer interrupt
+pub static TIMER_WHEEL: qor_core::tasks::TimerWheel =
+    qor_core::tasks::TimerWheel::new(&qor_riscv::trap::interrupts::MachineInterrupts);
+
+/// Suspend the calling task until `duration` has passed. The task is not polled while it sleeps, it is woken by the
+/// CLINT timer interrupt on the first tick after the deadline.
+///
+/// The future resolves to an error if the CLINT time could not be read.
+pub fn sleep(duration: Microseconds) -> qor_core::tasks::TimerFuture<'static, ()> {
+    qor_core::tasks::wait_on(&CLINT_DRIVER, &TIMER_WHEEL, duration)
+}
+
 // Safety: This is the base address given in the specification for the `virt` platform by QEMU (https://github.com/qemu/qemu/blob/master/hw/riscv/virt.c)
 pub static PLIC_DRIVER: PLICDriver = unsafe { PLICDriver::new(0xc00_0000) };
 
@@ -26,6 +46,44 @@ pub static BLOCK_DRIVER: atomic_ref::AtomicRef<
     >,
 > = atomic_ref::AtomicRef::new(None);
 
+/// Driver of the Virt IO entropy source, if one was found while probing
+pub static ENTROPY_DRIVER: atomic_ref::AtomicRef<'static, virtio::entropy::EntropyDriver> =
+    atomic_ref::AtomicRef::new(None);
+
+/// Driver of the Virt IO network card, if one was found while probing
+pub static NETWORK_DRIVER: atomic_ref::AtomicRef<'static, virtio::net::NetworkDriver> =
+    atomic_ref::AtomicRef::new(None);
+
+/// Devices found while probing the Virt IO address range
+pub static DEVICE_REGISTRY: DeviceRegistry = DeviceRegistry::new();
+
+/// Move the UART driver to the first `ns16550a` compatible device in `device_tree`, returning its base address. The
+/// driver keeps the address of the `virt` platform if there is no such device.
+///
+/// # Panics
+///
+/// This function will panic if the UART driver has already been initialized.
+pub fn locate_uart(device_tree: &DeviceTree) -> Option<usize> {
+    let base = usize::try_from(device_tree.find_compatible("ns16550a")?.reg?.start).ok()?;
+
+    // Safety: The device tree describes a 16550 UART at this address
+    unsafe { UART_DRIVER.relocate(base) };
+
+    Some(base)
+}
+
+/// Find the base address of the `sifive_test` finisher device in `device_tree`, used to power off the machine
+#[must_use]
+pub fn locate_test_finisher(device_tree: &DeviceTree) -> Option<usize> {
+    usize::try_from(device_tree.find_compatible("sifive,test0")?.reg?.start).ok()
+}
+
+/// Count the harts described by `device_tree`, as the number of `riscv` compatible cpu nodes
+#[must_use]
+pub fn count_harts(device_tree: &DeviceTree) -> usize {
+    device_tree.devices().filter(|device| device.is_compatible("riscv")).count()
+}
+
 /// Initialize the UART Driver
 ///
 /// # Errors
@@ -45,3 +103,22 @@ pub fn get_block_driver() -> &'static alloc::boxed::Box<
         .load(core::sync::atomic::Ordering::Acquire)
         .expect("Block device driver not initialized")
 }
+
+/// Get the status of every device the kernel knows of, the UART, CLINT and PLIC, followed by each Virt IO device found
+/// while probing.
+pub fn status_report() -> alloc::vec::Vec<DeviceStatus> {
+    let mut report = alloc::vec![
+        DeviceStatus::Uart {
+            initialized: UART_DRIVER.is_initialized(),
+        },
+        DeviceStatus::Timer {
+            frequency: Hertz::from_period(CLINT_DRIVER.quantum()),
+        },
+        DeviceStatus::InterruptController {
+            initialized: PLIC_DRIVER.is_initialized(),
+        },
+    ];
+    report.extend(DEVICE_REGISTRY.devices());
+
+    report
+}
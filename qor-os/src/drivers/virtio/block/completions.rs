@@ -0,0 +1,93 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU16, Ordering};
+use core::task::Waker;
+
+use qor_core::{interfaces::mmio::MMIOInterface, sync::Mutex};
+use qor_riscv::drivers::virtio::generic::{completion::PendingRequests, raw, structures::UsedRing};
+
+/// Slot a [`super::BlockOperationFuture`] leaves its waker in, to be woken once the device completes its request
+pub type RequestWaker = Mutex<Option<Waker>>;
+
+/// Requests in flight on a block device's queue.
+///
+/// This is shared with the interrupt handler, which cannot wait on the device lock as that is held by the tasks
+/// awaiting the requests. Neither lock here is ever waited on by the interrupt handler, if it finds one held, it has
+/// interrupted the holder, which checks the used ring again after releasing it.
+pub struct Completions {
+    pending: Mutex<PendingRequests<Arc<RequestWaker>>>,
+    acknowledged: AtomicU16,
+    used: *const UsedRing,
+    mmio: MMIOInterface,
+}
+
+// Safety: The used ring is only read, and always through volatile reads, as the device writes it concurrently
+unsafe impl Send for Completions {}
+// Safety: As above
+unsafe impl Sync for Completions {}
+
+impl Completions {
+    /// Construct an empty set of completions for the queue whose used ring is at `used`, on the device at `mmio`.
+    ///
+    /// # Safety
+    ///
+    /// `used` must point to the used ring of a queue of the device at `mmio`, which is never freed.
+    pub const unsafe fn new(used: *const UsedRing, mmio: MMIOInterface) -> Self {
+        Self {
+            pending: Mutex::new(PendingRequests::new()),
+            acknowledged: AtomicU16::new(0),
+            used,
+            mmio,
+        }
+    }
+
+    /// Record the request whose descriptor chain starts at `head` as in flight, `waker` is woken when it completes.
+    /// This must be called before the device is notified of the request.
+    pub fn submit(&self, head: u16, waker: Arc<RequestWaker>) {
+        self.pending.spin_lock().insert(head, waker);
+
+        // An interrupt taken while the lock was held could not process the ring
+        self.process();
+    }
+
+    /// Acknowledge the device's interrupt and wake the tasks waiting on every request it has completed
+    pub fn handle_interrupt(&self) {
+        // Safety: `mmio` is the device's register interface, as required by `new`
+        unsafe {
+            raw::set_interrupt_ack(&self.mmio, raw::read_interrupt_status(&self.mmio));
+        }
+
+        self.process();
+    }
+
+    /// Wake the tasks waiting on requests which the device has returned through the used ring. This is called from
+    /// the interrupt handler, and by the futures awaiting requests, so they still complete with interrupts masked.
+    pub fn process(&self) {
+        loop {
+            let Some(mut pending) = self.pending.try_lock() else {
+                return;
+            };
+
+            // Safety: `used` points to a used ring which is never freed, as required by `new`
+            pending.complete(unsafe { &*self.used }, |waker, _| wake(&waker));
+            self.acknowledged
+                .store(pending.acknowledged(), Ordering::Release);
+            drop(pending);
+
+            // Requests completed after the ring was read, whose interrupt found the lock held, are handled here
+            // Safety: As above
+            let used_index = unsafe { core::ptr::addr_of!((*self.used).idx).read_volatile() };
+            if used_index == self.acknowledged.load(Ordering::Acquire) {
+                return;
+            }
+        }
+    }
+}
+
+/// Wake the task whose waker is in `slot`, if there is one
+fn wake(slot: &RequestWaker) {
+    // A future re-checks its request's status after registering its waker, so if the slot is held, the future sees
+    // the request has completed without needing to be woken
+    if let Some(waker) = slot.try_lock().and_then(|mut slot| slot.take()) {
+        waker.wake();
+    }
+}
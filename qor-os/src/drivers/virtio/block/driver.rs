@@ -2,7 +2,7 @@
 
 use core::marker::PhantomData;
 
-use alloc::{boxed::Box, sync::Arc};
+use alloc::sync::Arc;
 use qor_core::memory::allocators::page::bitmap::PageBox;
 
 use qor_riscv::{
@@ -18,36 +18,36 @@ use qor_riscv::{
 
 use crate::memory::get_page_bitmap_allocator;
 
-use super::{BlockOperationFuture, Request, VIRTIO_BLK_T_IN, VIRTIO_BLK_T_OUT};
+use super::{
+    BlockOperationFuture, Completions, Request, VIRTIO_BLK_F_BLK_SIZE, VIRTIO_BLK_F_SIZE_MAX,
+    VIRTIO_BLK_F_TOPOLOGY, VIRTIO_BLK_T_IN, VIRTIO_BLK_T_OUT,
+};
+
+/// Offsets of fields within the block device configuration space
+const CONFIG_SIZE_MAX_OFFSET: usize = 8;
+const CONFIG_BLK_SIZE_OFFSET: usize = 20;
+const CONFIG_OPT_IO_SIZE_OFFSET: usize = 28;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VirtIOBlockDeviceError {
     IOError,
     UnsupportedOperation,
+    /// The request ran past the end of the partition it was made through, and was never sent to the device
+    OutOfPartition(qor_core::drivers::block::OutOfPartition),
+}
+
+impl From<qor_core::drivers::block::OutOfPartition> for VirtIOBlockDeviceError {
+    fn from(value: qor_core::drivers::block::OutOfPartition) -> Self {
+        Self::OutOfPartition(value)
+    }
 }
 
 #[allow(dead_code)]
 pub struct VirtIOBlockDevice {
     inner: VirtIOWrapper,
     queue: Option<PageBox<'static, Page, Queue>>,
+    completions: Option<Arc<Completions>>,
     index: u16,
-    ack_used_index: u16,
-}
-
-fn alloc_request<'a>(
-    request_type: u32,
-    sector: u64,
-    buffer: *mut u8,
-    status: u8,
-) -> Box<Request<'a>> {
-    Box::new(Request {
-        request_type,
-        reserved: 0,
-        sector,
-        data: buffer,
-        status: core::sync::atomic::AtomicU8::new(status),
-        _marker: PhantomData,
-    })
 }
 
 fn arc_alloc_request<'a>(
@@ -62,6 +62,7 @@ fn arc_alloc_request<'a>(
         sector,
         data: buffer,
         status: core::sync::atomic::AtomicU8::new(status),
+        waker: Arc::new(qor_core::sync::Mutex::new(None)),
         _marker: PhantomData,
     })
 }
@@ -79,8 +80,8 @@ impl VirtIOBlockDevice {
         Self {
             inner,
             queue: None,
+            completions: None,
             index: 0,
-            ack_used_index: 0,
         }
     }
 
@@ -102,11 +103,66 @@ impl VirtIOBlockDevice {
                 .alloc_boxed(Queue::default())
                 .expect("Couldn't allocate queue")
         })?;
+
+        // Safety: The queue is owned by this device, which is never dropped once initialized, and is a queue of the
+        // device behind `mmio_layer`
+        let completions =
+            unsafe { Completions::new(core::ptr::addr_of!(queue.used), self.inner.mmio_layer) };
+        self.completions = Some(Arc::new(completions));
         self.queue = Some(queue);
 
         self.inner.complete_setup()
     }
 
+    /// Get the requests in flight on this device, to be completed from its interrupt handler.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device has not been initialized.
+    #[must_use]
+    pub fn completions(&self) -> Arc<Completions> {
+        self.completions
+            .clone()
+            .expect("Block device not initialized")
+    }
+
+    /// Get the largest number of 512 byte sectors which should be transferred in a single request. This is the
+    /// optimal I/O size reported in the device topology if one was negotiated, capped to the maximum segment size, as
+    /// each request transfers its data in a single segment. Falls back to a single sector if the device reports
+    /// neither.
+    #[must_use]
+    pub fn optimal_io_blocks(&self) -> u32 {
+        let optimal_bytes = if self.inner.has_feature(VIRTIO_BLK_F_TOPOLOGY) {
+            let logical_block_size = if self.inner.has_feature(VIRTIO_BLK_F_BLK_SIZE) {
+                // Safety: The block size field is present as the `VIRTIO_BLK_F_BLK_SIZE` feature was negotiated
+                unsafe { self.inner.read_device_config::<u32>(CONFIG_BLK_SIZE_OFFSET) }
+            } else {
+                512
+            };
+
+            // Safety: The topology fields are present as the `VIRTIO_BLK_F_TOPOLOGY` feature was negotiated
+            unsafe { self.inner.read_device_config::<u32>(CONFIG_OPT_IO_SIZE_OFFSET) }
+                .saturating_mul(logical_block_size)
+        } else {
+            0
+        };
+
+        let maximum_bytes = if self.inner.has_feature(VIRTIO_BLK_F_SIZE_MAX) {
+            // Safety: The maximum segment size field is present as the `VIRTIO_BLK_F_SIZE_MAX` feature was negotiated
+            unsafe { self.inner.read_device_config::<u32>(CONFIG_SIZE_MAX_OFFSET) }
+        } else {
+            u32::MAX
+        };
+
+        let bytes = match (optimal_bytes, maximum_bytes) {
+            (0, u32::MAX) => 512,
+            (0, maximum) => maximum,
+            (optimal, maximum) => optimal.min(maximum),
+        };
+
+        (bytes / 512).max(1)
+    }
+
     /// Begin executing a block operation.
     fn execute_request<'b, 'a: 'b>(
         &'b mut self,
@@ -140,6 +196,11 @@ impl VirtIOBlockDevice {
         };
         queue.add_descriptor(descriptor);
 
+        self.completions
+            .as_ref()
+            .expect("Block device not initialized")
+            .submit(head_index, request.waker.clone());
+
         let idx = queue.available.idx as usize % VIRTIO_RING_SIZE_USIZE;
         queue.available.ring[idx] = head_index;
         queue.available.idx = queue.available.idx.wrapping_add(1);
@@ -161,30 +222,18 @@ impl VirtIOBlockDevice {
         block_index: usize,
         write: bool,
     ) -> Result<(), VirtIOBlockDeviceError> {
-        let truncated_buffer_length =
-            u32::try_from(buffer_length).expect("Length exceeds maximum buffer size");
-
-        let request = alloc_request(
-            if write {
-                VIRTIO_BLK_T_OUT
-            } else {
-                VIRTIO_BLK_T_IN
-            },
-            block_index as u64,
-            buffer,
-            111,
-        );
-        let status_ptr = self.execute_request(&request, truncated_buffer_length, write);
-
-        while status_ptr.load(core::sync::atomic::Ordering::Acquire) == 111 {}
-        let value = status_ptr.load(core::sync::atomic::Ordering::Acquire);
+        // Safety: The buffer outlives the operation, as it is awaited before returning
+        let operation = unsafe {
+            self.non_blocking_block_operation(
+                buffer,
+                buffer_length,
+                block_index,
+                write,
+                PhantomData::<&u8>,
+            )
+        };
 
-        match value {
-            0 => Ok(()),
-            1 => Err(VirtIOBlockDeviceError::IOError),
-            2 => Err(VirtIOBlockDeviceError::UnsupportedOperation),
-            _ => unimplemented!(),
-        }
+        qor_core::tasks::block_on(operation)
     }
 
     unsafe fn non_blocking_block_operation<'b, 'a: 'b>(
@@ -210,7 +259,7 @@ impl VirtIOBlockDevice {
         );
         self.execute_request(&request, truncated_buffer_length, write);
 
-        BlockOperationFuture::new(111, request)
+        BlockOperationFuture::new(111, request, self.completions())
     }
 
     /// Execute a blocking read operation.
@@ -288,15 +337,4 @@ impl VirtIOBlockDevice {
             )
         }
     }
-
-    /// Clean up after the used ring, note that we don't actually free anything here, as individual requests are
-    /// responsible for doing that themselves.
-    pub fn clean_up(&mut self) {
-        let queue = self.queue.as_mut().expect("Queue not initialized");
-
-        while self.ack_used_index != queue.used.idx {
-            // warn!("A");
-            self.ack_used_index = self.ack_used_index.wrapping_add(1);
-        }
-    }
 }
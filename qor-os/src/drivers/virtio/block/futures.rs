@@ -1,18 +1,20 @@
 use alloc::sync::Arc;
 
-use super::{Request, VirtIOBlockDeviceError};
+use super::{Completions, Request, VirtIOBlockDeviceError};
 
 #[derive(Clone)]
 pub struct BlockOperationFuture<'a> {
     original_value: u8,
     request: Arc<Request<'a>>,
+    completions: Arc<Completions>,
 }
 
 impl<'a> BlockOperationFuture<'a> {
-    pub fn new(original_value: u8, request: Arc<Request<'a>>) -> Self {
+    pub fn new(original_value: u8, request: Arc<Request<'a>>, completions: Arc<Completions>) -> Self {
         Self {
             original_value,
             request,
+            completions,
         }
     }
 }
@@ -22,13 +24,34 @@ impl<'a> core::future::Future for BlockOperationFuture<'a> {
 
     fn poll(
         self: core::pin::Pin<&mut Self>,
-        _cx: &mut core::task::Context<'_>,
+        cx: &mut core::task::Context<'_>,
     ) -> core::task::Poll<Self::Output> {
-        let value = self
+        let mut value = self
             .request
             .status
             .load(core::sync::atomic::Ordering::Acquire);
 
+        if value == self.original_value {
+            *self.request.waker.spin_lock() = Some(cx.waker().clone());
+
+            // The request may have completed before the waker was in place, in which case it may not be woken
+            value = self
+                .request
+                .status
+                .load(core::sync::atomic::Ordering::Acquire);
+        }
+
+        if value == self.original_value {
+            // Polled with interrupts masked, such as from a trap handler, the completion interrupt never arrives, so
+            // the used ring is checked here as well
+            self.completions.process();
+
+            value = self
+                .request
+                .status
+                .load(core::sync::atomic::Ordering::Acquire);
+        }
+
         if value == self.original_value {
             core::task::Poll::Pending
         } else {
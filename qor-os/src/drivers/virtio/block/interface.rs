@@ -2,14 +2,28 @@ use qor_core::{drivers::block::BlockDeviceDriver, sync::Mutex};
 
 use alloc::boxed::Box;
 
-use super::{VirtIOBlockDevice, VirtIOBlockDeviceError};
+use super::{Completions, VirtIOBlockDevice, VirtIOBlockDeviceError};
 
-pub struct BlockDriver(Mutex<VirtIOBlockDevice>);
+pub struct BlockDriver {
+    device: Mutex<VirtIOBlockDevice>,
+    completions: alloc::sync::Arc<Completions>,
+    optimal_io_blocks: u32,
+}
 
 impl BlockDriver {
-    /// Creates a new [`BlockDriver`] by wrapping a [`VirtIOBlockDevice`] in a [`Mutex`].
-    pub const fn new(block: VirtIOBlockDevice) -> Self {
-        Self(Mutex::new(block))
+    /// Creates a new [`BlockDriver`] by wrapping an initialized [`VirtIOBlockDevice`] in a [`Mutex`]. The optimal I/O
+    /// size and the device's completions are taken up front, so they can be used without waiting on in flight
+    /// requests.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device has not been initialized.
+    pub fn new(block: VirtIOBlockDevice) -> Self {
+        Self {
+            optimal_io_blocks: block.optimal_io_blocks(),
+            completions: block.completions(),
+            device: Mutex::new(block),
+        }
     }
 }
 
@@ -23,13 +37,21 @@ impl BlockDeviceDriver<512, VirtIOBlockDeviceError, u32> for BlockDriver {
         Ok(())
     }
 
+    fn optimal_io_blocks(&self) -> u32 {
+        self.optimal_io_blocks
+    }
+
+    fn handle_interrupt(&self) {
+        self.completions.handle_interrupt();
+    }
+
     /// Read a block from the block device
     async fn read_blocks<'b, 'a: 'b>(
         &'b self,
         index: u32,
         buffer: &'a mut [[u8; 512]],
     ) -> Result<(), VirtIOBlockDeviceError> {
-        let mut guard = self.0.async_lock().await;
+        let mut guard = self.device.async_lock().await;
         guard.non_blocking_read(buffer, index as usize).await
     }
 
@@ -39,7 +61,7 @@ impl BlockDeviceDriver<512, VirtIOBlockDeviceError, u32> for BlockDriver {
         index: u32,
         buffer: &'a [[u8; 512]],
     ) -> Result<(), VirtIOBlockDeviceError> {
-        let mut guard = self.0.async_lock().await;
+        let mut guard = self.device.async_lock().await;
         guard.non_blocking_write(buffer, index as usize).await
     }
 }
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+pub mod completions;
+pub use completions::*;
+
 pub mod driver;
 pub use driver::*;
 
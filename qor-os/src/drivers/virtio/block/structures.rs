@@ -2,6 +2,10 @@
 
 use core::marker::PhantomData;
 
+use alloc::sync::Arc;
+
+use super::RequestWaker;
+
 pub const VIRTIO_F_RING_INDIRECT_DESC: u32 = 28;
 pub const VIRTIO_F_RING_EVENT_IDX: u32 = 29;
 pub const VIRTIO_F_VERSION_1: u32 = 32;
@@ -39,13 +43,15 @@ pub const VIRTIO_BLK_F_DISCARD: u32 = 13;
 pub const VIRTIO_BLK_F_WRITE_ZEROES: u32 = 14;
 
 #[repr(C)]
-#[derive(Debug)]
 pub struct Request<'a> {
     pub request_type: u32,
     pub reserved: u32,
     pub sector: u64,
     pub data: *mut u8,
     pub status: core::sync::atomic::AtomicU8,
+    /// Waker of the task awaiting the request, which is only read by the driver, the device only sees the fields
+    /// before it
+    pub waker: Arc<RequestWaker>,
     pub _marker: PhantomData<&'a u8>,
 }
 
@@ -0,0 +1,111 @@
+use qor_core::{interfaces::mmio::MMIOInterface, memory::allocators::page::bitmap::PageBox};
+
+use qor_riscv::{
+    drivers::virtio::generic::{
+        completion::PendingRequests,
+        driver::VirtIOWrapper,
+        structures::{
+            Descriptor, DeviceID, Queue, VirtIOError, VIRTIO_DESC_F_WRITE, VIRTIO_RING_SIZE,
+            VIRTIO_RING_SIZE_USIZE,
+        },
+    },
+    memory::Page,
+};
+
+use crate::memory::get_page_bitmap_allocator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtIOEntropyDeviceError {
+    /// The device completed a request without writing any bytes
+    NoEntropy,
+}
+
+pub struct VirtIOEntropyDevice {
+    inner: VirtIOWrapper,
+    queue: Option<PageBox<'static, Page, Queue>>,
+    pending: PendingRequests<()>,
+}
+
+impl VirtIOEntropyDevice {
+    /// Creates a new [`VirtIOEntropyDevice`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device is not an entropy source.
+    #[must_use]
+    pub fn new(inner: VirtIOWrapper) -> Self {
+        assert_eq!(inner.device_id(), Ok(Some(DeviceID::EntropySource)));
+
+        Self {
+            inner,
+            queue: None,
+            pending: PendingRequests::new(),
+        }
+    }
+
+    /// Initialize this [`VirtIOEntropyDevice`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the initialization failed.
+    pub fn initialize(&mut self) -> Result<(), VirtIOError> {
+        let max_queue_size = self.inner.maximum_queue_size()?;
+        self.inner.set_queue_size(VIRTIO_RING_SIZE)?;
+
+        if max_queue_size < VIRTIO_RING_SIZE {
+            return Err(VirtIOError::BadQueueSize);
+        }
+
+        let queue = self.inner.add_queue(0, || {
+            get_page_bitmap_allocator()
+                .alloc_boxed(Queue::default())
+                .expect("Couldn't allocate queue")
+        })?;
+        self.queue = Some(queue);
+
+        self.inner.complete_setup()
+    }
+
+    /// Get the register interface of the device, used to acknowledge its interrupts without holding the device.
+    #[must_use]
+    pub const fn mmio_layer(&self) -> MMIOInterface {
+        self.inner.mmio_layer
+    }
+
+    /// Request random bytes from the device into `buffer`, spinning until it returns them. The device may write fewer
+    /// bytes than the buffer holds, the number it wrote is returned.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device has not been initialized.
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let queue = self.queue.as_mut().expect("Queue not initialized");
+
+        let head_index = queue.add_descriptor(Descriptor {
+            addr: buffer.as_mut_ptr() as u64,
+            len: u32::try_from(buffer.len()).unwrap_or(u32::MAX),
+            flags: VIRTIO_DESC_F_WRITE,
+            next: 0,
+        });
+        self.pending.insert(head_index, ());
+
+        let idx = queue.available.idx as usize % VIRTIO_RING_SIZE_USIZE;
+        queue.available.ring[idx] = head_index;
+        queue.available.idx = queue.available.idx.wrapping_add(1);
+
+        // The descriptor and available ring must be written before the device is told to read them
+        core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+        self.inner.queue_notify(0);
+
+        let mut written = None;
+        while written.is_none() {
+            self.pending
+                .complete(&queue.used, |(), length| written = Some(length));
+            core::hint::spin_loop();
+        }
+
+        written
+            .map_or(0, |length| length as usize)
+            .min(buffer.len())
+    }
+}
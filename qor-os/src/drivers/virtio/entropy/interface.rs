@@ -0,0 +1,49 @@
+use qor_core::{drivers::random::RandomSource, interfaces::mmio::MMIOInterface, sync::Mutex};
+use qor_riscv::drivers::virtio::generic::raw;
+
+use super::{VirtIOEntropyDevice, VirtIOEntropyDeviceError};
+
+pub struct EntropyDriver {
+    device: Mutex<VirtIOEntropyDevice>,
+    mmio: MMIOInterface,
+}
+
+impl EntropyDriver {
+    /// Creates a new [`EntropyDriver`] by wrapping an initialized [`VirtIOEntropyDevice`] in a [`Mutex`]. The device's
+    /// registers are kept separately, so its interrupts can be acknowledged while a request holds the device.
+    pub const fn new(device: VirtIOEntropyDevice) -> Self {
+        Self {
+            mmio: device.mmio_layer(),
+            device: Mutex::new(device),
+        }
+    }
+
+    /// Acknowledge an interrupt raised by the device. Requests poll the used ring for completion, so there is nothing
+    /// to wake.
+    pub fn handle_interrupt(&self) {
+        // Safety: `mmio` is the register interface of the entropy device
+        unsafe {
+            raw::set_interrupt_ack(&self.mmio, raw::read_interrupt_status(&self.mmio));
+        }
+    }
+}
+
+impl RandomSource for EntropyDriver {
+    type RandomSourceError = VirtIOEntropyDeviceError;
+
+    fn fill_bytes(&self, buffer: &mut [u8]) -> Result<(), VirtIOEntropyDeviceError> {
+        let mut device = self.device.spin_lock();
+
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let written = device.read(&mut buffer[filled..]);
+            if written == 0 {
+                return Err(VirtIOEntropyDeviceError::NoEntropy);
+            }
+
+            filled += written;
+        }
+
+        Ok(())
+    }
+}
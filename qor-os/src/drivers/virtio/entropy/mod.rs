@@ -0,0 +1,6 @@
+pub mod driver;
+pub use driver::*;
+
+pub mod interface;
+#[allow(clippy::module_name_repetitions)]
+pub use interface::EntropyDriver;
@@ -1,8 +1,13 @@
-use qor_riscv::drivers::virtio::generic::structures::DeviceID;
+use qor_core::drivers::status::DeviceStatus;
+use qor_riscv::drivers::virtio::generic::structures::{DeviceID, VIRTIO_RING_SIZE};
 
 use block::VirtIOBlockDevice;
+use entropy::VirtIOEntropyDevice;
+use net::VirtIONetworkDevice;
 
 pub mod block;
+pub mod entropy;
+pub mod net;
 
 /// Prove the Virt IO Address Range for Devices
 pub fn probe_virt_io_address_range() {
@@ -11,6 +16,19 @@ pub fn probe_virt_io_address_range() {
 
         if let Ok(virt_io) = unsafe { qor_riscv::drivers::virtio::probe_virt_io_address(address) } {
             if let Ok(Some(device_id)) = virt_io.verify() {
+                let driver = match device_id {
+                    DeviceID::BlockDevice => Some("block"),
+                    DeviceID::EntropySource => Some("entropy"),
+                    DeviceID::NetworkCard => Some("net"),
+                    _ => None,
+                };
+                crate::drivers::DEVICE_REGISTRY.register(DeviceStatus::VirtIO {
+                    address,
+                    device_id: device_id as u32,
+                    driver,
+                    queue_size: driver.map(|_| VIRTIO_RING_SIZE),
+                });
+
                 if device_id == DeviceID::BlockDevice {
                     info!("Initializing Block Device");
                     virt_io
@@ -31,6 +49,39 @@ pub fn probe_virt_io_address_range() {
                     crate::drivers::BLOCK_DRIVER
                         .store(Some(block), core::sync::atomic::Ordering::Release);
                     info!("Block Device Initialization Complete");
+                } else if device_id == DeviceID::EntropySource {
+                    info!("Initializing Entropy Source");
+                    virt_io.start_setup(|_| Some(0)).expect("Setup Failed");
+                    let mut entropy = VirtIOEntropyDevice::new(virt_io);
+                    entropy
+                        .initialize()
+                        .expect("Unable to initialize entropy source");
+
+                    let entropy = alloc::boxed::Box::leak(alloc::boxed::Box::new(
+                        entropy::EntropyDriver::new(entropy),
+                    ));
+                    crate::drivers::ENTROPY_DRIVER
+                        .store(Some(entropy), core::sync::atomic::Ordering::Release);
+                    info!("Entropy Source Initialization Complete");
+                } else if device_id == DeviceID::NetworkCard {
+                    info!("Initializing Network Card");
+                    virt_io
+                        .start_setup(|features| Some(features & (1 << net::VIRTIO_NET_F_MAC)))
+                        .expect("Setup Failed");
+                    let mut network = VirtIONetworkDevice::new(virt_io);
+                    network
+                        .initialize()
+                        .expect("Unable to initialize network card");
+
+                    let network = alloc::boxed::Box::leak(alloc::boxed::Box::new(
+                        net::NetworkDriver::new(network),
+                    ));
+                    if let Some(mac_address) = network.mac_address() {
+                        info!("Network Card MAC address {}", mac_address);
+                    }
+                    crate::drivers::NETWORK_DRIVER
+                        .store(Some(network), core::sync::atomic::Ordering::Release);
+                    info!("Network Card Initialization Complete");
                 }
             }
         }
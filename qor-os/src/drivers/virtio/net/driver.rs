@@ -0,0 +1,228 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+use qor_core::{interfaces::mmio::MMIOInterface, memory::allocators::page::bitmap::PageBox};
+
+use qor_riscv::{
+    drivers::virtio::generic::{
+        completion::PendingRequests,
+        driver::VirtIOWrapper,
+        structures::{
+            Descriptor, DeviceID, Queue, VirtIOError, VIRTIO_DESC_F_WRITE, VIRTIO_RING_SIZE,
+            VIRTIO_RING_SIZE_USIZE,
+        },
+    },
+    memory::Page,
+};
+
+use crate::memory::get_page_bitmap_allocator;
+
+use super::{
+    MacAddress, CONFIG_MAC_OFFSET, MAX_FRAME_SIZE, NET_HEADER_SIZE, RECEIVE_BUFFER_COUNT,
+    RECEIVE_BUFFER_SIZE, RECEIVE_QUEUE, TRANSMIT_QUEUE, VIRTIO_NET_F_MAC,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtIONetworkDeviceError {
+    /// The frame is larger than [`MAX_FRAME_SIZE`]
+    FrameTooLarge,
+}
+
+pub struct VirtIONetworkDevice {
+    inner: VirtIOWrapper,
+    receive_queue: Option<PageBox<'static, Page, Queue>>,
+    transmit_queue: Option<PageBox<'static, Page, Queue>>,
+    /// Buffers the device writes received frames to, each is always described by the descriptor of the same index.
+    /// These are allocated once and never resized, as the device holds their addresses.
+    receive_buffers: Vec<[u8; RECEIVE_BUFFER_SIZE]>,
+    /// Receive buffers handed to the device, by index
+    receiving: PendingRequests<usize>,
+    transmitting: PendingRequests<()>,
+    /// Frames taken from the receive queue which have not yet been polled
+    received: VecDeque<Vec<u8>>,
+}
+
+impl VirtIONetworkDevice {
+    /// Creates a new [`VirtIONetworkDevice`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device is not a network card.
+    #[must_use]
+    pub fn new(inner: VirtIOWrapper) -> Self {
+        assert_eq!(inner.device_id(), Ok(Some(DeviceID::NetworkCard)));
+
+        Self {
+            inner,
+            receive_queue: None,
+            transmit_queue: None,
+            receive_buffers: Vec::new(),
+            receiving: PendingRequests::new(),
+            transmitting: PendingRequests::new(),
+            received: VecDeque::new(),
+        }
+    }
+
+    /// Initialize this [`VirtIONetworkDevice`], setting up its receive and transmit queues and handing every receive
+    /// buffer to the device.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the initialization failed.
+    pub fn initialize(&mut self) -> Result<(), VirtIOError> {
+        self.receive_queue = Some(self.add_queue(RECEIVE_QUEUE)?);
+        self.transmit_queue = Some(self.add_queue(TRANSMIT_QUEUE)?);
+
+        self.inner.complete_setup()?;
+
+        // The device may not be notified until setup is complete
+        self.receive_buffers = alloc::vec![[0; RECEIVE_BUFFER_SIZE]; RECEIVE_BUFFER_COUNT];
+        for index in 0..RECEIVE_BUFFER_COUNT {
+            self.make_receive_buffer_available(index);
+        }
+        self.inner.queue_notify(RECEIVE_QUEUE);
+
+        Ok(())
+    }
+
+    /// Allocate the queue with the given index and hand it to the device
+    fn add_queue(&self, index: u32) -> Result<PageBox<'static, Page, Queue>, VirtIOError> {
+        self.inner.select_queue(index);
+
+        if self.inner.maximum_queue_size()? < VIRTIO_RING_SIZE {
+            return Err(VirtIOError::BadQueueSize);
+        }
+        self.inner.set_queue_size(VIRTIO_RING_SIZE)?;
+
+        self.inner.add_queue(index, || {
+            get_page_bitmap_allocator()
+                .alloc_boxed(Queue::default())
+                .expect("Couldn't allocate queue")
+        })
+    }
+
+    /// Get the register interface of the device, used to acknowledge its interrupts without holding the device.
+    #[must_use]
+    pub const fn mmio_layer(&self) -> MMIOInterface {
+        self.inner.mmio_layer
+    }
+
+    /// Read the MAC address of the device, if it reports one.
+    #[must_use]
+    pub fn mac_address(&self) -> Option<MacAddress> {
+        if !self.inner.has_feature(VIRTIO_NET_F_MAC) {
+            return None;
+        }
+
+        let mut address = [0; 6];
+        for (offset, byte) in address.iter_mut().enumerate() {
+            // Safety: The MAC address field is present as the `VIRTIO_NET_F_MAC` feature was negotiated
+            *byte = unsafe {
+                self.inner
+                    .read_device_config::<u8>(CONFIG_MAC_OFFSET + offset)
+            };
+        }
+
+        Some(MacAddress(address))
+    }
+
+    /// Hand the receive buffer with the given index to the device. The device is not notified.
+    fn make_receive_buffer_available(&mut self, index: usize) {
+        let queue = self
+            .receive_queue
+            .as_mut()
+            .expect("Receive queue not initialized");
+        let head = u16::try_from(index).expect("Receive buffer index exceeds the ring");
+
+        queue.descriptors[index] = Descriptor::new(
+            self.receive_buffers[index].as_mut_ptr() as usize,
+            RECEIVE_BUFFER_SIZE,
+            VIRTIO_DESC_F_WRITE,
+            0,
+        );
+        self.receiving.insert(head, index);
+        make_available(queue, head);
+    }
+
+    /// Transmit a single Ethernet frame, spinning until the device has sent it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the frame is too large to send.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device has not been initialized.
+    pub fn send_frame(&mut self, frame: &[u8]) -> Result<(), VirtIONetworkDeviceError> {
+        if frame.len() > MAX_FRAME_SIZE {
+            return Err(VirtIONetworkDeviceError::FrameTooLarge);
+        }
+
+        let mut buffer = alloc::vec![0; NET_HEADER_SIZE + frame.len()];
+        buffer[NET_HEADER_SIZE..].copy_from_slice(frame);
+
+        let queue = self
+            .transmit_queue
+            .as_mut()
+            .expect("Transmit queue not initialized");
+        let head = queue.add_descriptor(Descriptor::new(
+            buffer.as_ptr() as usize,
+            buffer.len(),
+            0,
+            0,
+        ));
+        self.transmitting.insert(head, ());
+        make_available(queue, head);
+        self.inner.queue_notify(TRANSMIT_QUEUE);
+
+        // The buffer must outlive the request, so wait for the device to be done with it
+        let mut sent = false;
+        while !sent {
+            self.transmitting.complete(&queue.used, |(), _| sent = true);
+            core::hint::spin_loop();
+        }
+
+        Ok(())
+    }
+
+    /// Take the oldest frame the device has received, without its header, if there is one. The buffers of received
+    /// frames are handed back to the device.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the device has not been initialized.
+    pub fn poll_frame(&mut self) -> Option<Vec<u8>> {
+        let queue = self
+            .receive_queue
+            .as_ref()
+            .expect("Receive queue not initialized");
+
+        let mut returned = Vec::new();
+        let buffers = &self.receive_buffers;
+        let received = &mut self.received;
+        self.receiving.complete(&queue.used, |index, length| {
+            let length = (length as usize).clamp(NET_HEADER_SIZE, RECEIVE_BUFFER_SIZE);
+            received.push_back(buffers[index][NET_HEADER_SIZE..length].to_vec());
+            returned.push(index);
+        });
+
+        if !returned.is_empty() {
+            for index in returned {
+                self.make_receive_buffer_available(index);
+            }
+            self.inner.queue_notify(RECEIVE_QUEUE);
+        }
+
+        self.received.pop_front()
+    }
+}
+
+/// Add the descriptor chain starting at `head` to the available ring of `queue`
+fn make_available(queue: &mut Queue, head: u16) {
+    let idx = queue.available.idx as usize % VIRTIO_RING_SIZE_USIZE;
+    queue.available.ring[idx] = head;
+
+    // The descriptor must be written before the device can see it in the ring
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+    queue.available.idx = queue.available.idx.wrapping_add(1);
+    core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+}
@@ -0,0 +1,53 @@
+use alloc::vec::Vec;
+
+use qor_core::{interfaces::mmio::MMIOInterface, sync::Mutex};
+use qor_riscv::drivers::virtio::generic::raw;
+
+use super::{MacAddress, VirtIONetworkDevice, VirtIONetworkDeviceError};
+
+pub struct NetworkDriver {
+    device: Mutex<VirtIONetworkDevice>,
+    mac_address: Option<MacAddress>,
+    mmio: MMIOInterface,
+}
+
+impl NetworkDriver {
+    /// Creates a new [`NetworkDriver`] by wrapping an initialized [`VirtIONetworkDevice`] in a [`Mutex`]. The MAC
+    /// address is read up front, and the device's registers are kept separately, so its interrupts can be
+    /// acknowledged while a frame is being sent.
+    pub fn new(device: VirtIONetworkDevice) -> Self {
+        Self {
+            mac_address: device.mac_address(),
+            mmio: device.mmio_layer(),
+            device: Mutex::new(device),
+        }
+    }
+
+    /// Get the MAC address of the device, if it reports one
+    pub const fn mac_address(&self) -> Option<MacAddress> {
+        self.mac_address
+    }
+
+    /// Acknowledge an interrupt raised by the device. Frames are sent and received by polling the queues, so there is
+    /// nothing to wake.
+    pub fn handle_interrupt(&self) {
+        // Safety: `mmio` is the register interface of the network device
+        unsafe {
+            raw::set_interrupt_ack(&self.mmio, raw::read_interrupt_status(&self.mmio));
+        }
+    }
+
+    /// Transmit a single Ethernet frame, spinning until the device has sent it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame is too large to send.
+    pub fn send_frame(&self, frame: &[u8]) -> Result<(), VirtIONetworkDeviceError> {
+        self.device.spin_lock().send_frame(frame)
+    }
+
+    /// Take the oldest frame the device has received, if there is one
+    pub fn poll_frame(&self) -> Option<Vec<u8>> {
+        self.device.spin_lock().poll_frame()
+    }
+}
@@ -0,0 +1,9 @@
+pub mod driver;
+pub use driver::*;
+
+pub mod interface;
+#[allow(clippy::module_name_repetitions)]
+pub use interface::NetworkDriver;
+
+pub mod structures;
+pub use structures::*;
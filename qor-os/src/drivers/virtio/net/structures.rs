@@ -0,0 +1,39 @@
+// Feature bits
+pub const VIRTIO_NET_F_MAC: u32 = 5;
+
+// Queue indices
+pub const RECEIVE_QUEUE: u32 = 0;
+pub const TRANSMIT_QUEUE: u32 = 1;
+
+/// Size of the header which precedes every frame, without `VIRTIO_NET_F_MRG_RXBUF` negotiated. The driver leaves it
+/// zeroed, requesting no checksum or segmentation offload.
+pub const NET_HEADER_SIZE: usize = 10;
+
+/// Largest Ethernet frame, without its frame check sequence, which the driver sends or receives
+pub const MAX_FRAME_SIZE: usize = 1514;
+
+/// Size of each receive buffer, holding the header followed by the largest frame
+pub const RECEIVE_BUFFER_SIZE: usize = NET_HEADER_SIZE + MAX_FRAME_SIZE;
+
+/// Number of receive buffers kept available to the device
+pub const RECEIVE_BUFFER_COUNT: usize = 16;
+
+/// Offset of the MAC address within the network device configuration space
+pub const CONFIG_MAC_OFFSET: usize = 0;
+
+/// Hardware address of a network card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MacAddress(pub [u8; 6]);
+
+impl core::fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, byte) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 use alloc::{boxed::Box, sync::Arc};
-use qor_core::interfaces::fs::{
-    INodeReference, MountableFileSystem, ParentFileSystem, VirtualFileSystem,
+use qor_core::{
+    fs::{dev::DevFs, proc::ProcFileSystem},
+    interfaces::fs::{
+        FileSystemError, INodeReference, MountableFileSystem, ParentFileSystem, VirtualFileSystem,
+    },
 };
 use spin::RwLock;
 
@@ -8,6 +11,9 @@ pub type InnerGlobalFS = RwLock<Box<dyn ParentFileSystem + Send + Sync>>;
 
 pub static GLOBAL_FILE_SYSTEM: RwLock<Option<Arc<InnerGlobalFS>>> = RwLock::new(None);
 
+/// Set once the root file system has been mounted
+pub static ROOT_FS_MOUNTED: qor_core::sync::Event = qor_core::sync::Event::new();
+
 pub fn initialize_file_system() {
     let fs = VirtualFileSystem::new();
     GLOBAL_FILE_SYSTEM
@@ -22,10 +28,92 @@ pub fn global_fs() -> Arc<RwLock<Box<dyn ParentFileSystem + Send + Sync>>> {
     GLOBAL_FILE_SYSTEM.read().as_ref().unwrap().clone()
 }
 
+/// Mount the proc file system at `/proc`, if the root file system has a directory there to mount it over
+pub async fn mount_proc_fs() {
+    let result = mount_at_path(
+        "/proc",
+        Arc::new(ProcFileSystem::new(crate::process::ProcessTableSource)),
+    )
+    .await;
+
+    match result {
+        Ok(()) => info!("Mounted proc fs at /proc"),
+        Err(e) => warn!("Unable to mount proc fs at /proc: {:?}", e),
+    }
+}
+
+/// Mount a dev fs listing the UART and, if they were found, the entropy source and network card at `/dev`, if the root
+/// file system has a directory there to mount it over
+pub async fn mount_dev_fs() {
+    let devices = DevFs::new();
+
+    // Reads go through the receive buffer the UART interrupt fills, as for the console descriptors of a process
+    if let Err(e) = devices.add_device(
+        "uart0",
+        Box::new(|| Arc::new(crate::process::proc_interface::UARTFileDescriptor {})),
+    ) {
+        warn!("Unable to add uart0 to the dev fs: {:?}", e);
+    }
+
+    if let Some(entropy) =
+        crate::drivers::ENTROPY_DRIVER.load(core::sync::atomic::Ordering::Acquire)
+    {
+        if let Err(e) = devices.add_random_source("random0", entropy) {
+            warn!("Unable to add random0 to the dev fs: {:?}", e);
+        }
+    }
+
+    if let Some(network) =
+        crate::drivers::NETWORK_DRIVER.load(core::sync::atomic::Ordering::Acquire)
+    {
+        if let Err(e) = devices.add_device(
+            "net0",
+            Box::new(move || {
+                Arc::new(crate::process::proc_interface::NetworkFileDescriptor { device: network })
+            }),
+        ) {
+            warn!("Unable to add net0 to the dev fs: {:?}", e);
+        }
+    }
+
+    let result = mount_at_path("/dev", Arc::new(devices)).await;
+
+    match result {
+        Ok(()) => info!("Mounted dev fs at /dev"),
+        Err(e) => warn!("Unable to mount dev fs at /dev: {:?}", e),
+    }
+}
+
+/// Mount a file system over the directory at `path` in the global file system.
+///
+/// The path is resolved first, and the write lock is only taken for the mount itself, so it is never held across an
+/// `.await` which waits on a device while other tasks need the file system.
+///
+/// # Errors
+///
+/// Returns an error if `path` can not be found, is not a directory, or already has a file system mounted at it.
+async fn mount_at_path(
+    path: &str,
+    device: alloc::sync::Arc<dyn MountableFileSystem + Send + Sync + 'static>,
+) -> Result<(), FileSystemError> {
+    let fs = global_fs();
+    let inode = fs.read().lookup(path).await?;
+    if !fs.read().inode_data(inode).await?.is_directory() {
+        return Err(FileSystemError::NotDirectory);
+    }
+
+    mount_fs(inode, device)
+}
+
+/// Mount a file system over the directory `inode` of the global file system
+///
+/// # Errors
+///
+/// Returns an error if a file system is already mounted at `inode`.
 #[allow(clippy::module_name_repetitions)]
 pub fn mount_fs(
     inode: INodeReference,
     device: alloc::sync::Arc<dyn MountableFileSystem + Send + Sync + 'static>,
-) {
-    global_fs().write().as_mut().mount_filesystem(inode, device);
+) -> Result<(), FileSystemError> {
+    global_fs().write().as_mut().mount_filesystem(inode, device)
 }
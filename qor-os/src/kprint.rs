@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use qor_core::interfaces::bytes::GenericByteWriteInterface;
+use qor_core::{drivers::timer::HardwareTimerDriver, interfaces::bytes::GenericByteWriteInterface};
 
 /// Print text from the kernel to the UART port
 #[macro_export]
@@ -37,3 +37,25 @@ pub fn assign_uart_logger() {
     qor_core::logging::set_writer(&(uart_writer as fn(&str)));
     info!("Logger initialized to use UART port");
 }
+
+/// Public function for reading the time since boot from the CLINT, in microseconds
+pub fn clint_clock() -> u64 {
+    crate::drivers::CLINT_DRIVER
+        .time(qor_core::structures::id::HartID::from(0))
+        .map_or(0, |time| time.0)
+}
+
+/// Public function for reading the id of the hart running the caller
+pub fn hart_id() -> u64 {
+    qor_riscv::trap::current_hart_id().0 as u64
+}
+
+/// Set the logger in `qor_core` to include the id of the logging hart in messages
+pub fn assign_hart_id_source() {
+    qor_core::logging::set_hart_id_source(&(hart_id as fn() -> u64));
+}
+
+/// Set the logger in `qor_core` to timestamp messages using the CLINT
+pub fn assign_clint_clock() {
+    qor_core::logging::set_clock_source(&(clint_clock as fn() -> u64));
+}
@@ -6,6 +6,8 @@
 #![no_main]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
+use qor_core::structures::boot_config::BootConfig;
+
 use crate::fs::global_fs;
 
 #[macro_use]
@@ -19,24 +21,69 @@ mod fs;
 mod kprint;
 mod memory;
 mod panic;
+mod power;
 mod process;
 mod syscalls;
 mod trap;
 
+/// Length of time a process runs before the timer interrupt preempts it
+const SCHEDULER_QUANTUM: qor_core::structures::time::Microseconds =
+    qor_core::structures::time::Microseconds(10_000);
+
+/// Boot configuration selecting the root file system and init program, see
+/// [`qor_core::structures::boot_config::BootConfig`] for the recognised entries
+const BOOT_CONFIG: &str = "root_device=0 init=/bin/hello";
+
 /// Entry point for the boot sequence, no interrupts are enabled when this function is called, and we are in machine
-/// mode, no paging is enabled.
+/// mode, no paging is enabled. The firmware's device tree is found at `device_tree`.
 ///
 /// # Panics
 /// This function will panic if a resource which is essential to the boot process is unavailable. For example, no UART
 /// port being available, or there being insufficient memory to initialize the page table.
 #[no_mangle]
 #[repr(align(4))]
-pub extern "C" fn kinit() {
+pub extern "C" fn kinit(device_tree: usize) {
+    // Every hart entering the kernel records its id first, so anything it logs or traps on can tell which hart it is
+    qor_riscv::trap::record_hart_id();
+
+    // The device tree lies in memory which is handed to the allocators, so it is only read before they are set up
+    // Safety: `_start` passes on the device tree address given by the firmware
+    let device_tree = unsafe { qor_core::structures::fdt::DeviceTree::from_raw(device_tree as *const u8) };
+    let uart = device_tree.as_ref().ok().and_then(drivers::locate_uart);
+    let hart_count = device_tree.as_ref().map_or(1, drivers::count_harts);
+
+    // Without a device tree, assume the finisher is where QEMU's `virt` platform places it
+    if let Ok(device_tree) = &device_tree {
+        power::set_finisher_address(drivers::locate_test_finisher(device_tree));
+    }
+
     drivers::initialize_uart_driver().expect("Unable to initialize UART device driver");
 
-    // Initialize the system logger to use the UART port
+    // Initialize the system logger to use the UART port, timestamping messages with the CLINT
+    kprint::assign_clint_clock();
     kprint::assign_uart_logger();
 
+    // Only tell apart the lines logged by each hart if there is more than one
+    if hart_count > 1 {
+        kprint::assign_hart_id_source();
+    }
+
+    // Keep the most recent log messages in memory, so they can be recovered after a panic
+    qor_core::logging::set_ring_capacity(qor_core::logging::MAX_RING_CAPACITY);
+
+    match (device_tree, uart) {
+        (Ok(_), Some(base)) => info!("Found UART at {:#x} in the device tree", base),
+        (Ok(_), None) => warn!(
+            "No UART in the device tree, using {:#x}",
+            drivers::UART_DRIVER.base_address()
+        ),
+        (Err(e), _) => warn!(
+            "Unable to parse the device tree, using the UART at {:#x}: {:?}",
+            drivers::UART_DRIVER.base_address(),
+            e
+        ),
+    }
+
     // Initialize the global page grained bump allocator
     memory::initialize_page_bump_allocator().expect("Unable to initialize bump allocator");
 
@@ -64,6 +111,7 @@ pub extern "C" fn kinit() {
     crate::trap::initialize_trap_frame();
 
     // Note that by returning, we switch to supervisor mode, and move into `kmain`
+    qor_riscv::trap::set_in_machine_mode(false);
 }
 
 /// Entry point for the core kernel functionality. Interrupts are enabled in this function, and we are in supervisor
@@ -86,7 +134,7 @@ pub extern "C" fn kmain() {
     info!("PLIC Initialized");
 
     // Initialize the CLINT timer
-    crate::drivers::CLINT_DRIVER.set_frequency(qor_core::structures::time::Hertz(2));
+    crate::drivers::CLINT_DRIVER.set_quantum(SCHEDULER_QUANTUM);
     info!("CLINT Initialized");
 
     // Probe the virt io address range
@@ -94,38 +142,154 @@ pub extern "C" fn kmain() {
     crate::drivers::virtio::probe_virt_io_address_range();
     info!("VirtIO Device Discovery Complete");
 
-    qor_core::tasks::execute_task(qor_core::tasks::Task::new(mount_default_fs()));
+    for device in crate::drivers::status_report() {
+        info!("Device {}", device);
+    }
+
+    let config = BootConfig::parse(BOOT_CONFIG).unwrap_or_else(|e| {
+        error!("Invalid boot configuration, using the defaults: {:?}", e);
+        BootConfig::default()
+    });
+    info!("Boot configuration: {:?}", config);
+
+    qor_core::tasks::execute_task(qor_core::tasks::Task::new(mount_default_fs(config)));
     qor_core::tasks::execute_task(qor_core::tasks::Task::new(map_fs()));
-    qor_core::tasks::execute_task(qor_core::tasks::Task::new(open_file()));
+    qor_core::tasks::execute_task(qor_core::tasks::Task::new(open_file(config.init)));
+
+    #[cfg(feature = "fault-test")]
+    process::start_process(process::Process::from_fn_ptr(
+        illegal_instruction_program as usize,
+        qor_riscv::memory::PageCount::new(1),
+    ));
+
+    let page_allocator = memory::get_page_bitmap_allocator();
+    info!(
+        "{} of {} dynamic pages free after boot",
+        page_allocator.free_pages(),
+        page_allocator.total_pages()
+    );
 
     // Starting CLINT Timer
     crate::drivers::CLINT_DRIVER.start_timer(hart_id);
 }
 
-/// Mount the filesystem on the main block device
-pub async fn mount_default_fs() {
+/// User program which executes an illegal instruction straight away, it should be terminated while the rest of the
+/// system carries on
+#[cfg(feature = "fault-test")]
+extern "C" fn illegal_instruction_program() -> ! {
+    loop {
+        // Safety: `unimp` only raises an illegal instruction exception
+        unsafe {
+            core::arch::asm!("unimp");
+        }
+    }
+}
+
+/// Mount the root file system selected by `config`
+pub async fn mount_default_fs(config: BootConfig<'static>) {
+    if drivers::BLOCK_DRIVER.load(core::sync::atomic::Ordering::Acquire).is_none() {
+        error!("No block device available to mount the root file system from");
+        return;
+    }
+
+    // Before the timer is started nothing wakes the sleep, it finishes once the idle executor polls it again
+    if config.root_delay > 0 {
+        info!("Waiting {}ms for the root device", config.root_delay);
+        let delay = qor_core::structures::time::Microseconds::from_millis(config.root_delay);
+        if drivers::sleep(delay).await.is_err() {
+            warn!("Unable to read the time to wait for the root device");
+        }
+    }
+
+    // Only the first block device found is kept by the driver layer
+    if config.root_device != 0 {
+        error!("No block device {} to mount the root file system from", config.root_device);
+        return;
+    }
+
     let block_driver = drivers::get_block_driver();
-    let file_sys = qor_core::fs::ext2::Ext2FileSystem::new(block_driver.as_ref());
+    let device: &'static (dyn qor_core::drivers::block::BlockDeviceDriver<512, _, u32> + Send + Sync) =
+        match config.root_partition {
+            None => block_driver.as_ref(),
+            Some(number) => {
+                let Some(partition) = read_partition(block_driver.as_ref(), number).await else {
+                    return;
+                };
+
+                info!(
+                    "Mounting partition {} (type {:#04x}, {} sectors from {})",
+                    number, partition.partition_type, partition.sector_count, partition.start_lba
+                );
+
+                // The adapter lives as long as the mounted file system
+                alloc::boxed::Box::leak(alloc::boxed::Box::new(
+                    qor_core::drivers::block::PartitionAdapter::new(block_driver.as_ref(), partition),
+                ))
+            }
+        };
+    let file_sys = qor_core::fs::ext2::Ext2FileSystem::new(device, 64);
+
+    if let Ok(super_block) = file_sys.read_super_block().await {
+        info!(
+            "Mounting ext2 volume `{}` (UUID: {:x?})",
+            super_block.volume_label().unwrap_or("unlabeled"),
+            super_block.uuid()
+        );
+    }
 
     let fs = global_fs();
     let root_inode_result = fs.read().root_inode().await;
-    root_inode_result.map_or_else(
-        |_| {
-            error!("Unable to mount root file system");
-        },
-        |root_inode| {
-            // Make this device permanently resident in memory.
-            fs::mount_fs(root_inode, alloc::sync::Arc::new(file_sys));
-        },
-    );
+
+    // Make this device permanently resident in memory.
+    match root_inode_result.and_then(|root_inode| fs::mount_fs(root_inode, file_sys)) {
+        Ok(()) => fs::ROOT_FS_MOUNTED.set(),
+        Err(e) => error!("Unable to mount root file system: {:?}", e),
+    }
+
+    if fs::ROOT_FS_MOUNTED.is_set() {
+        fs::mount_proc_fs().await;
+        fs::mount_dev_fs().await;
+    }
+}
+
+/// Read the master boot record of `device` and find the primary partition with the given number, counting from 1.
+/// Failures are logged.
+async fn read_partition<E: core::fmt::Debug + Send + Sync>(
+    device: &(dyn qor_core::drivers::block::BlockDeviceDriver<512, E, u32> + Send + Sync),
+    number: usize,
+) -> Option<qor_core::structures::mbr::Partition> {
+    let mut sector = [[0; 512]];
+    if let Err(e) = device.read_blocks(0, &mut sector).await {
+        error!("Unable to read the partition table: {:?}", e);
+        return None;
+    }
+
+    let partitions = match qor_core::structures::mbr::parse_partition_table(&sector[0]) {
+        Ok(partitions) => partitions,
+        Err(e) => {
+            error!("Unable to parse the partition table: {:?}", e);
+            return None;
+        }
+    };
+
+    let partition = number
+        .checked_sub(1)
+        .and_then(|index| partitions.get(index).copied().flatten());
+    if partition.is_none() {
+        error!("No partition {} to mount the root file system from", number);
+    }
+
+    partition
 }
 
-/// List all files on the mounted file system
+/// List all files on the root file system, once it has been mounted
 ///
 /// # Panics
 ///
 /// This function will panic if any of the file system accesses fail
 pub async fn map_fs() {
+    fs::ROOT_FS_MOUNTED.wait().await;
+
     let fs = global_fs();
     let fs_r = fs.read();
 
@@ -133,21 +297,31 @@ pub async fn map_fs() {
     fs_r.walk_children(inode).await.unwrap();
 }
 
-/// Look at one of the ELF files
+/// Load and start the init program at `path`, once the root file system has been mounted
 ///
 /// # Panics
 ///
 /// This function will panic if any of the file system accesses fail
-pub async fn open_file() {
+pub async fn open_file(path: &'static str) {
+    fs::ROOT_FS_MOUNTED.wait().await;
+
     let fs = global_fs();
     let fs_r = fs.read();
 
-    let inode = fs_r.lookup("/bin/hello").await.unwrap();
+    let inode = fs_r.lookup(path).await.unwrap();
     warn!("{:?}", inode);
     let file = fs_r.read_to_data(inode).await.unwrap();
 
-    let elf = qor_core::structures::elf::Elf::parse(file.as_slice()).unwrap();
+    let elf = match qor_core::structures::elf::Elf::parse(file.as_slice()) {
+        Ok(elf) => elf,
+        Err(e) => {
+            error!("Unable to parse {} as an ELF file: {:?}", path, e);
+            return;
+        }
+    };
     
-    let proc = process::Process::from_elf_file(elf, qor_core::memory::KiByteCount::new(4).convert());
-    process::start_process(proc);
+    match process::Process::from_elf_file(elf, qor_core::memory::KiByteCount::new(4).convert()) {
+        Ok(proc) => process::start_process(proc),
+        Err(e) => error!("Unable to load {}: {:?}", path, e),
+    }
 }
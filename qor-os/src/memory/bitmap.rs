@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 
 use qor_core::memory::{
-    allocators::page::{bitmap::PageBitmapAllocator, bump::AllocationError},
+    allocators::page::{
+        bitmap::{BitmapInitError, PageBitmapAllocator},
+        bump::AllocationError,
+    },
     MemoryUnit,
 };
 
@@ -27,12 +30,33 @@ pub fn get_page_bitmap_allocator() -> &'static PageBitmapAllocator<Page> {
         )
 }
 
+/// Errors which can occur while initializing the global page bitmap allocator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitializationError {
+    /// The memory for the allocator could not be taken from the global page bump allocator
+    Allocation(AllocationError),
+    /// The memory taken could not be used as a bitmap allocator
+    Bitmap(BitmapInitError),
+}
+
+impl From<AllocationError> for InitializationError {
+    fn from(value: AllocationError) -> Self {
+        Self::Allocation(value)
+    }
+}
+
+impl From<BitmapInitError> for InitializationError {
+    fn from(value: BitmapInitError) -> Self {
+        Self::Bitmap(value)
+    }
+}
+
 /// Initialize the global page bitmap allocator with a certain amount of memory from the global page bump allocator
 pub fn initialize_page_bitmap_allocator(
     memory_amount: MemoryUnit<{ qor_riscv::memory::PAGE_SIZE }>,
-) -> Result<(), AllocationError> {
+) -> Result<(), InitializationError> {
     let alloted_memory = PAGE_BUMP_ALLOCATOR.allocate(memory_amount.raw())?;
-    let bitmap_allocator = PageBitmapAllocator::from_pages(alloted_memory);
+    let bitmap_allocator = PageBitmapAllocator::try_from_pages(alloted_memory)?;
     let static_allocator_reference = PAGE_BUMP_ALLOCATOR.allocate_object(bitmap_allocator)?;
 
     PAGE_BITMAP_ALLOCATOR.store(
@@ -61,15 +85,27 @@ impl PageSequence {
     }
 
     /// Construct a new `PageSequence` from a length, allocating it on the `GLOBAL_PAGE_BITMAP_ALLOCATOR`
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is no run of `length` free pages, see [`PageSequence::try_alloc`] for a
+    /// fallible version.
     pub fn alloc(length: usize) -> Self {
-        let page_sequence = get_page_bitmap_allocator()
-            .allocate(length)
-            .expect("Unable to allocate page sequence");
+        Self::try_alloc(length).expect("Unable to allocate page sequence")
+    }
 
-        Self {
+    /// Construct a new `PageSequence` from a length, allocating it on the `GLOBAL_PAGE_BITMAP_ALLOCATOR`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no run of `length` free pages.
+    pub fn try_alloc(length: usize) -> Result<Self, qor_core::memory::allocators::page::bitmap::AllocationError> {
+        let page_sequence = get_page_bitmap_allocator().allocate(length)?;
+
+        Ok(Self {
             ptr: core::ptr::NonNull::new(page_sequence).expect("Page sequence pointer is null"),
             page_count: length,
-        }
+        })
     }
 
     /// Get the number of pages in the allocation
@@ -91,3 +127,4 @@ impl core::ops::Drop for PageSequence {
 }
 
 unsafe impl Sync for PageSequence {}
+unsafe impl Send for PageSequence {}
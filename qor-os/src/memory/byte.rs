@@ -1,6 +1,9 @@
 use core::alloc::{GlobalAlloc, Layout};
 
-use qor_core::memory::{allocators::byte::AllocationTable, ByteCount, MemoryUnit};
+use qor_core::memory::{
+    allocators::byte::{AllocationStats, AllocationTable, AllocationUsage},
+    ByteCount, MemoryUnit,
+};
 use qor_riscv::memory::PAGE_SIZE;
 
 use crate::memory::get_page_bitmap_allocator;
@@ -8,6 +11,10 @@ use crate::memory::PAGE_BUMP_ALLOCATOR;
 
 const TRACE_BYTE_ALLOC: bool = false;
 
+/// Allocations needing fewer bytes than this, including their alignment, are served by the byte grained allocator.
+/// Larger ones take whole pages, so rounding up to a page wastes less than a quarter of the allocation.
+const BYTE_ALLOCATION_LIMIT: usize = 4 * PAGE_SIZE;
+
 #[global_allocator]
 static GLOBAL_BYTE_ALLOCATOR: GlobalByteAllocatorWrapper = GlobalByteAllocatorWrapper::new();
 
@@ -49,6 +56,9 @@ impl GlobalByteAllocatorWrapper {
             );
         }
 
+        // Merge the pages, which are usually contiguous, so allocations can span several of them
+        allocation_table.coalesce_free_regions();
+
         let static_allocator_reference = PAGE_BUMP_ALLOCATOR
             .allocate_object(allocation_table)
             .unwrap();
@@ -62,7 +72,7 @@ impl GlobalByteAllocatorWrapper {
 
 unsafe impl GlobalAlloc for GlobalByteAllocatorWrapper {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let ptr = if layout.size() + layout.align() < 4096 {
+        let ptr = if layout.size() + layout.align() < BYTE_ALLOCATION_LIMIT {
             self.get()
                 .alloc(layout.size(), layout.align())
                 .expect("Unable to allocate memory via the byte allocator")
@@ -92,7 +102,7 @@ unsafe impl GlobalAlloc for GlobalByteAllocatorWrapper {
             trace!("FREE {:?} {:?}", ptr, layout);
         }
 
-        if layout.size() + layout.align() < 4096 {
+        if layout.size() + layout.align() < BYTE_ALLOCATION_LIMIT {
             self.get().free(ptr as usize);
             self.get().coalesce_free_regions();
         } else if layout.align() <= PAGE_SIZE {
@@ -106,6 +116,35 @@ unsafe impl GlobalAlloc for GlobalByteAllocatorWrapper {
             panic!("Unsupported layout {:?} on free", layout);
         }
     }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Allocations which stay with the byte grained allocator may be able to grow into the free space after them
+        if layout.size() + layout.align() < BYTE_ALLOCATION_LIMIT
+            && new_size + layout.align() < BYTE_ALLOCATION_LIMIT
+            && self.get().try_grow(ptr as usize, new_size)
+        {
+            if TRACE_BYTE_ALLOC {
+                trace!("GROW {:?} {:?} to {}", ptr, layout, new_size);
+            }
+
+            return ptr;
+        }
+
+        // Safety: The caller guarantees `new_size` rounded up to the alignment does not overflow
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+
+        // Safety: The new layout has a non-zero size, as required of the caller
+        let new_ptr = unsafe { self.alloc(new_layout) };
+
+        // Safety: The old allocation is valid for `layout.size()` bytes, and the new one for `new_size` bytes, and they
+        // do not overlap as the old allocation is still live
+        unsafe { core::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size)) };
+
+        // Safety: The caller guarantees `ptr` was allocated by this allocator with `layout`
+        unsafe { self.dealloc(ptr, layout) };
+
+        new_ptr
+    }
 }
 
 /// Initialize the global byte grained allocator with a certain amount of memory from the global page bump allocator
@@ -117,3 +156,23 @@ pub fn initialize_global_byte_allocator(memory_amount: MemoryUnit<{ PAGE_SIZE }>
         memory_amount
     );
 }
+
+/// Snapshot the usage of the global byte grained allocator without blocking, returning `None` if it is not yet
+/// initialized or one of its entries is locked
+pub fn global_byte_allocator_usage() -> Option<AllocationUsage> {
+    GLOBAL_BYTE_ALLOCATOR
+        .inner
+        .load(core::sync::atomic::Ordering::Acquire)?
+        .try_usage()
+}
+
+/// Snapshot the usage counters of the global byte grained allocator, returning `None` if it is not yet initialized.
+/// This never waits on the allocator, so it is safe to call from any context.
+pub fn global_byte_allocator_stats() -> Option<AllocationStats> {
+    Some(
+        GLOBAL_BYTE_ALLOCATOR
+            .inner
+            .load(core::sync::atomic::Ordering::Acquire)?
+            .stats(),
+    )
+}
@@ -2,20 +2,29 @@
 
 use qor_core::{
     memory::{ByteCount, MemoryUnit},
-    structures::id::PID,
+    structures::{id::PID, syscall_error::SyscallError},
 };
 use qor_riscv::memory::{
     mmu::{
         addresses::{PhysicalAddress, VirtualAddress},
-        construct_satp,
+        coalesce_regions, construct_satp,
+        dump::MappedRange,
         entry::{EntryPermissionFlags, GlobalUserFlags},
-        table::PageTable,
+        flush_tlb, flush_tlb_addr, is_active_page_table,
+        table::{LeafCopy, PageTable},
     },
-    PAGE_SIZE,
+    PageCount, PAGE_SIZE,
 };
 
+/// A page table whose intermediate tables are allocated from the page bitmap allocator. When the table is the one
+/// installed on this hart, every method which changes or removes a mapping flushes the TLB entries of the addresses it
+/// changes, so the change takes effect immediately. Changes to any other table need no flush, as switching to a table
+/// discards every cached translation.
 pub struct ManagedPageTable(PageTable);
 
+/// The number of pages above which a change to a range of mappings flushes the whole TLB rather than each page
+const RANGE_FLUSH_LIMIT: usize = 32;
+
 static PAGE_ALLOC_FUNCTION: fn() -> *mut PageTable = || {
     crate::memory::get_page_bitmap_allocator()
         .allocate(1)
@@ -65,6 +74,10 @@ impl ManagedPageTable {
                 PAGE_ALLOC_FUNCTION,
             );
         };
+
+        if self.is_active() {
+            flush_tlb_addr(virt_addr);
+        }
     }
 
     /// Map a range of physical addresses to a range of virtual addresses.
@@ -90,6 +103,10 @@ impl ManagedPageTable {
                 PAGE_ALLOC_FUNCTION,
             );
         };
+
+        if self.is_active() {
+            flush_range(virt_addr, range_length);
+        }
     }
 
     /// Map a range of physical addresses to the equivalent range of virtual addresses. The range ends just before the
@@ -128,6 +145,106 @@ impl ManagedPageTable {
         self.0.virtual_to_physical_address(virt_addr)
     }
 
+    /// Verify that the whole `len` byte range starting at `start` is mapped with at least the `want` permissions,
+    /// checking every page the range touches rather than only the first byte. If `require_user` is set, every page
+    /// must also be accessible from user mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyscallError::Fault` if any part of the range is unmapped or lacks the required permissions.
+    pub fn check_range(
+        &self,
+        start: VirtualAddress,
+        len: usize,
+        want: EntryPermissionFlags,
+        require_user: bool,
+    ) -> Result<(), SyscallError> {
+        if self.0.check_range(start, len, want, require_user) {
+            Ok(())
+        } else {
+            Err(SyscallError::Fault)
+        }
+    }
+
+    /// Get the permissions of the mapping of a virtual address, or `None` if the address is not mapped.
+    #[must_use]
+    pub fn permissions_of(&self, virt_addr: VirtualAddress) -> Option<EntryPermissionFlags> {
+        self.0.permissions_of(virt_addr)
+    }
+
+    /// Replace the permissions of the mapping of a virtual address, leaving the page it maps to untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SyscallError::Fault` if the address is not mapped, and `SyscallError::InvalidArgument` if `perm_flags`
+    /// is `EntryPermissionFlags::None`, as such a mapping would be read as a pointer to another table.
+    pub fn set_permissions(
+        &mut self,
+        virt_addr: VirtualAddress,
+        perm_flags: EntryPermissionFlags,
+    ) -> Result<(), SyscallError> {
+        if perm_flags == EntryPermissionFlags::None {
+            return Err(SyscallError::InvalidArgument);
+        }
+
+        let size = self
+            .0
+            .set_permissions(virt_addr, perm_flags)
+            .ok_or(SyscallError::Fault)?;
+
+        if self.is_active() {
+            flush_range(
+                VirtualAddress(virt_addr.0 & !(size as u64 - 1)),
+                ByteCount::new(size).convert_ceil(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Remove the mapping of a virtual address, returning the number of bytes the removed mapping covered, or `None`
+    /// if the address was not mapped. The mapped pages are not freed.
+    pub fn unmap(&mut self, virt_addr: VirtualAddress) -> Option<usize> {
+        let removed = self.0.unmap(virt_addr)?;
+
+        if self.is_active() {
+            flush_range(virt_addr, ByteCount::new(removed).convert_ceil());
+        }
+
+        Some(removed)
+    }
+
+    /// Remove every mapping in the `length` page range starting at `virt_addr`, returning the number of bytes the
+    /// removed mappings covered, and free any intermediate tables left empty. The mapped pages themselves are not
+    /// freed, as they belong to whichever `MappedPageSequence` mapped them.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the `PageTableEntries` in the `PageTable` are not properly allocated by the allocator.
+    pub fn unmap_range(&mut self, virt_addr: VirtualAddress, length: PageCount) -> usize {
+        let removed = self.0.unmap_range(virt_addr, length);
+
+        // Safety:
+        // The passed `free_page` function is the pair to `PAGE_ALLOC_FUNCTION`
+        // and can free any pages allocated by that function.
+        unsafe {
+            self.0.free_empty_tables(|ptr| {
+                crate::memory::get_page_bitmap_allocator()
+                    .free(ptr.cast(), 1)
+                    .expect("Unable to free page from page table");
+            });
+        };
+
+        // A larger page only partly inside the range was removed entirely, so flushing just the range is not enough
+        if self.is_active() && removed > length.raw_bytes() {
+            flush_tlb();
+        } else if self.is_active() {
+            flush_range(virt_addr, length);
+        }
+
+        removed
+    }
+
     /// Free all of the mapped pages in this table.
     ///
     /// # Panics
@@ -146,6 +263,36 @@ impl ManagedPageTable {
                     .expect("Unable to free page from page table");
             });
         };
+
+        if self.is_active() {
+            flush_tlb();
+        }
+    }
+
+    /// Check if this is the page table currently installed on this hart
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        is_active_page_table(&self.0)
+    }
+
+    /// Copy this table into a new one with its own intermediate tables, for a forked process. `copy_leaf` decides
+    /// whether each user mapping is shared, shared read only, or redirected to a copy of its pages, while kernel
+    /// mappings are always shared as they are.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is not enough memory to allocate the intermediate tables.
+    #[must_use]
+    pub fn deep_copy(&self, copy_leaf: impl FnMut(MappedRange) -> LeafCopy) -> Self {
+        // Safety:
+        // `PAGE_ALLOC_FUNCTION` allocates pages from the bitmap allocator,
+        // which are uniquely owned.
+        Self(unsafe { self.0.deep_copy(PAGE_ALLOC_FUNCTION, copy_leaf) })
+    }
+
+    /// Log every mapping in this table at the trace level, with contiguous runs of pages collapsed into ranges.
+    pub fn debug_dump(&self) {
+        self.0.debug_dump();
     }
 
     /// Set this page table as the currently used page table
@@ -156,12 +303,53 @@ impl ManagedPageTable {
     /// Construct a SATP from this page table
     #[must_use]
     pub fn construct_satp(&self, pid: PID) -> usize {
-        construct_satp(pid.0, &self.0)
+        construct_satp(pid.asid(), &self.0)
     }
 }
 
-/// Identity map the kernel to a `ManagedPageTable` stored on the heap
+/// Discard the cached translations of the `length` pages starting at `virt_addr`, falling back to discarding every
+/// translation for ranges too long to be worth flushing page by page
+fn flush_range(virt_addr: VirtualAddress, length: MemoryUnit<PAGE_SIZE>) {
+    let pages = length.raw();
+    if pages > RANGE_FLUSH_LIMIT {
+        flush_tlb();
+        return;
+    }
+
+    for page in 0..pages as u64 {
+        flush_tlb_addr(VirtualAddress(virt_addr.0 + page * PAGE_SIZE as u64));
+    }
+}
+
+/// The CLINT registers on QEMU's `virt` platform
+pub const QEMU_VIRT_CLINT: core::ops::Range<u64> = 0x200_0000..0x201_0000;
+
+/// The PLIC registers on QEMU's `virt` platform
+pub const QEMU_VIRT_PLIC: core::ops::Range<u64> = 0xc00_0000..0xd00_0000;
+
+/// The window holding the UART and the Virt IO devices on QEMU's `virt` platform
+pub const QEMU_VIRT_MMIO: core::ops::Range<u64> = 0x1000_0000..0x1000_9000;
+
+/// Identity map the kernel to a `ManagedPageTable` stored on the heap, along with the UART and the devices of QEMU's
+/// `virt` platform
 pub fn identity_map_kernel(table: &mut ManagedPageTable, gu_flags: GlobalUserFlags) {
+    // The UART may have been moved out of the MMIO window by the device tree
+    let uart = crate::drivers::UART_DRIVER.base_address() as u64;
+
+    identity_map_kernel_with_devices(
+        table,
+        gu_flags,
+        &mut [uart..uart + 0x1000, QEMU_VIRT_CLINT, QEMU_VIRT_PLIC, QEMU_VIRT_MMIO],
+    );
+}
+
+/// Identity map the kernel to a `ManagedPageTable` stored on the heap, along with the memory mapped `device_regions`.
+/// The device regions may overlap, as they are merged so that each page is only mapped once.
+pub fn identity_map_kernel_with_devices(
+    table: &mut ManagedPageTable,
+    gu_flags: GlobalUserFlags,
+    device_regions: &mut [core::ops::Range<u64>],
+) {
     table.id_map_range(
         unsafe { crate::asm::HEAP_START }.into(),
         unsafe { crate::asm::HEAP_END }.into(),
@@ -204,35 +392,12 @@ pub fn identity_map_kernel(table: &mut ManagedPageTable, gu_flags: GlobalUserFla
         EntryPermissionFlags::ReadWrite,
     );
 
-    // UART PORT
-    table.id_map_range(
-        PhysicalAddress(0x1000_0000),
-        PhysicalAddress(0x1000_1000),
-        gu_flags,
-        EntryPermissionFlags::ReadWrite,
-    );
-
-    // CLINT Device
-    table.id_map_range(
-        PhysicalAddress(0x200_0000),
-        PhysicalAddress(0x201_0000),
-        gu_flags,
-        EntryPermissionFlags::ReadWrite,
-    );
-
-    // PLINT Device
-    table.id_map_range(
-        PhysicalAddress(0xc00_0000),
-        PhysicalAddress(0xd00_0000),
-        gu_flags,
-        EntryPermissionFlags::ReadWrite,
-    );
-
-    // Virt IO Devices
-    table.id_map_range(
-        PhysicalAddress(0x1000_0000),
-        PhysicalAddress(0x1000_9000),
-        gu_flags,
-        EntryPermissionFlags::ReadWrite,
-    );
+    for region in coalesce_regions(device_regions) {
+        table.id_map_range(
+            PhysicalAddress(region.start),
+            PhysicalAddress(region.end),
+            gu_flags,
+            EntryPermissionFlags::ReadWrite,
+        );
+    }
 }
@@ -1,7 +1,71 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once a panic has started dumping kernel state, so a panic raised by the dump itself does not recurse into it
+static DUMPING_STATE: AtomicBool = AtomicBool::new(false);
+
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
-    error!("{}", info);
-    loop {
-        core::hint::spin_loop();
+    let first_panic = !DUMPING_STATE.swap(true, Ordering::AcqRel);
+
+    // The report is written straight to the UART rather than through the logger, as the allocator or the logger's
+    // lock may be what panicked
+    let _ = qor_core::logging::write_panic_report(
+        &mut &crate::drivers::UART_DRIVER,
+        info.location(),
+        info.message(),
+    );
+
+    if first_panic {
+        dump_recent_log();
+        dump_state();
     }
+
+    crate::power::halt()
+}
+
+/// Write the log messages kept in memory to the UART, so the messages leading up to the panic are available even if
+/// they have scrolled off
+fn dump_recent_log() {
+    crate::kprintln!("---- Recent log messages ----");
+    if qor_core::logging::dump_ring(&mut &crate::drivers::UART_DRIVER).is_err() {
+        crate::kprintln!("Recent log messages unavailable");
+    }
+    crate::kprintln!("---- End of recent log messages ----");
+}
+
+/// Log the state of the allocators, and write the process table to the UART, for a postmortem. Nothing here waits on a
+/// lock, as the panicking code may be holding it, so anything which is locked is reported as unavailable instead.
+#[allow(clippy::option_if_let_else)]
+fn dump_state() {
+    if let Some(usage) = crate::memory::global_byte_allocator_usage() {
+        error!(
+            "Byte allocator: {} bytes in {} allocations, {} bytes free",
+            usage.allocated_bytes, usage.allocations, usage.free_bytes
+        );
+    } else {
+        error!("Byte allocator: unavailable");
+    }
+
+    if let Some(stats) = crate::memory::global_byte_allocator_stats() {
+        error!(
+            "Byte allocator counters: {} bytes in {} allocations, peak {} bytes",
+            stats.allocated_bytes, stats.live_allocations, stats.peak_allocated_bytes
+        );
+    }
+
+    if let Some(allocator) = crate::memory::PAGE_BITMAP_ALLOCATOR.load(Ordering::Acquire) {
+        error!(
+            "Page allocator: {} pages free, largest free run {} pages",
+            allocator.free_pages(),
+            allocator.largest_free_run()
+        );
+    } else {
+        error!("Page allocator: unavailable");
+    }
+
+    let _ = qor_core::structures::process::write_process_table(
+        &mut &crate::drivers::UART_DRIVER,
+        crate::process::processes(),
+        crate::process::Process::parent,
+    );
 }
@@ -0,0 +1,59 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use qor_core::interfaces::mmio::MMIOInterface;
+use qor_riscv::power::FinisherCommand;
+
+/// Base address of the `sifive_test` finisher device, or zero if the machine does not have one
+static FINISHER_ADDRESS: AtomicUsize = AtomicUsize::new(qor_riscv::power::QEMU_TEST_FINISHER);
+
+/// Set the base address of the `sifive_test` finisher device, or `None` if the machine does not have one, in which
+/// case powering off only halts this hart
+pub fn set_finisher_address(address: Option<usize>) {
+    FINISHER_ADDRESS.store(address.unwrap_or(0), Ordering::Release);
+}
+
+/// Power off the machine. The finisher is not mapped into the kernel's page table, so this must be called from
+/// machine mode, such as from the trap handler.
+pub fn shutdown() -> ! {
+    info!("Powering off");
+    request(FinisherCommand::Pass)
+}
+
+/// Reset the machine. The finisher is not mapped into the kernel's page table, so this must be called from machine
+/// mode, such as from the trap handler.
+pub fn reboot() -> ! {
+    info!("Rebooting");
+    request(FinisherCommand::Reset)
+}
+
+/// Issue `command` to the finisher device, halting this hart if there is none or the command does not take effect.
+///
+/// The kernel is booted with `-bios none` and handles machine mode traps itself, so there is no SBI firmware to fall
+/// back to.
+fn request(command: FinisherCommand) -> ! {
+    let address = FINISHER_ADDRESS.load(Ordering::Acquire);
+
+    if address == 0 {
+        error!("No finisher device to carry out {:?}, halting", command);
+    } else {
+        // Safety: The address is either that of the finisher on QEMU's `virt` platform, or the one found in the
+        // device tree
+        unsafe { qor_riscv::power::finish(&MMIOInterface::new(address), command) };
+        error!("The finisher at {:#x} did not carry out {:?}, halting", address, command);
+    }
+
+    halt()
+}
+
+/// Park this hart for good. Interrupts are masked first, so the timer can not schedule anything on it again, and it
+/// stays asleep.
+pub fn halt() -> ! {
+    qor_riscv::trap::interrupts::mask_all_interrupts();
+
+    loop {
+        // Safety: `wfi` only pauses the hart until an interrupt is pending, and touches no memory
+        unsafe {
+            core::arch::asm!("wfi");
+        }
+    }
+}
@@ -1,118 +1,214 @@
-use core::sync::atomic::AtomicUsize;
-
-use qor_core::{memory::allocators::page::bitmap::{PageBox, AllocationError}, structures::mem::PermissionFlags};
-use qor_riscv::memory::{Page, mmu::addresses::VirtualAddress, PageCount, PAGE_SIZE};
+use qor_core::{fs::proc::MappedRegion, memory::{allocators::page::bitmap::{PageBox, AllocationError}, copy_on_write::{CopyOnWritePages, WriteResolution}, statistics::TrackedPages}, structures::mem::{PermissionFlags, PermissionFlag}};
+use qor_riscv::memory::{Page, mmu::{addresses::VirtualAddress, entry::{EntryPermissionFlags, GlobalUserFlags}}, PageCount, PAGE_SIZE};
 
 use crate::memory::{get_page_bitmap_allocator, PageSequence, mmu::ManagedPageTable};
 
+pub use qor_core::memory::statistics::MemoryStatistics;
+
 pub struct ProcessBox<'allocator, Page: 'static, T> {
     inner: PageBox<'allocator, Page, T>,
-    stat_tracker: alloc::sync::Weak<MemoryStatistics>
+    _tracked: TrackedPages,
 }
 
-impl<'allocator, Page: 'static, T> core::ops::Drop for ProcessBox<'allocator, Page, T> {
-    fn drop(&mut self) {
-        if let Some(tracker) = self.stat_tracker.upgrade() {
-            tracker.resident.fetch_sub(self.inner.page_count(), core::sync::atomic::Ordering::AcqRel);
-        }
+impl<T> ProcessBox<'static, Page, T> {
+    /// Move `value` into freshly allocated pages, counted against `stats`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are not enough free pages to hold `value`.
+    pub fn alloc(stats: &alloc::sync::Arc<MemoryStatistics>, value: T) -> Result<Self, AllocationError> {
+        let inner = get_page_bitmap_allocator().alloc_boxed(value)?;
+
+        Ok(Self {
+            _tracked: stats.track(inner.page_count()),
+            inner,
+        })
     }
 }
 
 pub struct ProcessPageSequence {
     inner: PageSequence,
-    stat_tracker: alloc::sync::Weak<MemoryStatistics>
+    _tracked: TrackedPages,
 }
 
-impl core::ops::Drop for ProcessPageSequence {
-    fn drop(&mut self) {
-        if let Some(tracker) = self.stat_tracker.upgrade() {
-            tracker.resident.fetch_sub(self.inner.page_count(), core::sync::atomic::Ordering::AcqRel);
+impl ProcessPageSequence {
+    /// Allocate `length` pages, counted against `stats`
+    pub fn alloc(stats: &alloc::sync::Arc<MemoryStatistics>, length: PageCount) -> Self {
+        let inner = PageSequence::alloc(length.raw());
+
+        Self {
+            _tracked: stats.track(inner.page_count()),
+            inner,
         }
     }
 }
 
+/// A sequence of pages mapped into a process's address space. After a fork the underlying pages are shared between
+/// the parent and child, and both map them without write permission until the first write to each page copies it.
 pub struct MappedPageSequence {
     permissions: PermissionFlags,
     virtual_address: VirtualAddress,
-    inner: PageSequence,
-    stat_tracker: alloc::sync::Weak<MemoryStatistics>
+    pages: CopyOnWritePages<PageSequence>,
+    /// Set for pages mapped by the `mmap` syscall, the only ones `munmap` may remove
+    from_mmap: bool,
+    /// Counts the pages as resident, and as shared while they are copy-on-write
+    tracked: TrackedPages,
 }
 
-impl core::ops::Drop for MappedPageSequence {
-    fn drop(&mut self) {
-        if let Some(tracker) = self.stat_tracker.upgrade() {
-            tracker.resident.fetch_sub(self.inner.page_count(), core::sync::atomic::Ordering::AcqRel);
-        }
+impl MappedPageSequence {
+    /// Allocate `length` pages, counted against `stats`, and map them into `page_table` at `virtual_address` with the
+    /// given permissions
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is no run of `length` free pages.
+    pub fn map(stats: &alloc::sync::Arc<MemoryStatistics>, page_table: &mut ManagedPageTable, length: PageCount, virtual_address: VirtualAddress, permissions: PermissionFlags) -> Self {
+        Self::try_map(stats, page_table, length, virtual_address, permissions).expect("Unable to allocate page sequence")
     }
-}
 
+    /// Allocate `length` pages, counted against `stats`, and map them into `page_table` at `virtual_address` with the
+    /// given permissions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no run of `length` free pages, in which case nothing is mapped.
+    pub fn try_map(stats: &alloc::sync::Arc<MemoryStatistics>, page_table: &mut ManagedPageTable, length: PageCount, virtual_address: VirtualAddress, permissions: PermissionFlags) -> Result<Self, AllocationError> {
+        let inner = PageSequence::try_alloc(length.raw())?;
 
-#[allow(clippy::module_name_repetitions)]
-pub struct MemoryStatistics {
-    size: AtomicUsize,
-    resident: AtomicUsize,
-    shared: AtomicUsize,
-}
+        page_table.map_range(virtual_address, inner.inner().into(), length, GlobalUserFlags::User, permissions.try_into().expect("Unable to convert permission flags"));
 
-impl MemoryStatistics {
-    pub const fn new() -> Self {
-        Self {
-            size: AtomicUsize::new(0),
-            resident: AtomicUsize::new(0),
-            shared: AtomicUsize::new(0),
+        Ok(Self {
+            permissions,
+            virtual_address,
+            tracked: stats.track(inner.page_count()),
+            pages: CopyOnWritePages::new(inner, length.raw()),
+            from_mmap: false,
+        })
+    }
+
+    /// The permissions the page at `index` is currently mapped with, which lacks write permission while it is shared
+    fn mapped_permissions(&self, index: usize) -> EntryPermissionFlags {
+        let mut permissions = self.permissions;
+        if self.pages.is_read_only(index) {
+            permissions.clear_flag(PermissionFlag::Write);
         }
+
+        permissions.try_into().expect("Unable to convert permission flags")
     }
 
-    pub fn size(&self) -> usize {
-        self.size.load(core::sync::atomic::Ordering::Acquire)
+    /// Map the page at `index` into `table`, from wherever it is currently held and with its current permissions
+    fn map_page(&self, table: &mut ManagedPageTable, index: usize) {
+        let (backing, within) = self.pages.source(index);
+        // Safety: `within` is the index of a page inside `backing`, so the pointer stays within the allocation
+        let physical = unsafe { backing.inner().add(within) };
+
+        table.map_range(self.page_address(index), physical.into(), PageCount::new(1), GlobalUserFlags::User, self.mapped_permissions(index));
     }
 
-    pub fn resident(&self) -> usize {
-        self.resident.load(core::sync::atomic::Ordering::Acquire)
+    /// The virtual address of the page at `index`
+    fn page_address(&self, index: usize) -> VirtualAddress {
+        VirtualAddress(self.virtual_address.0 + (index * PAGE_SIZE) as u64)
     }
 
-    pub fn shared(&self) -> usize {
-        self.shared.load(core::sync::atomic::Ordering::Acquire)
+    /// The range of virtual addresses these pages are mapped at
+    pub fn range(&self) -> core::ops::Range<u64> {
+        let length = (self.pages.pages() * PAGE_SIZE) as u64;
+        self.virtual_address.0..self.virtual_address.0 + length
     }
 
-    pub fn alloc_page_box<T>(self: &alloc::sync::Arc<Self>, value: T) -> Result<ProcessBox<'static, Page, T>, AllocationError> {
-        let result = get_page_bitmap_allocator().alloc_boxed(value).map(|inner| ProcessBox {
-            inner,
-            stat_tracker: alloc::sync::Arc::downgrade(&self.clone()),
-        });
+    /// Describe these pages for the process's `maps` file, under the given name
+    pub fn region(&self, name: Option<&'static str>) -> MappedRegion {
+        let range = self.range();
+        MappedRegion { start: range.start, end: range.end, pages: self.pages.pages(), permissions: self.permissions, name }
+    }
 
-        if let Ok(value) = &result {
-            self.resident.fetch_add(value.inner.page_count(), core::sync::atomic::Ordering::AcqRel);
-        } 
+    /// Returns true if `address` lies within these pages
+    pub fn contains(&self, address: VirtualAddress) -> bool {
+        self.range().contains(&address.0)
+    }
 
-        result
+    /// Returns true if these pages were mapped by the `mmap` syscall, rather than for the program image, the heap or the
+    /// stack
+    pub fn is_from_mmap(&self) -> bool {
+        self.from_mmap
     }
 
-    pub fn alloc_page_sequence(self: &alloc::sync::Arc<Self>, length: PageCount) -> ProcessPageSequence {
-        let inner = PageSequence::alloc(length.raw());
-        self.resident.fetch_add(inner.page_count(), core::sync::atomic::Ordering::AcqRel);
+    /// Mark these pages as mapped by the `mmap` syscall, so `munmap` may remove them
+    pub fn mark_from_mmap(&mut self) {
+        self.from_mmap = true;
+    }
 
-        ProcessPageSequence {
-            inner,
-            stat_tracker: alloc::sync::Arc::downgrade(&self.clone()),
-        }
+    /// Returns true if the page holding `address`, which must lie within these pages, lacks write permission only
+    /// because it is shared with a forked process
+    pub fn is_copy_on_write(&self, address: VirtualAddress) -> bool {
+        self.permissions & PermissionFlag::Write && self.pages.is_read_only(self.page_index(address))
     }
 
-    pub fn map_page_sequence(self: &alloc::sync::Arc<Self>, page_table: &mut ManagedPageTable, length: PageCount, virtual_address: VirtualAddress, permissions: PermissionFlags) -> MappedPageSequence {
-        let inner = PageSequence::alloc(length.raw());
-        self.resident.fetch_add(inner.page_count(), core::sync::atomic::Ordering::AcqRel);
+    /// The index of the page holding `address` within these pages
+    fn page_index(&self, address: VirtualAddress) -> usize {
+        ((address.0 - self.virtual_address.0) / PAGE_SIZE as u64) as usize
+    }
 
-        page_table.map_range(virtual_address, inner.inner().into(), length, qor_riscv::memory::mmu::entry::GlobalUserFlags::User, permissions.try_into().expect("Unable to convert permission flags"));
+    /// Stop sharing the page holding `address`, taking a private copy of just that page if another process still
+    /// refers to it, and map it into `table` with its full permissions again. If no other process refers to the
+    /// pages any longer, every page still shared is taken over without copying.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no free page for the copy, in which case the page stays shared.
+    pub fn break_copy_on_write(&mut self, table: &mut ManagedPageTable, address: VirtualAddress) -> Result<(), AllocationError> {
+        let resolution = self.pages.resolve_write(self.page_index(address), |backing, within| {
+            let copy = PageSequence::try_alloc(1)?;
+            // Safety: `within` is the index of a page inside `backing`, and the copy was just allocated so the two pages
+            // cannot overlap
+            unsafe { core::ptr::copy_nonoverlapping(backing.inner().add(within), copy.inner(), 1) };
+
+            Ok(copy)
+        })?;
+        self.tracked.set_shared(self.pages.shared_pages());
+
+        match resolution {
+            Some(WriteResolution::Copied(index)) => self.map_page(table, index),
+            Some(WriteResolution::TakenOver(indices)) => {
+                // The pages are already mapped, they only need their write permission back
+                for index in indices {
+                    table.set_permissions(self.page_address(index), self.mapped_permissions(index)).expect("Mapped page sequence is not in the page table");
+                }
+            }
+            None => {}
+        }
 
-        MappedPageSequence {
-            permissions,
-            virtual_address,
-            inner,
-            stat_tracker: alloc::sync::Arc::downgrade(&self.clone()),
+        Ok(())
+    }
+
+    /// Share these pages copy-on-write with a forked child, remapping them without write permission in `table` (the
+    /// table they are currently mapped in) and mapping them the same way into `child_table`. Both processes count the
+    /// pages as shared until they are copied or unmapped.
+    pub fn share_copy_on_write(&mut self, table: &mut ManagedPageTable, child_stats: &alloc::sync::Arc<MemoryStatistics>, child_table: &mut ManagedPageTable) -> Self {
+        let pages = self.pages.share();
+        self.tracked.set_shared(self.pages.shared_pages());
+
+        let mut tracked = child_stats.track(pages.pages());
+        tracked.set_shared(pages.shared_pages());
+
+        let child = Self {
+            permissions: self.permissions,
+            virtual_address: self.virtual_address,
+            pages,
+            from_mmap: self.from_mmap,
+            tracked,
+        };
+
+        for index in 0..self.pages.pages() {
+            self.map_page(table, index);
+            child.map_page(child_table, index);
         }
+
+        child
     }
 }
 
+
 impl<'allocator, Page: 'static, T> core::ops::Deref for ProcessBox<'allocator, Page, T> {
     type Target = T;
 
@@ -127,18 +223,27 @@ impl<'allocator, Page: 'static, T> core::ops::DerefMut for ProcessBox<'allocator
     }
 }
 
+impl MappedPageSequence {
+    /// The allocation holding every page, which is only laid out contiguously until a page is given a private copy
+    fn contiguous(&self) -> &PageSequence {
+        self.pages.contiguous().expect("Pages of a mapping with private copies are not contiguous")
+    }
+}
+
 impl core::ops::Deref for MappedPageSequence {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
+        let inner = self.contiguous();
         // TODO: Add Safety Justification
-        unsafe { core::slice::from_raw_parts(self.inner.inner().cast(), self.inner.page_count() * PAGE_SIZE) }
+        unsafe { core::slice::from_raw_parts(inner.inner().cast(), inner.page_count() * PAGE_SIZE) }
     }
 }
 
 impl core::ops::DerefMut for MappedPageSequence {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        let inner = self.contiguous();
         // TODO: Add Safety Justification
-        unsafe { core::slice::from_raw_parts_mut(self.inner.inner().cast(), self.inner.page_count() * PAGE_SIZE) }
+        unsafe { core::slice::from_raw_parts_mut(inner.inner().cast(), inner.page_count() * PAGE_SIZE) }
     }
 }
\ No newline at end of file
@@ -3,53 +3,75 @@
 use core::{sync::atomic::AtomicU16, ops::DerefMut};
 
 use alloc::sync::Arc;
-use qor_core::{structures::{id::{ProcessID, PID}, elf::Elf, mem::{PermissionFlags, PermissionFlag}, syscall_error::SyscallError}, memory::ByteCount, interfaces::fs::FileDescriptor};
+use qor_core::{fs::proc::{ProcessSnapshot, ProcessSource}, structures::{id::PID, process::Schedulable, elf::{Elf, TargetMismatch, enums::{Architecture, BitWidth, ProgramHeaderType}}, mem::{PermissionFlags, PermissionFlag}, syscall_error::SyscallError, program_break::{ProgramBreak, ProgramBreakError}, stack::StackRegion, region::{aligned_length, find_free_region, overlaps}, transfer::{page_chunks, MAXIMUM_TRANSFER}}, memory::ByteCount, interfaces::fs::FileDescriptor};
 use qor_riscv::{
-    memory::{mmu::{entry::GlobalUserFlags, addresses::VirtualAddress}, Page, PageCount, PAGE_SIZE},
-    trap::frame::TrapFrame,
+    memory::{mmu::{entry::{EntryPermissionFlags, GlobalUserFlags}, addresses::VirtualAddress}, Page, PageCount, PAGE_SIZE},
+    trap::{frame::TrapFrame, resume::ResumePoint},
 };
 
 use crate::{
     memory::mmu::ManagedPageTable,
-    trap::allocate_trap_frame, syscalls::structures::UserspaceAddress,
+    trap::{allocate_trap_frame, structures::SynchronousTrap}, syscalls::structures::UserspaceAddress,
 };
 
 use self::{memory::{MemoryStatistics, ProcessBox, MappedPageSequence}, proc_interface::ProcessData};
 
+pub use qor_core::structures::process::ProcessState;
+
 pub mod boxed;
 pub mod memory;
 pub mod proc_interface;
+pub mod scheduler;
 
 static PID_COUNTER: AtomicU16 = AtomicU16::new(1);
 
 type ProgramTableMutex = qor_core::sync::Mutex<alloc::collections::BTreeMap<PID, Process>>;
 static PROGRAM_TABLE: ProgramTableMutex = qor_core::sync::Mutex::new(alloc::collections::BTreeMap::new());
 
-/// Get the next PID to be used
+/// Get the next PID to be used. PIDs double as the ASID of the process's page table, so the counter wraps at 16 bits,
+/// skipping zero, which is the ASID the kernel's own page table is installed with.
 fn new_pid() -> PID {
-    ProcessID(PID_COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed))
+    loop {
+        let pid = PID::from_asid(PID_COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed));
+        if pid.asid() != 0 {
+            return pid;
+        }
+    }
 }
 
+/// Errors which can occur while loading a process from an executable
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessLoadError {
+    /// The executable was not built for a 64 bit RISC-V hart
+    IncompatibleTarget(TargetMismatch),
+    /// The executable is dynamically linked and requests an interpreter, which is not yet supported
+    RequiresInterpreter,
+    /// The entry point does not lie within an executable loadable segment
+    InvalidEntryPoint,
+}
+
+/// Largest a process's stack may grow to unless configured otherwise, 1 MiB
+pub const DEFAULT_MAXIMUM_STACK_SIZE: PageCount = PageCount::new(256);
+
+/// Where the heap starts for processes without loaded segments to place it above
+pub const DEFAULT_HEAP_START: u64 = 0x4000_0000;
+
+/// Region searched for anonymous mappings when the process leaves the choice of address to the kernel, from above the
+/// stack to the top of the lower half of the Sv39 address space
+pub const MMAP_REGION: core::ops::Range<u64> = 0x20_0000_0000..0x40_0000_0000;
+
 /// Execution state for process execution. Includes a trap frame (which doesn't store the information for executing
 /// traps, but for executing user mode), a program counter storing where in the executable we return to, and a sequence
-/// of pages used for the stack.
+/// of pages used for the stack. The stack starts out at its initial size, and grows downwards a page at a time as
+/// faults just below it are taken, until it reaches its maximum size.
 pub struct ExecutionState {
-    program_counter: usize,
+    program_counter: ResumePoint,
     stack: MappedPageSequence,
+    stack_region: StackRegion<PAGE_SIZE>,
     trap_frame: ProcessBox<'static, Page, TrapFrame>,
 }
 
-#[allow(clippy::module_name_repetitions)]
-#[repr(C)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ProcessState {
-    Active,
-    Running,
-    Sleeping,
-    Waiting,
-    Terminated,
-}
-
 #[repr(C)]
 pub struct Process {
     pid: PID,
@@ -58,23 +80,26 @@ pub struct Process {
     page_table: ProcessBox<'static, Page, ManagedPageTable>,
     memory_stats: alloc::sync::Arc<MemoryStatistics>,
     mapped_pages: alloc::vec::Vec<MappedPageSequence>,
-    interface_data: ProcessData
+    interface_data: ProcessData,
+    parent: Option<PID>,
+    heap_break: ProgramBreak<PAGE_SIZE>,
 }
 
 impl ExecutionState {
     pub fn from_components(memory_stats: &alloc::sync::Arc<MemoryStatistics>, page_table: &mut ManagedPageTable, initial_program_counter: usize, stack_size: PageCount) -> Self {
-        let mut trap_frame = memory_stats
-            .alloc_page_box(allocate_trap_frame())
+        let mut trap_frame = ProcessBox::alloc(memory_stats, allocate_trap_frame())
             .expect("Unable to allocate trap frame space");
         
         let stack_addr = 0x1_0000_0000;
 
-        let stack = memory_stats.map_page_sequence(page_table, stack_size, VirtualAddress(stack_addr), PermissionFlags::new(0) | PermissionFlag::Read | PermissionFlag::Write);
-        trap_frame.registers[2] = stack_addr + stack_size.raw_bytes() as u64;
+        let stack = MappedPageSequence::map(memory_stats, page_table, stack_size, VirtualAddress(stack_addr), PermissionFlags::new(0) | PermissionFlag::Read | PermissionFlag::Write);
+        let stack_top = stack_addr + stack_size.raw_bytes() as u64;
+        trap_frame.registers[2] = stack_top;
 
         Self {
-            program_counter: initial_program_counter,
+            program_counter: ResumePoint::at_entry(initial_program_counter),
             stack,
+            stack_region: StackRegion::new(stack_addr, stack_top, DEFAULT_MAXIMUM_STACK_SIZE.raw_bytes() as u64),
             trap_frame
         }
     }
@@ -97,14 +122,16 @@ impl Process {
             page_table,
             memory_stats,
             mapped_pages: alloc::vec::Vec::new(),
-            interface_data: ProcessData::new()
+            interface_data: ProcessData::new(),
+            parent: None,
+            heap_break: ProgramBreak::new(DEFAULT_HEAP_START),
         }
     }
 
     pub fn from_fn_ptr(function: usize, stack_size: PageCount) -> Self {
         let mem_stats = alloc::sync::Arc::new(MemoryStatistics::new());
 
-        let mut page_table = mem_stats.alloc_page_box(crate::memory::mmu::ManagedPageTable::empty())
+        let mut page_table = ProcessBox::alloc(&mem_stats, crate::memory::mmu::ManagedPageTable::empty())
             .expect("Unable to allocate space for process page table");
         crate::memory::mmu::identity_map_kernel(&mut page_table, GlobalUserFlags::User);
 
@@ -115,41 +142,329 @@ impl Process {
         )
     }
 
-    pub fn from_elf_file(elf: Elf<'_>, stack_size: PageCount) -> Self {
+    /// Mark the process as terminated, and release its open file descriptors so any shared open files are closed once
+    /// no other process refers to them. Its memory is only freed when it is reaped, as its page table may still be
+    /// installed and the trap currently being handled may still be using its trap frame.
+    pub fn terminate(&mut self) {
+        self.state = ProcessState::Terminated;
+
+        let pid = self.pid;
+        let interface_data = &mut self.interface_data;
+        qor_core::tasks::execute_task(qor_core::tasks::Task::new(async move {
+            if let Err(e) = interface_data.release_file_descriptors().await {
+                warn!("Unable to close file descriptors of {:?} on exit: {:?}", pid, e);
+            }
+        }));
+    }
+
+    /// Terminate the process after it took the fault `cause` at `pc`. The trap value `value` is logged alongside, which
+    /// is the faulting address for access and page faults.
+    pub fn fault_kill(&mut self, cause: SynchronousTrap, pc: usize, value: usize) {
+        error!(
+            "{:?} faulted with {:?} at 0x{:x} (trap value 0x{:x}), terminating it",
+            self.pid, cause, pc, value
+        );
+        self.terminate();
+    }
+
+    /// Fork this process, giving the child a copy of its address space, open files, and registers. The mapped pages
+    /// are shared copy-on-write rather than copied, so both processes lose write access to them until the first write
+    /// faults and takes a private copy. The child resumes at this process's saved program counter with `a0` set to
+    /// zero, while `a0` of this process is set to the child's PID.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if there is not enough memory for the child's page table or trap frame.
+    pub fn fork(&mut self) -> Self {
+        let mem_stats = alloc::sync::Arc::new(MemoryStatistics::new());
+
+        let mut page_table = ProcessBox::alloc(&mem_stats, crate::memory::mmu::ManagedPageTable::empty())
+            .expect("Unable to allocate space for process page table");
+        crate::memory::mmu::identity_map_kernel(&mut page_table, GlobalUserFlags::User);
+
+        let stack = self.main_execution.stack.share_copy_on_write(&mut self.page_table, &mem_stats, &mut page_table);
+        let mapped_pages = self.mapped_pages.iter_mut()
+            .map(|sequence| sequence.share_copy_on_write(&mut self.page_table, &mem_stats, &mut page_table))
+            .collect();
+
+        // The child needs its own trap stack, so only the register state is copied across
+        let mut trap_frame = ProcessBox::alloc(&mem_stats, allocate_trap_frame())
+            .expect("Unable to allocate trap frame space");
+        trap_frame.registers = self.main_execution.trap_frame.registers;
+        trap_frame.floating_point_registers = self.main_execution.trap_frame.floating_point_registers;
+        trap_frame.registers[10] = 0;
+
+        let child = Self {
+            pid: new_pid(),
+            main_execution: ExecutionState {
+                program_counter: self.main_execution.program_counter,
+                stack,
+                stack_region: self.main_execution.stack_region,
+                trap_frame,
+            },
+            state: ProcessState::Active,
+            page_table,
+            memory_stats: mem_stats,
+            mapped_pages,
+            interface_data: self.interface_data.clone(),
+            parent: Some(self.pid),
+            heap_break: self.heap_break,
+        };
+
+        self.main_execution.trap_frame.registers[10] = u64::from(child.pid.0);
+
+        child
+    }
+
+    /// Construct a process from an ELF executable, mapping its loadable segments into a fresh address space
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the executable does not target a 64 bit RISC-V hart, is dynamically linked, or its entry
+    /// point is not within an executable segment
+    pub fn from_elf_file(elf: Elf<'_>, stack_size: PageCount) -> Result<Self, ProcessLoadError> {
+        elf.header.check_target(Architecture::RISCV, BitWidth::Bit64).map_err(ProcessLoadError::IncompatibleTarget)?;
+
+        // Without a dynamic linker, execution would start at the entry point and jump into unresolved PLT stubs
+        if elf.program_headers.iter().any(|header| header.header_type == ProgramHeaderType::Interpreter) {
+            error!("Executable requests the interpreter {:?}, but dynamic linking is not supported", elf.interpreter());
+            return Err(ProcessLoadError::RequiresInterpreter);
+        }
+
+        if !elf.entry_is_valid() {
+            error!("Executable entry point {:#x} is not within an executable segment", elf.header.entry);
+            return Err(ProcessLoadError::InvalidEntryPoint);
+        }
+
         let mem_stats = alloc::sync::Arc::new(MemoryStatistics::new());
 
-        let mut page_table = mem_stats.alloc_page_box(crate::memory::mmu::ManagedPageTable::empty())
+        let mut page_table = ProcessBox::alloc(&mem_stats, crate::memory::mmu::ManagedPageTable::empty())
             .expect("Unable to allocate space for process page table");
         crate::memory::mmu::identity_map_kernel(&mut page_table, GlobalUserFlags::User);
 
         let mut proc = Self::from_components(ExecutionState::from_components(&mem_stats, &mut page_table, elf.header.entry.try_into().unwrap(), stack_size), page_table, mem_stats);
+
+        let segments_end = elf.program_headers.iter()
+            .filter(|header| header.header_type == ProgramHeaderType::Load)
+            .map(|header| header.virtual_addr + header.memory_size)
+            .max();
+        if let Some(segments_end) = segments_end {
+            proc.heap_break = ProgramBreak::new(segments_end);
+        }
     
         for program_header in elf.program_headers {
-            if program_header.header_type == qor_core::structures::elf::enums::ProgramHeaderType::Load {
+            if program_header.header_type == ProgramHeaderType::Load {
                 let permissions: PermissionFlags = program_header.flags.into();
                 let virtual_address = VirtualAddress(program_header.virtual_addr & !(PAGE_SIZE as u64 - 1));
                 let page_offset: usize = (program_header.virtual_addr & (PAGE_SIZE as u64 - 1)).try_into().unwrap();
-                let file_length: usize = program_header.file_size.try_into().unwrap();
-                let length = ByteCount::new(program_header.memory_size.try_into().unwrap()).convert();
-                let file_offset = program_header.offset.try_into().unwrap();
+                let memory_length: usize = program_header.memory_size.try_into().unwrap();
+                let length = ByteCount::new(page_offset + memory_length).convert_ceil();
 
+                // Copy the file image of the segment, zeroing the remainder of the memory image (the `.bss` region)
                 let sequence = proc.map_page_sequence(virtual_address, length, permissions);
-                sequence.deref_mut()[page_offset..page_offset + file_length].copy_from_slice(&elf.data[file_offset.. file_offset + file_length]);
+                program_header.load_segment(elf.data, &mut sequence.deref_mut()[page_offset..page_offset + memory_length]);
             } 
         }
         
-        proc
+        Ok(proc)
+    }
+
+    /// Resolve a load or store page fault at `address`, after which the access can be retried. An access just below
+    /// the stack grows the stack down to cover it, and a write to a page shared copy-on-write with a forked process
+    /// gives this process its own copy of that page. As the faulting process's table is the one installed, remapping
+    /// the pages flushes the stale translations of just those pages.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Fault` if `address` is unmapped and beyond the stack's maximum size, or for a store, if its page is
+    /// genuinely without write permission, and `OutOfMemory` if there is no free page to grow the stack or copy a shared
+    /// page into.
+    pub fn handle_page_fault(&mut self, address: VirtualAddress, is_store: bool) -> Result<(), SyscallError> {
+        if let Some(growth) = self.main_execution.stack_region.growth_for(address.0) {
+            return self.grow_stack(growth);
+        }
+
+        if !is_store {
+            return Err(SyscallError::Fault);
+        }
+
+        let sequence = core::iter::once(&mut self.main_execution.stack)
+            .chain(self.mapped_pages.iter_mut())
+            .find(|sequence| sequence.contains(address))
+            .filter(|sequence| sequence.is_copy_on_write(address))
+            .ok_or(SyscallError::Fault)?;
+
+        sequence.break_copy_on_write(&mut self.page_table, address).map_err(|_| SyscallError::OutOfMemory)
+    }
+
+    /// Map the pages of `growth`, just below the bottom of the stack, and move the bottom of the stack down to them
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfMemory` if there are not enough free pages, in which case the stack is left as it was.
+    fn grow_stack(&mut self, growth: core::ops::Range<u64>) -> Result<(), SyscallError> {
+        let length = ByteCount::new((growth.end - growth.start).try_into().unwrap()).convert_ceil();
+
+        // The new pages live with the other mapped pages, so they are shared on fork and freed on exit like the rest
+        let sequence = self.try_map_page_sequence(VirtualAddress(growth.start), length, PermissionFlags::new(0) | PermissionFlag::Read | PermissionFlag::Write)?;
+        sequence.deref_mut().fill(0);
+        self.main_execution.stack_region.grow_to(growth.start);
+
+        Ok(())
+    }
+
+    /// Move the program break by `delta` bytes, returning the previous break. Growing the heap past the pages it
+    /// already has maps fresh zeroed pages to cover it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if the break would move below the start of the heap, `Fault` if the heap would grow
+    /// into the region reserved for the stack or over any other existing mapping, and `OutOfMemory` if there are not
+    /// enough free pages to grow it, in which case the break is left where it was.
+    pub fn sbrk(&mut self, delta: i64) -> Result<VirtualAddress, SyscallError> {
+        let stack_limit = self.main_execution.stack_region.limit();
+
+        // Work on a copy, so the break is only moved once the new pages are mapped
+        let mut heap_break = self.heap_break;
+        let adjustment = heap_break.adjust(delta, stack_limit).map_err(|e| match e {
+            ProgramBreakError::BelowStart => SyscallError::InvalidArgument,
+            ProgramBreakError::Collision => SyscallError::Fault,
+        })?;
+
+        let new_pages = adjustment.new_pages;
+        if !new_pages.is_empty() {
+            if new_pages.clone().step_by(PAGE_SIZE).any(|page| self.page_table.virtual_to_physical_address(VirtualAddress(page)).is_some()) {
+                return Err(SyscallError::Fault);
+            }
+
+            let length = ByteCount::new((new_pages.end - new_pages.start).try_into().unwrap()).convert_ceil();
+            let sequence = self.try_map_page_sequence(VirtualAddress(new_pages.start), length, PermissionFlags::new(0) | PermissionFlag::Read | PermissionFlag::Write)?;
+            sequence.deref_mut().fill(0);
+        }
+
+        self.heap_break = heap_break;
+        Ok(VirtualAddress(adjustment.previous))
+    }
+
+    /// Map `length` bytes, rounded up to whole pages, of fresh zeroed memory with the given permissions, returning the
+    /// address it was mapped at. With a fixed `address` the mapping is placed exactly there, otherwise the lowest free
+    /// range in [`MMAP_REGION`] is chosen.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if `length` is zero, a fixed `address` is not page aligned or the range would leave
+    /// the user half of the address space, or the permissions cannot be mapped (no access at all, or write without
+    /// read). Returns `AlreadyExists` if a fixed range overlaps an existing mapping or the stack's growth region, and
+    /// `OutOfMemory` if `length` is larger than [`MMAP_REGION`], no free range is large enough, or there are not
+    /// enough free pages to back it.
+    pub fn mmap(&mut self, address: Option<VirtualAddress>, length: usize, permissions: PermissionFlags) -> Result<VirtualAddress, SyscallError> {
+        if length == 0 || matches!(EntryPermissionFlags::try_from(permissions), Err(()) | Ok(EntryPermissionFlags::None)) {
+            return Err(SyscallError::InvalidArgument);
+        }
+
+        // Capped before rounding to whole pages, so a huge length can not overflow
+        let bytes = aligned_length(length as u64, PAGE_SIZE as u64, MMAP_REGION.end - MMAP_REGION.start)
+            .ok_or(SyscallError::OutOfMemory)?;
+        let length = PageCount::new((bytes / PAGE_SIZE as u64) as usize);
+
+        let base = if let Some(address) = address {
+            if address.0 % PAGE_SIZE as u64 != 0 {
+                return Err(SyscallError::InvalidArgument);
+            }
+
+            let range = address.0..address.0.checked_add(bytes).ok_or(SyscallError::InvalidArgument)?;
+            if range.end > MMAP_REGION.end {
+                return Err(SyscallError::InvalidArgument);
+            }
+
+            if overlaps(&range, &self.main_execution.stack_region.reserved())
+                || range.clone().step_by(PAGE_SIZE).any(|page| self.page_table.virtual_to_physical_address(VirtualAddress(page)).is_some()) {
+                return Err(SyscallError::AlreadyExists);
+            }
+
+            range.start
+        } else {
+            find_free_region(&self.mapped_pages.iter().map(MappedPageSequence::range), MMAP_REGION, bytes, PAGE_SIZE as u64)
+                .ok_or(SyscallError::OutOfMemory)?
+        };
+
+        let sequence = self.try_map_page_sequence(VirtualAddress(base), length, permissions)?;
+        sequence.mark_from_mmap();
+        sequence.deref_mut().fill(0);
+
+        Ok(VirtualAddress(base))
+    }
+
+    /// Remove a mapping made by [`Process::mmap`], freeing its pages. The range must cover exactly one earlier
+    /// mapping, with `length` rounded up to whole pages as it was when mapped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if `length` is zero, or the range does not match a mapping made by `mmap`. The
+    /// program image, heap and stack can not be unmapped.
+    pub fn munmap(&mut self, address: VirtualAddress, length: usize) -> Result<(), SyscallError> {
+        if length == 0 {
+            return Err(SyscallError::InvalidArgument);
+        }
+
+        let bytes = aligned_length(length as u64, PAGE_SIZE as u64, MMAP_REGION.end - MMAP_REGION.start)
+            .ok_or(SyscallError::InvalidArgument)?;
+        let range = address.0..address.0.checked_add(bytes).ok_or(SyscallError::InvalidArgument)?;
+
+        let index = self.mapped_pages.iter()
+            .position(|sequence| sequence.is_from_mmap() && sequence.range() == range)
+            .ok_or(SyscallError::InvalidArgument)?;
+
+        self.page_table.unmap_range(address, PageCount::new((bytes / PAGE_SIZE as u64) as usize));
+
+        // Dropping the sequence frees its pages, unless a forked process still shares them
+        self.mapped_pages.swap_remove(index);
+
+        Ok(())
+    }
+
+    /// Set the largest size the stack may grow to. Faults below a stack of this size terminate the process.
+    pub const fn set_maximum_stack_size(&mut self, size: PageCount) {
+        self.main_execution.stack_region.set_maximum_size(size.raw_bytes() as u64);
     }
 
     pub fn map_page_sequence(&mut self, virtual_address: VirtualAddress, length: PageCount, permissions: PermissionFlags) -> &mut MappedPageSequence {
-        let sequence = self.memory_stats.map_page_sequence(&mut self.page_table, length, virtual_address, permissions);
+        let sequence = MappedPageSequence::map(&self.memory_stats, &mut self.page_table, length, virtual_address, permissions);
         self.mapped_pages.push(sequence);
 
         self.mapped_pages.last_mut().unwrap()
     }
 
+    /// Map `length` fresh pages at `virtual_address` with the given permissions, like [`Process::map_page_sequence`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfMemory` if there are not enough free pages, in which case nothing is mapped.
+    pub fn try_map_page_sequence(&mut self, virtual_address: VirtualAddress, length: PageCount, permissions: PermissionFlags) -> Result<&mut MappedPageSequence, SyscallError> {
+        let sequence = MappedPageSequence::try_map(&self.memory_stats, &mut self.page_table, length, virtual_address, permissions)
+            .map_err(|_| SyscallError::OutOfMemory)?;
+        self.mapped_pages.push(sequence);
+
+        Ok(self.mapped_pages.last_mut().unwrap())
+    }
+
+    /// Returns true if `frame` is this process's trap frame, that is, the trap was taken while executing this process
+    pub fn owns_trap_frame(&self, frame: &TrapFrame) -> bool {
+        core::ptr::eq(frame, core::ptr::addr_of!(*self.main_execution.trap_frame))
+    }
+
+    /// Record that this process trapped at `epc`, so it resumes there if it is switched out before the trap returns
+    pub fn record_trap(&mut self, epc: usize) {
+        self.main_execution.program_counter.record_trap(epc);
+    }
+
+    /// Resume after the `length` byte instruction the process trapped on, once a trap has completed it
+    pub fn advance_program_counter(&mut self, length: usize) {
+        self.main_execution.program_counter.advance(length);
+    }
+
     pub fn get_switching_data(&self) -> (usize, usize, usize) {
-        (self.page_table.construct_satp(self.pid), core::ptr::addr_of!(*self.main_execution.trap_frame) as usize, self.main_execution.program_counter)
+        (self.page_table.construct_satp(self.pid), core::ptr::addr_of!(*self.main_execution.trap_frame) as usize, self.main_execution.program_counter.program_counter())
     }
 
     pub fn switch(data: (usize, usize, usize)) -> ! {
@@ -168,7 +483,7 @@ impl Process {
         unsafe {
             switch_to_user(
                 core::ptr::addr_of!(*self.main_execution.trap_frame) as usize,
-                self.main_execution.program_counter,
+                self.main_execution.program_counter.program_counter(),
                 satp,
             )
         }
@@ -178,10 +493,126 @@ impl Process {
         self.page_table.virtual_to_physical_address(VirtualAddress(address.0.try_into().unwrap())).map(|v| v.0.try_into().unwrap()).ok_or(SyscallError::Fault)
     }
 
+    /// Get a kernel pointer to a user buffer of `length` bytes, after checking the whole buffer is mapped for user
+    /// access with the `want` permissions
+    pub fn user_buffer(&self, address: UserspaceAddress, length: usize, want: EntryPermissionFlags) -> Result<usize, SyscallError> {
+        self.page_table.check_range(VirtualAddress(address.0.try_into().unwrap()), length, want, true)?;
+
+        self.kernel_pointer(address)
+    }
+
+    /// Copy `length` bytes out of user memory starting at `address`. Callers taking a length from user space should
+    /// clamp it with [`qor_core::structures::transfer::clamp_transfer`] first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidArgument` if `length` is larger than [`MAXIMUM_TRANSFER`], and `Fault` if any page of the range
+    /// is unmapped, not readable, or not accessible from user mode. Both are checked before the kernel buffer is
+    /// allocated.
+    pub fn copy_from_user(&self, address: UserspaceAddress, length: usize) -> Result<alloc::vec::Vec<u8>, SyscallError> {
+        if length > MAXIMUM_TRANSFER {
+            return Err(SyscallError::InvalidArgument);
+        }
+
+        self.page_table.check_range(VirtualAddress(address.0.try_into().unwrap()), length, EntryPermissionFlags::ReadOnly, true)?;
+        let mut data = alloc::vec![0; length];
+
+        self.for_each_user_page(address, length, EntryPermissionFlags::ReadOnly, |pointer, offset, chunk| {
+            // Safety: The range was checked to be mapped and readable, and `pointer` is the kernel's view of it
+            unsafe { core::ptr::copy_nonoverlapping(pointer, data[offset..].as_mut_ptr(), chunk) };
+        })?;
+
+        Ok(data)
+    }
+
+    /// Copy `data` into user memory starting at `address`. Pages which the process itself could write after a page
+    /// fault, those shared copy-on-write or just below the stack, are resolved first so the copy never writes through
+    /// to memory shared with another process.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Fault` if any page of the range is unmapped, not writable, or not accessible from user mode.
+    pub fn copy_to_user(&mut self, address: UserspaceAddress, data: &[u8]) -> Result<(), SyscallError> {
+        let start: u64 = address.0.try_into().unwrap();
+        let end = start.checked_add(data.len() as u64).ok_or(SyscallError::Fault)?;
+
+        let mut page = start & !(PAGE_SIZE as u64 - 1);
+        while page < end {
+            if self.page_table.check_range(VirtualAddress(page), 1, EntryPermissionFlags::ReadWrite, true).is_err() {
+                self.handle_page_fault(VirtualAddress(page), true)?;
+            }
+            page += PAGE_SIZE as u64;
+        }
+
+        self.for_each_user_page(address, data.len(), EntryPermissionFlags::ReadWrite, |pointer, offset, chunk| {
+            // Safety: The range was checked to be mapped and writable, and `pointer` is the kernel's view of it
+            unsafe { core::ptr::copy_nonoverlapping(data[offset..].as_ptr(), pointer, chunk) };
+        })
+    }
+
+    /// Call `f` with a kernel pointer to each piece of the `length` byte user range starting at `address`, along with
+    /// the piece's offset into the range and its length. Pieces are split at page boundaries, as consecutive user
+    /// pages need not be physically contiguous. The whole range is checked before `f` is first called.
+    fn for_each_user_page(&self, address: UserspaceAddress, length: usize, want: EntryPermissionFlags, mut f: impl FnMut(*mut u8, usize, usize)) -> Result<(), SyscallError> {
+        self.page_table.check_range(VirtualAddress(address.0.try_into().unwrap()), length, want, true)?;
+
+        for (offset, chunk) in page_chunks::<PAGE_SIZE>(address.0, length) {
+            f(self.kernel_pointer(UserspaceAddress(address.0 + offset))? as *mut u8, offset, chunk);
+        }
+
+        Ok(())
+    }
+
     pub fn file_descriptor(&self, descriptor: usize) -> Result<&Arc<dyn FileDescriptor>, SyscallError> {
         self.interface_data.file_descriptors.get(&descriptor).ok_or(SyscallError::BadFileDescriptor)
     }
 
+    /// Add an open file to the descriptor table under the lowest free descriptor number, returning that number
+    ///
+    /// # Errors
+    ///
+    /// Returns `TooManyOpenFiles` if the process already holds the maximum number of descriptors.
+    pub fn insert_file_descriptor(&mut self, descriptor: Arc<dyn FileDescriptor>) -> Result<usize, SyscallError> {
+        qor_core::interfaces::fs::insert_file_descriptor(&mut self.interface_data.file_descriptors, descriptor, proc_interface::MAXIMUM_FILE_DESCRIPTORS)
+            .ok_or(SyscallError::TooManyOpenFiles)
+    }
+
+    /// Remove a descriptor from the descriptor table, returning it so the caller can release it
+    ///
+    /// # Errors
+    ///
+    /// Returns `BadFileDescriptor` if no descriptor with that number is open.
+    pub fn remove_file_descriptor(&mut self, descriptor: usize) -> Result<Arc<dyn FileDescriptor>, SyscallError> {
+        self.interface_data.file_descriptors.remove(&descriptor).ok_or(SyscallError::BadFileDescriptor)
+    }
+
+    pub const fn pid(&self) -> PID {
+        self.pid
+    }
+
+    /// The process which is responsible for collecting this process's exit status, if any
+    pub const fn parent(&self) -> Option<PID> {
+        self.parent
+    }
+
+    pub const fn state(&self) -> ProcessState {
+        self.state
+    }
+
+    pub const fn set_state(&mut self, state: ProcessState) {
+        self.state = state;
+    }
+
+    /// Copy out the regions mapped into the process, in address order, so they can be listed without holding the
+    /// process table
+    pub fn snapshot(&self) -> ProcessSnapshot {
+        let mut regions = self.mapped_pages.iter().map(|sequence| sequence.region(None)).collect::<alloc::vec::Vec<_>>();
+        regions.push(self.main_execution.stack.region(Some("[stack]")));
+        regions.sort_by_key(|region| region.start);
+
+        ProcessSnapshot { pid: self.pid, regions }
+    }
+
     pub fn registers(&self) -> &[u64; 32] {
         &self.main_execution.trap_frame.registers
     }
@@ -191,6 +622,20 @@ impl Process {
     }
 }
 
+impl Schedulable for Process {
+    fn pid(&self) -> PID {
+        self.pid
+    }
+
+    fn state(&self) -> ProcessState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: ProcessState) {
+        self.state = state;
+    }
+}
+
 #[allow(clippy::module_name_repetitions)]
 pub fn start_process(proc: Process) {
     PROGRAM_TABLE.spin_lock().insert(proc.pid, proc);
@@ -198,4 +643,37 @@ pub fn start_process(proc: Process) {
 
 pub fn processes() -> &'static ProgramTableMutex {
     &PROGRAM_TABLE
-}
\ No newline at end of file
+}
+
+/// Lists the processes in the process table for the proc file system. The table is only held while each process is
+/// copied out.
+pub struct ProcessTableSource;
+
+impl ProcessSource for ProcessTableSource {
+    fn processes(&self) -> alloc::vec::Vec<PID> {
+        PROGRAM_TABLE.spin_lock().keys().copied().collect()
+    }
+
+    fn snapshot(&self, pid: PID) -> Option<ProcessSnapshot> {
+        PROGRAM_TABLE.spin_lock().get(&pid).map(Process::snapshot)
+    }
+}
+
+/// Remove the terminated process `pid` from the process table, tearing down its address space and freeing
+/// everything it still holds. Returns false, leaving the table untouched, if there is no such process, it has not
+/// terminated, or its page table is still installed on this hart.
+pub fn reap(table: &mut alloc::collections::BTreeMap<PID, Process>, pid: PID) -> bool {
+    if !table.get(&pid).is_some_and(|proc| proc.state == ProcessState::Terminated && !proc.page_table.is_active()) {
+        return false;
+    }
+
+    let mut proc = table.remove(&pid).expect("Process vanished while the table was locked");
+    let memory_stats = proc.memory_stats.clone();
+
+    // Unmap before dropping the sequences, so the table never refers to freed pages
+    proc.page_table.unmap_all();
+    drop(proc);
+
+    debug_assert_eq!(memory_stats.resident(), 0, "{:?} leaked resident pages", pid);
+    true
+}
@@ -5,6 +5,11 @@ use qor_core::interfaces::{fs::{FileDescriptor, FileSystemError, SeekMode}, byte
 
 use crate::drivers::UART_DRIVER;
 
+/// Largest number of file descriptors a single process may hold open
+pub const MAXIMUM_FILE_DESCRIPTORS: usize = 64;
+
+/// Per process kernel interface state. Cloning it shares every open file with the clone, as a forked child does.
+#[derive(Clone)]
 pub struct ProcessData {
     pub file_descriptors: BTreeMap<usize, Arc<dyn FileDescriptor>>
 }
@@ -18,8 +23,18 @@ impl FileDescriptor for UARTFileDescriptor {
     /// # Errors
     ///
     /// Returns an error if the operation failed.
-    async fn read(&self, _buffer: &mut [u8]) -> Result<usize, FileSystemError> {
-        todo!()
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        // Only the bytes the UART interrupt has already received are returned, the read does not wait for more to arrive
+        let mut count = 0;
+        for byte in buffer.iter_mut() {
+            match UART_DRIVER.try_read_byte() {
+                Some(value) => *byte = value,
+                None => break,
+            }
+            count += 1;
+        }
+
+        Ok(count)
     }
 
     /// Writes bytes to the file starting at the cursor. Returns the number of bytes written.
@@ -44,6 +59,52 @@ impl FileDescriptor for UARTFileDescriptor {
     }
 }
 
+/// File descriptor for a network card, through which whole Ethernet frames are sent and received
+pub struct NetworkFileDescriptor {
+    pub device: &'static crate::drivers::virtio::net::NetworkDriver,
+}
+
+#[async_trait::async_trait]
+impl FileDescriptor for NetworkFileDescriptor {
+    /// Read the oldest frame the card has received into `buffer`, truncating it if it does not fit. Returns the number
+    /// of bytes read, which is zero if no frame is waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation failed.
+    async fn read(&self, buffer: &mut [u8]) -> Result<usize, FileSystemError> {
+        let Some(frame) = self.device.poll_frame() else {
+            return Ok(0);
+        };
+
+        let count = frame.len().min(buffer.len());
+        buffer[..count].copy_from_slice(&frame[..count]);
+
+        Ok(count)
+    }
+
+    /// Send `buffer` as a single frame. Returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame is too large to send.
+    async fn write(&self, buffer: &[u8]) -> Result<usize, FileSystemError> {
+        match self.device.send_frame(buffer) {
+            Ok(()) => Ok(buffer.len()),
+            Err(_) => Err(FileSystemError::GenericError)
+        }
+    }
+
+    /// Seeks to a position in the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation failed.
+    async fn seek(&self, _seek: SeekMode) -> Result<usize, FileSystemError> {
+        Ok(0)
+    }
+}
+
 impl ProcessData {
     pub fn new() -> Self {
         // TODO: Don't immediately just add these, eventually a process will be opening these
@@ -51,9 +112,19 @@ impl ProcessData {
 
         file_descriptors.insert(0, Arc::new(UARTFileDescriptor {}) as Arc<dyn FileDescriptor>);
         file_descriptors.insert(1, Arc::new(UARTFileDescriptor {}) as Arc<dyn FileDescriptor>);
+        file_descriptors.insert(2, Arc::new(UARTFileDescriptor {}) as Arc<dyn FileDescriptor>);
 
         Self {
             file_descriptors
         }
     }
+
+    /// Release every open file descriptor held by the process, closing those which are no longer shared
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error raised while closing a descriptor, all descriptors are released regardless
+    pub async fn release_file_descriptors(&mut self) -> Result<(), FileSystemError> {
+        qor_core::interfaces::fs::release_file_descriptors(&mut self.file_descriptors).await
+    }
 }
\ No newline at end of file
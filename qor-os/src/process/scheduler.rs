@@ -0,0 +1,49 @@
+use qor_core::structures::{
+    id::{HartID, PID},
+    process::schedule_next,
+};
+
+use crate::trap::structures::TrapInfo;
+
+use super::{processes, reap, Process, ProcessState};
+
+/// PID of the process most recently switched to, the round robin cursor starts searching just after it
+static CURSOR: qor_core::sync::Mutex<Option<PID>> = qor_core::sync::Mutex::new(None);
+
+/// Pick the next runnable process in round robin order, and switch to it from the hart `info` was taken on. Returns
+/// without switching if no process is runnable. The interrupted process's registers and pc were already saved when
+/// the trap was taken, so it resumes where it left off when next chosen.
+pub fn schedule(info: &TrapInfo) {
+    let hart = HartID::from(info.hart);
+    let mut table = processes().spin_lock();
+
+    // A terminated process is kept until its parent collects its exit status, so only those without a live parent
+    // are reaped here. The process whose trap frame this trap is using is left for a later tick, as the trap returns
+    // into it if nothing else is runnable, as is one whose page table is still installed, which `reap` refuses.
+    let orphans = table
+        .values()
+        .filter(|proc| {
+            proc.state() == ProcessState::Terminated
+                && !proc
+                    .parent()
+                    .is_some_and(|parent| table.contains_key(&parent))
+                && !proc.owns_trap_frame(info.frame)
+        })
+        .map(Process::pid)
+        .collect::<alloc::vec::Vec<_>>();
+
+    for pid in orphans {
+        reap(&mut table, pid);
+    }
+
+    let mut cursor = CURSOR.spin_lock();
+    let Some(next) = schedule_next(&mut table, &mut cursor, hart) else {
+        return;
+    };
+
+    let switching_data = table[&next].get_switching_data();
+    drop(cursor);
+    drop(table);
+
+    Process::switch(switching_data);
+}
@@ -1,34 +1,95 @@
-use qor_core::memory::ByteCount;
+use qor_core::{memory::ByteCount, structures::syscall_error::SyscallError};
+use qor_riscv::trap::syscall::{
+    set_syscall_result, syscall_arguments, syscall_number, ECALL_INSTRUCTION_SIZE,
+    SYSCALL_ARGUMENTS,
+};
 
-use crate::{process::Process, syscalls::{handlers, structures::UserspaceAddress}};
+use crate::{
+    process::Process,
+    syscalls::{handlers, structures::UserspaceAddress},
+    trap::structures::TrapInfo,
+};
 
 use super::structures::SyscallNumber;
 
-pub fn raw_handle_syscall(proc: &mut Process) {
-
-    let syscall_number = proc.registers()[17];
-    
-    #[allow(clippy::option_if_let_else)]
-    if let Some(syscall) = SyscallNumber::from_number(syscall_number) {
-        let result = match syscall {
-            SyscallNumber::Write => handlers::write::write(proc, 
-                proc.registers()[10].try_into().unwrap(),
-            UserspaceAddress(proc.registers()[11].try_into().unwrap()), 
-            ByteCount::new(proc.registers()[12].try_into().unwrap())),
-            _ => todo!()
-        };
-
-        debug!("{:?}", result);
-
-        match result {
-            Ok(value) => { proc.registers_mut()[10] = value as u64 },
-            Err(value) => {
-                let e: isize = value.into();
-                proc.registers_mut()[10] = u64::from_ne_bytes(i64::to_ne_bytes(e as i64));
-            }
+/// Handle a syscall made by `proc`, writing the result into its `a0` register and returning the pc to resume at.
+///
+/// Errors are returned as the negated errno value, and unknown or unimplemented syscall numbers return
+/// [`SyscallError::NotImplemented`] to the process.
+pub fn handle_syscall(proc: &mut Process, info: &TrapInfo) -> usize {
+    let syscall_number = syscall_number(proc.registers());
+    let arguments = syscall_arguments(proc.registers());
+
+    let result = if let Some(syscall) = SyscallNumber::from_number(syscall_number) {
+        dispatch(proc, syscall, arguments)
+    } else {
+        warn!(
+            "Unknown syscall number {} from {:?}",
+            syscall_number,
+            proc.pid()
+        );
+        Err(SyscallError::NotImplemented)
+    };
+
+    debug!("{:?}", result);
+
+    set_syscall_result(proc.registers_mut(), result);
+
+    proc.advance_program_counter(ECALL_INSTRUCTION_SIZE);
+    info.trap_pc + ECALL_INSTRUCTION_SIZE
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn dispatch(
+    proc: &mut Process,
+    syscall: SyscallNumber,
+    arguments: [usize; SYSCALL_ARGUMENTS],
+) -> Result<usize, SyscallError> {
+    match syscall {
+        SyscallNumber::Read => handlers::read::read(
+            proc,
+            arguments[0],
+            UserspaceAddress(arguments[1]),
+            ByteCount::new(arguments[2]),
+        ),
+        SyscallNumber::Write => handlers::write::write(
+            proc,
+            arguments[0],
+            UserspaceAddress(arguments[1]),
+            ByteCount::new(arguments[2]),
+        ),
+        SyscallNumber::Open => {
+            handlers::open::open(proc, UserspaceAddress(arguments[0]), arguments[1])
         }
+        SyscallNumber::Close => handlers::close::close(proc, arguments[0]),
+        SyscallNumber::Stat => handlers::stat::stat(
+            proc,
+            UserspaceAddress(arguments[0]),
+            UserspaceAddress(arguments[1]),
+        ),
+        SyscallNumber::Fstat => {
+            handlers::fstat::fstat(proc, arguments[0], UserspaceAddress(arguments[1]))
+        }
+        SyscallNumber::Mmap => handlers::mmap::mmap(
+            proc,
+            UserspaceAddress(arguments[0]),
+            arguments[1],
+            arguments[2],
+            arguments[3],
+        ),
+        SyscallNumber::Munmap => {
+            handlers::munmap::munmap(proc, UserspaceAddress(arguments[0]), arguments[1])
+        }
+        SyscallNumber::Sbrk => handlers::sbrk::sbrk(proc, arguments[0] as i64),
+        SyscallNumber::GetDents => handlers::getdents::getdents(
+            proc,
+            arguments[0],
+            UserspaceAddress(arguments[1]),
+            ByteCount::new(arguments[2]),
+        ),
+        SyscallNumber::GetPid => handlers::getpid::getpid(proc),
+        SyscallNumber::Exit => handlers::exit::exit(proc, arguments[0]),
+        SyscallNumber::Reboot => handlers::reboot::reboot(proc, arguments[0]),
+        _ => Err(SyscallError::NotImplemented),
     }
-    else {
-        panic!("Unknown syscall number {}", syscall_number);
-    }
-}
\ No newline at end of file
+}
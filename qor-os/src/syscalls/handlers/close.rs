@@ -0,0 +1,11 @@
+use qor_core::{interfaces::fs::release_file_descriptor, structures::syscall_error::SyscallError, tasks::block_on};
+
+use crate::process::Process;
+
+/// Close `file_descriptor`, releasing the underlying file once no other descriptor refers to it.
+pub fn close(proc: &mut Process, file_descriptor: usize) -> Result<usize, SyscallError> {
+    let file_descriptor = proc.remove_file_descriptor(file_descriptor)?;
+    block_on(release_file_descriptor(file_descriptor))?;
+
+    Ok(0)
+}
@@ -0,0 +1,13 @@
+use qor_core::structures::syscall_error::SyscallError;
+
+use crate::process::Process;
+
+/// Terminate the calling process with the exit status `status`. The result is never seen, as the process does not
+/// run again.
+#[allow(clippy::unnecessary_wraps)]
+pub fn exit(proc: &mut Process, status: usize) -> Result<usize, SyscallError> {
+    info!("{:?} exited with status {}", proc.pid(), status);
+    proc.terminate();
+
+    Ok(0)
+}
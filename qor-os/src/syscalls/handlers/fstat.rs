@@ -0,0 +1,22 @@
+use qor_core::{
+    structures::{stat::Stat, syscall_error::SyscallError},
+    tasks::block_on,
+};
+
+use crate::{fs::global_fs, process::Process, syscalls::structures::UserspaceAddress};
+
+/// Write the [`Stat`] for the file open as `file_descriptor` to the user buffer at `buffer`.
+///
+/// Descriptors which are not backed by an inode, such as the console, have nothing to describe and fail with
+/// [`SyscallError::BadInode`].
+pub fn fstat(proc: &mut Process, file_descriptor: usize, buffer: UserspaceAddress) -> Result<usize, SyscallError> {
+    let inode = proc.file_descriptor(file_descriptor)?.inode().ok_or(SyscallError::BadInode)?;
+
+    let fs = global_fs();
+    let fs = fs.read();
+    let data = block_on(fs.inode_data(inode))?;
+
+    proc.copy_to_user(buffer, Stat::from(&data).as_bytes())?;
+
+    Ok(0)
+}
@@ -0,0 +1,17 @@
+use qor_core::{memory::ByteCount, structures::{syscall_error::SyscallError, transfer::clamp_transfer}, tasks::block_on};
+
+use crate::{process::Process, syscalls::structures::UserspaceAddress};
+
+/// Fill the user buffer at `buffer` with packed records for the next entries of the open directory
+/// `file_descriptor`, returning the number of bytes written, or zero once the whole directory has been listed. At most
+/// [`qor_core::structures::transfer::MAXIMUM_TRANSFER`] bytes are filled at once, the rest of the entries are returned
+/// by the next call.
+pub fn getdents(proc: &mut Process, file_descriptor: usize, buffer: UserspaceAddress, length: ByteCount) -> Result<usize, SyscallError> {
+    let file_descriptor = proc.file_descriptor(file_descriptor)?.clone();
+    let mut data = alloc::vec![0; clamp_transfer(length.raw_bytes())];
+
+    let count = block_on(file_descriptor.read_directory(&mut data))?;
+    proc.copy_to_user(buffer, &data[..count])?;
+
+    Ok(count)
+}
@@ -0,0 +1,9 @@
+use qor_core::structures::syscall_error::SyscallError;
+
+use crate::process::Process;
+
+/// Get the PID of the calling process.
+#[allow(clippy::unnecessary_wraps)]
+pub fn getpid(proc: &Process) -> Result<usize, SyscallError> {
+    Ok(usize::from(proc.pid().0))
+}
@@ -0,0 +1,43 @@
+use qor_core::structures::{
+    mem::{PermissionFlag, PermissionFlags},
+    syscall_error::SyscallError,
+};
+use qor_riscv::memory::mmu::addresses::VirtualAddress;
+
+use crate::{process::Process, syscalls::structures::UserspaceAddress};
+
+const PROT_READ: usize = 0x1;
+const PROT_WRITE: usize = 0x2;
+const PROT_EXEC: usize = 0x4;
+
+const MAP_FIXED: usize = 0x10;
+const MAP_ANONYMOUS: usize = 0x20;
+
+/// Map `length` bytes of zeroed anonymous memory into the calling process, returning the address of the mapping.
+///
+/// The address is only used with `MAP_FIXED`, and unlike Linux a fixed mapping never replaces an existing one. Only
+/// anonymous mappings are supported, so file backed mappings return [`SyscallError::NotImplemented`].
+pub fn mmap(
+    proc: &mut Process,
+    address: UserspaceAddress,
+    length: usize,
+    protection: usize,
+    flags: usize,
+) -> Result<usize, SyscallError> {
+    if flags & MAP_ANONYMOUS == 0 {
+        return Err(SyscallError::NotImplemented);
+    }
+
+    let mut permissions = PermissionFlags::new(0);
+    permissions.set_flag_state(PermissionFlag::Read, protection & PROT_READ != 0);
+    permissions.set_flag_state(PermissionFlag::Write, protection & PROT_WRITE != 0);
+    permissions.set_flag_state(PermissionFlag::Execute, protection & PROT_EXEC != 0);
+
+    let address = (flags & MAP_FIXED != 0).then_some(VirtualAddress(address.0 as u64));
+
+    Ok(proc
+        .mmap(address, length, permissions)?
+        .0
+        .try_into()
+        .unwrap())
+}
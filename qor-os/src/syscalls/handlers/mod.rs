@@ -1 +1,13 @@
+pub mod close;
+pub mod exit;
+pub mod fstat;
+pub mod getdents;
+pub mod getpid;
+pub mod mmap;
+pub mod munmap;
+pub mod open;
+pub mod read;
+pub mod reboot;
+pub mod sbrk;
+pub mod stat;
 pub mod write;
\ No newline at end of file
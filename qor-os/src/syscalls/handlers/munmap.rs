@@ -0,0 +1,15 @@
+use qor_core::structures::syscall_error::SyscallError;
+use qor_riscv::memory::mmu::addresses::VirtualAddress;
+
+use crate::{process::Process, syscalls::structures::UserspaceAddress};
+
+/// Remove a mapping made by `mmap` from the calling process.
+pub fn munmap(
+    proc: &mut Process,
+    address: UserspaceAddress,
+    length: usize,
+) -> Result<usize, SyscallError> {
+    proc.munmap(VirtualAddress(address.0 as u64), length)?;
+
+    Ok(0)
+}
@@ -0,0 +1,69 @@
+use alloc::{string::String, vec::Vec};
+use qor_core::{
+    interfaces::fs::{OpenFlag, OpenFlags},
+    structures::syscall_error::SyscallError,
+    tasks::block_on,
+};
+use qor_riscv::memory::PAGE_SIZE;
+
+use crate::{fs::global_fs, process::Process, syscalls::structures::UserspaceAddress};
+
+/// Longest path, including its nul terminator, which will be read from userspace
+const MAXIMUM_PATH_LENGTH: usize = 4096;
+
+const O_CREAT: usize = 0o100;
+const O_TRUNC: usize = 0o1000;
+const O_APPEND: usize = 0o2000;
+
+/// Open the file at the absolute path held in the nul terminated user string at `path`, returning the new file
+/// descriptor.
+///
+/// `O_CREAT`, `O_TRUNC` and `O_APPEND` are honoured, every other flag, including the access mode, is ignored.
+pub fn open(
+    proc: &mut Process,
+    path: UserspaceAddress,
+    flags: usize,
+) -> Result<usize, SyscallError> {
+    let path = read_user_path(proc, path)?;
+
+    let mut open_flags = OpenFlags::default();
+    open_flags.set_flag_state(OpenFlag::Create, flags & O_CREAT != 0);
+    open_flags.set_flag_state(OpenFlag::Truncate, flags & O_TRUNC != 0);
+    open_flags.set_flag_state(OpenFlag::Append, flags & O_APPEND != 0);
+
+    // There is no working directory to resolve relative paths against
+    if !path.starts_with('/') {
+        return Err(SyscallError::InvalidArgument);
+    }
+
+    let fs = global_fs();
+    let fs = fs.read();
+    let descriptor = block_on(fs.open_path(&path, open_flags))?;
+
+    proc.insert_file_descriptor(descriptor)
+}
+
+/// Read a nul terminated path from userspace. It is copied a page at a time, so the copy never reaches past the page
+/// holding the terminator, which may be the last one mapped.
+pub fn read_user_path(proc: &Process, address: UserspaceAddress) -> Result<String, SyscallError> {
+    let mut path = Vec::new();
+    let mut current = address.0;
+
+    loop {
+        let chunk = (PAGE_SIZE - current % PAGE_SIZE).min(MAXIMUM_PATH_LENGTH - path.len());
+        if chunk == 0 {
+            return Err(SyscallError::NameTooLong);
+        }
+
+        let bytes = proc.copy_from_user(UserspaceAddress(current), chunk)?;
+        if let Some(end) = bytes.iter().position(|byte| *byte == 0) {
+            path.extend_from_slice(&bytes[..end]);
+            break;
+        }
+
+        path.extend_from_slice(&bytes);
+        current += chunk;
+    }
+
+    String::from_utf8(path).map_err(|_| SyscallError::InvalidArgument)
+}
@@ -0,0 +1,15 @@
+use qor_core::{memory::ByteCount, structures::{syscall_error::SyscallError, transfer::clamp_transfer}, tasks::block_on};
+
+use crate::{process::Process, syscalls::structures::UserspaceAddress};
+
+/// Read up to `length` bytes from `file_descriptor` into the user buffer at `buffer`, returning the number of bytes
+/// read. At most [`qor_core::structures::transfer::MAXIMUM_TRANSFER`] bytes are read at once.
+pub fn read(proc: &mut Process, file_descriptor: usize, buffer: UserspaceAddress, length: ByteCount) -> Result<usize, SyscallError> {
+    let file_descriptor = proc.file_descriptor(file_descriptor)?.clone();
+    let mut data = alloc::vec![0; clamp_transfer(length.raw_bytes())];
+
+    let count = block_on(file_descriptor.read(&mut data))?;
+    proc.copy_to_user(buffer, &data[..count])?;
+
+    Ok(count)
+}
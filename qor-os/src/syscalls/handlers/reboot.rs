@@ -0,0 +1,24 @@
+use qor_core::structures::syscall_error::SyscallError;
+
+use crate::process::Process;
+
+/// Command restarting the machine, as for Linux's `reboot`
+pub const REBOOT_CMD_RESTART: usize = 0x0123_4567;
+
+/// Command powering off the machine, as for Linux's `reboot`
+pub const REBOOT_CMD_POWER_OFF: usize = 0x4321_fedc;
+
+/// Restart or power off the machine, as selected by `command`. Only returns if the command is not recognized.
+pub fn reboot(proc: &Process, command: usize) -> Result<usize, SyscallError> {
+    match command {
+        REBOOT_CMD_RESTART => {
+            info!("{:?} requested a reboot", proc.pid());
+            crate::power::reboot()
+        }
+        REBOOT_CMD_POWER_OFF => {
+            info!("{:?} requested a power off", proc.pid());
+            crate::power::shutdown()
+        }
+        _ => Err(SyscallError::InvalidArgument),
+    }
+}
@@ -0,0 +1,8 @@
+use qor_core::structures::syscall_error::SyscallError;
+
+use crate::process::Process;
+
+/// Move the program break of the calling process by `delta` bytes, returning the previous break.
+pub fn sbrk(proc: &mut Process, delta: i64) -> Result<usize, SyscallError> {
+    Ok(proc.sbrk(delta)?.0.try_into().unwrap())
+}
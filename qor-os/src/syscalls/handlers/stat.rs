@@ -0,0 +1,37 @@
+use qor_core::{
+    interfaces::fs::FileSystemError,
+    structures::{stat::Stat, syscall_error::SyscallError},
+    tasks::block_on,
+};
+
+use crate::{
+    fs::global_fs,
+    process::Process,
+    syscalls::{handlers::open::read_user_path, structures::UserspaceAddress},
+};
+
+/// Write the [`Stat`] for the file at the absolute path held in the nul terminated user string at `path` to the user
+/// buffer at `buffer`.
+pub fn stat(proc: &mut Process, path: UserspaceAddress, buffer: UserspaceAddress) -> Result<usize, SyscallError> {
+    let path = read_user_path(proc, path)?;
+
+    // There is no working directory to resolve relative paths against
+    if !path.starts_with('/') {
+        return Err(SyscallError::InvalidArgument);
+    }
+
+    let fs = global_fs();
+    let fs = fs.read();
+    let data = block_on(async {
+        let inode = fs.lookup(&path).await?;
+        fs.inode_data(inode).await
+    })
+    .map_err(|e| match e {
+        FileSystemError::PathNotFound => SyscallError::BadInode,
+        e => e.into(),
+    })?;
+
+    proc.copy_to_user(buffer, Stat::from(&data).as_bytes())?;
+
+    Ok(0)
+}
@@ -1,15 +1,13 @@
-use qor_core::{structures::syscall_error::SyscallError, memory::ByteCount, tasks::Task};
+use qor_core::{memory::ByteCount, structures::{syscall_error::SyscallError, transfer::clamp_transfer}, tasks::block_on};
 
 use crate::{process::Process, syscalls::structures::UserspaceAddress};
 
-pub fn write(proc: &mut Process, file_descriptor: usize, buffer: UserspaceAddress, length: ByteCount) -> Result<usize, SyscallError> {
-    let ptr = proc.kernel_pointer(buffer)? as *mut u8;
-
+/// Write `length` bytes from the user buffer at `buffer` to `file_descriptor`, returning the number of bytes written.
+/// At most [`qor_core::structures::transfer::MAXIMUM_TRANSFER`] bytes are written at once, so a longer write is cut
+/// short.
+pub fn write(proc: &Process, file_descriptor: usize, buffer: UserspaceAddress, length: ByteCount) -> Result<usize, SyscallError> {
     let file_descriptor = proc.file_descriptor(file_descriptor)?.clone();
+    let data = proc.copy_from_user(buffer, clamp_transfer(length.raw_bytes()))?;
 
-    unsafe {
-        qor_core::tasks::execute_task(Task::ignore_result(file_descriptor.write(core::slice::from_raw_parts(ptr, length.raw_bytes()))));
-    }
-    
-    Ok(length.raw_bytes())
-}
\ No newline at end of file
+    Ok(block_on(file_descriptor.write(&data))?)
+}
@@ -1,6 +1,27 @@
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// System Call Numbers
+///
+/// The number is passed in `a7` and arguments in `a0` through `a6`, the result is returned in `a0` as either the
+/// value below or a negated errno value. `stat` and `fstat` fill `buffer` with a
+/// [`qor_core::structures::stat::Stat`].
+///
+/// | Number | Name       | Arguments                          | Returns                |
+/// |--------|------------|------------------------------------|------------------------|
+/// | 0      | `read`     | `fd`, `buffer`, `length`           | bytes read             |
+/// | 1      | `write`    | `fd`, `buffer`, `length`           | bytes written          |
+/// | 2      | `open`     | `path`, `flags`                    | new fd                 |
+/// | 3      | `close`    | `fd`                               | 0                      |
+/// | 4      | `stat`     | `path`, `buffer`                   | 0                      |
+/// | 5      | `fstat`    | `fd`, `buffer`                     | 0                      |
+/// | 6      | `lstat`    | -                                  | not implemented        |
+/// | 9      | `mmap`     | `addr`, `length`, `prot`, `flags`  | mapped address         |
+/// | 11     | `munmap`   | `addr`, `length`                   | 0                      |
+/// | 12     | `sbrk`     | `delta`                            | previous break         |
+/// | 39     | `getpid`   | -                                  | PID of the caller      |
+/// | 60     | `exit`     | `status`                           | does not return        |
+/// | 169    | `reboot`   | `command`                          | does not return        |
+/// | 217    | `getdents` | `fd`, `buffer`, `length`           | bytes written          |
 pub enum SyscallNumber {
     Read = 0,
     Write = 1,
@@ -9,7 +30,13 @@ pub enum SyscallNumber {
     Stat = 4,
     Fstat = 5,
     Lstat = 6,
+    Mmap = 9,
+    Munmap = 11,
+    Sbrk = 12,
+    GetPid = 39,
     Exit = 60,
+    Reboot = 169,
+    GetDents = 217,
 }
 
 /// Address in userspace memory
@@ -27,7 +54,13 @@ impl SyscallNumber {
             4 => Some(Self::Stat),
             5 => Some(Self::Fstat),
             6 => Some(Self::Lstat),
+            9 => Some(Self::Mmap),
+            11 => Some(Self::Munmap),
+            12 => Some(Self::Sbrk),
+            39 => Some(Self::GetPid),
             60 => Some(Self::Exit),
+            169 => Some(Self::Reboot),
+            217 => Some(Self::GetDents),
             _ => None,
         }
     }
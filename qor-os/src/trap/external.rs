@@ -1,46 +1,51 @@
-use qor_core::{drivers::plic::PLICDriverInterface, interfaces::bytes::GenericByteReadInterface};
+use qor_riscv::drivers::plic::{DispatchError, InterruptHandlers, InterruptSource};
 
-use crate::{
-    drivers::{
-        PLIC_DRIVER, UART_DRIVER, UART_INTERRUPT, VIRTIO_INTERRUPT_1, VIRTIO_INTERRUPT_2,
-        VIRTIO_INTERRUPT_3, VIRTIO_INTERRUPT_4, VIRTIO_INTERRUPT_5, VIRTIO_INTERRUPT_6,
-        VIRTIO_INTERRUPT_7, VIRTIO_INTERRUPT_8,
-    },
-    kprint,
-};
+use crate::drivers::{PLIC_DRIVER, UART_DRIVER, UART_INTERRUPT, VIRTIO_INTERRUPTS};
 
 use super::structures::TrapInfo;
 
-/// Function which is executed when an external interrupt is triggered
+/// Handlers for every external interrupt source enabled in the PLIC
+static INTERRUPT_HANDLERS: InterruptHandlers = interrupt_handlers();
+
+/// Build the table of handlers for each enabled interrupt source
+const fn interrupt_handlers() -> InterruptHandlers {
+    let mut handlers = InterruptHandlers::new().with_handler(UART_INTERRUPT, handle_uart_interrupt);
+
+    let mut index = 0;
+    while index < VIRTIO_INTERRUPTS.len() {
+        handlers = handlers.with_handler(VIRTIO_INTERRUPTS[index], handle_virtio_interrupt);
+        index += 1;
+    }
+
+    handlers
+}
+
+fn handle_uart_interrupt(_: InterruptSource) {
+    UART_DRIVER.handle_interrupt();
+}
+
+fn handle_virtio_interrupt(_: InterruptSource) {
+    // Each driver only acknowledges interrupts its own device has raised, so all of them can be given every interrupt
+    if let Some(driver) = crate::drivers::BLOCK_DRIVER.load(core::sync::atomic::Ordering::Acquire) {
+        driver.handle_interrupt();
+    }
+
+    if let Some(driver) = crate::drivers::ENTROPY_DRIVER.load(core::sync::atomic::Ordering::Acquire) {
+        driver.handle_interrupt();
+    }
+
+    if let Some(driver) = crate::drivers::NETWORK_DRIVER.load(core::sync::atomic::Ordering::Acquire) {
+        driver.handle_interrupt();
+    }
+}
+
+/// Function which is executed when an external interrupt is triggered, claims the interrupt from the PLIC, runs the
+/// handler for its source, then completes it
 pub fn handle_external_interrupt(info: &TrapInfo) {
-    #[allow(clippy::option_if_let_else)]
-    if let Some(interrupt_id) = PLIC_DRIVER
-        .poll_interrupt(info.hart.into())
-        .expect("Unable to poll PLIC")
-    {
-        match interrupt_id {
-            UART_INTERRUPT => {
-                if let Some(byte) = UART_DRIVER
-                    .read_byte()
-                    .expect("Unable to read byte from UART")
-                {
-                    kprint!("{}", byte as char);
-                }
-            }
-            VIRTIO_INTERRUPT_1 | VIRTIO_INTERRUPT_2 | VIRTIO_INTERRUPT_3 | VIRTIO_INTERRUPT_4
-            | VIRTIO_INTERRUPT_5 | VIRTIO_INTERRUPT_6 | VIRTIO_INTERRUPT_7 | VIRTIO_INTERRUPT_8 => {
-                // We don't do anything here yet
-                // TODO
-            }
-            _ => {
-                panic!("Unhandled interrupt: {:?}", interrupt_id);
-            }
-        }
-
-        PLIC_DRIVER
-            .complete_interrupt(info.hart.into(), interrupt_id)
-            .expect("Unable to complete interrupt");
-    } else {
-        panic!("No interrupt found, this is unexpected");
+    match INTERRUPT_HANDLERS.dispatch(&PLIC_DRIVER, info.hart.into()) {
+        Ok(Some(_)) => {}
+        Ok(None) => panic!("No interrupt found, this is unexpected"),
+        Err(DispatchError::Unhandled(source)) => panic!("Unhandled interrupt: {source:?}"),
+        Err(DispatchError::Driver(error)) => panic!("Unable to dispatch interrupt: {error:?}"),
     }
 }
@@ -1,4 +1,7 @@
-use crate::process::{processes, Process};
+use qor_core::{drivers::timer::HardwareTimerDriver, structures::id::PID};
+use qor_riscv::{memory::mmu::addresses::VirtualAddress, trap::fault::FaultRoute};
+
+use crate::process::{processes, ProcessState};
 
 use super::{
     external::handle_external_interrupt,
@@ -11,16 +14,16 @@ pub fn handle_trap(info: &TrapInfo) -> usize {
     match info.cause {
         TrapCause::AsynchronousTrap(AsynchronousTrap::MachineTimer) => {
             debug!("Machine timer interrupt");
+            // Re-arm the timer for the next quantum before switching, as `schedule` does not return if it switches
             crate::drivers::CLINT_DRIVER.handle_interrupt(info.hart.into());
 
-            let mut lock = crate::process::processes().spin_lock();
-            if let Some(entry) = lock.first_entry() {
-                let switching_data = entry.get().get_switching_data();
-                drop(lock);
-
-                Process::switch(switching_data);
+            // Wake sleeping tasks whose deadline has passed, so they run promptly however busy the executor is
+            if let Ok(now) = crate::drivers::CLINT_DRIVER.time(info.hart.into()) {
+                crate::drivers::TIMER_WHEEL.wake_expired(now);
             }
 
+            // Preempt the interrupted process, its registers are already saved in its trap frame
+            crate::process::scheduler::schedule(info);
         }
         TrapCause::AsynchronousTrap(AsynchronousTrap::MachineExternal) => {
             handle_external_interrupt(info);
@@ -28,20 +31,34 @@ pub fn handle_trap(info: &TrapInfo) -> usize {
         TrapCause::Synchronous(SynchronousTrap::Breakpoint) => {
             debug!("Breakpoint at 0x{:x}", info.trap_pc);
         }
+        TrapCause::Synchronous(SynchronousTrap::EnvironmentCallFromSMode) => {
+            let call = info.frame.registers[17] as usize;
+            if !qor_riscv::trap::interrupts::handle_supervisor_call(call) {
+                panic!("Unknown supervisor call {:#x}: {:x?}", call, info);
+            }
+        }
         TrapCause::Synchronous(SynchronousTrap::EnvironmentCallFromUMode) => {
-            warn!("Syscall!");
-            warn!("PID: {:?}", qor_riscv::trap::get_pid());
-
             let pid = qor_riscv::trap::get_pid();
             let mut lock = processes().spin_lock();
 
-            #[allow(clippy::option_if_let_else)]
             if let Some(proc) = lock.get_mut(&pid) {
-                crate::syscalls::handler::raw_handle_syscall(proc);
-            }
-            else {
-                error!("Got syscall from non-existant process {:?}", pid);
+                let resume_pc = crate::syscalls::handler::handle_syscall(proc, info);
+                if proc.state() != ProcessState::Terminated {
+                    return resume_pc;
+                }
+                drop(lock);
+
+                switch_from_terminated(info, pid);
             }
+
+            error!("Got syscall from non-existant process {:?}", pid);
+        }
+        TrapCause::Synchronous(cause) => {
+            let Some(route) = info.fault else {
+                panic!("Unhandled trap: {:x?}", info);
+            };
+
+            return handle_fault(info, cause, route);
         }
         _ => {
             panic!("Unhandled trap: {:x?}", info);
@@ -54,3 +71,42 @@ pub fn handle_trap(info: &TrapInfo) -> usize {
         info.trap_pc
     }
 }
+
+/// Deal with the fault `cause` as `route` directs, returning the pc to resume at if the fault was resolved.
+fn handle_fault(info: &TrapInfo, cause: SynchronousTrap, route: FaultRoute) -> usize {
+    // Checked before taking any lock, as the kernel may have faulted while holding it
+    if route == FaultRoute::Fatal {
+        panic!("Kernel fault {:?}: {:x?}", cause, info);
+    }
+
+    let pid = qor_riscv::trap::get_pid();
+    let mut lock = processes().spin_lock();
+
+    let Some(proc) = lock.get_mut(&pid) else {
+        panic!("{:?} outside of any process: {:x?}", cause, info);
+    };
+
+    match route {
+        FaultRoute::ResolvePage { is_store } => {
+            let address = VirtualAddress(info.trap_value.try_into().unwrap());
+            if proc.handle_page_fault(address, is_store).is_ok() {
+                // Retry the access now that the page is mapped with the permissions it needs
+                return info.trap_pc;
+            }
+        }
+        FaultRoute::KillProcess | FaultRoute::Fatal => {}
+    }
+
+    proc.fault_kill(cause, info.trap_pc, info.trap_value);
+    drop(lock);
+
+    switch_from_terminated(info, pid)
+}
+
+/// Switch to another process after `pid` was terminated, powering off once there are none left to run
+fn switch_from_terminated(info: &TrapInfo, pid: PID) -> ! {
+    crate::process::scheduler::schedule(info);
+
+    info!("No processes left to run after {:?} was terminated", pid);
+    crate::power::shutdown()
+}
@@ -19,10 +19,24 @@ pub extern "C" fn m_trap(
     hart: usize,
     status: usize,
     frame: &'static TrapFrame,
-    _satp: usize,
+    satp: usize,
 ) -> usize {
+    // Keep the trapped pc of a user process, so it resumes where it left off if the trap switches to another process
+    if qor_riscv::trap::resume::trapped_from_user(status) {
+        let pid = qor_riscv::trap::pid_from_satp(satp);
+        if let Some(proc) = crate::process::processes().spin_lock().get_mut(&pid) {
+            proc.record_trap(epc);
+        }
+    }
+
+    qor_riscv::trap::set_in_machine_mode(true);
+
     let trap_info = TrapInfo::from_raw(epc, tval, cause, hart, status, frame);
-    crate::trap::handle_trap(&trap_info)
+    let resume_pc = crate::trap::handle_trap(&trap_info);
+
+    // Handlers which switch to another process do not return here, but user mode never runs kernel code
+    qor_riscv::trap::set_in_machine_mode(qor_riscv::trap::resume::trapped_from_machine(status));
+    resume_pc
 }
 
 /// Initialize the trap frame
@@ -37,7 +51,7 @@ pub fn initialize_trap_frame() {
         satp: 0,
         trap_stack: unsafe { stack.as_mut_ptr().add(2) },
         trap_stack_size: 2,
-        hart_id: qor_core::structures::id::HartID(0)
+        hart_id: qor_riscv::trap::current_hart_id(),
     };
 
     let frame = crate::memory::bump::PAGE_BUMP_ALLOCATOR
@@ -43,6 +43,8 @@ pub struct TrapInfo {
     pub hart: usize,
     pub status: usize,
     pub frame: &'static qor_riscv::trap::frame::TrapFrame,
+    /// How the trap is dealt with if it is a fault
+    pub fault: Option<qor_riscv::trap::fault::FaultRoute>,
 }
 
 impl TrapCause {
@@ -104,6 +106,13 @@ impl TrapInfo {
             hart,
             status,
             frame,
+            fault: qor_riscv::trap::fault::route_fault(cause, status),
         }
     }
+
+    /// Returns true if the trap was taken from a user process, rather than from the kernel itself
+    #[must_use]
+    pub const fn from_user(&self) -> bool {
+        qor_riscv::trap::resume::trapped_from_user(self.status)
+    }
 }
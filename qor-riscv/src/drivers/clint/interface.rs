@@ -7,6 +7,15 @@ use qor_core::{
     },
 };
 
+/// Frequency of the `mtime` counter, as documented for the QEMU `virt` machine
+pub const CLINT_TIMEBASE: Hertz = Hertz(10_000_000);
+
+/// Get the `mtime` value `delay` after `now`, saturating rather than wrapping around to a deadline in the past
+#[must_use]
+pub fn deadline_after(now: u64, delay: Microseconds) -> u64 {
+    now.saturating_add(delay.to_ticks(CLINT_TIMEBASE))
+}
+
 pub struct HardwareTimer {
     mmio: MMIOInterface,
     step_size: atomic::Atomic<u64>,
@@ -36,7 +45,19 @@ impl HardwareTimer {
     /// Set the frequency for the timer. Note that this impacts the frequency of the timer on every HART.
     pub fn set_frequency(&self, frequency: Hertz) {
         self.step_size
-            .store(1_000_000 / frequency.0, atomic::Ordering::Release);
+            .store(frequency.period().0, atomic::Ordering::Release);
+    }
+
+    /// Set the scheduling quantum, the time between timer interrupts. Note that this impacts the timer on every HART,
+    /// and takes effect when the timer is next re-armed.
+    pub fn set_quantum(&self, quantum: Microseconds) {
+        self.step_size.store(quantum.0, atomic::Ordering::Release);
+    }
+
+    /// Get the scheduling quantum, the time between timer interrupts.
+    #[must_use]
+    pub fn quantum(&self) -> Microseconds {
+        Microseconds(self.step_size.load(atomic::Ordering::Acquire))
     }
 
     /// Start the timer for a given HART
@@ -44,6 +65,23 @@ impl HardwareTimer {
         self.set_time(hart_id, Microseconds(0))
             .expect("Unable to start CLINT timer");
     }
+
+    /// Read the `mtime` register, the number of ticks of [`CLINT_TIMEBASE`] since the timer was last reset
+    #[must_use]
+    pub fn current_time(&self) -> u64 {
+        // Safety: The requirements on the `mmio` value for the `HardwareTimer` ensure this is a valid base address.
+        // There is a single `mtime` register shared by every HART, so it is always read at the first HART's offset.
+        unsafe { super::raw::read_machine_time_register(&self.mmio, HartID(0)) }
+    }
+
+    /// Fire the timer interrupt on the given HART once `mtime` reaches `absolute_time`, by writing its `mtimecmp`
+    /// register
+    pub fn set_deadline(&self, hart_id: HartID, absolute_time: u64) {
+        // Safety: The requirements on the `mmio` value for the `HardwareTimer` ensure this is a valid base address.
+        unsafe {
+            super::raw::set_machine_time_compare_register(&self.mmio, absolute_time, hart_id);
+        }
+    }
 }
 
 impl HardwareTimerDriver for HardwareTimer {
@@ -58,33 +96,61 @@ impl HardwareTimerDriver for HardwareTimer {
     }
 
     fn set_time(&self, id: HartID, time: Microseconds) -> Result<(), Self::HardwareTimerError> {
-        let current_time = self.time(id)?;
+        // Computed in ticks, as rounding the current time down to whole microseconds would bring the deadline forward
+        self.set_deadline(id, deadline_after(self.current_time(), time));
+
+        Ok(())
+    }
 
+    fn time(&self, _id: HartID) -> Result<Microseconds, Self::HardwareTimerError> {
+        Ok(Microseconds::from_ticks(self.current_time(), CLINT_TIMEBASE))
+    }
+
+    fn reset(&self, _id: HartID) -> Result<(), Self::HardwareTimerError> {
         // Safety: The requirements on the `mmio` value for the `HardwareTimer` ensure this is a valid base address.
+        // There is a single `mtime` register shared by every HART.
         unsafe {
-            super::raw::set_machine_time_compare_register(
-                &self.mmio,
-                10 * (current_time.0 + time.0),
-                id,
-            );
+            super::raw::set_machine_time_register(&self.mmio, 0, HartID(0));
         }
 
         Ok(())
     }
+}
 
-    fn time(&self, id: HartID) -> Result<Microseconds, Self::HardwareTimerError> {
-        // Safety: The requirements on the `mmio` value for the `HardwareTimer` ensure this is a valid base address.
-        let raw_time = unsafe { super::raw::read_machine_time_register(&self.mmio, id) };
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use qor_core::structures::time::{Hertz, Microseconds};
 
-        Ok(Microseconds(raw_time / 10))
+    use super::{deadline_after, HardwareTimer, CLINT_TIMEBASE};
+
+    #[test]
+    pub fn quantum_test() {
+        // Safety: The quantum is only stored in the driver, the CLINT registers are never accessed
+        let timer = unsafe { HardwareTimer::new(0) };
+        assert_eq!(timer.quantum(), Microseconds(1_000_000));
+
+        timer.set_frequency(Hertz(4));
+        assert_eq!(timer.quantum(), Microseconds(250_000));
+
+        timer.set_quantum(Microseconds(10_000));
+        assert_eq!(timer.quantum(), Microseconds(10_000));
     }
 
-    fn reset(&self, id: HartID) -> Result<(), Self::HardwareTimerError> {
-        // Safety: The requirements on the `mmio` value for the `HardwareTimer` ensure this is a valid base address.
-        unsafe {
-            super::raw::set_machine_time_register(&self.mmio, 0, id);
-        }
+    #[test]
+    pub fn deadline_test() {
+        // Safety: The quantum is only stored in the driver, the CLINT registers are never accessed
+        let timer = unsafe { HardwareTimer::new(0) };
+        timer.set_frequency(Hertz(2));
 
-        Ok(())
+        // Twice a second is every five million ticks of the 10 MHz timebase, counted from the exact current tick
+        assert_eq!(CLINT_TIMEBASE, Hertz(10_000_000));
+        assert_eq!(deadline_after(1_234_567, timer.quantum()), 6_234_567);
+        assert_eq!(
+            deadline_after(1_234_567, timer.quantum()) - 1_234_567,
+            Hertz(2).reload_ticks(CLINT_TIMEBASE).unwrap()
+        );
+
+        assert_eq!(deadline_after(u64::MAX - 1, timer.quantum()), u64::MAX);
     }
 }
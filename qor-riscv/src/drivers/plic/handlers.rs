@@ -0,0 +1,214 @@
+use qor_core::{drivers::plic::PLICDriverInterface, structures::id::HartID};
+
+use super::InterruptSource;
+
+/// Number of entries in an [`InterruptHandlers`] table, one for each source number up to the highest
+const HANDLER_SLOTS: usize = InterruptSource::Source53 as usize + 1;
+
+/// Function run when an interrupt from the source it is registered for is claimed
+pub type InterruptHandler = fn(InterruptSource);
+
+/// Errors which can occur while dispatching an interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchError<E> {
+    /// The PLIC driver failed to claim or complete the interrupt
+    Driver(E),
+    /// No handler is registered for the claimed source, the interrupt was still completed
+    Unhandled(InterruptSource),
+}
+
+/// Table mapping each interrupt source to the handler run when it is claimed.
+///
+/// The table is built before it is shared, so the trap handler can look up handlers without taking any locks.
+#[allow(clippy::module_name_repetitions)]
+pub struct InterruptHandlers {
+    handlers: [Option<InterruptHandler>; HANDLER_SLOTS],
+}
+
+impl InterruptHandlers {
+    /// Construct a table with no handlers registered
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            handlers: [None; HANDLER_SLOTS],
+        }
+    }
+
+    /// Register `handler` to be run for interrupts from `source`, replacing any handler already registered for it
+    #[must_use]
+    pub const fn with_handler(
+        mut self,
+        source: InterruptSource,
+        handler: InterruptHandler,
+    ) -> Self {
+        self.handlers[source as usize] = Some(handler);
+        self
+    }
+
+    /// Get the handler registered for `source`, if there is one
+    #[must_use]
+    pub const fn handler(&self, source: InterruptSource) -> Option<InterruptHandler> {
+        self.handlers[source as usize]
+    }
+
+    /// Claim the interrupt pending for `hart_id`, run the handler registered for its source, then complete it.
+    /// Returns the source which was handled, or `None` if there was no interrupt to claim.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interrupt could not be claimed or completed, or if no handler is registered for its
+    /// source.
+    pub fn dispatch<P: PLICDriverInterface<InterruptSource = InterruptSource>>(
+        &self,
+        plic: &P,
+        hart_id: HartID,
+    ) -> Result<Option<InterruptSource>, DispatchError<P::PLICDriverError>> {
+        let Some(source) = plic
+            .poll_interrupt(hart_id)
+            .map_err(DispatchError::Driver)?
+        else {
+            return Ok(None);
+        };
+
+        let handler = self.handler(source);
+        if let Some(handler) = handler {
+            handler(source);
+        }
+
+        // Completed even without a handler, so the source is not left unable to raise further interrupts
+        plic.complete_interrupt(hart_id, source)
+            .map_err(DispatchError::Driver)?;
+
+        handler
+            .map(|_| Some(source))
+            .ok_or(DispatchError::Unhandled(source))
+    }
+}
+
+impl Default for InterruptHandlers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::{sync::Mutex, vec::Vec};
+
+    use qor_core::{drivers::plic::PLICDriverInterface, structures::id::HartID};
+
+    use super::{DispatchError, InterruptHandlers};
+    use crate::drivers::plic::{InterruptPriority, InterruptSource};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Event {
+        Claim(Option<InterruptSource>),
+        Handle(InterruptSource),
+        Complete(InterruptSource),
+    }
+
+    /// Every claim, handler call and completion, in the order they happen
+    static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+    /// PLIC which hands out a fixed list of pending interrupts, recording each claim and completion
+    struct FakePLIC {
+        pending: Mutex<Vec<InterruptSource>>,
+    }
+
+    impl PLICDriverInterface for FakePLIC {
+        type PLICDriverError = ();
+        type InterruptSource = InterruptSource;
+        type Priority = InterruptPriority;
+
+        fn is_initialized(&self) -> bool {
+            true
+        }
+
+        fn initialize(&self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn enable_interrupt_source(&self, _: HartID, _: InterruptSource) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn disable_interrupt_source(&self, _: HartID, _: InterruptSource) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn set_interrupt_priority(
+            &self,
+            _: InterruptSource,
+            _: InterruptPriority,
+        ) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn set_hart_threshold(&self, _: HartID, _: InterruptPriority) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn poll_interrupt(&self, _: HartID) -> Result<Option<InterruptSource>, ()> {
+            let source = self.pending.lock().unwrap().pop();
+            EVENTS.lock().unwrap().push(Event::Claim(source));
+            Ok(source)
+        }
+
+        fn complete_interrupt(&self, _: HartID, source: InterruptSource) -> Result<(), ()> {
+            EVENTS.lock().unwrap().push(Event::Complete(source));
+            Ok(())
+        }
+    }
+
+    fn record(source: InterruptSource) {
+        EVENTS.lock().unwrap().push(Event::Handle(source));
+    }
+
+    #[test]
+    pub fn dispatch_claims_handles_completes_test() {
+        const HANDLERS: InterruptHandlers = InterruptHandlers::new()
+            .with_handler(InterruptSource::Source1, record)
+            .with_handler(InterruptSource::Source10, record);
+
+        // Popped from the end, so Source10 is claimed first
+        let plic = FakePLIC {
+            pending: Mutex::new(std::vec![
+                InterruptSource::Source3,
+                InterruptSource::Source1,
+                InterruptSource::Source10,
+            ]),
+        };
+        let hart = HartID::from(0);
+
+        assert_eq!(
+            HANDLERS.dispatch(&plic, hart),
+            Ok(Some(InterruptSource::Source10))
+        );
+        assert_eq!(
+            HANDLERS.dispatch(&plic, hart),
+            Ok(Some(InterruptSource::Source1))
+        );
+        assert_eq!(
+            HANDLERS.dispatch(&plic, hart),
+            Err(DispatchError::Unhandled(InterruptSource::Source3))
+        );
+        assert_eq!(HANDLERS.dispatch(&plic, hart), Ok(None));
+
+        assert_eq!(
+            *EVENTS.lock().unwrap(),
+            [
+                Event::Claim(Some(InterruptSource::Source10)),
+                Event::Handle(InterruptSource::Source10),
+                Event::Complete(InterruptSource::Source10),
+                Event::Claim(Some(InterruptSource::Source1)),
+                Event::Handle(InterruptSource::Source1),
+                Event::Complete(InterruptSource::Source1),
+                // An interrupt without a handler is still completed
+                Event::Claim(Some(InterruptSource::Source3)),
+                Event::Complete(InterruptSource::Source3),
+                Event::Claim(None),
+            ]
+        );
+    }
+}
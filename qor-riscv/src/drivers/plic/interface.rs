@@ -19,6 +19,48 @@ impl PLICDriver {
             mmio: MMIOInterface::new(base_address),
         }
     }
+
+    /// Claim the highest priority interrupt pending for `hart_id`, returning its source, or `None` if no interrupt is
+    /// pending. The source raises no further interrupts until it is completed.
+    #[must_use]
+    pub fn claim(&self, hart_id: HartID) -> Option<InterruptSource> {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        InterruptSource::from_num(unsafe { raw::read_claim_register(&self.mmio, hart_id) })
+    }
+
+    /// Allow `source` to interrupt `hart_id`, once its priority is above the hart's threshold.
+    pub fn enable_source(&self, hart_id: HartID, source: InterruptSource) {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        let (word, mask) =
+            unsafe { raw::atomic_interrupt_enable_register(&self.mmio, hart_id, source) };
+        word.fetch_or(mask, core::sync::atomic::Ordering::AcqRel);
+    }
+
+    /// Stop `source` from interrupting `hart_id`.
+    pub fn disable_source(&self, hart_id: HartID, source: InterruptSource) {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        let (word, mask) =
+            unsafe { raw::atomic_interrupt_enable_register(&self.mmio, hart_id, source) };
+        word.fetch_and(!mask, core::sync::atomic::Ordering::AcqRel);
+    }
+
+    /// Set the priority of `source`, a hart is only interrupted by sources whose priority is above its threshold.
+    pub fn set_priority(&self, source: InterruptSource, priority: InterruptPriority) {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        unsafe { raw::write_source_priority_register(&self.mmio, source, priority) };
+    }
+
+    /// Set the threshold of `hart_id`, it is only interrupted by sources whose priority is above it.
+    pub fn set_threshold(&self, hart_id: HartID, threshold: InterruptPriority) {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        unsafe { raw::write_threshold_register(&self.mmio, hart_id, threshold) };
+    }
+
+    /// Complete the interrupt from `source` claimed by `hart_id`, allowing the source to interrupt again.
+    pub fn complete(&self, hart_id: HartID, source: InterruptSource) {
+        // Safety: The only way to construct a `PLICDriver` is with the base address of a PLIC
+        unsafe { raw::write_complete_register(&self.mmio, hart_id, source) };
+    }
 }
 
 impl PLICDriverInterface for PLICDriver {
@@ -39,18 +81,7 @@ impl PLICDriverInterface for PLICDriver {
         hart_id: HartID,
         source: Self::InterruptSource,
     ) -> Result<(), Self::PLICDriverError> {
-        let source_index = source as u32;
-
-        if source_index < 32 {
-            unsafe { raw::atomic_interrupt_enable_low_register(&self.mmio, hart_id) }
-                .fetch_or(1 << source_index, core::sync::atomic::Ordering::AcqRel);
-        } else {
-            unsafe { raw::atomic_interrupt_enable_high_register(&self.mmio, hart_id) }.fetch_or(
-                1 << (source_index - 32),
-                core::sync::atomic::Ordering::AcqRel,
-            );
-        }
-
+        self.enable_source(hart_id, source);
         Ok(())
     }
 
@@ -59,18 +90,7 @@ impl PLICDriverInterface for PLICDriver {
         hart_id: HartID,
         source: Self::InterruptSource,
     ) -> Result<(), Self::PLICDriverError> {
-        let source_index = source as u32;
-
-        if source_index < 32 {
-            unsafe { raw::atomic_interrupt_enable_low_register(&self.mmio, hart_id) }
-                .fetch_and(!(1 << source_index), core::sync::atomic::Ordering::AcqRel);
-        } else {
-            unsafe { raw::atomic_interrupt_enable_high_register(&self.mmio, hart_id) }.fetch_and(
-                !(1 << (source_index - 32)),
-                core::sync::atomic::Ordering::AcqRel,
-            );
-        }
-
+        self.disable_source(hart_id, source);
         Ok(())
     }
 
@@ -79,7 +99,7 @@ impl PLICDriverInterface for PLICDriver {
         source: Self::InterruptSource,
         priority: Self::Priority,
     ) -> Result<(), Self::PLICDriverError> {
-        unsafe { raw::write_source_priority_register(&self.mmio, source, priority) };
+        self.set_priority(source, priority);
         Ok(())
     }
 
@@ -88,7 +108,7 @@ impl PLICDriverInterface for PLICDriver {
         hart_id: HartID,
         threshold: Self::Priority,
     ) -> Result<(), Self::PLICDriverError> {
-        unsafe { raw::write_threshold_register(&self.mmio, hart_id, threshold) };
+        self.set_threshold(hart_id, threshold);
         Ok(())
     }
 
@@ -96,9 +116,7 @@ impl PLICDriverInterface for PLICDriver {
         &self,
         hart_id: HartID,
     ) -> Result<Option<Self::InterruptSource>, Self::PLICDriverError> {
-        Ok(InterruptSource::from_num(unsafe {
-            raw::read_claim_register(&self.mmio, hart_id)
-        }))
+        Ok(self.claim(hart_id))
     }
 
     fn complete_interrupt(
@@ -106,7 +124,7 @@ impl PLICDriverInterface for PLICDriver {
         hart_id: HartID,
         source: Self::InterruptSource,
     ) -> Result<(), Self::PLICDriverError> {
-        unsafe { raw::write_complete_register(&self.mmio, hart_id, source) };
+        self.complete(hart_id, source);
         Ok(())
     }
 }
@@ -1,3 +1,6 @@
+pub mod handlers;
+pub use handlers::*;
+
 pub mod interface;
 pub use interface::*;
 
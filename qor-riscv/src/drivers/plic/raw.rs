@@ -6,8 +6,9 @@ use qor_core::interfaces::mmio::MMIOInterface;
 use qor_core::structures::id::HartID;
 
 pub const SOURCE_PRIORITY_REGISTER_BASE: usize = 0x0;
-pub const INTERRUPT_ENABLE_LOW_REGISTER_BASE: usize = 0x2000;
-pub const INTERRUPT_ENABLE_HIGH_REGISTER_BASE: usize = 0x2001;
+pub const INTERRUPT_ENABLE_REGISTER_BASE: usize = 0x2000;
+/// Distance between the interrupt enable bits of consecutive harts
+pub const INTERRUPT_ENABLE_HART_STRIDE: usize = 0x80;
 pub const THRESHOLD_REGISTER_BASE: usize = 0x20_0000;
 pub const CLAIM_REGISTER_BASE: usize = 0x20_0004;
 pub const COMPLETE_REGISTER_BASE: usize = 0x20_0004;
@@ -42,20 +43,6 @@ macro_rules! write_impl_source_index {
     };
 }
 
-macro_rules! atomic_impl_hart_index {
-    ($name: literal, $extra_docs: literal, $extra_safety: literal, $size: ty) => {
-        read_impl_hart_index!($name, $extra_docs, $extra_safety, $size, core::mem::size_of::<$size>());
-    };
-    ($name: literal, $extra_docs: literal, $extra_safety: literal, $size: ty, $offset: expr) => {
-        paste! {
-            #[doc="Get atomic access to the " $name " register for the given interrupt source." $extra_docs "\n \n # Safety\n \n The `mmio` interface must point to a valid base address of a memory mapped FU540-C000 PLIC  device." $extra_safety]
-            pub unsafe fn [<atomic_ $name:snake:lower _register>](mmio: &MMIOInterface, offset: HartID) -> &atomic::Atomic<$size> {
-                mmio.atomic_access([<$name:snake:upper _REGISTER_BASE>] + offset.0 as usize * $offset)
-            }
-        }
-    };
-}
-
 macro_rules! read_impl_hart_index {
     ($name: literal, $extra_docs: literal, $extra_safety: literal, $size: ty) => {
         read_impl_hart_index!($name, $extra_docs, $extra_safety, $size, core::mem::size_of::<$size>());
@@ -99,10 +86,58 @@ macro_rules! read_write_impl_hart_index {
 }
 
 read_write_impl_source_index!("source_priority", "", "", InterruptPriority, 4);
-read_write_impl_hart_index!("interrupt_enable_low", "", "", u32, 0x80);
-read_write_impl_hart_index!("interrupt_enable_high", "", "", u32, 0x80);
-atomic_impl_hart_index!("interrupt_enable_low", "", "", u32, 0x80);
-atomic_impl_hart_index!("interrupt_enable_high", "", "", u32, 0x80);
 read_write_impl_hart_index!("threshold", "", "", InterruptPriority, 0x1000);
 read_impl_hart_index!("claim", "", "", u32, 0x1000);
 write_impl_hart_index!("complete", "", "", InterruptSource, 0x1000);
+
+/// Get the offset of the word holding the interrupt enable bit of `source` for the given HART, along with the mask of
+/// that bit within the word. Each HART's enable bits are a run of 32 bit words, one bit per source.
+#[must_use]
+pub const fn interrupt_enable_location(hart: HartID, source: InterruptSource) -> (usize, u32) {
+    let source = source as usize;
+
+    (
+        INTERRUPT_ENABLE_REGISTER_BASE + hart.0 * INTERRUPT_ENABLE_HART_STRIDE + source / 32 * 4,
+        1 << (source % 32),
+    )
+}
+
+/// Get atomic access to the interrupt enable word holding the bit of `source` for the given HART, along with the mask
+/// of that bit within the word.
+///
+/// # Safety
+///
+/// The `mmio` interface must point to a valid base address of a memory mapped FU540-C000 PLIC device.
+pub unsafe fn atomic_interrupt_enable_register(
+    mmio: &MMIOInterface,
+    hart: HartID,
+    source: InterruptSource,
+) -> (&atomic::Atomic<u32>, u32) {
+    let (offset, mask) = interrupt_enable_location(hart, source);
+    (mmio.atomic_access(offset), mask)
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use qor_core::structures::id::HartID;
+
+    use super::{interrupt_enable_location, InterruptSource};
+
+    #[test]
+    pub fn interrupt_enable_location_test() {
+        let location = |hart, source| interrupt_enable_location(HartID(hart), source);
+
+        assert_eq!(location(0, InterruptSource::Source1), (0x2000, 1 << 1));
+        assert_eq!(location(0, InterruptSource::Source10), (0x2000, 1 << 10));
+        assert_eq!(location(0, InterruptSource::Source31), (0x2000, 1 << 31));
+
+        // Sources from 32 are in the next word of the same HART
+        assert_eq!(location(0, InterruptSource::Source32), (0x2004, 1 << 0));
+        assert_eq!(location(0, InterruptSource::Source53), (0x2004, 1 << 21));
+
+        // Each HART's words start a full stride after the previous HART's
+        assert_eq!(location(1, InterruptSource::Source1), (0x2080, 1 << 1));
+        assert_eq!(location(2, InterruptSource::Source33), (0x2104, 1 << 1));
+    }
+}
@@ -1,17 +1,37 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
 use qor_core::{
     drivers::uart::UARTDriverInterface,
     interfaces::{
         bytes::{GenericByteInterface, GenericByteReadInterface, GenericByteWriteInterface},
         mmio::MMIOInterface,
     },
+    sync::{IrqSpinLock, Mutex},
+    utils::ring_buffer::ByteRingBuffer,
 };
 
 use super::raw;
+use crate::trap::interrupts::MachineInterrupts;
+
+/// Number of received bytes held for readers, bytes received while it is full are dropped
+pub const RECEIVE_BUFFER_SIZE: usize = 256;
 
 /// UART Driver for the RISCV Platform
 pub struct UARTDriver {
-    mmio: MMIOInterface,
+    /// Base address of the device registers, only changed before the driver is initialized
+    base_address: core::sync::atomic::AtomicUsize,
     is_initialized: core::sync::atomic::AtomicBool,
+    received: ByteRingBuffer<RECEIVE_BUFFER_SIZE>,
+    /// Serializes readers taking bytes from `received`, never taken by the interrupt handler
+    reader: Mutex<()>,
+    /// Waker of the task waiting for input, taken by the interrupt handler
+    reader_waker: IrqSpinLock<Option<Waker>, MachineInterrupts>,
+    /// Whether received bytes are written back out as they arrive
+    echo: core::sync::atomic::AtomicBool,
 }
 
 /// Errors which can be returned by the UART Driver API
@@ -29,11 +49,116 @@ impl UARTDriver {
     #[must_use]
     pub const unsafe fn new(base_address: usize) -> Self {
         Self {
-            mmio: MMIOInterface::new(base_address),
+            base_address: core::sync::atomic::AtomicUsize::new(base_address),
             is_initialized: core::sync::atomic::AtomicBool::new(false),
+            received: ByteRingBuffer::new(),
+            reader: Mutex::new(()),
+            reader_waker: IrqSpinLock::new(None, MachineInterrupts),
+            echo: core::sync::atomic::AtomicBool::new(true),
         }
     }
 
+    /// Move the driver to the device at `base_address`, such as one found in the device tree.
+    ///
+    /// # Safety
+    ///
+    /// The `base_address` given must be a valid base address of a memory mapped 16550 UART chipset.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the driver has already been initialized.
+    pub unsafe fn relocate(&self, base_address: usize) {
+        assert!(
+            !self
+                .is_initialized
+                .load(core::sync::atomic::Ordering::Acquire),
+            "Unable to relocate an initialized UART driver"
+        );
+
+        self.base_address
+            .store(base_address, core::sync::atomic::Ordering::Release);
+    }
+
+    /// Get the base address of the device registers.
+    #[must_use]
+    pub fn base_address(&self) -> usize {
+        self.base_address
+            .load(core::sync::atomic::Ordering::Acquire)
+    }
+
+    /// Register interface of the device
+    fn mmio(&self) -> MMIOInterface {
+        MMIOInterface::new(self.base_address())
+    }
+
+    /// Set whether received bytes are echoed back to the console as they arrive, which they are by default.
+    pub fn set_echo(&self, echo: bool) {
+        self.echo
+            .store(echo, core::sync::atomic::Ordering::Release);
+    }
+
+    /// Function which is called every time the UART's receive interrupt is fired. Moves every byte waiting in the
+    /// receive FIFO into the driver's buffer, echoing it if enabled, and wakes the task waiting for input, if there
+    /// is one.
+    pub fn handle_interrupt(&self) {
+        let echo = self.echo.load(core::sync::atomic::Ordering::Acquire);
+
+        // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
+        while unsafe { raw::read_line_status_register(&self.mmio()) } & 1 != 0 {
+            // Safety: As above, and the interrupt is only enabled once initialization has cleared the DLAB bit.
+            let byte = unsafe { raw::read_receiver_buffer_register(&self.mmio()) };
+            self.received.push(byte);
+
+            if echo {
+                // The interrupt is only enabled once the driver is initialized, so this cannot fail
+                let _ = self.inner_write_byte(byte);
+            }
+        }
+
+        // Registering a waker masks this interrupt, so the handler never spins on a lock held by the task it
+        // interrupted
+        let waker = self.reader_waker.lock_from_interrupt().take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Take the oldest byte received by the interrupt handler, if there is one.
+    pub fn try_read_byte(&self) -> Option<u8> {
+        let _reader = self.reader.spin_lock();
+        self.received.pop()
+    }
+
+    /// Get the number of received bytes dropped because the buffer was full
+    #[must_use]
+    pub fn dropped_bytes(&self) -> usize {
+        self.received.dropped()
+    }
+
+    /// Wait for the next byte received by the interrupt handler. Only one task may wait for input at a time, the
+    /// waker of a second waiting task replaces that of the first.
+    pub const fn receive(&self) -> ReceiveFuture<'_> {
+        ReceiveFuture { driver: self }
+    }
+
+    /// Wait for a line of input, storing it in `buffer` up to and including the carriage return or newline which
+    /// ends it, and returning its length. If `buffer` fills first, the bytes received so far are returned.
+    pub async fn read_line(&self, buffer: &mut [u8]) -> usize {
+        let mut length = 0;
+
+        for slot in buffer.iter_mut() {
+            let byte = self.receive().await;
+            *slot = byte;
+            length += 1;
+
+            if byte == b'\r' || byte == b'\n' {
+                break;
+            }
+        }
+
+        length
+    }
+
     /// Initialize the UART Connection
     ///
     /// # Errors
@@ -45,7 +170,7 @@ impl UARTDriver {
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
         unsafe {
-            raw::set_line_control_register(&self.mmio, line_control_value);
+            raw::set_line_control_register(&self.mmio(), line_control_value);
         }
 
         // A value for the FIFO Control Register which enables the fifo
@@ -53,7 +178,7 @@ impl UARTDriver {
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
         unsafe {
-            raw::set_fifo_control_register(&self.mmio, fifo_control_value);
+            raw::set_fifo_control_register(&self.mmio(), fifo_control_value);
         }
 
         // A value to enable the buffer interrupts
@@ -61,7 +186,7 @@ impl UARTDriver {
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address. Additionally, the Line Control Register currently has the DLAB bit cleared.
         unsafe {
-            raw::set_interrupt_enable_register(&self.mmio, interrupt_enable_value);
+            raw::set_interrupt_enable_register(&self.mmio(), interrupt_enable_value);
         }
 
         // Compute the clock divisor
@@ -74,17 +199,17 @@ impl UARTDriver {
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
         unsafe {
-            raw::set_line_control_register(&self.mmio, line_control_value_dlab);
+            raw::set_line_control_register(&self.mmio(), line_control_value_dlab);
         }
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address. Additionally, the Line Control Register currently has the DLAB bit set.
-        unsafe { raw::set_divisor_latch_ls_register(&self.mmio, divisor_low) };
-        unsafe { raw::set_divisor_latch_ms_register(&self.mmio, divisor_high) };
+        unsafe { raw::set_divisor_latch_ls_register(&self.mmio(), divisor_low) };
+        unsafe { raw::set_divisor_latch_ms_register(&self.mmio(), divisor_high) };
 
         // Now that we have set the divisor, we can clear the dlab bit again
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
         unsafe {
-            raw::set_line_control_register(&self.mmio, line_control_value);
+            raw::set_line_control_register(&self.mmio(), line_control_value);
         }
 
         self.is_initialized
@@ -111,14 +236,14 @@ impl UARTDriver {
         self.ensure_initialized()?;
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address.
-        let is_pending_byte = unsafe { raw::read_line_control_register(&self.mmio) } & 1;
+        let is_pending_byte = unsafe { raw::read_line_control_register(&self.mmio()) } & 1;
 
         if is_pending_byte == 0 {
             Ok(None)
         } else {
             // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address. The initialization function leaves the DLAB bit cleared, and we have ensured initialization, so we are ready to read data.
             Ok(Some(unsafe {
-                raw::read_receiver_buffer_register(&self.mmio)
+                raw::read_receiver_buffer_register(&self.mmio())
             }))
         }
     }
@@ -128,13 +253,35 @@ impl UARTDriver {
 
         // Safety: The requirements on the `mmio` value for the `UARTDriver` ensure this is a valid base address. The initialization function leaves the DLAB bit cleared, and we have ensured initialization, so we are ready to send data.
         unsafe {
-            raw::set_transmitter_holding_register(&self.mmio, b);
+            raw::set_transmitter_holding_register(&self.mmio(), b);
         }
 
         Ok(())
     }
 }
 
+/// Future which resolves to the next byte received by a [`UARTDriver`]
+pub struct ReceiveFuture<'a> {
+    driver: &'a UARTDriver,
+}
+
+impl Future for ReceiveFuture<'_> {
+    type Output = u8;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(byte) = self.driver.try_read_byte() {
+            return Poll::Ready(byte);
+        }
+
+        *self.driver.reader_waker.lock() = Some(cx.waker().clone());
+
+        // A byte received before the waker was in place would not have woken the task
+        self.driver
+            .try_read_byte()
+            .map_or(Poll::Pending, Poll::Ready)
+    }
+}
+
 impl UARTDriverInterface for UARTDriver {
     type UARTError = UARTError;
 
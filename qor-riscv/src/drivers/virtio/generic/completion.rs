@@ -0,0 +1,125 @@
+use super::structures::{UsedRing, VIRTIO_RING_SIZE_USIZE};
+
+/// Requests submitted to a virtqueue which the device has not yet returned through the used ring, indexed by the head
+/// descriptor of each request's chain.
+pub struct PendingRequests<T> {
+    requests: [Option<T>; VIRTIO_RING_SIZE_USIZE],
+    acknowledged: u16,
+}
+
+impl<T> PendingRequests<T> {
+    /// Construct an empty table, for a queue whose used ring has not been written to
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            requests: [const { None }; VIRTIO_RING_SIZE_USIZE],
+            acknowledged: 0,
+        }
+    }
+
+    /// Record `request` as in flight in the descriptor chain starting at `head`, returning the request it replaces if
+    /// that chain was still marked as in flight.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `head` is not a descriptor index within the ring.
+    pub const fn insert(&mut self, head: u16, request: T) -> Option<T> {
+        self.requests[head as usize].replace(request)
+    }
+
+    /// Get the used ring index up to which completions have been taken
+    #[must_use]
+    pub const fn acknowledged(&self) -> u16 {
+        self.acknowledged
+    }
+
+    /// Take every request the device has returned through `used` since the last call, passing each one to `complete`
+    /// along with the number of bytes the device wrote, in the order the device completed them. Used elements which
+    /// do not name an in flight request are skipped.
+    pub fn complete(&mut self, used: &UsedRing, mut complete: impl FnMut(T, u32)) {
+        // The device writes the ring concurrently, so every read must go to memory
+        // Safety: The pointers are derived from a reference, so they are valid and aligned
+        let used_index = unsafe { core::ptr::addr_of!(used.idx).read_volatile() };
+
+        while self.acknowledged != used_index {
+            let slot = self.acknowledged as usize % VIRTIO_RING_SIZE_USIZE;
+            // Safety: As above, `slot` is within the ring. The fields are read one at a time, as volatile reads of a
+            // whole struct may be split or merged arbitrarily
+            let (id, len) = unsafe {
+                (
+                    core::ptr::addr_of!(used.ring[slot].id).read_volatile(),
+                    core::ptr::addr_of!(used.ring[slot].len).read_volatile(),
+                )
+            };
+            self.acknowledged = self.acknowledged.wrapping_add(1);
+
+            if let Some(request) = usize::try_from(id)
+                .ok()
+                .and_then(|head| self.requests.get_mut(head))
+                .and_then(Option::take)
+            {
+                complete(request, len);
+            }
+        }
+    }
+}
+
+impl<T> Default for PendingRequests<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+
+    use super::PendingRequests;
+    use crate::drivers::virtio::generic::structures::{
+        UsedElement, UsedRing, VIRTIO_RING_SIZE_U16,
+    };
+
+    /// Return the chain starting at `head` through the next slot of the used ring, as the device would
+    fn device_completes(used: &mut UsedRing, head: u16, len: u32) {
+        used.ring[usize::from(used.idx % VIRTIO_RING_SIZE_U16)] = UsedElement {
+            id: u32::from(head),
+            len,
+        };
+        used.idx = used.idx.wrapping_add(1);
+    }
+
+    #[test]
+    pub fn complete_matches_heads_test() {
+        let mut used = UsedRing::default();
+        let mut pending = PendingRequests::new();
+        assert_eq!(pending.insert(1, "first"), None);
+        assert_eq!(pending.insert(4, "second"), None);
+        assert_eq!(pending.insert(7, "third"), None);
+
+        // Completed out of submission order
+        device_completes(&mut used, 7, 512);
+        device_completes(&mut used, 1, 1024);
+
+        let mut completed = Vec::new();
+        pending.complete(&used, |request, len| completed.push((request, len)));
+        assert_eq!(completed, [("third", 512), ("first", 1024)]);
+        assert_eq!(pending.acknowledged(), 2);
+
+        // Nothing new has been returned
+        completed.clear();
+        pending.complete(&used, |request, len| completed.push((request, len)));
+        assert!(completed.is_empty());
+
+        // A stale or unknown head is skipped, and the used index wraps
+        used.idx = u16::MAX;
+        pending = PendingRequests::new();
+        pending.complete(&used, |_, _| {});
+        pending.insert(4, "second");
+        device_completes(&mut used, 9, 0);
+        device_completes(&mut used, 4, 0);
+        pending.complete(&used, |request, len| completed.push((request, len)));
+        assert_eq!(completed, [("second", 0)]);
+        assert_eq!(pending.acknowledged(), 1);
+    }
+}
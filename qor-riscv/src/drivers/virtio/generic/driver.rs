@@ -9,6 +9,7 @@ use super::{
 /// Wrapper object for a Virt IO device
 pub struct VirtIOWrapper {
     pub mmio_layer: MMIOInterface,
+    negotiated_features: core::sync::atomic::AtomicU32,
 }
 
 impl VirtIOWrapper {
@@ -21,6 +22,7 @@ impl VirtIOWrapper {
     pub const unsafe fn new(base: usize) -> Self {
         Self {
             mmio_layer: MMIOInterface::new(base),
+            negotiated_features: core::sync::atomic::AtomicU32::new(0),
         }
     }
 
@@ -120,6 +122,8 @@ impl VirtIOWrapper {
 
         // Inform the device of the features we accept
         unsafe { raw::set_guest_features(&self.mmio_layer, negotiated_features) };
+        self.negotiated_features
+            .store(negotiated_features, core::sync::atomic::Ordering::Release);
 
         // Set the features ok bit
         self.set_status_bits(bits::STATUS_BIT_FEATURES_OK)?;
@@ -136,6 +140,26 @@ impl VirtIOWrapper {
         Ok(())
     }
 
+    /// Returns true if the feature with the given bit index was accepted during setup
+    #[must_use]
+    pub fn has_feature(&self, bit: u32) -> bool {
+        self.negotiated_features
+            .load(core::sync::atomic::Ordering::Acquire)
+            & (1 << bit)
+            != 0
+    }
+
+    /// Read a value from the device specific configuration space, `offset` bytes past its start.
+    ///
+    /// # Safety
+    ///
+    /// The `offset` must lie within the configuration space of this device type, and `T` must match the width of the
+    /// field being read.
+    #[must_use]
+    pub unsafe fn read_device_config<T: Copy>(&self, offset: usize) -> T {
+        raw::read_device_config(&self.mmio_layer, offset)
+    }
+
     /// Complete device setup
     ///
     /// # Errors
@@ -146,6 +170,12 @@ impl VirtIOWrapper {
         Ok(())
     }
 
+    /// Select the queue which later queue size reads and writes apply to. Queue 0 is selected until another is.
+    pub fn select_queue(&self, index: u32) {
+        // Safety: The only safe way to construct a `VirtIOWrapper` is by providing a proper base address.
+        unsafe { raw::set_queue_sel(&self.mmio_layer, index) };
+    }
+
     /// Read the maximum queue size from the device
     ///
     /// # Errors
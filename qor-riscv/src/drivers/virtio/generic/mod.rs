@@ -1,4 +1,5 @@
 pub mod bits;
+pub mod completion;
 pub mod driver;
 pub mod raw;
 pub mod structures;
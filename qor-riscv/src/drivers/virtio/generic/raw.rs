@@ -95,7 +95,18 @@ read_impl!("queue_align", "", "");
 read_write_impl!("queue_pfn", "", "");
 read_write_impl!("queue_notify", "", "");
 read_impl!("interrupt_status", "", "");
-read_impl!("interrupt_ack", "", "");
+write_impl!("interrupt_ack", "", "");
 read_write_impl!("status", "", "");
 atomic_impl!("status", "", "");
 read_impl!("config", "", "");
+
+/// Read a value from the device specific configuration space, `offset` bytes past its start.
+///
+/// # Safety
+///
+/// The `mmio` interface must point to a valid base address of a memory mapped Virt IO device, and `offset` must lie
+/// within the configuration space of that device type.
+#[must_use]
+pub unsafe fn read_device_config<T: Copy>(mmio: &MMIOInterface, offset: usize) -> T {
+    mmio.read_offset(CONFIG_OFFSET + offset)
+}
@@ -10,4 +10,5 @@ extern crate qor_core;
 
 pub mod drivers;
 pub mod memory;
+pub mod power;
 pub mod trap;
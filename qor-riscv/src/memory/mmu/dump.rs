@@ -0,0 +1,145 @@
+use super::{
+    addresses::{PhysicalAddress, VirtualAddress},
+    entry::{EntryPermissionFlags, GlobalUserFlags},
+};
+
+/// A run of pages which are contiguous in both virtual and physical memory, and are all mapped with the same flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappedRange {
+    pub virt_addr: VirtualAddress,
+    pub phys_addr: PhysicalAddress,
+    pub length: u64,
+    pub gu_flags: GlobalUserFlags,
+    pub perm_flags: EntryPermissionFlags,
+}
+
+impl MappedRange {
+    /// Extend this range to also cover `next` if `next` picks up exactly where this range ends, in both virtual and
+    /// physical memory, with the same flags. Returns false, leaving this range unchanged, otherwise.
+    pub fn try_extend(&mut self, next: &Self) -> bool {
+        let continues = self.virt_addr.0.checked_add(self.length) == Some(next.virt_addr.0)
+            && self.phys_addr.0.checked_add(self.length) == Some(next.phys_addr.0)
+            && self.gu_flags == next.gu_flags
+            && self.perm_flags == next.perm_flags;
+
+        if continues {
+            self.length += next.length;
+        }
+
+        continues
+    }
+}
+
+impl core::fmt::Display for MappedRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:#x}..{:#x} -> {:#x}..{:#x} [{}{}]",
+            self.virt_addr.0,
+            self.virt_addr.0.wrapping_add(self.length),
+            self.phys_addr.0,
+            self.phys_addr.0.wrapping_add(self.length),
+            self.gu_flags,
+            self.perm_flags
+        )
+    }
+}
+
+/// Collapses a sequence of mappings, given in increasing virtual address order, into the fewest [`MappedRange`]s
+/// which cover them.
+#[derive(Debug, Default)]
+pub struct RangeCollapser {
+    current: Option<MappedRange>,
+}
+
+impl RangeCollapser {
+    /// Construct a [`RangeCollapser`] which has not yet seen any mappings
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { current: None }
+    }
+
+    /// Add the next mapping, returning the previous range if `range` does not continue it, as that range is then
+    /// complete
+    pub fn push(&mut self, range: MappedRange) -> Option<MappedRange> {
+        if let Some(current) = &mut self.current {
+            if current.try_extend(&range) {
+                return None;
+            }
+        }
+
+        self.current.replace(range)
+    }
+
+    /// Take the final range, once every mapping has been pushed
+    #[must_use]
+    pub const fn finish(self) -> Option<MappedRange> {
+        self.current
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+
+    use alloc::vec::Vec;
+
+    use super::{MappedRange, RangeCollapser};
+    use crate::memory::mmu::{
+        addresses::{PhysicalAddress, VirtualAddress},
+        entry::{EntryPermissionFlags, GlobalUserFlags},
+    };
+
+    const fn page(virt: u64, phys: u64, perm_flags: EntryPermissionFlags) -> MappedRange {
+        MappedRange {
+            virt_addr: VirtualAddress(virt),
+            phys_addr: PhysicalAddress(phys),
+            length: 0x1000,
+            gu_flags: GlobalUserFlags::User,
+            perm_flags,
+        }
+    }
+
+    fn collapse(ranges: &[MappedRange]) -> Vec<MappedRange> {
+        let mut collapser = RangeCollapser::new();
+        let mut result: Vec<_> = ranges.iter().filter_map(|range| collapser.push(*range)).collect();
+        result.extend(collapser.finish());
+        result
+    }
+
+    #[test]
+    pub fn collapse_contiguous_test() {
+        let collapsed = collapse(&[
+            page(0x1000, 0x8000_1000, EntryPermissionFlags::ReadWrite),
+            page(0x2000, 0x8000_2000, EntryPermissionFlags::ReadWrite),
+            page(0x3000, 0x8000_3000, EntryPermissionFlags::ReadWrite),
+        ]);
+
+        assert_eq!(
+            collapsed,
+            [MappedRange {
+                length: 0x3000,
+                ..page(0x1000, 0x8000_1000, EntryPermissionFlags::ReadWrite)
+            }]
+        );
+        assert_eq!(
+            alloc::format!("{}", collapsed[0]),
+            "0x1000..0x4000 -> 0x80001000..0x80004000 [-urw-]"
+        );
+    }
+
+    #[test]
+    pub fn collapse_breaks_test() {
+        // A virtual gap, a physical gap, and a change of permissions each start a new range
+        let ranges = [
+            page(0x1000, 0x8000_1000, EntryPermissionFlags::ReadWrite),
+            page(0x3000, 0x8000_2000, EntryPermissionFlags::ReadWrite),
+            page(0x4000, 0x8000_4000, EntryPermissionFlags::ReadWrite),
+            page(0x5000, 0x8000_5000, EntryPermissionFlags::ReadOnly),
+        ];
+
+        assert_eq!(collapse(&ranges), ranges);
+        assert_eq!(collapse(&[]), []);
+    }
+}
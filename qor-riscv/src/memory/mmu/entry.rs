@@ -232,6 +232,14 @@ impl PageTableEntry {
     }
 }
 
+impl EntryPermissionFlags {
+    /// Returns true if a mapping with these permissions permits every access in `want`
+    #[must_use]
+    pub const fn allows(self, want: Self) -> bool {
+        (self as u64) & (want as u64) == want as u64
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct FlagDebug(PageTableEntry);
 
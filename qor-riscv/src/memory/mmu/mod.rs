@@ -1,4 +1,5 @@
 pub mod addresses;
+pub mod dump;
 pub mod entry;
 pub mod table;
 
@@ -8,9 +9,135 @@ pub fn set_page_table(table: &'static mut table::PageTable) {
     unsafe { riscv::register::satp::set(riscv::register::satp::Mode::Sv39, 0, addr >> 12) }
 }
 
-/// Construct a SATP value
+/// Construct a SATP value for the Sv39 page table `table`, tagged with the address space identifier `asid`
 #[must_use]
 pub fn construct_satp(asid: u16, table: &table::PageTable) -> usize {
     let addr = table as *const table::PageTable as usize;
     (8 << 60) | ((asid as usize) << 44) | (addr >> 12)
 }
+
+/// Check if `table` is the page table currently installed on this hart, in which case changes to it must be followed
+/// by a TLB flush
+#[must_use]
+pub fn is_active_page_table(table: &table::PageTable) -> bool {
+    let addr = table as *const table::PageTable as usize;
+    riscv::register::satp::read().ppn() == addr >> 12
+}
+
+/// Widen each of `regions` to whole pages, then sort them and merge any which overlap or touch, so that identity
+/// mapping the result writes each page once. The merged regions are moved to the front of the slice and returned, and
+/// empty regions are dropped.
+pub fn coalesce_regions(regions: &mut [core::ops::Range<u64>]) -> &[core::ops::Range<u64>] {
+    let page_size = super::PAGE_SIZE as u64;
+    for region in regions.iter_mut() {
+        region.start &= !(page_size - 1);
+        region.end = region.end.next_multiple_of(page_size);
+    }
+
+    regions.sort_unstable_by_key(|region| region.start);
+
+    let mut count = 0;
+    for index in 0..regions.len() {
+        if regions[index].is_empty() {
+            continue;
+        }
+
+        if count > 0 && regions[index].start <= regions[count - 1].end {
+            regions[count - 1].end = regions[count - 1].end.max(regions[index].end);
+        } else {
+            regions[count] = regions[index].clone();
+            count += 1;
+        }
+    }
+
+    &regions[..count]
+}
+
+/// A request to discard cached address translations, as recorded by the test hook in place of `sfence.vma`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlbFlush {
+    /// Every translation was discarded
+    All,
+    /// Only translations of the page holding the address were discarded
+    Address(addresses::VirtualAddress),
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static RECORDED_FLUSHES: core::cell::RefCell<std::vec::Vec<TlbFlush>> =
+        const { core::cell::RefCell::new(std::vec::Vec::new()) };
+}
+
+/// Take the flushes requested on this thread since the last call, which are recorded rather than executed when
+/// built for the host
+#[cfg(feature = "std")]
+#[must_use]
+pub fn take_recorded_flushes() -> std::vec::Vec<TlbFlush> {
+    RECORDED_FLUSHES.with(core::cell::RefCell::take)
+}
+
+/// Discard every cached address translation on this hart, in every address space, so changes to the page tables
+/// take effect
+pub fn flush_tlb() {
+    #[cfg(feature = "std")]
+    RECORDED_FLUSHES.with(|flushes| flushes.borrow_mut().push(TlbFlush::All));
+
+    // Safety: `sfence.vma` only discards cached translations, which are reloaded from the page tables on demand
+    #[cfg(not(feature = "std"))]
+    unsafe {
+        riscv::asm::sfence_vma_all();
+    }
+}
+
+/// Discard the cached translations of the page holding `addr` on this hart, in every address space
+#[allow(unused_variables)]
+pub fn flush_tlb_addr(addr: addresses::VirtualAddress) {
+    #[cfg(feature = "std")]
+    RECORDED_FLUSHES.with(|flushes| flushes.borrow_mut().push(TlbFlush::Address(addr)));
+
+    // Safety: `sfence.vma` only discards cached translations, which are reloaded from the page tables on demand
+    #[cfg(all(not(feature = "std"), target_arch = "riscv64"))]
+    unsafe {
+        core::arch::asm!("sfence.vma {0}, zero", in(reg) addr.inner());
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{
+        addresses::VirtualAddress, coalesce_regions, flush_tlb, flush_tlb_addr, take_recorded_flushes, TlbFlush,
+    };
+
+    #[test]
+    pub fn coalesce_regions_test() {
+        let mut regions = [
+            0x1000_0000..0x1000_9000,
+            0x200_0000..0x201_0000,
+            0x1000_0000..0x1000_0100,
+            0x1000_9000..0x1000_a000,
+            0x3000..0x3000,
+            0xc00_0800..0xc00_1000,
+        ];
+
+        assert_eq!(
+            coalesce_regions(&mut regions),
+            [0x200_0000..0x201_0000, 0xc00_0000..0xc00_1000, 0x1000_0000..0x1000_a000]
+        );
+        assert_eq!(coalesce_regions(&mut []), []);
+    }
+
+    #[test]
+    pub fn recorded_flushes_test() {
+        assert!(take_recorded_flushes().is_empty());
+
+        flush_tlb_addr(VirtualAddress(0x4000_1000));
+        flush_tlb();
+
+        assert_eq!(
+            take_recorded_flushes(),
+            [TlbFlush::Address(VirtualAddress(0x4000_1000)), TlbFlush::All]
+        );
+        assert!(take_recorded_flushes().is_empty());
+    }
+}
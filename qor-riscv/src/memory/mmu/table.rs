@@ -4,6 +4,7 @@ use crate::memory::{Page, PAGE_SIZE};
 
 use super::{
     addresses::{PhysicalAddress, VirtualAddress},
+    dump::{MappedRange, RangeCollapser},
     entry::{EntryPermissionFlags, GlobalUserFlags, PageTableEntry},
 };
 
@@ -34,6 +35,31 @@ impl core::default::Default for PageTable {
     }
 }
 
+/// How a user mapping is carried over when a [`PageTable`] is copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafCopy {
+    /// Map the same pages with the same permissions, so writes are visible through both tables
+    Share,
+    /// Map the same pages without write permission, so the first write through the copy faults. The original mapping
+    /// keeps its permissions, and must be made read only separately if it is to be copy-on-write too.
+    ShareReadOnly,
+    /// Map a different set of pages, which the caller has filled with a copy of the data, with the same permissions
+    Duplicate(PhysicalAddress),
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static LEAVES_WRITTEN: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// Take the number of leaf entries written by `map` on this thread since the last call, which is only counted when
+/// built for the host
+#[cfg(feature = "std")]
+#[must_use]
+pub fn take_leaves_written() -> usize {
+    LEAVES_WRITTEN.with(|count| count.replace(0))
+}
+
 static_assertions::assert_eq_align!(PageTable, Page);
 static_assertions::assert_eq_size!(PageTable, Page);
 
@@ -97,6 +123,9 @@ impl PageTable {
             perm_flags,
         );
         *walking_reference = entry;
+
+        #[cfg(feature = "std")]
+        LEAVES_WRITTEN.with(|count| count.set(count.get() + 1));
     }
 
     /// Map a range of physical addresses to a range of virtual addresses
@@ -224,6 +253,416 @@ impl PageTable {
         None
     }
 
+    /// Find the leaf [`PageTableEntry`] mapping a virtual address, along with the level at which it was found, or
+    /// `None` if the address is not mapped.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    #[must_use]
+    pub fn leaf_entry(&self, virt_addr: VirtualAddress) -> Option<(PageTableEntry, usize)> {
+        let mut walking_reference = &self.0[(virt_addr.vpn2() % 512) as usize];
+
+        for level_index in (0..=2).rev() {
+            if !walking_reference.is_valid() {
+                return None;
+            } else if walking_reference.is_leaf() {
+                return Some((*walking_reference, level_index));
+            } else if level_index == 0 {
+                // A non-leaf entry at the lowest level is malformed, and would page fault
+                return None;
+            }
+
+            // Safety:
+            // Because this entry must be valid by the time we get here, we
+            // have a valid pointer to the page table, because we have a
+            // reference to one `PageTable`, we are able to safely construct a
+            // reference to it.
+            let table_ref =
+                unsafe { (walking_reference.physical_address().0 as *mut Self).as_ref() }.unwrap();
+            walking_reference = &table_ref.0[(virt_addr.vpn(level_index - 1) % 512) as usize];
+        }
+
+        None
+    }
+
+    /// Invalidate the leaf [`PageTableEntry`] mapping a virtual address, returning the number of bytes the removed
+    /// mapping covered, or `None` if the address was not mapped. The intermediate tables and the mapped pages are left
+    /// allocated.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub fn unmap(&mut self, virt_addr: VirtualAddress) -> Option<usize> {
+        let (entry, level) = self.leaf_entry_mut(virt_addr)?;
+        *entry = PageTableEntry::invalid_entry();
+
+        Some(LEVEL_SIZES[level])
+    }
+
+    /// Get the permissions of the mapping of a virtual address, or `None` if the address is not mapped.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    #[must_use]
+    pub fn permissions_of(&self, virt_addr: VirtualAddress) -> Option<EntryPermissionFlags> {
+        self.leaf_entry(virt_addr)
+            .and_then(|(entry, _)| entry.permission_flags())
+    }
+
+    /// Replace the permissions of the mapping of a virtual address, leaving the mapped page untouched, and returning
+    /// the number of bytes the mapping covers. Returns `None` if the address is not mapped, or if `perm_flags` is
+    /// [`EntryPermissionFlags::None`], as a leaf without permissions would instead be read as a pointer to a table.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub fn set_permissions(
+        &mut self,
+        virt_addr: VirtualAddress,
+        perm_flags: EntryPermissionFlags,
+    ) -> Option<usize> {
+        if perm_flags == EntryPermissionFlags::None {
+            return None;
+        }
+
+        let (entry, level) = self.leaf_entry_mut(virt_addr)?;
+        entry.set_permission_flags(perm_flags);
+
+        Some(LEVEL_SIZES[level])
+    }
+
+    /// Find the leaf [`PageTableEntry`] mapping a virtual address for modification, along with the level at which it
+    /// was found, or `None` if the address is not mapped.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    fn leaf_entry_mut(&mut self, virt_addr: VirtualAddress) -> Option<(&mut PageTableEntry, usize)> {
+        let mut walking_reference = &mut self.0[(virt_addr.vpn2() % 512) as usize];
+
+        for level_index in (0..=2).rev() {
+            if !walking_reference.is_valid() {
+                return None;
+            } else if walking_reference.is_leaf() {
+                return Some((walking_reference, level_index));
+            } else if level_index == 0 {
+                // A non-leaf entry at the lowest level is malformed, and would page fault
+                return None;
+            }
+
+            // Safety:
+            // Because this entry must be valid by the time we get here, we
+            // have a valid pointer to the page table, because we have a
+            // mutable reference to one `PageTable`, we also have unique access
+            // to the pointers stored within it.
+            let table_ref =
+                unsafe { (walking_reference.physical_address().0 as *mut Self).as_mut() }.unwrap();
+            walking_reference = &mut table_ref.0[(virt_addr.vpn(level_index - 1) % 512) as usize];
+        }
+
+        None
+    }
+
+    /// Invalidate every leaf [`PageTableEntry`] mapping part of the `range_length` page range starting at `virt_addr`,
+    /// returning the number of bytes the removed mappings covered. A larger page which only partly overlaps the range
+    /// is removed entirely. The intermediate tables and the mapped pages are left allocated.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub fn unmap_range(&mut self, virt_addr: VirtualAddress, range_length: MemoryUnit<PAGE_SIZE>) -> usize {
+        let end = virt_addr.inner().saturating_add(range_length.raw_bytes() as u64);
+        let mut address = virt_addr.inner() & !(PAGE_SIZE as u64 - 1);
+        let mut removed = 0;
+
+        while address < end {
+            let size = self.unmap(VirtualAddress(address)).map_or(PAGE_SIZE, |size| {
+                removed += size;
+                size
+            });
+
+            let Some(next) = (address | (size as u64 - 1)).checked_add(1) else {
+                break;
+            };
+            address = next;
+        }
+
+        removed
+    }
+
+    /// Returns true if every byte of the `length` byte range starting at `start` is mapped with at least the `want`
+    /// permissions, and if `require_user` is set, is accessible from user mode. An empty range is always accepted.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    #[must_use]
+    pub fn check_range(
+        &self,
+        start: VirtualAddress,
+        length: usize,
+        want: EntryPermissionFlags,
+        require_user: bool,
+    ) -> bool {
+        let Some(end) = start.inner().checked_add(length as u64) else {
+            return false;
+        };
+
+        let mut address = start.inner();
+
+        // Walk the range one mapping at a time, so a gigapage is only checked once
+        while address < end {
+            let Some((entry, level)) = self.leaf_entry(VirtualAddress(address)) else {
+                return false;
+            };
+
+            let permitted = entry
+                .permission_flags()
+                .is_some_and(|flags| flags.allows(want));
+
+            if !permitted || (require_user && !entry.user()) {
+                return false;
+            }
+
+            let Some(next) = (address | (LEVEL_SIZES[level] as u64 - 1)).checked_add(1) else {
+                return true;
+            };
+            address = next;
+        }
+
+        true
+    }
+
+    /// Call `f` with every leaf mapping in this table, in increasing virtual address order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub fn for_each_leaf(&self, mut f: impl FnMut(MappedRange)) {
+        self.walk_leaves(2, 0, &mut f);
+    }
+
+    /// Walk the leaves of this table, which sits at `level` and maps the virtual addresses starting at `base`
+    fn walk_leaves(&self, level: usize, base: u64, f: &mut impl FnMut(MappedRange)) {
+        for (index, entry) in self.0.iter().enumerate() {
+            if !entry.is_valid() {
+                continue;
+            }
+
+            let mut virt_addr = base | ((index as u64) << (12 + 9 * level));
+            // Sv39 addresses must have bits 63-39 equal to bit 38
+            if level == 2 && index >= 256 {
+                virt_addr |= !((1 << 39) - 1);
+            }
+
+            if entry.is_leaf() {
+                f(MappedRange {
+                    virt_addr: VirtualAddress(virt_addr),
+                    phys_addr: entry.physical_address(),
+                    length: LEVEL_SIZES[level] as u64,
+                    gu_flags: entry.global_user_flags().unwrap_or(GlobalUserFlags::None),
+                    perm_flags: entry.permission_flags().unwrap_or(EntryPermissionFlags::None),
+                });
+            } else if level > 0 {
+                // Safety:
+                // Because this entry is valid and not a leaf, it holds a valid
+                // pointer to a page table, and because we have a reference to
+                // one `PageTable`, we are able to safely construct a reference
+                // to it.
+                let table_ref =
+                    unsafe { (entry.physical_address().0 as *const Self).as_ref() }.unwrap();
+                table_ref.walk_leaves(level - 1, virt_addr, f);
+            }
+        }
+    }
+
+    /// Copy this table, allocating fresh intermediate tables for the copy. `copy_leaf` decides how each user mapping
+    /// is carried over, while kernel mappings, which are either global or inaccessible from user mode, are identical in
+    /// every table and so are always shared as they are.
+    ///
+    /// # Safety
+    ///
+    /// The `alloc_page` function must return a pointer to a uniquely owned `PageTable` allocation.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    #[must_use]
+    pub unsafe fn deep_copy(
+        &self,
+        alloc_page: impl Fn() -> *mut Self,
+        mut copy_leaf: impl FnMut(MappedRange) -> LeafCopy,
+    ) -> Self {
+        let mut copy = Self::empty();
+        self.copy_into(&mut copy, 2, 0, &alloc_page, &mut copy_leaf);
+        copy
+    }
+
+    /// Copy the entries of this table, which sits at `level` and maps the virtual addresses starting at `base`, into
+    /// the empty table `dest`
+    unsafe fn copy_into(
+        &self,
+        dest: &mut Self,
+        level: usize,
+        base: u64,
+        alloc_page: &impl Fn() -> *mut Self,
+        copy_leaf: &mut impl FnMut(MappedRange) -> LeafCopy,
+    ) {
+        for (index, (entry, dest_entry)) in self.0.iter().zip(dest.0.iter_mut()).enumerate() {
+            if !entry.is_valid() {
+                continue;
+            }
+
+            let mut virt_addr = base | ((index as u64) << (12 + 9 * level));
+            if level == 2 && index >= 256 {
+                virt_addr |= !((1 << 39) - 1);
+            }
+
+            if entry.is_leaf() {
+                *dest_entry = *entry;
+
+                if entry.global() || !entry.user() {
+                    continue;
+                }
+
+                let range = MappedRange {
+                    virt_addr: VirtualAddress(virt_addr),
+                    phys_addr: entry.physical_address(),
+                    length: LEVEL_SIZES[level] as u64,
+                    gu_flags: entry.global_user_flags().unwrap_or(GlobalUserFlags::None),
+                    perm_flags: entry.permission_flags().unwrap_or(EntryPermissionFlags::None),
+                };
+
+                match copy_leaf(range) {
+                    LeafCopy::Share => {}
+                    LeafCopy::ShareReadOnly => {
+                        let read_only = match range.perm_flags {
+                            EntryPermissionFlags::ReadWrite => EntryPermissionFlags::ReadOnly,
+                            EntryPermissionFlags::ReadWriteExecute => EntryPermissionFlags::ReadExecute,
+                            other => other,
+                        };
+                        dest_entry.set_permission_flags(read_only);
+                    }
+                    LeafCopy::Duplicate(phys_addr) => {
+                        dest_entry.set_ppn0(phys_addr.ppn0());
+                        dest_entry.set_ppn1(phys_addr.ppn1());
+                        dest_entry.set_ppn2(phys_addr.ppn2());
+                    }
+                }
+            } else if level > 0 {
+                let allocated_page = alloc_page();
+                allocated_page.write(Self::empty());
+
+                // Safety:
+                // Because this entry is valid and not a leaf, it holds a valid
+                // pointer to a page table, and the freshly allocated table is
+                // uniquely owned by the copy.
+                let table_ref = (entry.physical_address().0 as *const Self).as_ref().unwrap();
+                table_ref.copy_into(
+                    allocated_page.as_mut().unwrap(),
+                    level - 1,
+                    virt_addr,
+                    alloc_page,
+                    copy_leaf,
+                );
+
+                let physical_allocated_page = PhysicalAddress(allocated_page as u64);
+                *dest_entry = PageTableEntry::construct_valid(
+                    [
+                        physical_allocated_page.ppn0(),
+                        physical_allocated_page.ppn1(),
+                        physical_allocated_page.ppn2(),
+                    ],
+                    0,
+                    GlobalUserFlags::None,
+                    EntryPermissionFlags::None,
+                );
+            }
+        }
+    }
+
+    /// Log every mapping in this table at the trace level, collapsing runs of contiguous pages with the same flags into
+    /// a single line. Nothing is walked unless trace messages are being displayed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub fn debug_dump(&self) {
+        if !qor_core::logging::check_log_level(module_path!(), qor_core::logging::LogLevel::Trace) {
+            return;
+        }
+
+        let addr = self as *const Self as usize;
+        trace!("Mappings in the page table at {:x}:", addr);
+
+        let mut collapser = RangeCollapser::new();
+        self.for_each_leaf(|range| {
+            if let Some(complete) = collapser.push(range) {
+                trace!("  {}", complete);
+            }
+        });
+
+        if let Some(complete) = collapser.finish() {
+            trace!("  {}", complete);
+        }
+    }
+
+    /// Free every intermediate table which no longer holds any valid entries, invalidating the entries which pointed to
+    /// them.
+    ///
+    /// # Safety
+    ///
+    /// The `free_page` function must be able to free a page allocated by the `alloc_page` function passed to the `map` functions.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a valid [`PageTableEntry`] points to a null address.
+    pub unsafe fn free_empty_tables(&mut self, free_page: impl Fn(*mut Self)) {
+        for entry2 in &mut self.0 {
+            if !entry2.is_valid() || entry2.is_leaf() {
+                continue;
+            }
+
+            // Safety:
+            // Because this entry is valid and not a leaf, it holds a valid
+            // pointer to a page table, and because we have a mutable reference
+            // to the entire table, we have unique access to it.
+            let level1_table = unsafe {
+                (entry2.physical_address().inner() as *mut Self)
+                    .as_mut()
+                    .unwrap()
+            };
+
+            for entry1 in &mut level1_table.0 {
+                if !entry1.is_valid() || entry1.is_leaf() {
+                    continue;
+                }
+
+                let level0_table = entry1.physical_address().inner() as *mut Self;
+
+                // Safety:
+                // This entry is valid and not a leaf, so it points to a page
+                // table which is only reachable through this entry.
+                if unsafe { level0_table.as_ref() }.unwrap().is_empty() {
+                    *entry1 = PageTableEntry::invalid_entry();
+                    free_page(level0_table);
+                }
+            }
+
+            if level1_table.is_empty() {
+                *entry2 = PageTableEntry::invalid_entry();
+                free_page(level1_table as *mut Self);
+            }
+        }
+    }
+
+    /// Returns true if none of the entries in this table are valid
+    fn is_empty(&self) -> bool {
+        self.0.iter().all(|entry| !entry.is_valid())
+    }
+
     /// Free all of the mapped pages in this table.
     ///
     /// # Safety
@@ -269,3 +708,323 @@ impl PageTable {
         }
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+
+    use alloc::boxed::Box;
+    use qor_core::memory::MemoryUnit;
+
+    use super::{LeafCopy, PageTable};
+    use crate::memory::{
+        mmu::{
+            addresses::{PhysicalAddress, VirtualAddress},
+            entry::{EntryPermissionFlags, GlobalUserFlags},
+        },
+        PAGE_SIZE,
+    };
+
+    const BASE: u64 = 0x4000_0000;
+
+    fn leak_page_table() -> *mut PageTable {
+        Box::leak(Box::new(PageTable::empty()))
+    }
+
+    /// Construct a page table with two user read/write pages at `BASE`, followed by an unmapped page, and a kernel
+    /// only read only page after that.
+    fn test_table() -> Box<PageTable> {
+        let mut table = Box::new(PageTable::empty());
+
+        // Safety: Every page table allocation is a uniquely owned, leaked box
+        unsafe {
+            table.map_range(
+                VirtualAddress(BASE),
+                PhysicalAddress(0x8000_0000),
+                MemoryUnit::new(2),
+                GlobalUserFlags::User,
+                EntryPermissionFlags::ReadWrite,
+                leak_page_table,
+            );
+            table.map(
+                VirtualAddress(BASE + 3 * PAGE_SIZE as u64),
+                PhysicalAddress(0x8000_3000),
+                GlobalUserFlags::None,
+                EntryPermissionFlags::ReadOnly,
+                0,
+                leak_page_table,
+            );
+        }
+
+        table
+    }
+
+    #[test]
+    pub fn check_mapped_range_test() {
+        let table = test_table();
+
+        assert!(table.check_range(
+            VirtualAddress(BASE + 0x10),
+            2 * PAGE_SIZE - 0x10,
+            EntryPermissionFlags::ReadWrite,
+            true
+        ));
+        assert!(table.check_range(
+            VirtualAddress(BASE + 0xFFF),
+            2,
+            EntryPermissionFlags::ReadOnly,
+            true
+        ));
+        assert!(table.check_range(
+            VirtualAddress(BASE + 2 * PAGE_SIZE as u64),
+            0,
+            EntryPermissionFlags::ReadWrite,
+            true
+        ));
+    }
+
+    #[test]
+    pub fn check_straddling_range_test() {
+        let table = test_table();
+
+        // The last byte of the range lies in the unmapped third page
+        assert!(!table.check_range(
+            VirtualAddress(BASE + PAGE_SIZE as u64),
+            PAGE_SIZE + 1,
+            EntryPermissionFlags::ReadOnly,
+            true
+        ));
+        assert!(!table.check_range(
+            VirtualAddress(u64::MAX),
+            2,
+            EntryPermissionFlags::ReadOnly,
+            false
+        ));
+    }
+
+    #[test]
+    pub fn check_range_permissions_test() {
+        let table = test_table();
+        let kernel_page = VirtualAddress(BASE + 3 * PAGE_SIZE as u64);
+
+        assert!(!table.check_range(
+            VirtualAddress(BASE),
+            16,
+            EntryPermissionFlags::ReadExecute,
+            true
+        ));
+        assert!(table.check_range(kernel_page, 16, EntryPermissionFlags::ReadOnly, false));
+        assert!(!table.check_range(kernel_page, 16, EntryPermissionFlags::ReadOnly, true));
+        assert!(!table.check_range(kernel_page, 16, EntryPermissionFlags::ReadWrite, false));
+    }
+
+    #[test]
+    pub fn unmap_test() {
+        let mut table = test_table();
+
+        assert_eq!(table.unmap(VirtualAddress(BASE + 0x10)), Some(PAGE_SIZE));
+        assert_eq!(
+            table.virtual_to_physical_address(VirtualAddress(BASE)),
+            None
+        );
+        assert_eq!(
+            table.virtual_to_physical_address(VirtualAddress(BASE + PAGE_SIZE as u64)),
+            Some(PhysicalAddress(0x8000_1000))
+        );
+
+        assert_eq!(table.unmap(VirtualAddress(BASE)), None);
+        assert_eq!(
+            table.unmap(VirtualAddress(BASE + 2 * PAGE_SIZE as u64)),
+            None
+        );
+    }
+
+    #[test]
+    pub fn for_each_leaf_test() {
+        let table = test_table();
+        let mut leaves = alloc::vec::Vec::new();
+        table.for_each_leaf(|range| leaves.push((range.virt_addr, range.phys_addr, range.perm_flags)));
+
+        assert_eq!(
+            leaves,
+            [
+                (VirtualAddress(BASE), PhysicalAddress(0x8000_0000), EntryPermissionFlags::ReadWrite),
+                (
+                    VirtualAddress(BASE + PAGE_SIZE as u64),
+                    PhysicalAddress(0x8000_1000),
+                    EntryPermissionFlags::ReadWrite
+                ),
+                (
+                    VirtualAddress(BASE + 3 * PAGE_SIZE as u64),
+                    PhysicalAddress(0x8000_3000),
+                    EntryPermissionFlags::ReadOnly
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn deep_copy_test() {
+        let mut table = test_table();
+
+        // Safety: Every page table allocation is a uniquely owned, leaked box
+        unsafe {
+            table.map(
+                VirtualAddress(BASE + 4 * PAGE_SIZE as u64),
+                PhysicalAddress(0x8000_4000),
+                GlobalUserFlags::User,
+                EntryPermissionFlags::ReadWrite,
+                0,
+                leak_page_table,
+            );
+        }
+
+        let mut seen = alloc::vec::Vec::new();
+        // Safety: Every page table allocation is a uniquely owned, leaked box
+        let copy = unsafe {
+            table.deep_copy(leak_page_table, |range| {
+                seen.push(range.virt_addr);
+                match range.virt_addr.0 - BASE {
+                    0 => LeafCopy::Share,
+                    0x1000 => LeafCopy::ShareReadOnly,
+                    _ => LeafCopy::Duplicate(PhysicalAddress(0x9000_0000)),
+                }
+            })
+        };
+
+        // The kernel only page is copied without consulting the callback
+        assert_eq!(
+            seen,
+            [
+                VirtualAddress(BASE),
+                VirtualAddress(BASE + PAGE_SIZE as u64),
+                VirtualAddress(BASE + 4 * PAGE_SIZE as u64)
+            ]
+        );
+
+        for page in 0..4 {
+            let address = VirtualAddress(BASE + page * PAGE_SIZE as u64 + 0x10);
+            assert_eq!(
+                copy.virtual_to_physical_address(address),
+                table.virtual_to_physical_address(address)
+            );
+        }
+        assert_eq!(
+            copy.virtual_to_physical_address(VirtualAddress(BASE + 4 * PAGE_SIZE as u64 + 0x10)),
+            Some(PhysicalAddress(0x9000_0010))
+        );
+
+        assert_eq!(copy.permissions_of(VirtualAddress(BASE)), Some(EntryPermissionFlags::ReadWrite));
+        assert_eq!(
+            copy.permissions_of(VirtualAddress(BASE + PAGE_SIZE as u64)),
+            Some(EntryPermissionFlags::ReadOnly)
+        );
+        assert_eq!(
+            table.permissions_of(VirtualAddress(BASE + PAGE_SIZE as u64)),
+            Some(EntryPermissionFlags::ReadWrite)
+        );
+
+        // The copy has its own intermediate tables
+        table.unmap(VirtualAddress(BASE));
+        assert_eq!(
+            copy.virtual_to_physical_address(VirtualAddress(BASE)),
+            Some(PhysicalAddress(0x8000_0000))
+        );
+    }
+
+    #[test]
+    pub fn permissions_test() {
+        let mut table = test_table();
+        let page = VirtualAddress(BASE + PAGE_SIZE as u64 + 0x10);
+
+        assert_eq!(table.permissions_of(page), Some(EntryPermissionFlags::ReadWrite));
+        assert_eq!(table.set_permissions(page, EntryPermissionFlags::ReadOnly), Some(PAGE_SIZE));
+        assert_eq!(table.permissions_of(page), Some(EntryPermissionFlags::ReadOnly));
+        assert_eq!(
+            table.virtual_to_physical_address(page),
+            Some(PhysicalAddress(0x8000_1010))
+        );
+        assert_eq!(
+            table.permissions_of(VirtualAddress(BASE)),
+            Some(EntryPermissionFlags::ReadWrite)
+        );
+
+        assert_eq!(table.set_permissions(page, EntryPermissionFlags::None), None);
+        let unmapped = VirtualAddress(BASE + 2 * PAGE_SIZE as u64);
+        assert_eq!(table.permissions_of(unmapped), None);
+        assert_eq!(table.set_permissions(unmapped, EntryPermissionFlags::ReadWrite), None);
+    }
+
+    #[test]
+    pub fn identity_map_coalesced_regions_test() {
+        let mut regions = [
+            0x1000_0000..0x1000_1000,
+            0x200_0000..0x201_0000,
+            0x1000_0000..0x1000_9000,
+        ];
+        let mut table = Box::new(PageTable::empty());
+        let _ = super::take_leaves_written();
+
+        for region in crate::memory::mmu::coalesce_regions(&mut regions) {
+            // Safety: Every page table allocation is a uniquely owned, leaked box
+            unsafe {
+                table.id_map_range(
+                    PhysicalAddress(region.start),
+                    MemoryUnit::new(((region.end - region.start) / PAGE_SIZE as u64) as usize),
+                    GlobalUserFlags::None,
+                    EntryPermissionFlags::ReadWrite,
+                    leak_page_table,
+                );
+            }
+        }
+
+        // The UART page lies within the virtio window, so is only written once
+        assert_eq!(super::take_leaves_written(), 0x10 + 0x9);
+    }
+
+    #[test]
+    pub fn unmap_range_test() {
+        let mut table = test_table();
+
+        // The range covers the second page, the unmapped third page, and the first byte of the kernel page
+        assert_eq!(
+            table.unmap_range(VirtualAddress(BASE + PAGE_SIZE as u64), MemoryUnit::new(3)),
+            2 * PAGE_SIZE
+        );
+        assert_eq!(
+            table.virtual_to_physical_address(VirtualAddress(BASE)),
+            Some(PhysicalAddress(0x8000_0000))
+        );
+        assert_eq!(
+            table.virtual_to_physical_address(VirtualAddress(BASE + PAGE_SIZE as u64)),
+            None
+        );
+        assert_eq!(
+            table.virtual_to_physical_address(VirtualAddress(BASE + 3 * PAGE_SIZE as u64 + 0x10)),
+            None
+        );
+        assert_eq!(table.unmap_range(VirtualAddress(BASE + PAGE_SIZE as u64), MemoryUnit::new(3)), 0);
+    }
+
+    #[test]
+    pub fn free_empty_tables_test() {
+        let mut table = test_table();
+        let freed = core::cell::Cell::new(0);
+
+        // Safety: Every page table allocation is a uniquely owned, leaked box
+        let free_page = |ptr: *mut PageTable| {
+            drop(unsafe { Box::from_raw(ptr) });
+            freed.set(freed.get() + 1);
+        };
+
+        table.unmap_range(VirtualAddress(BASE), MemoryUnit::new(2));
+        unsafe { table.free_empty_tables(free_page) };
+        assert_eq!(freed.get(), 0);
+
+        table.unmap_range(VirtualAddress(BASE), MemoryUnit::new(4));
+        unsafe { table.free_empty_tables(free_page) };
+        assert_eq!(freed.get(), 2);
+        assert!(*table == PageTable::empty());
+    }
+}
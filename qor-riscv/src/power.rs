@@ -0,0 +1,71 @@
+use qor_core::interfaces::mmio::MMIOInterface;
+
+/// Base address of the `sifive_test` finisher device on the QEMU `virt` machine
+pub const QEMU_TEST_FINISHER: usize = 0x10_0000;
+
+/// Commands understood by the `sifive_test` finisher, written to its register as a 32 bit value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinisherCommand {
+    /// Power off, with QEMU exiting with status zero
+    Pass,
+    /// Power off, with QEMU exiting with the given status
+    Fail(u16),
+    /// Reset the machine
+    Reset,
+}
+
+impl FinisherCommand {
+    /// Get the value to write to the finisher register to issue this command. The low 16 bits hold the command, and
+    /// for `Fail` the high 16 bits hold the exit status.
+    #[must_use]
+    pub const fn value(self) -> u32 {
+        match self {
+            Self::Pass => 0x5555,
+            Self::Fail(status) => (status as u32) << 16 | 0x3333,
+            Self::Reset => 0x7777,
+        }
+    }
+}
+
+/// Issue `command` to the `sifive_test` finisher device accessed through `mmio`
+///
+/// On QEMU this does not return for any command, but it does if the device is missing.
+///
+/// # Safety
+///
+/// The `mmio` interface must point to the base address of a memory mapped `sifive_test` device.
+pub unsafe fn finish(mmio: &MMIOInterface, command: FinisherCommand) {
+    mmio.write_offset(0, command.value());
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use qor_core::interfaces::mmio::MMIOInterface;
+
+    use super::{finish, FinisherCommand, QEMU_TEST_FINISHER};
+
+    #[test]
+    pub fn finisher_command_test() {
+        assert_eq!(QEMU_TEST_FINISHER, 0x10_0000);
+
+        assert_eq!(FinisherCommand::Pass.value(), 0x5555);
+        assert_eq!(FinisherCommand::Reset.value(), 0x7777);
+        assert_eq!(FinisherCommand::Fail(1).value(), 0x0001_3333);
+        assert_eq!(FinisherCommand::Fail(0xFFFF).value(), 0xFFFF_3333);
+    }
+
+    #[test]
+    pub fn finish_writes_register_test() {
+        let mut register = 0u32;
+        let mmio = MMIOInterface::new(core::ptr::addr_of_mut!(register) as usize);
+
+        // Safety: The interface points to a `u32` standing in for the finisher register
+        unsafe { finish(&mmio, FinisherCommand::Pass) };
+        assert_eq!(register, 0x5555);
+
+        // Safety: As above
+        unsafe { finish(&mmio, FinisherCommand::Fail(3)) };
+        assert_eq!(register, 0x0003_3333);
+    }
+}
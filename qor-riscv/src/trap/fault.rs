@@ -0,0 +1,85 @@
+use super::resume::trapped_from_user;
+
+/// How the trap handler deals with a synchronous exception which is a fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultRoute {
+    /// A load or store page fault taken by a user process, which may be resolved by mapping the page, for instance to
+    /// grow the stack or break a copy on write mapping. The process is terminated if it can not be.
+    ResolvePage { is_store: bool },
+    /// Terminate the user process which took the fault, and carry on with the other processes
+    KillProcess,
+    /// The fault was taken by the kernel itself, which is fatal
+    Fatal,
+}
+
+/// Decide how to deal with the trap with the raw `mcause` value `cause`, taken with the raw `mstatus` value `status`.
+///
+/// Returns `None` if the trap is not a fault, such as an interrupt, a breakpoint or an `ecall`.
+#[must_use]
+pub const fn route_fault(cause: usize, status: usize) -> Option<FaultRoute> {
+    let is_store = match cause {
+        // Misaligned, access and illegal instruction faults, and instruction page faults
+        0..=2 | 4..=7 | 12 => None,
+        13 => Some(false),
+        15 => Some(true),
+        _ => return None,
+    };
+
+    Some(if !trapped_from_user(status) {
+        FaultRoute::Fatal
+    } else if let Some(is_store) = is_store {
+        FaultRoute::ResolvePage { is_store }
+    } else {
+        FaultRoute::KillProcess
+    })
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{route_fault, FaultRoute};
+    use crate::trap::resume::MPP_SHIFT;
+
+    const USER: usize = 0;
+    const SUPERVISOR: usize = 0b01 << MPP_SHIFT;
+    const MACHINE: usize = 0b11 << MPP_SHIFT;
+
+    #[test]
+    pub fn user_fault_route_test() {
+        // Illegal instruction, load access fault and instruction page fault
+        for cause in [2, 5, 12] {
+            assert_eq!(route_fault(cause, USER), Some(FaultRoute::KillProcess));
+        }
+
+        assert_eq!(
+            route_fault(13, USER),
+            Some(FaultRoute::ResolvePage { is_store: false })
+        );
+        assert_eq!(
+            route_fault(15, USER),
+            Some(FaultRoute::ResolvePage { is_store: true })
+        );
+    }
+
+    #[test]
+    pub fn kernel_fault_route_test() {
+        for status in [SUPERVISOR, MACHINE] {
+            for cause in [0, 2, 5, 7, 12, 13, 15] {
+                assert_eq!(route_fault(cause, status), Some(FaultRoute::Fatal));
+            }
+        }
+    }
+
+    #[test]
+    pub fn not_a_fault_test() {
+        // Breakpoints and `ecall`s from each mode
+        for cause in [3, 8, 9, 11] {
+            assert_eq!(route_fault(cause, USER), None);
+            assert_eq!(route_fault(cause, MACHINE), None);
+        }
+
+        // The machine timer and external interrupts
+        assert_eq!(route_fault(0x8000_0000_0000_0007, USER), None);
+        assert_eq!(route_fault(0x8000_0000_0000_000B, USER), None);
+    }
+}
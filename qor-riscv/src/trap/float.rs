@@ -0,0 +1,94 @@
+/// State of the floating point unit as tracked by the `FS` field of the `mstatus` register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatingPointState {
+    Off,
+    Initial,
+    Clean,
+    Dirty,
+}
+
+/// Bit offset of the `FS` field within the `mstatus` register.
+pub const FS_SHIFT: usize = 13;
+
+/// Mask of the `FS` field within the `mstatus` register.
+pub const FS_MASK: usize = 0b11 << FS_SHIFT;
+
+impl FloatingPointState {
+    /// Extract the floating point state from the raw value of the `mstatus` register.
+    #[must_use]
+    pub const fn from_status(status: usize) -> Self {
+        match (status & FS_MASK) >> FS_SHIFT {
+            0 => Self::Off,
+            1 => Self::Initial,
+            2 => Self::Clean,
+            _ => Self::Dirty,
+        }
+    }
+
+    /// Get the raw value of the `FS` field for this state, already shifted into position within `mstatus`.
+    #[must_use]
+    pub const fn to_status_bits(self) -> usize {
+        (self as usize) << FS_SHIFT
+    }
+
+    /// Returns true if the floating point registers have been written since they were last saved or restored, and
+    /// must be saved to the trap frame. This is the same check `asm_trap_vector` performs on entry and exit.
+    #[must_use]
+    pub const fn needs_save(self) -> bool {
+        matches!(self, Self::Dirty)
+    }
+}
+
+impl From<riscv::register::mstatus::FS> for FloatingPointState {
+    fn from(value: riscv::register::mstatus::FS) -> Self {
+        match value {
+            riscv::register::mstatus::FS::Off => Self::Off,
+            riscv::register::mstatus::FS::Initial => Self::Initial,
+            riscv::register::mstatus::FS::Clean => Self::Clean,
+            riscv::register::mstatus::FS::Dirty => Self::Dirty,
+        }
+    }
+}
+
+/// Read the current floating point state from the `mstatus` register.
+#[must_use]
+pub fn read_floating_point_state() -> FloatingPointState {
+    riscv::register::mstatus::read().fs().into()
+}
+
+/// Mark the floating point registers as clean, so the next trap will not save them unless they are written again.
+///
+/// # Safety
+///
+/// The caller must ensure the current contents of the floating point registers have been saved, or are otherwise not
+/// needed, as they will no longer be saved on the next trap.
+pub unsafe fn clear_floating_point_state() {
+    if read_floating_point_state().needs_save() {
+        riscv::register::mstatus::set_fs(riscv::register::mstatus::FS::Clean);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::FloatingPointState;
+
+    #[test]
+    pub fn save_decision_test() {
+        // Unrelated bits (MPP and MPIE) should not affect the decision
+        let other_bits = (0b11 << 11) | (1 << 7);
+
+        for (fs, state, save) in [
+            (0b00, FloatingPointState::Off, false),
+            (0b01, FloatingPointState::Initial, false),
+            (0b10, FloatingPointState::Clean, false),
+            (0b11, FloatingPointState::Dirty, true),
+        ] {
+            let status = other_bits | (fs << super::FS_SHIFT);
+
+            assert_eq!(FloatingPointState::from_status(status), state);
+            assert_eq!(state.needs_save(), save);
+            assert_eq!(state.to_status_bits(), fs << super::FS_SHIFT);
+        }
+    }
+}
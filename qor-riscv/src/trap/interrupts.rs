@@ -0,0 +1,120 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use qor_core::sync::InterruptControl;
+
+/// Value of `a7` for a supervisor mode `ecall` asking the trap handler to mask machine interrupts, "QOR" in ASCII
+/// followed by the request number
+pub const MASK_INTERRUPTS_CALL: usize = 0x514F_5201;
+
+/// Value of `a7` for a supervisor mode `ecall` asking the trap handler to unmask machine interrupts
+pub const UNMASK_INTERRUPTS_CALL: usize = 0x514F_5202;
+
+/// Set while supervisor mode code holds machine interrupts masked on this hart
+static MASKED: AtomicBool = AtomicBool::new(false);
+
+/// Masks the machine timer, software and external interrupts on the current hart through their enable bits in `mie`,
+/// for use by code running in supervisor mode.
+///
+/// Machine interrupts are always taken while the hart is in supervisor mode, whatever the value of `mstatus.MIE`, and
+/// `mie` can only be written from machine mode, so the bits are changed by the trap handler through an `ecall`, see
+/// [`handle_supervisor_call`]. Machine mode code runs with `mstatus.MIE` cleared by the trap which entered it, so it
+/// must not use this, and instead takes locks with [`qor_core::sync::IrqSpinLock::lock_from_interrupt`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MachineInterrupts;
+
+impl InterruptControl for MachineInterrupts {
+    fn disable(&self) -> bool {
+        // A nested critical section leaves the interrupts to the outermost one
+        if MASKED.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+
+        supervisor_call(MASK_INTERRUPTS_CALL);
+        true
+    }
+
+    fn enable(&self) {
+        MASKED.store(false, Ordering::Release);
+        supervisor_call(UNMASK_INTERRUPTS_CALL);
+    }
+}
+
+/// Make an `ecall` from supervisor mode with `call` in `a7`
+#[allow(unused_variables, clippy::missing_const_for_fn)]
+fn supervisor_call(call: usize) {
+    #[cfg(all(not(feature = "std"), target_arch = "riscv64"))]
+    {
+        // Safety: The trap handler saves and restores every register around the call
+        unsafe {
+            core::arch::asm!("ecall", in("a7") call);
+        }
+    }
+}
+
+/// Mask every interrupt on this hart for good, such as before parking it. This works from both machine and supervisor
+/// mode, using [`super::in_machine_mode`] to tell which it is running in.
+pub fn mask_all_interrupts() {
+    if super::in_machine_mode() {
+        // Safety: The interrupts are never unmasked, so nothing else runs on this hart
+        unsafe {
+            mask_machine_interrupts();
+            riscv::register::mstatus::clear_mie();
+        }
+    } else {
+        // Supervisor mode can only mask machine interrupts through the trap handler
+        let _ = MachineInterrupts.disable();
+    }
+}
+
+/// Clear the enable bits in `mie` of the machine interrupts the kernel takes
+///
+/// # Safety
+///
+/// This must be called from machine mode.
+unsafe fn mask_machine_interrupts() {
+    riscv::register::mie::clear_msoft();
+    riscv::register::mie::clear_mtimer();
+    riscv::register::mie::clear_mext();
+}
+
+/// Handle an `ecall` from supervisor mode made by [`MachineInterrupts`], returning false if `call` is not one of its
+/// requests. This must be called from machine mode.
+#[must_use]
+pub fn handle_supervisor_call(call: usize) -> bool {
+    match call {
+        // Safety: Masking interrupts only defers them until they are unmasked
+        MASK_INTERRUPTS_CALL => unsafe { mask_machine_interrupts() },
+        // Safety: Interrupts are only unmasked once the critical section which masked them has ended
+        UNMASK_INTERRUPTS_CALL => unsafe {
+            riscv::register::mie::set_msoft();
+            riscv::register::mie::set_mtimer();
+            riscv::register::mie::set_mext();
+        },
+        _ => return false,
+    }
+
+    true
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use qor_core::sync::InterruptControl;
+
+    use super::MachineInterrupts;
+
+    #[test]
+    pub fn nested_disable_test() {
+        let control = MachineInterrupts;
+
+        assert!(control.disable());
+
+        // A nested critical section does not re-enable interrupts, only the outermost one does
+        assert!(!control.disable());
+        control.enable();
+
+        assert!(control.disable());
+        assert!(!control.disable());
+        control.enable();
+    }
+}
@@ -1,9 +1,81 @@
-use qor_core::structures::id::PID;
+use core::sync::atomic::{AtomicBool, Ordering};
 
+use qor_core::structures::id::{HartID, PID};
+
+pub mod fault;
+pub mod float;
 pub mod frame;
+pub mod interrupts;
+pub mod resume;
+pub mod syscall;
+
+/// Set while kernel code on this hart runs in machine mode, the hart boots in machine mode
+static IN_MACHINE_MODE: AtomicBool = AtomicBool::new(true);
+
+/// Record whether kernel code on this hart is about to run in machine mode, as it does on entering a trap, or in
+/// supervisor mode, as it does after returning from a trap taken from supervisor mode.
+pub fn set_in_machine_mode(in_machine_mode: bool) {
+    IN_MACHINE_MODE.store(in_machine_mode, Ordering::Release);
+}
+
+/// Returns true if the calling kernel code is running in machine mode, as last recorded by [`set_in_machine_mode`]
+#[must_use]
+pub fn in_machine_mode() -> bool {
+    IN_MACHINE_MODE.load(Ordering::Acquire)
+}
 
+/// Get the PID of the process whose page table is currently installed on this hart
 #[must_use]
 pub fn get_pid() -> PID {
+    pid_from_satp(riscv::register::satp::read().bits())
+}
+
+/// Record the id of this hart in `sscratch`, where it can be read from supervisor mode, unlike `mhartid`.
+///
+/// This must be called from machine mode, on the init path of every hart, before [`current_hart_id`] is used there.
+pub fn record_hart_id() {
+    riscv::register::sscratch::write(riscv::register::mhartid::read());
+}
+
+/// Get the id of this hart, as recorded by [`record_hart_id`]
+#[must_use]
+pub fn current_hart_id() -> HartID {
+    HartID(riscv::register::sscratch::read())
+}
+
+/// Get the PID of the process whose page table is installed by the given `satp` value, the inverse of
+/// [`crate::memory::mmu::construct_satp`]
+#[must_use]
+pub const fn pid_from_satp(satp: usize) -> PID {
     #[allow(clippy::cast_possible_truncation)]
-    ((riscv::register::satp::read().asid() & 0xffff) as u16).into()
-}
\ No newline at end of file
+    PID::from_asid(((satp >> 44) & 0xffff) as u16)
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+
+    use alloc::boxed::Box;
+    use qor_core::structures::id::PID;
+
+    use super::pid_from_satp;
+    use crate::memory::mmu::{construct_satp, table::PageTable};
+
+    #[test]
+    pub fn pid_satp_round_trip_test() {
+        let table = Box::new(PageTable::empty());
+
+        for pid in [0, 1, 0x1234, 0x8000, u16::MAX].map(PID::from) {
+            let satp = construct_satp(pid.asid(), &table);
+            assert_eq!(pid_from_satp(satp), pid);
+
+            // The page table address and mode are left intact alongside the ASID
+            assert_eq!(
+                satp & ((1 << 44) - 1),
+                core::ptr::addr_of!(*table) as usize >> 12
+            );
+            assert_eq!(satp >> 60, 8);
+        }
+    }
+}
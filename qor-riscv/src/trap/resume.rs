@@ -0,0 +1,89 @@
+/// Bit offset of the `MPP` field, the privilege mode a trap was taken from, within the `mstatus` register.
+pub const MPP_SHIFT: usize = 11;
+
+/// Mask of the `MPP` field within the `mstatus` register.
+pub const MPP_MASK: usize = 0b11 << MPP_SHIFT;
+
+/// Returns true if the trap which saved the raw `mstatus` value `status` was taken from user mode.
+#[must_use]
+pub const fn trapped_from_user(status: usize) -> bool {
+    status & MPP_MASK == 0
+}
+
+/// Returns true if the trap which saved the raw `mstatus` value `status` was taken from machine mode, such as a fault
+/// raised by a trap handler.
+#[must_use]
+pub const fn trapped_from_machine(status: usize) -> bool {
+    status & MPP_MASK == MPP_MASK
+}
+
+/// Program counter a user mode thread of execution continues from the next time it is switched to.
+///
+/// It starts out at the program's entry point, and every trap taken from the thread moves it to the trapped pc, so a
+/// thread which is preempted and later rescheduled picks up where it left off instead of restarting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResumePoint(usize);
+
+impl ResumePoint {
+    /// Construct a resume point for a thread which has not run yet, and starts at `entry`
+    #[must_use]
+    pub const fn at_entry(entry: usize) -> Self {
+        Self(entry)
+    }
+
+    /// Record that the thread trapped with the pc `epc`, the instruction it resumes at unless the trap is handled by
+    /// completing that instruction
+    pub const fn record_trap(&mut self, epc: usize) {
+        self.0 = epc;
+    }
+
+    /// Move past the trapping instruction, `length` bytes long, for traps such as `ecall` which are handled by
+    /// completing it
+    pub const fn advance(&mut self, length: usize) {
+        self.0 += length;
+    }
+
+    /// Get the program counter to resume at
+    #[must_use]
+    pub const fn program_counter(&self) -> usize {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use super::{trapped_from_machine, trapped_from_user, ResumePoint, MPP_SHIFT};
+
+    #[test]
+    pub fn trapped_from_user_test() {
+        assert!(trapped_from_user(0));
+        assert!(trapped_from_user(!(0b11 << MPP_SHIFT)));
+        assert!(!trapped_from_user(0b11 << MPP_SHIFT));
+        assert!(!trapped_from_user(0b01 << MPP_SHIFT));
+    }
+
+    #[test]
+    pub fn trapped_from_machine_test() {
+        assert!(trapped_from_machine(0b11 << MPP_SHIFT));
+        assert!(trapped_from_machine(usize::MAX));
+        assert!(!trapped_from_machine(0b01 << MPP_SHIFT));
+        assert!(!trapped_from_machine(!(0b11 << MPP_SHIFT)));
+    }
+
+    #[test]
+    pub fn reschedule_resumes_at_trap_test() {
+        let entry = 0x1_0000;
+        let mut resume = ResumePoint::at_entry(entry);
+        assert_eq!(resume.program_counter(), entry);
+
+        // Preempted by the timer at 0x1_0420, the next switch continues from there rather than from the entry point
+        resume.record_trap(0x1_0420);
+        assert_eq!(resume.program_counter(), 0x1_0420);
+
+        // A syscall completes the `ecall`, so the thread continues after it
+        resume.record_trap(0x1_0800);
+        resume.advance(4);
+        assert_eq!(resume.program_counter(), 0x1_0804);
+    }
+}
@@ -0,0 +1,102 @@
+use qor_core::structures::syscall_error::SyscallError;
+
+/// Size of the `ecall` instruction, which the trap pc must be advanced past to resume the process
+pub const ECALL_INSTRUCTION_SIZE: usize = 4;
+
+/// Index of the `a0` register, which holds the first argument of a syscall and, on return, its result
+const A0: usize = 10;
+
+/// Index of the `a7` register, which holds the syscall number
+const A7: usize = 17;
+
+/// Number of argument registers, `a0` through `a6`, a syscall can take arguments in
+pub const SYSCALL_ARGUMENTS: usize = 7;
+
+/// Get the number of the syscall requested by a process with the general purpose registers `registers`
+#[must_use]
+pub const fn syscall_number(registers: &[u64; 32]) -> u64 {
+    registers[A7]
+}
+
+/// Get the arguments of the syscall requested by a process with the general purpose registers `registers`, from `a0`
+/// through `a6`
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn syscall_arguments(registers: &[u64; 32]) -> [usize; SYSCALL_ARGUMENTS] {
+    core::array::from_fn(|index| registers[A0 + index] as usize)
+}
+
+/// Encode the result of a syscall as the value returned to the process. Errors are returned as the negated errno
+/// value, so every error reads as a small negative number.
+#[must_use]
+pub fn encode_syscall_result(result: Result<usize, SyscallError>) -> u64 {
+    match result {
+        Ok(value) => value as u64,
+        Err(error) => {
+            let errno: isize = error.into();
+            (-(errno as i64)).cast_unsigned()
+        }
+    }
+}
+
+/// Write the result of a syscall into the `a0` register of a process with the general purpose registers `registers`
+pub fn set_syscall_result(registers: &mut [u64; 32], result: Result<usize, SyscallError>) {
+    registers[A0] = encode_syscall_result(result);
+}
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod test {
+    use qor_core::structures::syscall_error::SyscallError;
+
+    use super::{encode_syscall_result, set_syscall_result, syscall_arguments, syscall_number};
+
+    #[test]
+    pub fn decode_registers_test() {
+        let mut registers = [0u64; 32];
+        for (index, register) in registers.iter_mut().enumerate() {
+            *register = 0x100 + index as u64;
+        }
+
+        // `a7` holds the number, `a0` through `a6` the arguments, and the registers either side are ignored
+        assert_eq!(syscall_number(&registers), 0x111);
+        assert_eq!(
+            syscall_arguments(&registers),
+            [0x10a, 0x10b, 0x10c, 0x10d, 0x10e, 0x10f, 0x110]
+        );
+    }
+
+    #[test]
+    pub fn encode_result_test() {
+        assert_eq!(encode_syscall_result(Ok(0)), 0);
+        assert_eq!(encode_syscall_result(Ok(0x1234)), 0x1234);
+        assert_eq!(encode_syscall_result(Ok(usize::MAX)), u64::MAX);
+
+        // Errors come back as the negated errno
+        assert_eq!(
+            encode_syscall_result(Err(SyscallError::NoEntry)),
+            (-2i64).cast_unsigned()
+        );
+        assert_eq!(
+            encode_syscall_result(Err(SyscallError::Fault)),
+            (-14i64).cast_unsigned()
+        );
+        assert_eq!(
+            encode_syscall_result(Err(SyscallError::NotImplemented)).cast_signed(),
+            -38
+        );
+    }
+
+    #[test]
+    pub fn set_result_test() {
+        let mut registers = [7u64; 32];
+        set_syscall_result(&mut registers, Err(SyscallError::BadFileDescriptor));
+        assert_eq!(registers[10].cast_signed(), -9);
+
+        // Only `a0` is written
+        assert!(registers
+            .iter()
+            .enumerate()
+            .all(|(index, register)| index == 10 || *register == 7));
+    }
+}